@@ -288,6 +288,26 @@ async function copyRuntimesToBinaries(
     console.log(`   ✅ uvx -> ${uvxDestName}`);
 }
 
+/**
+ * Write a gzipped copy of Bun and its checksum as resources for Linux builds.
+ *
+ * linuxdeploy patches the rpath of binaries packed into the AppImage, which
+ * corrupts Bun. The shell extracts this untouched copy at runtime instead.
+ */
+async function writeBunPayload(bunBinaryPath: string) {
+    console.log("📦 Writing Bun payload for the AppImage...");
+
+    const runtimeResourceDir = path.join(TAURI_RESOURCES_DIR, "runtime");
+    await fs.rm(runtimeResourceDir, { recursive: true, force: true });
+    await fs.mkdir(runtimeResourceDir, { recursive: true });
+
+    const bun = await fs.readFile(bunBinaryPath);
+    const checksum = new Bun.CryptoHasher("sha256").update(bun).digest("hex");
+    await fs.writeFile(path.join(runtimeResourceDir, "bun.gz"), Bun.gzipSync(bun));
+    await fs.writeFile(path.join(runtimeResourceDir, "bun.sha256"), `${checksum}\n`);
+    console.log(`   ✅ bun.gz (sha256 ${checksum})`);
+}
+
 /**
  * Build the server for Tauri bundling.
  *
@@ -556,6 +576,9 @@ async function main() {
 
         // Copy runtimes to Tauri binaries
         await copyRuntimesToBinaries(platform, bunBinaryPath, uvDir);
+        if (platform.startsWith("linux")) {
+            await writeBunPayload(bunBinaryPath);
+        }
 
         // Build server bundle (bundles code + installs minimal external deps)
         await buildServerBundle();
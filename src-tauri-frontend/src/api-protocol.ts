@@ -0,0 +1,8 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+
+/**
+ * Base URL of the shell's pipali-api:// protocol, which forwards requests to
+ * the sidecar with its auth token added. Windows serves custom protocols
+ * from http://<scheme>.localhost instead.
+ */
+export const API_PROTOCOL_URL = convertFileSrc("", "pipali-api").replace(/\/$/, "");
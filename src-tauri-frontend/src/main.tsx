@@ -1,24 +1,67 @@
 import React from "react";
 import { createRoot } from "react-dom/client";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import App from "@/app";
 import { SidecarProvider } from "./sidecar-context";
-import { setApiBaseUrl } from "@/utils/api";
+import { setApiBaseUrl, setBeforeApiRequest, setServerUrl, setWebSocketUrlResolver } from "@/utils/api";
+import { ensureSidecarRunning } from "@/utils/tauri";
+import { API_PROTOCOL_URL } from "./api-protocol";
 
 interface SidecarConfig {
     host: string;
     port: number;
+    tlsPort: number | null;
 }
 
 async function initApp() {
     // Get sidecar config from Tauri backend
     const config = await invoke<SidecarConfig>("get_sidecar_config");
-    const SIDECAR_BASE_URL = `http://${config.host}:${config.port}`;
-    const SIDECAR_WS_URL = `ws://${config.host}:${config.port}`;
+    // The sidecar has a TLS listener too when the local TLS setting is on
+    const SIDECAR_BASE_URL = config.tlsPort
+        ? `https://${config.host}:${config.tlsPort}`
+        : `http://${config.host}:${config.port}`;
+    const SIDECAR_WS_URL = SIDECAR_BASE_URL.replace(/^http/, "ws");
 
-    // Set the API base URL BEFORE rendering the app
-    // This ensures all API calls use the sidecar URL from the start
-    setApiBaseUrl(SIDECAR_BASE_URL);
+    // Set the API base URL BEFORE rendering the app. Requests go through the
+    // shell, which adds the auth token, so it never reaches the webview.
+    setApiBaseUrl(API_PROTOCOL_URL);
+    setServerUrl(SIDECAR_BASE_URL);
+    // WebSockets connect to the sidecar directly, each with a fresh ticket
+    setWebSocketUrlResolver(() => invoke<string>("get_websocket_url"));
+
+    // The sidecar may not be started yet (lazy start) or stopped while idle,
+    // so start it on the next API request or interaction. Skip the round
+    // trip while it's up.
+    let sidecarRunning = false;
+    let idleStopped = false;
+    const ensureRunning = async () => {
+        if (sidecarRunning) return;
+        // Background polling mustn't wake a server stopped for being idle
+        if (idleStopped && document.hidden) {
+            throw new Error("Server is stopped while Pipali is idle");
+        }
+        await ensureSidecarRunning();
+        sidecarRunning = true;
+        idleStopped = false;
+    };
+    const startOnInteraction = () => {
+        for (const event of ["pointerdown", "keydown", "focus"]) {
+            window.addEventListener(event, () => ensureRunning().catch(console.warn), { once: true });
+        }
+    };
+    await listen("sidecar://idle-stopped", () => {
+        idleStopped = true;
+    });
+    await listen("sidecar://stopped", () => {
+        sidecarRunning = false;
+        startOnInteraction();
+    });
+    setBeforeApiRequest(ensureRunning);
+    startOnInteraction();
+
+    // Everything shown belongs to the profile's database, so start afresh
+    await listen("profile://switched", () => window.location.reload());
 
     const container = document.getElementById("root");
     if (!container) {
@@ -27,7 +70,7 @@ async function initApp() {
 
     const root = createRoot(container);
     root.render(
-        <SidecarProvider baseUrl={SIDECAR_BASE_URL} wsBaseUrl={SIDECAR_WS_URL}>
+        <SidecarProvider baseUrl={API_PROTOCOL_URL} wsBaseUrl={SIDECAR_WS_URL}>
             <App />
         </SidecarProvider>
     );
@@ -0,0 +1,117 @@
+import React, { useEffect, useRef, useState } from "react";
+import { createRoot } from "react-dom/client";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { apiFetch, setApiBaseUrl, setBeforeApiRequest } from "@/utils/api";
+import { ensureSidecarRunning } from "@/utils/tauri";
+import { API_PROTOCOL_URL } from "./api-protocol";
+
+interface ChatResponse {
+    response: string;
+    conversationId: string;
+}
+
+type Answer =
+    | { state: "idle" }
+    | { state: "asking" }
+    | { state: "answered"; response: string; conversationId: string }
+    | { state: "failed"; error: string };
+
+function QuickAsk() {
+    const inputRef = useRef<HTMLInputElement>(null);
+    const [question, setQuestion] = useState("");
+    const [answer, setAnswer] = useState<Answer>({ state: "idle" });
+
+    useEffect(() => {
+        // Start afresh each time the prompt bar is summoned
+        const unlisten = listen("quick-ask://shown", () => {
+            setQuestion("");
+            setAnswer({ state: "idle" });
+            inputRef.current?.focus();
+        });
+        inputRef.current?.focus();
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    useEffect(() => {
+        const onKeyDown = (event: KeyboardEvent) => {
+            if (event.key === "Escape") {
+                invoke("hide_quick_ask").catch(console.warn);
+            }
+        };
+        window.addEventListener("keydown", onKeyDown);
+        return () => window.removeEventListener("keydown", onKeyDown);
+    }, []);
+
+    const ask = async () => {
+        const message = question.trim();
+        if (!message || answer.state === "asking") return;
+        setAnswer({ state: "asking" });
+        try {
+            const res = await apiFetch("/api/chat", {
+                method: "POST",
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ message }),
+            });
+            const body = await res.json();
+            if (!res.ok) {
+                throw new Error(body.error || `Request failed with status ${res.status}`);
+            }
+            const { response, conversationId } = body as ChatResponse;
+            setAnswer({ state: "answered", response, conversationId });
+        } catch (err) {
+            setAnswer({ state: "failed", error: err instanceof Error ? err.message : String(err) });
+        }
+    };
+
+    // Continue the conversation in the main window
+    const openInPipali = async (conversationId: string) => {
+        await invoke("open_deep_link", { url: `pipali://chat/${conversationId}` });
+        await invoke("hide_quick_ask");
+    };
+
+    return (
+        <div className="quick-ask">
+            <input
+                ref={inputRef}
+                className="quick-ask-input"
+                placeholder="Ask Pipali..."
+                value={question}
+                onChange={(event) => setQuestion(event.target.value)}
+                onKeyDown={(event) => {
+                    if (event.key === "Enter") ask();
+                }}
+            />
+            <div className="quick-ask-answer">
+                {answer.state === "asking" && <span className="quick-ask-status">Thinking...</span>}
+                {answer.state === "answered" && answer.response}
+                {answer.state === "failed" && <span className="quick-ask-status">{answer.error}</span>}
+            </div>
+            <div className="quick-ask-footer">
+                <span>Enter to ask, Esc to dismiss</span>
+                {answer.state === "answered" && (
+                    <button onClick={() => openInPipali(answer.conversationId).catch(console.warn)}>
+                        Open in Pipali
+                    </button>
+                )}
+            </div>
+        </div>
+    );
+}
+
+async function initQuickAsk() {
+    // The shell forwards to whichever port the sidecar is on
+    setApiBaseUrl(API_PROTOCOL_URL);
+    // Asking is an interaction, so start the server if it isn't running
+    setBeforeApiRequest(ensureSidecarRunning);
+
+    const container = document.getElementById("root");
+    if (!container) {
+        throw new Error("Root element not found");
+    }
+    createRoot(container).render(<QuickAsk />);
+}
+
+initQuickAsk().catch(console.error);
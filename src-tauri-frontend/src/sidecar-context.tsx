@@ -1,24 +1,45 @@
-import type { ReactNode } from "react";
+import { useEffect, useState, type ReactNode } from "react";
+import { listen } from "@tauri-apps/api/event";
 // Import the shared context from the client hooks - this is the same context
 // that useSidecar() reads from in app.tsx
 import { SidecarContext } from "@/hooks/useSidecar";
+import { setServerUrl } from "@/utils/api";
+
+interface SidecarPortChanged {
+    port: number;
+    baseUrl: string;
+}
 
 /**
  * Provider that wraps the app and supplies sidecar configuration.
  * This uses the shared SidecarContext so that useSidecar() in app.tsx
- * can read the values.
+ * can read the values. The WebSocket URL is updated when the sidecar moves to
+ * another port, while API requests keep going through the shell's protocol.
  */
 export function SidecarProvider({
-    baseUrl,
-    wsBaseUrl,
+    baseUrl: initialBaseUrl,
+    wsBaseUrl: initialWsBaseUrl,
     children,
 }: {
     baseUrl: string;
     wsBaseUrl: string;
     children: ReactNode;
 }) {
+    const [urls, setUrls] = useState({ baseUrl: initialBaseUrl, wsBaseUrl: initialWsBaseUrl });
+
+    useEffect(() => {
+        const unlisten = listen<SidecarPortChanged>("sidecar://port-changed", (event) => {
+            const serverUrl = event.payload.baseUrl;
+            setServerUrl(serverUrl);
+            setUrls((urls) => ({ ...urls, wsBaseUrl: serverUrl.replace(/^http/, "ws") }));
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
     return (
-        <SidecarContext.Provider value={{ baseUrl, wsBaseUrl }}>
+        <SidecarContext.Provider value={urls}>
             {children}
         </SidecarContext.Provider>
     );
@@ -1,11 +1,40 @@
-import { defineConfig } from "vite";
+import { defineConfig, type Plugin } from "vite";
 import react from "@vitejs/plugin-react";
+import fs from "fs";
 import path from "path";
 
 const host = process.env.TAURI_DEV_HOST;
 
+// The client's icons, which the web UI loads from the server. The shell's
+// sidecar is API-only, so they're bundled here instead.
+const clientIconsDir = path.resolve(__dirname, "../src/client/public/icons");
+
+function clientIcons(): Plugin {
+    return {
+        name: "pipali-client-icons",
+        configureServer(server) {
+            server.middlewares.use("/icons", (req, res, next) => {
+                const name = path.basename((req.url ?? "").split("?")[0]);
+                const file = path.join(clientIconsDir, name);
+                if (!name || !fs.existsSync(file)) return next();
+                res.setHeader("Content-Type", "image/png");
+                fs.createReadStream(file).pipe(res);
+            });
+        },
+        generateBundle() {
+            for (const name of fs.readdirSync(clientIconsDir)) {
+                this.emitFile({
+                    type: "asset",
+                    fileName: `icons/${name}`,
+                    source: fs.readFileSync(path.join(clientIconsDir, name)),
+                });
+            }
+        },
+    };
+}
+
 export default defineConfig({
-    plugins: [react()],
+    plugins: [react(), clientIcons()],
     root: ".",
     // Use relative paths for Tauri's file:// protocol
     base: "./",
@@ -48,6 +77,7 @@ export default defineConfig({
             input: {
                 main: path.resolve(__dirname, "index.html"),
                 splash: path.resolve(__dirname, "splash.html"),
+                quickAsk: path.resolve(__dirname, "quick-ask.html"),
             },
         },
     },
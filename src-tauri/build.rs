@@ -1,3 +1,14 @@
 fn main() {
+    // The server reports the root package's version, which the shell checks
+    // the sidecar against
+    println!("cargo:rerun-if-changed=../package.json");
+    let package = std::fs::read_to_string("../package.json").expect("Failed to read package.json");
+    let package: serde_json::Value =
+        serde_json::from_str(&package).expect("Failed to parse package.json");
+    let version = package["version"]
+        .as_str()
+        .expect("package.json has no version");
+    println!("cargo:rustc-env=PIPALI_SERVER_VERSION={}", version);
+
     tauri_build::build();
 }
@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// A file or folder the user picked for Pipali to read, persisted to
+/// `access-grants.json` in the app config dir
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessGrant {
+    pub path: String,
+    pub is_dir: bool,
+    /// RFC 3339 time the user picked it
+    pub granted_at: String,
+    /// Security-scoped bookmark, base64 encoded (macOS only), which keeps
+    /// access across restarts and follows the item if it's moved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bookmark: Option<String>,
+}
+
+#[derive(Default)]
+pub struct AccessGrantsState {
+    grants: Mutex<Vec<AccessGrant>>,
+}
+
+fn grants_path(app: &AppHandle) -> Option<PathBuf> {
+    crate::get_app_config_dir(app)
+        .ok()
+        .map(|dir| dir.join("access-grants.json"))
+}
+
+fn write_grants(app: &AppHandle, grants: &[AccessGrant]) -> Result<(), String> {
+    let path = grants_path(app).ok_or("Config directory is unavailable")?;
+    let contents = serde_json::to_string_pretty(grants).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to save access grants: {}", e))
+}
+
+/// Regain access to the locations picked in earlier runs
+///
+/// Grants whose bookmark no longer resolves are kept, in case the drive
+/// they're on is just not mounted. Moved items get their new path.
+pub fn setup(app: &AppHandle) {
+    let mut grants: Vec<AccessGrant> = grants_path(app)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    let mut changed = false;
+    for grant in grants.iter_mut() {
+        let Some(ref bookmark) = grant.bookmark else {
+            continue;
+        };
+        match platform::resolve_bookmark(bookmark) {
+            Ok(resolved) => {
+                if resolved.path != grant.path {
+                    log::info!("[App] {} moved to {}", grant.path, resolved.path);
+                    grant.path = resolved.path;
+                    changed = true;
+                }
+                if let Some(bookmark) = resolved.bookmark {
+                    grant.bookmark = Some(bookmark);
+                    changed = true;
+                }
+            }
+            Err(e) => log::warn!("[App] Failed to restore access to {}: {}", grant.path, e),
+        }
+    }
+    if changed {
+        if let Err(e) = write_grants(app, &grants) {
+            log::warn!("[App] {}", e);
+        }
+    }
+    if !grants.is_empty() {
+        log::info!("[App] Restored {} access grant(s)", grants.len());
+    }
+    *app.state::<AccessGrantsState>().grants.lock().unwrap() = grants;
+}
+
+/// Remember picked paths, replacing earlier grants for the same paths
+pub fn grant(app: &AppHandle, paths: &[PathBuf]) -> Result<(), String> {
+    let granted_at = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let state = app.state::<AccessGrantsState>();
+    let mut grants = state.grants.lock().unwrap();
+    for path in paths {
+        let path_str = path.to_string_lossy().into_owned();
+        let bookmark = platform::create_bookmark(path).unwrap_or_else(|e| {
+            log::warn!("[App] Failed to bookmark {}: {}", path_str, e);
+            None
+        });
+        grants.retain(|grant| grant.path != path_str);
+        grants.push(AccessGrant {
+            path: path_str,
+            is_dir: path.is_dir(),
+            granted_at: granted_at.clone(),
+            bookmark,
+        });
+    }
+    write_grants(app, &grants)
+}
+
+fn picked_paths(paths: Vec<tauri_plugin_dialog::FilePath>) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .filter_map(|path| path.into_path().ok())
+        .collect()
+}
+
+/// Pick files with the native file picker, granting Pipali lasting access to
+/// them (exposed to frontend)
+///
+/// Resolves to no paths if the user cancels.
+#[tauri::command]
+pub async fn pick_files(
+    app: AppHandle,
+    title: Option<String>,
+    multiple: bool,
+) -> Result<Vec<String>, String> {
+    let handle = app.clone();
+    let paths = tauri::async_runtime::spawn_blocking(move || {
+        let mut dialog = handle.dialog().file();
+        if let Some(title) = title {
+            dialog = dialog.set_title(title);
+        }
+        if multiple {
+            dialog.blocking_pick_files().unwrap_or_default()
+        } else {
+            dialog.blocking_pick_file().into_iter().collect()
+        }
+    })
+    .await
+    .map_err(|e| format!("File picker failed: {}", e))?;
+    let paths = picked_paths(paths);
+    grant(&app, &paths)?;
+    Ok(paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// Pick a folder with the native folder picker, granting Pipali lasting
+/// access to it (exposed to frontend)
+///
+/// Resolves to null if the user cancels.
+#[tauri::command]
+pub async fn pick_folder(app: AppHandle, title: Option<String>) -> Result<Option<String>, String> {
+    let handle = app.clone();
+    let path = tauri::async_runtime::spawn_blocking(move || {
+        let mut dialog = handle.dialog().file();
+        if let Some(title) = title {
+            dialog = dialog.set_title(title);
+        }
+        dialog.blocking_pick_folder()
+    })
+    .await
+    .map_err(|e| format!("Folder picker failed: {}", e))?;
+    let paths = picked_paths(path.into_iter().collect());
+    grant(&app, &paths)?;
+    Ok(paths
+        .first()
+        .map(|path| path.to_string_lossy().into_owned()))
+}
+
+/// Paths of the files and folders picked for Pipali to read
+pub fn granted_paths(app: &AppHandle) -> Vec<PathBuf> {
+    app.state::<AccessGrantsState>()
+        .grants
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|grant| PathBuf::from(&grant.path))
+        .collect()
+}
+
+/// List the files and folders Pipali was granted access to (exposed to frontend)
+#[tauri::command]
+pub fn list_access_grants(state: State<'_, AccessGrantsState>) -> Vec<AccessGrant> {
+    state.grants.lock().unwrap().clone()
+}
+
+/// Forget a picked file or folder (exposed to frontend)
+///
+/// Access already given lasts until Pipali quits.
+#[tauri::command]
+pub fn revoke_access_grant(
+    app: AppHandle,
+    state: State<'_, AccessGrantsState>,
+    path: String,
+) -> Result<(), String> {
+    let mut grants = state.grants.lock().unwrap();
+    grants.retain(|grant| grant.path != path);
+    write_grants(&app, &grants)?;
+    log::info!("[App] Revoked access grant for {}", path);
+    Ok(())
+}
+
+/// Where a bookmark points now, with a fresh bookmark if the old one is stale
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+struct ResolvedBookmark {
+    path: String,
+    bookmark: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use base64::Engine;
+    use objc2::rc::{autoreleasepool, Retained};
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+    use std::ffi::{c_char, CStr, CString};
+    use std::path::Path;
+
+    use super::ResolvedBookmark;
+
+    /// NSURLBookmarkCreationWithSecurityScope and
+    /// NSURLBookmarkCreationSecurityScopeAllowOnlyReadAccess
+    const CREATION_OPTIONS: usize = (1 << 11) | (1 << 12);
+    /// NSURLBookmarkResolutionWithSecurityScope
+    const RESOLUTION_OPTIONS: usize = 1 << 10;
+
+    unsafe fn error_description(error: *mut AnyObject) -> String {
+        if error.is_null() {
+            return "unknown error".to_string();
+        }
+        let description: *mut AnyObject = msg_send![error, localizedDescription];
+        let utf8: *const c_char = msg_send![description, UTF8String];
+        CStr::from_ptr(utf8).to_string_lossy().into_owned()
+    }
+
+    unsafe fn bookmark_for(url: &AnyObject) -> Result<String, String> {
+        let mut error: *mut AnyObject = std::ptr::null_mut();
+        let data: Option<Retained<AnyObject>> = msg_send![
+            url,
+            bookmarkDataWithOptions: CREATION_OPTIONS,
+            includingResourceValuesForKeys: std::ptr::null::<AnyObject>(),
+            relativeToURL: std::ptr::null::<AnyObject>(),
+            error: &mut error
+        ];
+        let data = data.ok_or_else(|| error_description(error))?;
+        let bytes: *const u8 = msg_send![&data, bytes];
+        let length: usize = msg_send![&data, length];
+        let bytes = std::slice::from_raw_parts(bytes, length);
+        Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn create_bookmark(path: &Path) -> Result<Option<String>, String> {
+        let path = CString::new(path.to_string_lossy().into_owned()).map_err(|e| e.to_string())?;
+        autoreleasepool(|_| unsafe {
+            let path: Retained<AnyObject> =
+                msg_send![class!(NSString), stringWithUTF8String: path.as_ptr()];
+            let url: Retained<AnyObject> = msg_send![class!(NSURL), fileURLWithPath: &*path];
+            bookmark_for(&url).map(Some)
+        })
+    }
+
+    /// Resolve a bookmark and start accessing what it points to, for as long
+    /// as Pipali runs
+    pub fn resolve_bookmark(bookmark: &str) -> Result<ResolvedBookmark, String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(bookmark)
+            .map_err(|e| format!("Invalid bookmark: {}", e))?;
+        autoreleasepool(|_| unsafe {
+            let data: Retained<AnyObject> = msg_send![
+                class!(NSData),
+                dataWithBytes: bytes.as_ptr(),
+                length: bytes.len()
+            ];
+            let mut stale = Bool::NO;
+            let mut error: *mut AnyObject = std::ptr::null_mut();
+            let url: Option<Retained<AnyObject>> = msg_send![
+                class!(NSURL),
+                URLByResolvingBookmarkData: &*data,
+                options: RESOLUTION_OPTIONS,
+                relativeToURL: std::ptr::null::<AnyObject>(),
+                bookmarkDataIsStale: &mut stale,
+                error: &mut error
+            ];
+            let url = url.ok_or_else(|| error_description(error))?;
+            // Balanced by the process exiting, so access lasts the whole run
+            let _: Bool = msg_send![&url, startAccessingSecurityScopedResource];
+
+            let path: *mut AnyObject = msg_send![&url, path];
+            let utf8: *const c_char = msg_send![path, UTF8String];
+            let path = CStr::from_ptr(utf8).to_string_lossy().into_owned();
+            let bookmark = if stale.as_bool() {
+                bookmark_for(&url).ok()
+            } else {
+                None
+            };
+            Ok(ResolvedBookmark { path, bookmark })
+        })
+    }
+}
+
+/// Elsewhere apps can read whatever the user can, so a grant is just a
+/// record of the path
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use std::path::Path;
+
+    use super::ResolvedBookmark;
+
+    pub fn create_bookmark(_path: &Path) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    pub fn resolve_bookmark(_bookmark: &str) -> Result<ResolvedBookmark, String> {
+        Err("Bookmarks are only supported on macOS".to_string())
+    }
+}
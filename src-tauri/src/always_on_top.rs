@@ -0,0 +1,82 @@
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, CheckMenuItemBuilder};
+use tauri::{AppHandle, Manager, State, Wry};
+
+use crate::settings::SettingsState;
+
+/// Menu items showing whether the main window is kept above other windows
+#[derive(Default)]
+pub struct AlwaysOnTopState {
+    items: Mutex<Vec<CheckMenuItem<Wry>>>,
+}
+
+/// A "Keep Window on Top" item for the app or tray menu, kept checked while
+/// the window is pinned
+pub fn menu_item(app: &tauri::App, id: &str) -> tauri::Result<CheckMenuItem<Wry>> {
+    let item = CheckMenuItemBuilder::with_id(id, "Keep Window on Top")
+        .checked(app.state::<SettingsState>().get().always_on_top)
+        .build(app)?;
+    app.state::<AlwaysOnTopState>()
+        .items
+        .lock()
+        .unwrap()
+        .push(item.clone());
+    Ok(item)
+}
+
+fn apply(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("main") {
+        window
+            .set_always_on_top(enabled)
+            .map_err(|e| format!("Failed to keep window on top: {}", e))?;
+    }
+    for item in app.state::<AlwaysOnTopState>().items.lock().unwrap().iter() {
+        let _ = item.set_checked(enabled);
+    }
+    Ok(())
+}
+
+/// Pin the main window again if it was pinned when Pipali last quit
+pub fn setup(app: &AppHandle) {
+    if app.state::<SettingsState>().get().always_on_top {
+        if let Err(e) = apply(app, true) {
+            log::warn!("[App] {}", e);
+        }
+    }
+}
+
+/// Keep the main window above other windows, or stop doing so
+pub fn set(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    apply(app, enabled)?;
+    app.state::<SettingsState>()
+        .update(|settings| settings.always_on_top = enabled)?;
+    log::info!(
+        "[App] Always on top {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Flip the setting from a menu item
+///
+/// Check items toggle themselves when clicked, so they're set from the
+/// setting rather than read.
+pub fn toggle(app: &AppHandle) {
+    let enabled = !app.state::<SettingsState>().get().always_on_top;
+    if let Err(e) = set(app, enabled) {
+        log::error!("[App] {}", e);
+    }
+}
+
+/// Whether the main window is kept above other windows (exposed to frontend)
+#[tauri::command]
+pub fn get_always_on_top(settings: State<'_, SettingsState>) -> bool {
+    settings.get().always_on_top
+}
+
+/// Pin the main window above other windows, e.g. an editor the agent is
+/// giving instructions for (exposed to frontend)
+#[tauri::command]
+pub fn set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set(&app, enabled)
+}
@@ -0,0 +1,220 @@
+use std::io::Read;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::http::{Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, State, UriSchemeContext, UriSchemeResponder, Wry};
+
+use crate::{command_scope, SidecarState};
+
+/// Scheme the webview reaches the sidecar's API through, e.g.
+/// `pipali-api://localhost/api/conversations` or, on Windows,
+/// `http://pipali-api.localhost/api/conversations`
+pub const SCHEME: &str = "pipali-api";
+
+/// Request headers the shell sets itself rather than passing on. ureq
+/// negotiates compression, and decompresses the body it reads.
+const DROPPED_REQUEST_HEADERS: [&str; 5] = [
+    "accept-encoding",
+    "authorization",
+    "connection",
+    "content-length",
+    "host",
+];
+/// Response headers describing how the body was sent, rather than the body
+/// handed to the webview
+const DROPPED_RESPONSE_HEADERS: [&str; 4] = [
+    "connection",
+    "content-encoding",
+    "content-length",
+    "transfer-encoding",
+];
+
+/// API paths only the shell itself calls, with the token it holds. They're
+/// refused to every window, as an injected script could otherwise read
+/// secrets, take backups or stop the server through the protocol.
+const SHELL_ONLY_PATHS: &[&str] = &[
+    "/api/attachments",
+    "/api/backup",
+    "/api/confirmations/native",
+    "/api/network",
+    "/api/secrets",
+    "/api/shutdown",
+    "/api/throttle",
+    "/api/vacuum",
+    "/api/ws-ticket",
+];
+
+/// Agent for forwarded requests, which can take as long as a model call
+fn forwarding_agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(|| {
+        ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(2))
+            .redirects(0)
+            .build()
+    })
+}
+
+fn error_response(status: StatusCode, message: String) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/plain")
+        .body(message.into_bytes())
+        .unwrap()
+}
+
+/// A request path as the server routes it: percent-decoded, lowercased and
+/// with empty, `.` and `..` segments resolved
+fn normalize_path(path: &str) -> String {
+    let decoded = percent_decode(path).to_lowercase();
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match path.get(i + 1..i + 3) {
+            Some(hex) if bytes[i] == b'%' && hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                u8::from_str_radix(hex, 16).ok()
+            }
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Whether a path is, or is under, one of [`SHELL_ONLY_PATHS`]
+fn is_shell_only(path: &str) -> bool {
+    let path = normalize_path(path);
+    SHELL_ONLY_PATHS.iter().any(|shell_only| {
+        path.strip_prefix(shell_only)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Send a webview request on to the sidecar with the auth token added
+fn forward(app: &AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let state: State<SidecarState> = app.state();
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path| path.as_str());
+    let url = format!("http://{}:{}{}", state.host, state.port(), path);
+
+    let mut forwarded = forwarding_agent().request(request.method().as_str(), &url);
+    for (name, value) in request.headers() {
+        if DROPPED_REQUEST_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        if let Ok(value) = value.to_str() {
+            forwarded = forwarded.set(name.as_str(), value);
+        }
+    }
+    forwarded = forwarded.set("Authorization", &crate::bearer(&state.auth_token.get()));
+
+    let response = match forwarded.send_bytes(request.body()) {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => {
+            log::debug!("[Sidecar] Failed to forward request to {}: {}", path, e);
+            return error_response(StatusCode::BAD_GATEWAY, e.to_string());
+        }
+    };
+
+    let mut builder = Response::builder().status(response.status());
+    for name in response.headers_names() {
+        if DROPPED_RESPONSE_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        for value in response.all(&name) {
+            builder = builder.header(&name, value);
+        }
+    }
+    let mut body = Vec::new();
+    if let Err(e) = response.into_reader().read_to_end(&mut body) {
+        return error_response(StatusCode::BAD_GATEWAY, e.to_string());
+    }
+    builder.body(body).unwrap_or_else(|e| {
+        error_response(StatusCode::BAD_GATEWAY, format!("Invalid response: {}", e))
+    })
+}
+
+/// Handle a `pipali-api://` request off the main thread
+///
+/// The webview sends API requests here instead of to the sidecar's port, so
+/// the auth token stays in the shell and other local apps can't reuse it.
+pub fn handle(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let path = request.uri().path();
+    if is_shell_only(path) || !command_scope::allows_api_path(ctx.webview_label(), path) {
+        return responder.respond(error_response(
+            StatusCode::FORBIDDEN,
+            format!("{} isn't available in this window", path),
+        ));
+    }
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn_blocking(move || responder.respond(forward(&app, request)));
+}
+
+#[derive(serde::Deserialize)]
+struct WebSocketTicket {
+    ticket: String,
+}
+
+/// URL the webview opens the chat WebSocket on, with a single-use ticket in
+/// place of the auth token (exposed to frontend)
+///
+/// WebSockets can't go through a custom protocol, so they connect to the
+/// sidecar directly, over TLS when the local TLS setting is on. Call again
+/// for each connection.
+#[tauri::command]
+pub async fn get_websocket_url(app: AppHandle) -> Result<String, String> {
+    let state: State<SidecarState> = app.state();
+    let (host, port) = (state.host.clone(), state.port());
+    let url = format!("http://{}:{}/api/ws-ticket", host, port);
+    let auth_token = state.auth_token.get();
+    let ticket = tauri::async_runtime::spawn_blocking(move || {
+        let body = forwarding_agent()
+            .post(&url)
+            .set("Authorization", &crate::bearer(&auth_token))
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str::<WebSocketTicket>(&body)
+            .map_err(|e| format!("Invalid response: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Request task failed: {}", e))??;
+    Ok(match state.tls_port() {
+        Some(tls_port) => format!(
+            "wss://{}:{}/ws/chat?ticket={}",
+            host, tls_port, ticket.ticket
+        ),
+        None => format!("ws://{}:{}/ws/chat?ticket={}", host, port, ticket.ticket),
+    })
+}
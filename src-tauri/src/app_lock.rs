@@ -0,0 +1,267 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::SettingsState;
+
+/// How often the user's idle time is checked against `lockAfterIdleMins`
+const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// Shown by the OS in its authentication prompt
+const UNLOCK_REASON: &str = "unlock Pipali";
+
+#[derive(Default)]
+pub struct AppLockState {
+    /// Windows that were showing when the app locked, to show again once
+    /// it's unlocked. `None` while unlocked.
+    hidden: Mutex<Option<Vec<String>>>,
+    /// Held while the OS prompt is up, so a second show doesn't stack another
+    unlocking: tokio::sync::Mutex<()>,
+}
+
+pub fn is_locked(app: &AppHandle) -> bool {
+    app.state::<AppLockState>().hidden.lock().unwrap().is_some()
+}
+
+/// Hide every window until the user authenticates again
+fn lock(app: &AppHandle) -> Result<(), String> {
+    platform::check_available()?;
+    let state = app.state::<AppLockState>();
+    let mut hidden = state.hidden.lock().unwrap();
+    if hidden.is_some() {
+        return Ok(());
+    }
+    let mut visible = Vec::new();
+    for (label, window) in app.webview_windows() {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+            visible.push(label);
+        }
+    }
+    *hidden = Some(visible);
+    drop(hidden);
+    crate::hide_from_dock(app);
+    let _ = app.emit("app://locked", ());
+    log::info!("[Lock] App locked");
+    Ok(())
+}
+
+/// Ask the OS to authenticate the user, then show the windows the lock hid.
+/// Resolves to whether the app is now unlocked.
+async fn unlock(app: &AppHandle) -> Result<bool, String> {
+    let state = app.state::<AppLockState>();
+    let _unlocking = state.unlocking.lock().await;
+    if !is_locked(app) {
+        return Ok(true);
+    }
+    let authenticated =
+        tauri::async_runtime::spawn_blocking(|| platform::authenticate(UNLOCK_REASON))
+            .await
+            .map_err(|e| format!("Authentication task failed: {}", e))??;
+    if !authenticated {
+        log::info!("[Lock] Authentication was cancelled or failed");
+        return Ok(false);
+    }
+
+    let hidden = state.hidden.lock().unwrap().take().unwrap_or_default();
+    for label in hidden {
+        // The prompt bar only makes sense when summoned
+        if label == crate::quick_ask::LABEL {
+            continue;
+        }
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.show();
+        }
+    }
+    let _ = app.emit("app://unlocked", ());
+    log::info!("[Lock] App unlocked");
+    Ok(true)
+}
+
+/// Prompt to unlock in the background, showing the main window on success.
+/// Used by everything that would otherwise show a window while locked.
+pub fn unlock_then_show(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match unlock(&app).await {
+            Ok(true) => crate::show_window(&app),
+            Ok(false) => {}
+            Err(e) => log::error!("[Lock] {}", e),
+        }
+    });
+}
+
+/// Lock the app after `lockAfterIdleMins` without keyboard or mouse input
+/// anywhere on the system
+pub fn spawn_lock_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(LOCK_CHECK_INTERVAL).await;
+
+            let Some(limit) = app
+                .state::<SettingsState>()
+                .get()
+                .lock_after_idle_mins
+                .filter(|mins| *mins > 0)
+                .map(|mins| mins * 60)
+            else {
+                continue;
+            };
+            if is_locked(&app) || platform::idle_secs().is_none_or(|idle| idle < limit) {
+                continue;
+            }
+            if let Err(e) = lock(&app) {
+                log::warn!("[Lock] Not locking after {}s idle: {}", limit, e);
+            }
+        }
+    });
+}
+
+/// Lock the app now (exposed to frontend)
+///
+/// Fails where the OS has no Touch ID, Windows Hello or account password
+/// prompt to unlock it with.
+#[tauri::command]
+pub fn lock_app(app: AppHandle) -> Result<(), String> {
+    lock(&app)
+}
+
+/// Ask the user to authenticate and unlock the app (exposed to frontend)
+///
+/// Resolves to false if they cancel the prompt.
+#[tauri::command]
+pub async fn unlock_app(app: AppHandle) -> Result<bool, String> {
+    let unlocked = unlock(&app).await?;
+    if unlocked {
+        crate::show_window(&app);
+    }
+    Ok(unlocked)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use block2::RcBlock;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+    use std::ffi::CString;
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+    }
+
+    /// kCGEventSourceStateHIDSystemState
+    const HID_SYSTEM_STATE: i32 = 1;
+    /// kCGAnyInputEventType
+    const ANY_INPUT_EVENT: u32 = u32::MAX;
+    /// LAPolicyDeviceOwnerAuthentication: Touch ID, falling back to the
+    /// account password
+    const DEVICE_OWNER_AUTHENTICATION: isize = 2;
+
+    pub fn idle_secs() -> Option<u64> {
+        // SAFETY: a pure query of the window server's input state
+        let secs =
+            unsafe { CGEventSourceSecondsSinceLastEventType(HID_SYSTEM_STATE, ANY_INPUT_EVENT) };
+        (secs >= 0.0).then_some(secs as u64)
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        // SAFETY: LAContext is created, queried and released on this thread
+        let available: bool = unsafe {
+            let context: Retained<AnyObject> = msg_send![class!(LAContext), new];
+            let mut error: *mut AnyObject = std::ptr::null_mut();
+            msg_send![&context, canEvaluatePolicy: DEVICE_OWNER_AUTHENTICATION, error: &mut error]
+        };
+        if !available {
+            return Err("This Mac has no Touch ID or login password to unlock with".to_string());
+        }
+        Ok(())
+    }
+
+    /// Show the Touch ID / password prompt and wait for the user's answer
+    pub fn authenticate(reason: &str) -> Result<bool, String> {
+        let reason = CString::new(reason).map_err(|e| e.to_string())?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut AnyObject| {
+            let _ = tx.send(success.as_bool());
+        });
+        // SAFETY: the context stays alive until the reply arrives, which
+        // LocalAuthentication sends on a queue of its own
+        unsafe {
+            let reason: Retained<AnyObject> =
+                msg_send![class!(NSString), stringWithUTF8String: reason.as_ptr()];
+            let context: Retained<AnyObject> = msg_send![class!(LAContext), new];
+            let _: () = msg_send![
+                &context,
+                evaluatePolicy: DEVICE_OWNER_AUTHENTICATION,
+                localizedReason: &*reason,
+                reply: &*reply
+            ];
+            let result = rx
+                .recv()
+                .map_err(|e| format!("Authentication failed: {}", e));
+            drop(context);
+            result
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    pub fn idle_secs() -> Option<u64> {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        // SAFETY: info is a correctly sized LASTINPUTINFO
+        if unsafe { GetLastInputInfo(&mut info) } == 0 {
+            return None;
+        }
+        // Both are milliseconds since boot, wrapping every 49.7 days
+        let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+        Some(u64::from(idle_ms) / 1000)
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        let availability = UserConsentVerifier::CheckAvailabilityAsync()
+            .and_then(|operation| operation.get())
+            .map_err(|e| format!("Failed to check for Windows Hello: {}", e))?;
+        if availability != UserConsentVerifierAvailability::Available {
+            return Err("Windows Hello isn't set up on this PC".to_string());
+        }
+        Ok(())
+    }
+
+    /// Show the Windows Hello prompt and wait for the user's answer
+    pub fn authenticate(reason: &str) -> Result<bool, String> {
+        let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+            .and_then(|operation| operation.get())
+            .map_err(|e| format!("Windows Hello failed: {}", e))?;
+        Ok(result == UserConsentVerificationResult::Verified)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    pub fn idle_secs() -> Option<u64> {
+        None
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        Err("App lock needs Touch ID or Windows Hello, which Linux doesn't offer".to_string())
+    }
+
+    pub fn authenticate(_reason: &str) -> Result<bool, String> {
+        Err("App lock isn't supported on Linux".to_string())
+    }
+}
@@ -0,0 +1,135 @@
+use tauri::menu::{Menu, MenuItemBuilder, SubmenuBuilder};
+use tauri::{AppHandle, Emitter, Wry};
+
+/// Menu items the frontend acts on, sent as the payload of `menu://action`
+const FRONTEND_ACTIONS: [(&str, &str); 3] = [
+    ("menu_new_conversation", "new-conversation"),
+    ("menu_settings", "settings"),
+    ("menu_find", "find"),
+];
+
+fn build(app: &tauri::App) -> tauri::Result<Menu<Wry>> {
+    let app_menu = SubmenuBuilder::new(app, "Pipali")
+        .about(None)
+        .text("menu_check_for_updates", "Check for Updates…")
+        .separator()
+        .item(
+            &MenuItemBuilder::with_id("menu_settings", "Settings…")
+                .accelerator("CmdOrCtrl+,")
+                .build(app)?,
+        )
+        .separator()
+        .services()
+        .separator()
+        .hide()
+        .hide_others()
+        .show_all()
+        .separator()
+        // Not the predefined Quit, which skips stopping the sidecar
+        .item(
+            &MenuItemBuilder::with_id("menu_quit", "Quit Pipali")
+                .accelerator("CmdOrCtrl+Q")
+                .build(app)?,
+        )
+        .build()?;
+    let file_menu = SubmenuBuilder::new(app, "File")
+        .item(
+            &MenuItemBuilder::with_id("menu_new_conversation", "New Conversation")
+                .accelerator("CmdOrCtrl+N")
+                .build(app)?,
+        )
+        .separator()
+        .close_window()
+        .build()?;
+    let edit_menu = SubmenuBuilder::new(app, "Edit")
+        .undo()
+        .redo()
+        .separator()
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .separator()
+        .item(
+            &MenuItemBuilder::with_id("menu_find", "Find…")
+                .accelerator("CmdOrCtrl+F")
+                .build(app)?,
+        )
+        .build()?;
+    let view_menu = SubmenuBuilder::new(app, "View")
+        .fullscreen()
+        .item(&crate::always_on_top::menu_item(app, "menu_always_on_top")?)
+        .separator()
+        .text("menu_restart_server", "Restart Server")
+        .build()?;
+    let window_menu = SubmenuBuilder::new(app, "Window")
+        .minimize()
+        .maximize()
+        .build()?;
+    let help_menu = SubmenuBuilder::new(app, "Help")
+        .text("menu_open_logs", "Open Logs")
+        .text("menu_website", "Pipali Website")
+        .build()?;
+
+    Menu::with_items(
+        app,
+        &[
+            &app_menu,
+            &file_menu,
+            &edit_menu,
+            &view_menu,
+            &window_menu,
+            &help_menu,
+        ],
+    )
+}
+
+fn handle(app: &AppHandle, id: &str) {
+    if let Some((_, action)) = FRONTEND_ACTIONS.iter().find(|(item, _)| *item == id) {
+        crate::show_window(app);
+        let _ = app.emit_to("main", "menu://action", *action);
+        return;
+    }
+    match id {
+        "menu_check_for_updates" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::app_update::check_interactively(&app).await;
+            });
+        }
+        "menu_restart_server" => {
+            log::info!("[App] Server restart requested from app menu");
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::commands::restart(&app).await {
+                    log::error!("[App] Failed to restart server: {}", e);
+                }
+            });
+        }
+        "menu_always_on_top" => crate::always_on_top::toggle(app),
+        "menu_open_logs" => crate::open_logs_dir(app),
+        "menu_website" => {
+            use tauri_plugin_opener::OpenerExt;
+            if let Err(e) = app.opener().open_url("https://pipali.ai", None::<&str>) {
+                log::error!("[App] Failed to open website: {}", e);
+            }
+        }
+        "menu_quit" => {
+            log::info!("[App] Quit requested from app menu");
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+/// Set up the macOS menu bar, so standard shortcuts like Cmd+W, Cmd+H and
+/// the Edit shortcuts behave as in other apps
+///
+/// Other platforms have no app-wide menu bar, and one on the window would
+/// only take up space.
+pub fn setup(app: &tauri::App) -> tauri::Result<()> {
+    app.set_menu(build(app)?)?;
+    // Global, so tray menu events arrive here too; their ids don't clash
+    app.on_menu_event(|app, event| handle(app, event.id().as_ref()));
+    Ok(())
+}
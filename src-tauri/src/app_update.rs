@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::settings::SettingsState;
+
+/// How often to check for app updates after the check at startup
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// Least time between `update://progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// Where releases are published, under a folder per channel
+const DOWNLOAD_URL: &str = "https://download.pipali.ai";
+
+/// Which releases the app and its server update to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Release candidates, a week or two ahead of stable
+    Beta,
+    /// Built from main every night
+    Nightly,
+}
+
+impl UpdateChannel {
+    /// Update manifest for a product published on this channel, e.g.
+    /// "releases" for the app or "server" for server-only updates
+    pub fn manifest_url(self, product: &str) -> String {
+        let folder = match self {
+            UpdateChannel::Stable => "latest",
+            UpdateChannel::Beta => "beta",
+            UpdateChannel::Nightly => "nightly",
+        };
+        format!("{}/{}/{}/update.json", DOWNLOAD_URL, product, folder)
+    }
+}
+
+pub fn channel(app: &AppHandle) -> UpdateChannel {
+    app.state::<SettingsState>().get().update_channel
+}
+
+#[derive(Default)]
+pub struct AppUpdateState {
+    /// Update the last check found, for `download_update` to fetch
+    available: Mutex<Option<Update>>,
+    /// Update downloaded and verified, waiting for `install_update`
+    ready: Mutex<Option<ReadyUpdate>>,
+    /// Held while downloading, so the same update isn't fetched twice
+    downloading: tokio::sync::Mutex<()>,
+    /// Accept a stable release older than the running version, after the
+    /// user moved back to stable and asked for it
+    allow_downgrade: AtomicBool,
+}
+
+struct ReadyUpdate {
+    update: Update,
+    bytes: Vec<u8>,
+    info: UpdateInfo,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub notes: Option<String>,
+    /// Release date, in RFC 3339
+    pub date: Option<String>,
+    /// Size of the download, if the update server says
+    pub size_bytes: Option<u64>,
+}
+
+impl UpdateInfo {
+    fn of(update: &Update, size_bytes: Option<u64>) -> Self {
+        Self {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+            date: update.date.and_then(|date| {
+                date.format(&time::format_description::well_known::Rfc3339)
+                    .ok()
+            }),
+            size_bytes,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadProgress {
+    downloaded_bytes: u64,
+    /// Size of the download, if the server said
+    total_bytes: Option<u64>,
+}
+
+/// The version of the update downloaded and waiting to be installed, if any
+pub fn ready_version(app: &AppHandle) -> Option<String> {
+    let state = app.state::<AppUpdateState>();
+    let ready = state.ready.lock().unwrap();
+    ready.as_ref().map(|ready| ready.info.version.clone())
+}
+
+/// Size of a download from its Content-Length, without fetching it
+async fn download_size(url: url::Url) -> Option<u64> {
+    tauri::async_runtime::spawn_blocking(move || {
+        ureq::head(url.as_str())
+            .timeout(Duration::from_secs(10))
+            .call()
+            .ok()?
+            .header("Content-Length")?
+            .parse()
+            .ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Check for an app update without downloading it, emitting
+/// `update://available` if there's one that isn't downloaded yet
+async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let state = app.state::<AppUpdateState>();
+    let mut builder = app.updater_builder();
+    // Stable uses the endpoint in tauri.conf.json
+    let channel = channel(app);
+    if channel != UpdateChannel::Stable {
+        let endpoint = channel
+            .manifest_url("releases")
+            .parse()
+            .map_err(|e| format!("Invalid update endpoint: {}", e))?;
+        builder = builder
+            .endpoints(vec![endpoint])
+            .map_err(|e| format!("Invalid update endpoint: {}", e))?;
+    }
+    if state.allow_downgrade.load(Ordering::SeqCst) {
+        builder = builder.version_comparator(|current, release| release.version != current);
+    }
+    let update = builder
+        .build()
+        .map_err(|e| format!("Failed to set up the updater: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+    let Some(update) = update else {
+        log::info!("[Updater] No updates available");
+        *state.available.lock().unwrap() = None;
+        return Ok(None);
+    };
+    let info = UpdateInfo::of(&update, download_size(update.download_url.clone()).await);
+    *state.available.lock().unwrap() = Some(update);
+    if ready_version(app).as_ref() != Some(&info.version) {
+        log::info!("[Updater] Update to {} available", info.version);
+        let _ = app.emit("update://available", &info);
+    }
+    Ok(Some(info))
+}
+
+/// Download and verify the update the last check found, emitting
+/// `update://progress` as it downloads and `update://ready` once verified
+async fn download(app: &AppHandle) -> Result<UpdateInfo, String> {
+    let state = app.state::<AppUpdateState>();
+    let _downloading = state.downloading.lock().await;
+    let update = state
+        .available
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No update to download, check for updates first")?;
+    if let Some(ready) = state.ready.lock().unwrap().as_ref() {
+        if ready.info.version == update.version {
+            return Ok(ready.info.clone());
+        }
+    }
+
+    log::info!("[Updater] Downloading update {}", update.version);
+    let mut downloaded_bytes = 0u64;
+    let mut size_bytes = None;
+    let mut last_emit = Instant::now();
+    let bytes = update
+        .download(
+            |chunk, total_bytes| {
+                downloaded_bytes += chunk as u64;
+                size_bytes = total_bytes;
+                if last_emit.elapsed() >= PROGRESS_INTERVAL {
+                    last_emit = Instant::now();
+                    let progress = DownloadProgress {
+                        downloaded_bytes,
+                        total_bytes,
+                    };
+                    let _ = app.emit("update://progress", progress);
+                }
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| format!("Failed to download update {}: {}", update.version, e))?;
+
+    log::info!(
+        "[Updater] Update {} downloaded and verified",
+        update.version
+    );
+    let still_wanted = state
+        .available
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|available| available.version == update.version);
+    if !still_wanted {
+        return Err("The update channel changed while downloading".to_string());
+    }
+    let info = UpdateInfo::of(&update, size_bytes.or(Some(bytes.len() as u64)));
+    let _ = app.emit("update://ready", &info);
+    *state.ready.lock().unwrap() = Some(ReadyUpdate {
+        update,
+        bytes,
+        info: info.clone(),
+    });
+    Ok(info)
+}
+
+/// Check for an app update and download it in the background, resolving to
+/// `None` if the app is up to date
+pub async fn check_and_download(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    if check(app).await?.is_none() {
+        return Ok(None);
+    }
+    download(app).await.map(Some)
+}
+
+/// Stop the sidecars, install the downloaded update and relaunch
+///
+/// The sidecars go first, as installers can't replace a server binary that's
+/// still running, and the new version starts its own.
+async fn install(app: &AppHandle) -> Result<(), String> {
+    let ready = app.state::<AppUpdateState>().ready.lock().unwrap().take();
+    let Some(ReadyUpdate { update, bytes, .. }) = ready else {
+        return Err("No update has been downloaded".to_string());
+    };
+    log::info!("[Updater] Installing update {}", update.version);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = crate::stop_sidecar(&handle) {
+            log::warn!("[Updater] Failed to stop sidecar before updating: {}", e);
+        }
+        crate::profiles::stop_all(&handle);
+    })
+    .await
+    .map_err(|e| format!("Failed to stop the server: {}", e))?;
+
+    if let Err(e) = update.install(&bytes) {
+        // Keep the app usable if the installer couldn't run
+        let _ = crate::start_sidecar(app);
+        return Err(format!(
+            "Failed to install update {}: {}",
+            update.version, e
+        ));
+    }
+    log::info!("[Updater] Update installed, restarting...");
+    app.restart();
+}
+
+/// Install the downloaded update as the app quits, in install-on-exit mode,
+/// relaunching into it if asked to. Called once the sidecars have stopped.
+///
+/// On Windows the installer takes over from here and decides itself whether
+/// to relaunch.
+pub fn install_on_exit(app: &AppHandle) {
+    let settings = app.state::<SettingsState>().get();
+    if !settings.install_updates_on_exit {
+        return;
+    }
+    let ready = app.state::<AppUpdateState>().ready.lock().unwrap().take();
+    let Some(ReadyUpdate { update, bytes, .. }) = ready else {
+        return;
+    };
+    log::info!("[Updater] Installing update {} on exit", update.version);
+    for window in app.webview_windows().values() {
+        let _ = window.hide();
+    }
+    if let Err(e) = update.install(&bytes) {
+        log::error!(
+            "[Updater] Failed to install update {}: {}",
+            update.version,
+            e
+        );
+        return;
+    }
+    if settings.relaunch_after_update {
+        log::info!("[Updater] Update installed, relaunching...");
+        tauri::process::restart(&app.env());
+    }
+}
+
+/// Offer to restart into a downloaded update
+fn prompt_to_install(app: &AppHandle, info: &UpdateInfo) {
+    let install_now = app
+        .dialog()
+        .message(format!(
+            "Update to {} is ready to install.\n\nRelease notes:\n{}",
+            info.version,
+            info.notes.as_deref().unwrap_or_default()
+        ))
+        .title("New Version Available")
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Restart Now".to_string(),
+            "Later".to_string(),
+        ))
+        .blocking_show();
+    if install_now {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = install(&app).await {
+                log::error!("[Updater] {}", e);
+            }
+        });
+    }
+}
+
+/// Check for updates at startup and every few hours, downloading any found
+/// and offering to restart into it
+///
+/// Releases that only change the server ship as a server binary alone, which
+/// is looked for whenever there's no app update, as a full update brings its
+/// own server.
+pub fn spawn_update_monitor(app: AppHandle) {
+    if cfg!(debug_assertions) {
+        log::info!("[Updater] Skipping update checks in debug build");
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut prompted = None;
+        loop {
+            match check_and_download(&app).await {
+                Ok(Some(info)) if prompted.as_ref() != Some(&info.version) => {
+                    prompted = Some(info.version.clone());
+                    if app.state::<SettingsState>().get().install_updates_on_exit {
+                        log::info!("[Updater] Update {} will install on exit", info.version);
+                    } else {
+                        let handle = app.clone();
+                        let _ = tauri::async_runtime::spawn_blocking(move || {
+                            prompt_to_install(&handle, &info)
+                        })
+                        .await;
+                    }
+                }
+                Ok(Some(_)) => {}
+                Ok(None) if crate::sidecar_path_override().is_none() => {
+                    if let Err(e) = crate::server_update::check_for_server_update(app.clone()).await
+                    {
+                        log::warn!("[ServerUpdate] Failed to check for updates: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("[Updater] {}", e),
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Check for updates when the user asks from the app menu, telling them if
+/// there's none
+#[cfg(target_os = "macos")]
+pub async fn check_interactively(app: &AppHandle) {
+    let result = check_and_download(app).await;
+    let handle = app.clone();
+    let _ = tauri::async_runtime::spawn_blocking(move || match result {
+        Ok(Some(info)) => prompt_to_install(&handle, &info),
+        Ok(None) => {
+            handle
+                .dialog()
+                .message(format!(
+                    "Pipali {} is the latest version.",
+                    handle.package_info().version
+                ))
+                .title("You're Up to Date")
+                .blocking_show();
+        }
+        Err(e) => {
+            log::warn!("[Updater] {}", e);
+            handle
+                .dialog()
+                .message(e)
+                .title("Couldn't Check for Updates")
+                .blocking_show();
+        }
+    })
+    .await;
+}
+
+/// Check for an app update without downloading it (exposed to frontend)
+///
+/// Resolves with the update's version, release notes and download size, or
+/// `None` if the app is up to date. Fetch it with `download_update`.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    check(&app).await
+}
+
+/// Download and verify the update found by `check_for_updates`, emitting
+/// `update://progress` along the way (exposed to frontend)
+#[tauri::command]
+pub async fn download_update(app: AppHandle) -> Result<UpdateInfo, String> {
+    download(&app).await
+}
+
+/// Get the release channel the app updates from (exposed to frontend)
+#[tauri::command]
+pub fn get_update_channel(settings: tauri::State<'_, SettingsState>) -> UpdateChannel {
+    settings.get().update_channel
+}
+
+/// Switch the release channel and check it for an update straight away
+/// (exposed to frontend)
+///
+/// Moving to an earlier channel keeps the running version until that
+/// channel catches up, unless `downgrade` is set when moving to stable. A
+/// downgrade is never automatic, as the newer version may have changed the
+/// database in ways the stable one can't read. Server-only downloads from
+/// the old channel are dropped either way.
+#[tauri::command]
+pub async fn set_update_channel(
+    app: AppHandle,
+    channel: UpdateChannel,
+    downgrade: Option<bool>,
+) -> Result<Option<UpdateInfo>, String> {
+    let previous = self::channel(&app);
+    app.state::<SettingsState>()
+        .update(|settings| settings.update_channel = channel)?;
+    let state = app.state::<AppUpdateState>();
+    let downgrade = channel == UpdateChannel::Stable && downgrade.unwrap_or(false);
+    state.allow_downgrade.store(downgrade, Ordering::SeqCst);
+    if channel != previous {
+        log::info!(
+            "[Updater] Switched from the {:?} to the {:?} channel",
+            previous,
+            channel
+        );
+        *state.available.lock().unwrap() = None;
+        *state.ready.lock().unwrap() = None;
+        crate::server_update::forget_downloads(&app);
+    }
+
+    let update = check(&app).await?;
+    if update.is_none() && crate::sidecar_path_override().is_none() {
+        if let Err(e) = crate::server_update::check_for_server_update(app.clone()).await {
+            log::warn!("[ServerUpdate] Failed to check for updates: {}", e);
+        }
+    }
+    Ok(update)
+}
+
+/// Choose whether downloaded updates install when the app quits, instead of
+/// asking to restart, and whether it starts again afterwards (exposed to
+/// frontend)
+#[tauri::command]
+pub fn set_install_updates_on_exit(
+    settings: tauri::State<'_, SettingsState>,
+    enabled: bool,
+    relaunch: Option<bool>,
+) -> Result<(), String> {
+    settings.update(|settings| {
+        settings.install_updates_on_exit = enabled;
+        if let Some(relaunch) = relaunch {
+            settings.relaunch_after_update = relaunch;
+        }
+    })
+}
+
+/// Install the downloaded update and relaunch into it (exposed to frontend)
+///
+/// Stops the sidecar first. Fails if no update has finished downloading,
+/// see `update://ready`.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    install(&app).await
+}
@@ -0,0 +1,111 @@
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+/// Bun, gzipped so linuxdeploy doesn't recognise it as a binary to patch
+const PAYLOAD: &str = "resources/runtime/bun.gz";
+/// SHA-256 of Bun as released, in hex
+const CHECKSUM: &str = "resources/runtime/bun.sha256";
+
+/// The copy of Bun verified this launch
+static VERIFIED_BUN: OnceLock<PathBuf> = OnceLock::new();
+
+/// Whether Pipali runs from an AppImage, whose runtime sets APPIMAGE
+fn is_appimage() -> bool {
+    cfg!(target_os = "linux") && std::env::var_os("APPIMAGE").is_some()
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Write Bun out of the payload, only moving it into place once it matches
+/// the checksum
+fn extract(payload: &Path, target: &Path, expected: &str) -> Result<(), String> {
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create runtime directory: {}", e))?;
+    }
+    let partial = target.with_extension("partial");
+    let result = (|| {
+        let mut decoder = GzDecoder::new(
+            File::open(payload).map_err(|e| format!("Failed to open bundled Bun: {}", e))?,
+        );
+        let mut file = File::create(&partial).map_err(|e| format!("Failed to write Bun: {}", e))?;
+        std::io::copy(&mut decoder, &mut file)
+            .map_err(|e| format!("Failed to extract Bun: {}", e))?;
+        drop(file);
+
+        let actual = sha256_file(&partial).map_err(|e| e.to_string())?;
+        if actual != expected {
+            return Err(format!(
+                "Bundled Bun doesn't match its checksum ({} != {})",
+                actual, expected
+            ));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Failed to make Bun executable: {}", e))?;
+        }
+        std::fs::rename(&partial, target).map_err(|e| format!("Failed to install Bun: {}", e))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+fn verified_bun(app: &AppHandle) -> Result<PathBuf, String> {
+    let resources = app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to get resource dir: {}", e))?;
+    let expected = std::fs::read_to_string(resources.join(CHECKSUM))
+        .map_err(|e| format!("Failed to read Bun checksum: {}", e))?
+        .trim()
+        .to_lowercase();
+    let target = crate::get_app_data_dir(app)?.join("runtime").join("bun");
+
+    match sha256_file(&target) {
+        Ok(actual) if actual == expected => return Ok(target),
+        Ok(_) => log::warn!("[Sidecar] Extracted Bun is damaged, repairing it"),
+        Err(_) => log::info!("[Sidecar] Extracting Bun to {:?}", target),
+    }
+    extract(&resources.join(PAYLOAD), &target, &expected)?;
+    log::info!("[Sidecar] Extracted Bun");
+    Ok(target)
+}
+
+/// Path to run Bun from when Pipali is an AppImage, or `None` to use the
+/// bundled sidecar
+///
+/// linuxdeploy rewrites the rpath of binaries it packs into an AppImage,
+/// which corrupts Bun's embedded payload. So an untouched copy is extracted
+/// to the app data dir, checked against the release checksum on each launch
+/// and rewritten if it was changed.
+pub fn bun_path(app: &AppHandle) -> Option<PathBuf> {
+    if !is_appimage() {
+        return None;
+    }
+    if let Some(path) = VERIFIED_BUN.get() {
+        return Some(path.clone());
+    }
+    match verified_bun(app) {
+        Ok(path) => Some(VERIFIED_BUN.get_or_init(|| path).clone()),
+        Err(e) => {
+            log::error!("[Sidecar] {}, falling back to the bundled Bun", e);
+            None
+        }
+    }
+}
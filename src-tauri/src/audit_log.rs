@@ -0,0 +1,310 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+use zeroize::Zeroizing;
+
+/// Commands that reach outside the webview's sandbox, and so are the first
+/// an injected script would try
+const PRIVILEGED_COMMANDS: &[&str] = &[
+    "apply_server_update",
+    "backup_database",
+    "capture_screen",
+    "compact_database",
+    "confirm_natively",
+    "delete_secret",
+    "encrypt_database",
+    "export_audit_log",
+    "export_conversations",
+    "get_secret",
+    "import_from_khoj",
+    "migrate_data_dir",
+    "open_data_dir",
+    "open_path",
+    "pick_files",
+    "pick_folder",
+    "reveal_in_file_manager",
+    "restart_sidecar",
+    "revoke_access_grant",
+    "set_backup_schedule",
+    "set_privacy_mode",
+    "set_secret",
+    "set_sidecar_env",
+    "set_sidecar_port",
+    "start_drag",
+    "start_profile",
+    "switch_profile",
+];
+/// Hash the first entry is chained to
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Keychain service the log's key and head are filed under, out of reach of
+/// anything that can only write to the logs folder
+const SERVICE: &str = "ai.pipali.audit";
+const KEY_ACCOUNT: &str = "key";
+/// Entry count and last hash, as `<count>:<hash>`, so dropping entries from
+/// the end of the log is caught too
+const HEAD_ACCOUNT: &str = "head";
+
+#[derive(Default)]
+pub struct AuditLogState {
+    /// Key and head of the chain, loaded on first use
+    chain: Mutex<Option<Chain>>,
+}
+
+struct Chain {
+    key: Zeroizing<String>,
+    entries: usize,
+    last_hash: String,
+}
+
+impl Chain {
+    /// Load the key and head from the keychain, generating a key if there's
+    /// none. A log without a recorded head predates the key, so it's moved
+    /// aside for a new chain to start.
+    fn load(path: &Path) -> Result<Self, String> {
+        let key = match get(KEY_ACCOUNT)? {
+            Some(key) => key,
+            None => {
+                let mut bytes = Zeroizing::new([0u8; 32]);
+                getrandom::getrandom(&mut *bytes)
+                    .map_err(|e| format!("Failed to generate an audit log key: {}", e))?;
+                let key = Zeroizing::new(hex(&*bytes));
+                set(KEY_ACCOUNT, &key)?;
+                key
+            }
+        };
+        let head = get(HEAD_ACCOUNT)?.and_then(|head| {
+            let (entries, last_hash) = head.split_once(':')?;
+            Some((entries.parse().ok()?, last_hash.to_string()))
+        });
+        let (entries, last_hash) = match head {
+            Some(head) => head,
+            None => {
+                if path.exists() {
+                    std::fs::rename(path, path.with_extension("unkeyed.jsonl"))
+                        .map_err(|e| format!("Failed to set aside the audit log: {}", e))?;
+                }
+                (0, GENESIS_HASH.to_string())
+            }
+        };
+        Ok(Self {
+            key,
+            entries,
+            last_hash,
+        })
+    }
+}
+
+fn keychain_entry(account: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, account).map_err(|e| format!("Failed to open keychain: {}", e))
+}
+
+fn get(account: &str) -> Result<Option<Zeroizing<String>>, String> {
+    match keychain_entry(account)?.get_password() {
+        Ok(value) => Ok(Some(Zeroizing::new(value))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read the audit log {}: {}", account, e)),
+    }
+}
+
+fn set(account: &str, value: &str) -> Result<(), String> {
+    keychain_entry(account)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to save the audit log {}: {}", account, e))
+}
+
+/// A line of the audit log. Each entry's hash is keyed, and covers the
+/// previous entry's, so editing or removing a line breaks the chain from
+/// there on and the chain can't be recomputed without the key.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditEntry {
+    timestamp: String,
+    command: String,
+    /// Label of the window that invoked the command
+    window: String,
+    /// Arguments are hashed rather than stored, as they may hold secrets
+    args_sha256: String,
+    prev_hash: String,
+    hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self, key: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(
+            format!(
+                "{}|{}|{}|{}|{}",
+                self.prev_hash, self.timestamp, self.command, self.window, self.args_sha256
+            )
+            .as_bytes(),
+        );
+        hex(&mac.finalize().into_bytes())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogExport {
+    pub path: String,
+    pub entries: usize,
+    /// Whether every entry's hash checks out, and none are missing
+    pub intact: bool,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn audit_log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::get_logs_dir(app)?.join("audit.jsonl"))
+}
+
+fn read_entries(path: &Path) -> Vec<AuditEntry> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Count the entries, and check each is chained to the one before and the
+/// last is the recorded head
+fn verify(path: &Path, chain: &Chain) -> (usize, bool) {
+    let entries = read_entries(path);
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let linked = entries.iter().all(|entry| {
+        let valid = entry.prev_hash == prev_hash && entry.hash == entry.compute_hash(&chain.key);
+        prev_hash = entry.hash.clone();
+        valid
+    });
+    let intact = linked && entries.len() == chain.entries && prev_hash == chain.last_hash;
+    (entries.len(), intact)
+}
+
+/// The loaded chain, loading it on first use
+fn loaded<'a>(chain: &'a mut Option<Chain>, path: &Path) -> Result<&'a mut Chain, String> {
+    if chain.is_none() {
+        *chain = Some(Chain::load(path)?);
+    }
+    Ok(chain.as_mut().expect("chain was just loaded"))
+}
+
+fn append(app: &AppHandle, command: &str, window: &str, args_sha256: String) {
+    let path = match audit_log_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("[Audit] {}", e);
+            return;
+        }
+    };
+    let state = app.state::<AuditLogState>();
+    let mut chain = state.chain.lock().unwrap();
+    let chain = match loaded(&mut chain, &path) {
+        Ok(chain) => chain,
+        Err(e) => {
+            log::warn!("[Audit] {}", e);
+            return;
+        }
+    };
+    let mut entry = AuditEntry {
+        timestamp: humantime::format_rfc3339_millis(std::time::SystemTime::now()).to_string(),
+        command: command.to_string(),
+        window: window.to_string(),
+        args_sha256,
+        prev_hash: chain.last_hash.clone(),
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash(&chain.key);
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+        })
+        .and_then(|mut file| {
+            let line = serde_json::to_string(&entry).map_err(std::io::Error::other)?;
+            writeln!(file, "{}", line)
+        });
+    if let Err(e) = result {
+        log::warn!("[Audit] Failed to write audit log: {}", e);
+        return;
+    }
+    chain.entries += 1;
+    chain.last_hash = entry.hash;
+    if let Err(e) = set(
+        HEAD_ACCOUNT,
+        &format!("{}:{}", chain.entries, chain.last_hash),
+    ) {
+        log::warn!("[Audit] {}", e);
+    }
+}
+
+/// Record an IPC call if it's to a privileged command, before it's handled
+pub fn record(invoke: &Invoke) {
+    let command = invoke.message.command();
+    if !PRIVILEGED_COMMANDS.contains(&command) {
+        return;
+    }
+    let args_sha256 = match invoke.message.payload() {
+        InvokeBody::Json(args) => hex(&Sha256::digest(args.to_string().as_bytes())),
+        InvokeBody::Raw(bytes) => hex(&Sha256::digest(bytes)),
+    };
+    let webview = invoke.message.webview();
+    append(webview.app_handle(), command, webview.label(), args_sha256);
+}
+
+/// Save a copy of the audit log where the user picks (exposed to frontend)
+///
+/// Resolves to null if the user cancels.
+#[tauri::command]
+pub async fn export_audit_log(app: AppHandle) -> Result<Option<AuditLogExport>, String> {
+    let source = audit_log_path(&app)?;
+    let handle = app.clone();
+    let destination = tauri::async_runtime::spawn_blocking(move || {
+        handle
+            .dialog()
+            .file()
+            .set_file_name("pipali-audit.jsonl")
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|e| format!("Save dialog failed: {}", e))?;
+    let Some(destination) = destination.and_then(|path| path.into_path().ok()) else {
+        return Ok(None);
+    };
+
+    // Hold the lock so no entry is half written while copying
+    let state = app.state::<AuditLogState>();
+    let mut chain = state.chain.lock().unwrap();
+    let (entries, intact) = verify(&source, loaded(&mut chain, &source)?);
+    if entries > 0 {
+        std::fs::copy(&source, &destination)
+            .map_err(|e| format!("Failed to export audit log: {}", e))?;
+    } else {
+        std::fs::write(&destination, b"")
+            .map_err(|e| format!("Failed to export audit log: {}", e))?;
+    }
+    if !intact {
+        log::warn!("[Audit] The audit log has been modified");
+    }
+    Ok(Some(AuditLogExport {
+        path: destination.to_string_lossy().into_owned(),
+        entries,
+        intact,
+    }))
+}
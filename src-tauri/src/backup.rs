@@ -0,0 +1,478 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use zeroize::Zeroizing;
+
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// Backup folders are named this followed by a UTC timestamp
+pub const FOLDER_PREFIX: &str = "pipali-backup-";
+/// Least time between `backup://progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// The server dumps the whole database before sending any of it
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How often the scheduler checks whether a backup is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// How long the scheduler waits after a failed backup before trying again
+const RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// How often the database is backed up automatically
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupFrequency {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl BackupFrequency {
+    fn interval(self) -> Option<Duration> {
+        match self {
+            BackupFrequency::Off => None,
+            BackupFrequency::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            BackupFrequency::Weekly => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    pub frequency: BackupFrequency,
+    /// Folder automatic backups go in
+    pub dir: PathBuf,
+    /// Backups kept in the folder before older ones are deleted
+    pub keep: u32,
+    /// When the newest backup in the folder was taken, in RFC 3339
+    pub last_backup_at: Option<String>,
+}
+
+#[derive(Default)]
+pub struct BackupState {
+    /// Held while a backup runs, so two never write at once
+    running: tokio::sync::Mutex<()>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// Folder the backup was written to
+    pub path: PathBuf,
+    /// When the backup was taken, in RFC 3339
+    pub created_at: String,
+    pub database_bytes: u64,
+    /// Whether the database snapshot is encrypted with the database key
+    pub encrypted: bool,
+    pub attachment_count: usize,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupProgress {
+    written_bytes: u64,
+    /// Size of the database snapshot, once the server has made it
+    total_bytes: Option<u64>,
+}
+
+/// An attachment as it was when the backup was taken
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentEntry {
+    /// Path under the attachments folder, with `/` separators
+    path: String,
+    size_bytes: u64,
+    /// Last modified, in seconds since the Unix epoch
+    modified: Option<u64>,
+}
+
+/// `manifest.json`, describing what a backup folder holds
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest<'a> {
+    app_version: String,
+    /// Newest database migration the snapshot includes
+    schema_version: Option<String>,
+    created_at: &'a str,
+    /// `database.tar.gz`, or `database.enc` when encrypted
+    database_file: &'a str,
+    encrypted: bool,
+    attachments: Vec<AttachmentEntry>,
+}
+
+/// Files under the attachments folder, which the manifest lists so a restore
+/// can tell what the conversations referred to
+fn list_attachments(dir: &Path) -> Vec<AttachmentEntry> {
+    let mut entries = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(folder) = pending.pop() {
+        let Ok(children) = std::fs::read_dir(&folder) else {
+            continue;
+        };
+        for child in children.flatten() {
+            let path = child.path();
+            let Ok(metadata) = child.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            entries.push(AttachmentEntry {
+                path: relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                size_bytes: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|elapsed| elapsed.as_secs()),
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+/// A database snapshot saved from the server
+struct Snapshot {
+    file_name: &'static str,
+    bytes: u64,
+    encrypted: bool,
+    schema_version: Option<String>,
+}
+
+/// Stream a snapshot of the live database from the server into `folder`,
+/// emitting `backup://progress` as it's written
+fn save_snapshot(
+    app: &AppHandle,
+    url: &str,
+    auth_token: &str,
+    folder: &Path,
+) -> Result<Snapshot, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(5))
+        .timeout_read(SNAPSHOT_TIMEOUT)
+        .build();
+    let response = agent
+        .get(url)
+        .set("Authorization", &crate::bearer(auth_token))
+        .call()
+        .map_err(|e| format!("The server couldn't snapshot the database: {}", e))?;
+    let encrypted = response.header("X-Database-Encrypted") == Some("true");
+    let schema_version = response
+        .header("X-Schema-Version")
+        .filter(|version| !version.is_empty())
+        .map(str::to_string);
+    let total_bytes = response
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok());
+    let file_name = if encrypted {
+        "database.enc"
+    } else {
+        "database.tar.gz"
+    };
+
+    let partial = folder.join(format!("{}.partial", file_name));
+    let mut file =
+        File::create(&partial).map_err(|e| format!("Failed to create the backup: {}", e))?;
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut written_bytes = 0u64;
+    let mut last_emit = Instant::now();
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read the database snapshot: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write the backup: {}", e))?;
+        written_bytes += read as u64;
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            let progress = BackupProgress {
+                written_bytes,
+                total_bytes,
+            };
+            let _ = app.emit("backup://progress", progress);
+        }
+    }
+    if total_bytes.is_some_and(|total| total != written_bytes) {
+        return Err("The database snapshot was cut short".to_string());
+    }
+    file.sync_all()
+        .map_err(|e| format!("Failed to write the backup: {}", e))?;
+    std::fs::rename(&partial, folder.join(file_name))
+        .map_err(|e| format!("Failed to write the backup: {}", e))?;
+    Ok(Snapshot {
+        file_name,
+        bytes: written_bytes,
+        encrypted,
+        schema_version,
+    })
+}
+
+/// `prefix` followed by a UTC timestamp, e.g. `pipali-backup-20260101-093000`,
+/// so folders named this way sort oldest first
+pub fn timestamped_name(prefix: &str, now: time::OffsetDateTime) -> String {
+    format!(
+        "{}{:04}{:02}{:02}-{:02}{:02}{:02}",
+        prefix,
+        now.year(),
+        u8::from(now.month()),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    )
+}
+
+/// Write a backup folder holding the snapshot and the manifest
+fn write_backup(
+    app: &AppHandle,
+    url: &str,
+    auth_token: &str,
+    dest: &Path,
+) -> Result<BackupInfo, String> {
+    let now = time::OffsetDateTime::now_utc();
+    let created_at = now
+        .format(&time::format_description::well_known::Rfc3339)
+        .map_err(|e| e.to_string())?;
+    let folder = dest.join(timestamped_name(FOLDER_PREFIX, now));
+    std::fs::create_dir_all(dest).map_err(|e| format!("Failed to create {:?}: {}", dest, e))?;
+    std::fs::create_dir(&folder).map_err(|e| format!("Failed to create {:?}: {}", folder, e))?;
+
+    let result = (|| {
+        let snapshot = save_snapshot(app, url, auth_token, &folder)?;
+        let attachments = list_attachments(&crate::get_attachments_dir(app)?);
+        let attachment_count = attachments.len();
+        let manifest = Manifest {
+            app_version: app.package_info().version.to_string(),
+            schema_version: snapshot.schema_version,
+            created_at: &created_at,
+            database_file: snapshot.file_name,
+            encrypted: snapshot.encrypted,
+            attachments,
+        };
+        let contents = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        std::fs::write(folder.join("manifest.json"), contents)
+            .map_err(|e| format!("Failed to write the backup manifest: {}", e))?;
+        Ok(BackupInfo {
+            path: folder.clone(),
+            created_at: created_at.clone(),
+            database_bytes: snapshot.bytes,
+            encrypted: snapshot.encrypted,
+            attachment_count,
+        })
+    })();
+    // Leave no half-written backup to be mistaken for a good one
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+    result
+}
+
+/// Back up the database and list the attachments into a new timestamped
+/// folder under `dest`, while the server keeps running
+pub async fn create(app: &AppHandle, dest: PathBuf) -> Result<BackupInfo, String> {
+    if !dest.is_absolute() {
+        return Err("The backup folder must be an absolute path".to_string());
+    }
+    let state = app.state::<BackupState>();
+    let Ok(_running) = state.running.try_lock() else {
+        return Err("A backup is already running".to_string());
+    };
+    let sidecar = app.state::<SidecarState>();
+    if sidecar.status() != SidecarStatus::Ready {
+        return Err("The server isn't running, so there's nothing to back up".to_string());
+    }
+    let url = format!("http://{}:{}/api/backup", sidecar.host, sidecar.port());
+    let auth_token: Zeroizing<String> = sidecar.auth_token.get();
+
+    log::info!("[Backup] Backing up the database to {:?}", dest);
+    let handle = app.clone();
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        write_backup(&handle, &url, &auth_token, &dest)
+    })
+    .await
+    .map_err(|e| format!("Backup task failed: {}", e))??;
+    log::info!(
+        "[Backup] Backed up {} bytes to {:?}",
+        info.database_bytes,
+        info.path
+    );
+    Ok(info)
+}
+
+/// Folder automatic backups go in
+fn scheduled_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match app.state::<SettingsState>().get().backup_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(crate::normalize_windows_path(crate::get_app_data_dir(app)?).join("backups")),
+    }
+}
+
+/// Finished backups in a folder, oldest first, with when each was taken
+///
+/// Backups are only complete once their manifest is written, so folders
+/// without one are left alone.
+fn list_backups(dir: &Path) -> Vec<(PathBuf, std::time::SystemTime)> {
+    let Ok(children) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(PathBuf, std::time::SystemTime)> = children
+        .flatten()
+        .filter(|child| {
+            child
+                .file_name()
+                .to_string_lossy()
+                .starts_with(FOLDER_PREFIX)
+        })
+        .filter_map(|child| {
+            let taken = std::fs::metadata(child.path().join("manifest.json"))
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            Some((child.path(), taken))
+        })
+        .collect();
+    backups.sort();
+    backups
+}
+
+/// Delete all but the newest `keep` backups in a folder
+fn prune(dir: &Path, keep: u32) {
+    let backups = list_backups(dir);
+    let excess = backups.len().saturating_sub(keep.max(1) as usize);
+    for (path, _) in backups.into_iter().take(excess) {
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => log::info!("[Backup] Deleted old backup {:?}", path),
+            Err(e) => log::warn!("[Backup] Failed to delete old backup {:?}: {}", path, e),
+        }
+    }
+}
+
+/// Take a backup if one is due by the schedule, resolving to whether one was
+/// attempted
+async fn run_if_due(app: &AppHandle) -> bool {
+    let settings = app.state::<SettingsState>().get();
+    let Some(interval) = settings.backup_frequency.interval() else {
+        return false;
+    };
+    let dir = match scheduled_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("[Backup] {}", e);
+            return false;
+        }
+    };
+    let last = list_backups(&dir).last().map(|(_, taken)| *taken);
+    let due = last.is_none_or(|taken| taken.elapsed().is_ok_and(|elapsed| elapsed >= interval));
+    if !due || app.state::<SidecarState>().status() != SidecarStatus::Ready {
+        return false;
+    }
+    if crate::battery::on_battery(app) {
+        log::debug!("[Backup] Backup due, waiting until off battery");
+        return false;
+    }
+
+    match create(app, dir.clone()).await {
+        Ok(info) => {
+            prune(&dir, settings.backups_to_keep);
+            let _ = app.emit("backup://completed", &info);
+        }
+        Err(e) => {
+            log::error!("[Backup] Scheduled backup failed: {}", e);
+            let _ = app.emit("backup://failed", &e);
+        }
+    }
+    true
+}
+
+/// Back up the database on the `backupFrequency` schedule, keeping the newest
+/// `backupsToKeep` backups, and emitting `backup://completed` or
+/// `backup://failed` after each run
+///
+/// Runs are put off while on battery, and until an hour after a failure.
+pub fn spawn_backup_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+            if run_if_due(&app).await {
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    });
+}
+
+/// Get the automatic backup schedule (exposed to frontend)
+#[tauri::command]
+pub fn get_backup_schedule(app: AppHandle) -> Result<BackupSchedule, String> {
+    let settings = app.state::<SettingsState>().get();
+    let dir = scheduled_dir(&app)?;
+    let last_backup_at = list_backups(&dir).last().and_then(|(_, taken)| {
+        time::OffsetDateTime::from(*taken)
+            .format(&time::format_description::well_known::Rfc3339)
+            .ok()
+    });
+    Ok(BackupSchedule {
+        frequency: settings.backup_frequency,
+        dir,
+        keep: settings.backups_to_keep,
+        last_backup_at,
+    })
+}
+
+/// Change how often and where the database is backed up automatically, and
+/// how many backups are kept (exposed to frontend)
+///
+/// `dir` and `keep` are left as they were if not given.
+#[tauri::command]
+pub fn set_backup_schedule(
+    app: AppHandle,
+    frequency: BackupFrequency,
+    dir: Option<String>,
+    keep: Option<u32>,
+) -> Result<BackupSchedule, String> {
+    if dir
+        .as_deref()
+        .is_some_and(|dir| !Path::new(dir).is_absolute())
+    {
+        return Err("The backup folder must be an absolute path".to_string());
+    }
+    if keep == Some(0) {
+        return Err("At least one backup must be kept".to_string());
+    }
+    app.state::<SettingsState>().update(|settings| {
+        settings.backup_frequency = frequency;
+        if let Some(dir) = dir {
+            settings.backup_dir = Some(dir);
+        }
+        if let Some(keep) = keep {
+            settings.backups_to_keep = keep;
+        }
+    })?;
+    log::info!("[Backup] Automatic backups set to {:?}", frequency);
+    get_backup_schedule(app)
+}
+
+/// Back up the database and an attachments manifest to a timestamped folder
+/// under `destPath`, without stopping the server (exposed to frontend)
+///
+/// Emits `backup://progress` as the snapshot is written. An encrypted
+/// database stays encrypted in its backup.
+#[tauri::command]
+pub async fn backup_database(app: AppHandle, dest_path: String) -> Result<BackupInfo, String> {
+    create(&app, PathBuf::from(dest_path)).await
+}
@@ -0,0 +1,52 @@
+use tauri::{AppHandle, Manager};
+
+/// Show how many responses and confirmations are waiting for the user on the
+/// dock icon, or clear the badge with a count of 0
+///
+/// Windows has no taskbar badge, so a dot is overlaid on the taskbar button
+/// instead.
+fn set(app: &AppHandle, count: u32) -> Result<(), String> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    #[cfg(target_os = "windows")]
+    let result = window.set_overlay_icon((count > 0).then(overlay_icon));
+    #[cfg(not(target_os = "windows"))]
+    let result = window.set_badge_count((count > 0).then_some(count as i64));
+    result.map_err(|e| format!("Failed to set badge: {}", e))
+}
+
+/// A red dot for the taskbar overlay
+#[cfg(target_os = "windows")]
+fn overlay_icon() -> tauri::image::Image<'static> {
+    const SIZE: u32 = 16;
+    let radius = SIZE as f32 / 2.0;
+    let mut rgba = vec![0u8; (SIZE * SIZE * 4) as usize];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance =
+                ((x as f32 + 0.5 - radius).powi(2) + (y as f32 + 0.5 - radius).powi(2)).sqrt();
+            if distance <= radius {
+                let pixel = ((y * SIZE + x) * 4) as usize;
+                rgba[pixel..pixel + 4].copy_from_slice(&[0xef, 0x44, 0x44, 0xff]);
+            }
+        }
+    }
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
+/// Clear the badge once the user is looking at the window
+pub fn on_main_window_focus(app: &AppHandle, focused: bool) {
+    if focused {
+        if let Err(e) = set(app, 0) {
+            log::warn!("[App] {}", e);
+        }
+    }
+}
+
+/// Set the badge count on the dock icon or taskbar button, with 0 clearing
+/// it (exposed to frontend)
+#[tauri::command]
+pub fn set_badge_count(app: AppHandle, count: u32) -> Result<(), String> {
+    set(&app, count)
+}
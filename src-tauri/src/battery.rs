@@ -0,0 +1,214 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// How often the power source and battery level are read
+const BATTERY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where the system is drawing power from
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerSource {
+    pub on_battery: bool,
+    /// Charge left, if the system has a battery
+    pub battery_percent: Option<u8>,
+}
+
+#[derive(Default)]
+pub struct BatteryState {
+    power: Mutex<PowerSource>,
+    /// Whether the sidecar was last asked to throttle background work
+    throttled: Mutex<bool>,
+}
+
+fn should_throttle(app: &AppHandle, power: &PowerSource) -> bool {
+    let threshold = app
+        .state::<SettingsState>()
+        .get()
+        .throttle_below_battery_percent
+        .filter(|&percent| percent > 0);
+    match (threshold, power.battery_percent) {
+        (Some(threshold), Some(percent)) => power.on_battery && percent < threshold,
+        _ => false,
+    }
+}
+
+/// Ask the sidecar to hold back background work, or stop doing so
+async fn notify_sidecar(app: &AppHandle, agent: &ureq::Agent, throttled: bool) -> bool {
+    let state: State<SidecarState> = app.state();
+    if state.status() != SidecarStatus::Ready {
+        return false;
+    }
+    let url = format!("http://{}:{}/api/throttle", state.host, state.port());
+    let body = serde_json::json!({ "throttled": throttled });
+    match crate::post_sidecar_json(agent, url, state.auth_token.get(), body).await {
+        Ok(()) => true,
+        Err(e) => {
+            log::debug!("[Power] Failed to update server throttling: {}", e);
+            false
+        }
+    }
+}
+
+/// Follow the power source and battery level, emitting
+/// `power://source-changed` when either changes
+///
+/// Below `throttleBelowBatteryPercent` on battery the sidecar is asked to run
+/// fewer automations at once. While throttled it's reminded on every check,
+/// so a sidecar restarted in the meantime is throttled too.
+pub fn spawn_battery_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = crate::health_check_agent();
+        loop {
+            match tauri::async_runtime::spawn_blocking(platform::read).await {
+                Ok(Ok(power)) => {
+                    let state = app.state::<BatteryState>();
+                    let previous =
+                        std::mem::replace(&mut *state.power.lock().unwrap(), power.clone());
+                    if previous != power {
+                        log::debug!("[Power] Power source changed: {:?}", power);
+                        let _ = app.emit("power://source-changed", &power);
+                    }
+
+                    let throttle = should_throttle(&app, &power);
+                    let throttled = *state.throttled.lock().unwrap();
+                    if throttle || throttle != throttled {
+                        if throttle != throttled {
+                            log::info!(
+                                "[Power] {} background work at {}% battery",
+                                if throttle { "Throttling" } else { "Resuming" },
+                                power.battery_percent.unwrap_or_default()
+                            );
+                        }
+                        if notify_sidecar(&app, &agent, throttle).await {
+                            *state.throttled.lock().unwrap() = throttle;
+                        }
+                    }
+                }
+                Ok(Err(e)) => log::debug!("[Power] Failed to read battery state: {}", e),
+                Err(_) => {}
+            }
+            tokio::time::sleep(BATTERY_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Whether the system was on battery at the last reading
+pub fn on_battery(app: &AppHandle) -> bool {
+    app.state::<BatteryState>().power.lock().unwrap().on_battery
+}
+
+/// Whether the system is on battery, and how charged it is (exposed to frontend)
+#[tauri::command]
+pub fn get_power_source(state: State<'_, BatteryState>) -> PowerSource {
+    state.power.lock().unwrap().clone()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::Command;
+
+    use super::PowerSource;
+
+    /// Parse `pmset -g batt`, which prints the source on its first line and
+    /// then a line per battery, with a tab before the charge, like
+    /// `-InternalBattery-0 (id=4653155)  85%; discharging; 3:20 remaining`
+    pub fn read() -> Result<PowerSource, String> {
+        let output = Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .map_err(|e| format!("Failed to run pmset: {}", e))?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        let on_battery = output
+            .lines()
+            .next()
+            .is_some_and(|line| line.contains("'Battery Power'"));
+        let battery_percent = output
+            .lines()
+            .filter(|line| line.contains("InternalBattery"))
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ';'))
+            .find_map(|token| token.strip_suffix('%')?.parse().ok());
+        Ok(PowerSource {
+            on_battery,
+            battery_percent,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    use super::PowerSource;
+
+    /// Set in BatteryFlag when the system has no battery
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    /// BatteryLifePercent when the charge isn't known
+    const UNKNOWN_PERCENT: u8 = 255;
+
+    pub fn read() -> Result<PowerSource, String> {
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        let has_battery = status.BatteryFlag & NO_SYSTEM_BATTERY == 0;
+        Ok(PowerSource {
+            on_battery: has_battery && status.ACLineStatus == 0,
+            battery_percent: (has_battery && status.BatteryLifePercent != UNKNOWN_PERCENT)
+                .then_some(status.BatteryLifePercent),
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::fs;
+    use std::path::Path;
+
+    use super::PowerSource;
+
+    fn read_attribute(supply: &Path, name: &str) -> Option<String> {
+        fs::read_to_string(supply.join(name))
+            .ok()
+            .map(|value| value.trim().to_string())
+    }
+
+    /// Read the power supplies the kernel lists in sysfs, skipping batteries
+    /// of peripherals like wireless mice
+    pub fn read() -> Result<PowerSource, String> {
+        let supplies = fs::read_dir("/sys/class/power_supply")
+            .map_err(|e| format!("Failed to list power supplies: {}", e))?;
+        let mut mains = None;
+        let mut discharging = false;
+        let mut charges = Vec::new();
+        for supply in supplies.filter_map(Result::ok).map(|entry| entry.path()) {
+            match read_attribute(&supply, "type").as_deref() {
+                Some("Mains") => {
+                    let online = read_attribute(&supply, "online").as_deref() == Some("1");
+                    mains = Some(mains.unwrap_or(false) || online);
+                }
+                Some("Battery")
+                    if read_attribute(&supply, "scope").as_deref() != Some("Device") =>
+                {
+                    discharging |=
+                        read_attribute(&supply, "status").as_deref() == Some("Discharging");
+                    charges.extend(
+                        read_attribute(&supply, "capacity").and_then(|c| c.parse::<u32>().ok()),
+                    );
+                }
+                _ => {}
+            }
+        }
+        let battery_percent = (!charges.is_empty())
+            .then(|| (charges.iter().sum::<u32>() / charges.len() as u32).min(100) as u8);
+        Ok(PowerSource {
+            // Without a mains supply listed, go by whether a battery is draining
+            on_battery: battery_percent.is_some() && mains.map_or(discharging, |online| !online),
+            battery_percent,
+        })
+    }
+}
@@ -0,0 +1,84 @@
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsState;
+
+const DEFAULT_HOST: &str = "127.0.0.1";
+/// How long to try each external address when checking the port isn't exposed
+const EXPOSURE_CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Host the sidecar listens on, from PIPALI_HOST or else loopback
+pub fn requested() -> String {
+    std::env::var("PIPALI_HOST")
+        .ok()
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| DEFAULT_HOST.to_string())
+}
+
+/// Whether a host name or address only reaches this machine
+pub fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+fn lan_mode(app: &AppHandle) -> bool {
+    app.state::<SettingsState>().get().lan_mode
+}
+
+/// Refuse to start a sidecar on a host other devices could reach, unless the
+/// user turned on LAN mode
+pub fn check(app: &AppHandle, host: &str) -> Result<(), String> {
+    if is_loopback(host) {
+        return Ok(());
+    }
+    if !lan_mode(app) {
+        return Err(format!(
+            "Refusing to listen on {}, which other devices could reach. Turn on LAN mode to allow it.",
+            host
+        ));
+    }
+    log::warn!(
+        "[Sidecar] LAN mode is on, listening on {} where other devices can reach the server",
+        host
+    );
+    Ok(())
+}
+
+/// Addresses of this machine's other network interfaces on which `port`
+/// accepts connections
+fn exposed_addresses(port: u16) -> Vec<SocketAddr> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            log::warn!("[Sidecar] Failed to list network interfaces: {}", e);
+            return Vec::new();
+        }
+    };
+    interfaces
+        .iter()
+        .filter(|interface| !interface.is_loopback())
+        .map(|interface| SocketAddr::new(interface.ip(), port))
+        // Link-local IPv6 addresses need a scope to connect to
+        .filter(|addr| !matches!(addr.ip(), IpAddr::V6(ip) if ip.segments()[0] & 0xffc0 == 0xfe80))
+        .filter(|addr| TcpStream::connect_timeout(addr, EXPOSURE_CONNECT_TIMEOUT).is_ok())
+        .collect()
+}
+
+/// Check that a sidecar meant to be loopback-only can't be reached through
+/// the machine's other interfaces, in case the server ignored `--host`
+///
+/// Returns the addresses it could be reached on.
+pub async fn verify_not_exposed(app: &AppHandle, host: &str, port: u16) -> Vec<SocketAddr> {
+    if !is_loopback(host) || lan_mode(app) {
+        return Vec::new();
+    }
+    tauri::async_runtime::spawn_blocking(move || exposed_addresses(port))
+        .await
+        .unwrap_or_default()
+}
@@ -0,0 +1,172 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+/// System clipboard, opened on first use
+///
+/// Kept open since on Linux what we copy is only on the clipboard while we
+/// hold it.
+#[derive(Default)]
+pub struct ClipboardState {
+    clipboard: Mutex<Option<arboard::Clipboard>>,
+}
+
+impl ClipboardState {
+    pub fn with<T>(
+        &self,
+        f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+    ) -> Result<T, String> {
+        let mut clipboard = self.clipboard.lock().unwrap();
+        if clipboard.is_none() {
+            *clipboard = Some(
+                arboard::Clipboard::new()
+                    .map_err(|e| format!("Failed to open clipboard: {}", e))?,
+            );
+        }
+        f(clipboard.as_mut().unwrap()).map_err(|e| format!("Clipboard error: {}", e))
+    }
+}
+
+/// An image from the clipboard, saved where it can be attached to a message
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardImage {
+    pub path: PathBuf,
+    pub name: String,
+    pub size_bytes: u64,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Nothing of the asked-for kind on the clipboard isn't an error
+fn optional<T>(result: Result<T, arboard::Error>) -> Result<Option<T>, arboard::Error> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Encode 8-bit RGBA pixels as a PNG file
+pub fn write_png(path: &Path, width: usize, height: usize, rgba: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create image: {}", e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|e| format!("Failed to encode image: {}", e))
+}
+
+/// Decode a PNG to 8-bit RGBA, as the clipboard takes it
+fn read_png(path: &Path) -> Result<arboard::ImageData<'static>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(
+        png::Transformations::normalize_to_color8() | png::Transformations::ALPHA,
+    );
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    buf.truncate(info.buffer_size());
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        other => return Err(format!("Unsupported image color type: {:?}", other)),
+    };
+    Ok(arboard::ImageData {
+        width: info.width as usize,
+        height: info.height as usize,
+        bytes: rgba.into(),
+    })
+}
+
+/// Save the image on the clipboard to the attachments dir, for pasting a
+/// screenshot into the chat (exposed to frontend)
+///
+/// Returns `None` if there's no image on the clipboard. Webviews only see
+/// clipboard images on some platforms, so pasting goes through the shell.
+#[tauri::command]
+pub async fn read_clipboard_image(
+    app: AppHandle,
+    state: State<'_, ClipboardState>,
+) -> Result<Option<ClipboardImage>, String> {
+    let Some(image) = state.with(|clipboard| optional(clipboard.get_image()))? else {
+        return Ok(None);
+    };
+
+    let dir = crate::get_attachments_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachments dir: {}", e))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let name = format!("pasted-image-{}.png", timestamp);
+    let path = dir.join(&name);
+    write_png(&path, image.width, image.height, &image.bytes)?;
+    let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    log::info!(
+        "[App] Saved {}x{} clipboard image to {}",
+        image.width,
+        image.height,
+        path.display()
+    );
+
+    Ok(Some(ClipboardImage {
+        path,
+        name,
+        size_bytes,
+        width: image.width,
+        height: image.height,
+    }))
+}
+
+/// Copy a PNG image file to the clipboard (exposed to frontend)
+#[tauri::command]
+pub async fn write_clipboard_image(
+    state: State<'_, ClipboardState>,
+    path: String,
+) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+    {
+        return Err("Only PNG images can be copied".to_string());
+    }
+    let image = read_png(&path)?;
+    state.with(|clipboard| clipboard.set_image(image))
+}
+
+/// Get the HTML on the clipboard, or `None` if there's only plain text or
+/// nothing (exposed to frontend)
+#[tauri::command]
+pub async fn read_clipboard_html(
+    state: State<'_, ClipboardState>,
+) -> Result<Option<String>, String> {
+    state.with(|clipboard| optional(clipboard.get().html()))
+}
+
+/// Copy rich text to the clipboard, with plain text for apps that don't take
+/// HTML (exposed to frontend)
+#[tauri::command]
+pub async fn write_clipboard_html(
+    state: State<'_, ClipboardState>,
+    html: String,
+    alt_text: Option<String>,
+) -> Result<(), String> {
+    state.with(|clipboard| clipboard.set_html(html, alt_text))
+}
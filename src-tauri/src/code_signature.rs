@@ -0,0 +1,194 @@
+use std::path::Path;
+
+/// Lets developers run unsigned sidecar builds, e.g. a locally built Bun
+const ALLOW_UNSIGNED_ENV: &str = "PIPALI_ALLOW_UNSIGNED_SIDECAR";
+
+/// Refuse to launch a sidecar binary without a valid signature, so a binary
+/// planted in the bundle or updates folder never gets to run with the user's
+/// data directory. When the app itself is signed, the binary must also be
+/// signed by the same team on macOS, or the same publisher on Windows.
+///
+/// Skipped for debug builds and `PIPALI_SIDECAR_PATH` overrides, which are
+/// unsigned by nature, and when PIPALI_ALLOW_UNSIGNED_SIDECAR=1 is set.
+pub fn verify(program: &Path) -> Result<(), String> {
+    if cfg!(debug_assertions) || crate::sidecar_path_override().is_some() {
+        return Ok(());
+    }
+    if std::env::var(ALLOW_UNSIGNED_ENV).is_ok_and(|value| value == "1") {
+        log::warn!(
+            "[Sidecar] {} is set, not checking the signature of {:?}",
+            ALLOW_UNSIGNED_ENV,
+            program
+        );
+        return Ok(());
+    }
+    platform::verify(program).map_err(|e| {
+        format!(
+            "Refusing to run {:?}: {}. Set {}=1 to run it anyway.",
+            program, e, ALLOW_UNSIGNED_ENV
+        )
+    })
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Team the binary at `path` is signed by, from `codesign -dv`, which
+    /// prints lines like `TeamIdentifier=ABCDE12345` to stderr
+    fn team_id(path: &Path) -> Result<Option<String>, String> {
+        let output = Command::new("codesign")
+            .args(["-dv", "--verbose=2"])
+            .arg(path)
+            .output()
+            .map_err(|e| format!("Failed to run codesign: {}", e))?;
+        Ok(String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .find_map(|line| line.strip_prefix("TeamIdentifier="))
+            .filter(|team| *team != "not set")
+            .map(str::to_string))
+    }
+
+    pub fn verify(program: &Path) -> Result<(), String> {
+        let status = Command::new("codesign")
+            .args(["--verify", "--strict"])
+            .arg(program)
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map_err(|e| format!("Failed to run codesign: {}", e))?;
+        if !status.success() {
+            return Err("it has no valid code signature".to_string());
+        }
+
+        let exe =
+            std::env::current_exe().map_err(|e| format!("Failed to locate the app: {}", e))?;
+        let ours = team_id(&exe)?;
+        if ours.is_some() && team_id(program)? != ours {
+            return Err("it's signed by a different developer than Pipali".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows_sys::Win32::Security::Cryptography::{
+        CertGetNameStringW, CERT_NAME_SIMPLE_DISPLAY_TYPE,
+    };
+    use windows_sys::Win32::Security::WinTrust::{
+        WTHelperGetProvCertFromChain, WTHelperGetProvSignerFromChain,
+        WTHelperProvDataFromStateData, WinVerifyTrust, WINTRUST_ACTION_GENERIC_VERIFY_V2,
+        WINTRUST_DATA, WINTRUST_FILE_INFO, WTD_CHOICE_FILE, WTD_REVOKE_NONE, WTD_STATEACTION_CLOSE,
+        WTD_STATEACTION_VERIFY, WTD_UI_NONE,
+    };
+
+    /// Name of the certificate that signed a verified file, from the state
+    /// WinVerifyTrust keeps until it's closed
+    unsafe fn signer_name(state: *mut std::ffi::c_void) -> Option<String> {
+        let provider = WTHelperProvDataFromStateData(state);
+        if provider.is_null() {
+            return None;
+        }
+        let signer = WTHelperGetProvSignerFromChain(provider, 0, 0, 0);
+        if signer.is_null() {
+            return None;
+        }
+        let cert = WTHelperGetProvCertFromChain(signer, 0);
+        if cert.is_null() || (*cert).pCert.is_null() {
+            return None;
+        }
+        let mut name = [0u16; 256];
+        let len = CertGetNameStringW(
+            (*cert).pCert,
+            CERT_NAME_SIMPLE_DISPLAY_TYPE,
+            0,
+            std::ptr::null(),
+            name.as_mut_ptr(),
+            name.len() as u32,
+        );
+        // The length counts the terminating null, and is 1 for no name
+        (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+    }
+
+    /// Check the binary's Authenticode signature chains to a trusted root,
+    /// and return the name of the certificate it's signed with
+    fn signer(program: &Path) -> Result<Option<String>, String> {
+        let path: Vec<u16> = program
+            .as_os_str()
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+        let mut file = WINTRUST_FILE_INFO {
+            cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+            pcwszFilePath: path.as_ptr(),
+            hFile: std::ptr::null_mut(),
+            pgKnownSubject: std::ptr::null_mut(),
+        };
+        // SAFETY: WINTRUST_DATA is plain data, for which zeroes are valid
+        let mut data: WINTRUST_DATA = unsafe { std::mem::zeroed() };
+        data.cbStruct = std::mem::size_of::<WINTRUST_DATA>() as u32;
+        data.dwUIChoice = WTD_UI_NONE;
+        // Revocation checks need the network, which may be down at launch
+        data.fdwRevocationChecks = WTD_REVOKE_NONE;
+        data.dwUnionChoice = WTD_CHOICE_FILE;
+        data.Anonymous.pFile = &mut file;
+        data.dwStateAction = WTD_STATEACTION_VERIFY;
+
+        let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+        // SAFETY: data and file outlive both calls, the signer is read before
+        // the second call releases the state the first one allocated
+        let (result, name) = unsafe {
+            let result = WinVerifyTrust(
+                std::ptr::null_mut(),
+                &mut action,
+                &mut data as *mut _ as *mut _,
+            );
+            let name = if result == 0 {
+                signer_name(data.hWVTStateData)
+            } else {
+                None
+            };
+            data.dwStateAction = WTD_STATEACTION_CLOSE;
+            WinVerifyTrust(
+                std::ptr::null_mut(),
+                &mut action,
+                &mut data as *mut _ as *mut _,
+            );
+            (result, name)
+        };
+        if result != 0 {
+            return Err(format!(
+                "it has no trusted signature (0x{:08x})",
+                result as u32
+            ));
+        }
+        Ok(name)
+    }
+
+    /// Check the binary has a trusted signature, from the same publisher as
+    /// the app when the app itself is signed
+    pub fn verify(program: &Path) -> Result<(), String> {
+        let theirs = signer(program)?;
+        let exe =
+            std::env::current_exe().map_err(|e| format!("Failed to locate the app: {}", e))?;
+        if let Ok(Some(ours)) = signer(&exe) {
+            if theirs.as_deref() != Some(ours.as_str()) {
+                return Err("it's signed by a different publisher than Pipali".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod platform {
+    use std::path::Path;
+
+    /// Linux packages don't carry code signatures to check
+    pub fn verify(_program: &Path) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use tauri::ipc::Invoke;
+
+use crate::{conversation_windows, quick_ask};
+
+/// What a window is allowed to ask of the shell
+enum Scope {
+    /// The full app, i.e. the main window
+    All,
+    Only {
+        commands: &'static [&'static str],
+        /// API paths it may reach through the `pipali-api` protocol, or
+        /// `None` for any the protocol serves
+        api_paths: Option<&'static [&'static str]>,
+    },
+}
+
+/// The quick-ask bar sends prompts and hands off to the main window, but
+/// can't reach secrets, settings or the server's lifecycle
+const QUICK_ASK: Scope = Scope::Only {
+    commands: &["ensure_sidecar_running", "hide_quick_ask", "open_deep_link"],
+    api_paths: Some(&["/api/chat"]),
+};
+/// Conversation windows chat, attach and open files and use the clipboard,
+/// but settings, secrets and the server's lifecycle are left to the main one
+const CONVERSATION: Scope = Scope::Only {
+    commands: &[
+        "acquire_wake_lock",
+        "capture_screen",
+        "confirm_natively",
+        "ensure_sidecar_running",
+        "focus_window",
+        "get_always_on_top",
+        "get_network_status",
+        "get_power_source",
+        "get_privacy_mode",
+        "get_server_info",
+        "get_sidecar_config",
+        "get_sidecar_host",
+        "get_sidecar_port",
+        "get_sidecar_status",
+        "get_system_theme",
+        "get_websocket_url",
+        "list_capture_targets",
+        "ocr_image",
+        "open_conversation_window",
+        "open_deep_link",
+        "open_path",
+        "pick_files",
+        "read_clipboard_html",
+        "read_clipboard_image",
+        "release_wake_lock",
+        "reveal_in_file_manager",
+        "show_context_menu",
+        "start_drag",
+        "write_clipboard_html",
+        "write_clipboard_image",
+    ],
+    api_paths: None,
+};
+/// Windows without a scope of their own, like the splash screen, get nothing
+const NONE: Scope = Scope::Only {
+    commands: &[],
+    api_paths: Some(&[]),
+};
+
+fn scope(label: &str) -> &'static Scope {
+    match label {
+        "main" => &Scope::All,
+        quick_ask::LABEL => &QUICK_ASK,
+        label if conversation_windows::is_conversation_window(label) => &CONVERSATION,
+        _ => &NONE,
+    }
+}
+
+/// Whether a window may call a command
+fn allows_command(label: &str, command: &str) -> bool {
+    match scope(label) {
+        Scope::All => true,
+        Scope::Only { commands, .. } => commands.contains(&command),
+    }
+}
+
+/// Whether a window may reach an API path through the shell's protocol
+pub fn allows_api_path(label: &str, path: &str) -> bool {
+    match scope(label) {
+        Scope::All => true,
+        Scope::Only { api_paths, .. } => api_paths.is_none_or(|paths| paths.contains(&path)),
+    }
+}
+
+/// Turn away a command call the calling window isn't allowed to make,
+/// handing the call back if it is allowed
+pub fn check(invoke: Invoke) -> Option<Invoke> {
+    let label = invoke.message.webview().label().to_string();
+    let command = invoke.message.command().to_string();
+    if allows_command(&label, &command) {
+        return Some(invoke);
+    }
+    log::warn!("[App] Window {:?} isn't allowed to call {}", label, command);
+    invoke
+        .resolver
+        .reject(format!("{} isn't available in this window", command));
+    None
+}
@@ -1,19 +1,38 @@
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use serde::Serialize;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::{show_window, start_sidecar, stop_sidecar, SidecarState};
+use crate::settings::SettingsState;
+use crate::sidecar_log::SidecarLogLine;
+use crate::{
+    check_sidecar_health, complete_sidecar_startup, health_check_agent, is_port_available,
+    port_pinned_by_env, set_sidecar_status, show_window, start_sidecar, stop_sidecar,
+    wait_for_sidecar_ready, ServerInfo, SidecarState, SidecarStatus,
+};
 
 #[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SidecarConfig {
     pub host: String,
     pub port: u16,
+    /// Port of the sidecar's TLS listener, when the local TLS setting is on
+    pub tls_port: Option<u16>,
+}
+
+/// Payload for the sidecar://port-changed event
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarPortChangedPayload {
+    pub port: u16,
+    pub base_url: String,
 }
 
 /// Get the sidecar port (exposed to frontend)
 #[tauri::command]
 pub fn get_sidecar_port(state: State<'_, SidecarState>) -> u16 {
-    state.port
+    state.port()
 }
 
 /// Get the sidecar host (exposed to frontend)
@@ -23,21 +42,300 @@ pub fn get_sidecar_host(state: State<'_, SidecarState>) -> String {
 }
 
 /// Get the sidecar config (host and port) - exposed to frontend
+///
+/// The auth token is left out, as API requests go through the `pipali-api`
+/// protocol and WebSockets use tickets from `get_websocket_url`.
 #[tauri::command]
 pub fn get_sidecar_config(state: State<'_, SidecarState>) -> SidecarConfig {
     SidecarConfig {
         host: state.host.clone(),
-        port: state.port,
+        port: state.port(),
+        tls_port: state.tls_port(),
+    }
+}
+
+/// Get the sidecar lifecycle status (exposed to frontend)
+#[tauri::command]
+pub fn get_sidecar_status(state: State<'_, SidecarState>) -> SidecarStatus {
+    state.status()
+}
+
+/// Get the server's version, database schema version and configured providers
+/// (exposed to frontend)
+///
+/// Asks the server afresh, falling back to what it last reported if it can't
+/// be reached right now.
+#[tauri::command]
+pub async fn get_server_info(state: State<'_, SidecarState>) -> Result<ServerInfo, String> {
+    let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+    match check_sidecar_health(&health_check_agent(), &health_url).await {
+        Ok(info) => {
+            state.set_server_info(info.clone());
+            Ok(info)
+        }
+        Err(e) => state
+            .server_info()
+            .ok_or_else(|| format!("Server info unavailable: {}", e)),
     }
 }
 
+/// Get the most recent sidecar output lines, oldest first (exposed to frontend)
+#[tauri::command]
+pub fn get_sidecar_logs(
+    state: State<'_, SidecarState>,
+    last_n: Option<usize>,
+) -> Vec<SidecarLogLine> {
+    state.logs.recent(last_n.unwrap_or(200))
+}
+
+/// Least time between restarts the frontend asks for, so a misbehaving page
+/// can't keep the server down by asking in a loop
+const RESTART_COOLDOWN: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+pub struct RestartState {
+    /// Held while a restart is under way, which other requests wait on
+    in_progress: tokio::sync::Mutex<()>,
+    /// When the frontend last had a restart go ahead
+    last_requested: Mutex<Option<Instant>>,
+}
+
+/// Let a restart the frontend asked for go ahead, unless it's within
+/// [`RESTART_COOLDOWN`] of the last one. Every command that restarts the
+/// sidecar calls this before doing anything else.
+///
+/// A request made while a restart is under way joins that restart instead,
+/// so it isn't refused.
+pub fn reserve_restart(app: &AppHandle) -> Result<(), String> {
+    let state: State<RestartState> = app.state();
+    if state.in_progress.try_lock().is_err() {
+        return Ok(());
+    }
+    let mut last_requested = state.last_requested.lock().unwrap();
+    if let Some(wait) = last_requested.and_then(|last| RESTART_COOLDOWN.checked_sub(last.elapsed()))
+    {
+        return Err(format!(
+            "The server was just restarted, try again in {}s",
+            wait.as_secs() + 1
+        ));
+    }
+    *last_requested = Some(Instant::now());
+    Ok(())
+}
+
 /// Restart the sidecar (exposed to frontend)
+///
+/// Resolves once the new server has passed a health check, so the frontend
+/// can reconnect straight away. Refused within [`RESTART_COOLDOWN`] of the
+/// last restart it asked for.
 #[tauri::command]
 pub async fn restart_sidecar(app: AppHandle) -> Result<(), String> {
-    stop_sidecar(&app)?;
-    // Small delay to ensure clean shutdown
-    std::thread::sleep(Duration::from_millis(500));
-    start_sidecar(&app)
+    reserve_restart(&app)?;
+    restart(&app).await
+}
+
+/// Restart the sidecar, or wait for the restart already under way
+///
+/// Concurrent requests share one restart, which emits `sidecar-ready` like a
+/// supervised restart does.
+pub async fn restart(app: &AppHandle) -> Result<(), String> {
+    let restart_state: State<RestartState> = app.state();
+    let Ok(_restarting) = restart_state.in_progress.try_lock() else {
+        drop(restart_state.in_progress.lock().await);
+        return match app.state::<SidecarState>().status() {
+            SidecarStatus::Ready => Ok(()),
+            _ => Err("The server failed to restart".to_string()),
+        };
+    };
+
+    // Stopping blocks until the old process has terminated
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || stop_sidecar(&handle))
+        .await
+        .map_err(|e| format!("Stop task failed: {}", e))??;
+
+    let state: State<SidecarState> = app.state();
+    state.reset_restarts();
+    start_sidecar(app)?;
+    wait_for_sidecar_ready(app)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+    let info = check_sidecar_health(&health_check_agent(), &health_url)
+        .await
+        .map_err(|e| format!("Restarted server failed its health check: {}", e))?;
+    state.set_server_info(info);
+    set_sidecar_status(app, SidecarStatus::Ready);
+    let _ = app.emit("sidecar-ready", ());
+    Ok(())
+}
+
+/// Start the sidecar if it isn't running yet, e.g. with lazy start or after
+/// it was stopped (exposed to frontend)
+///
+/// Resolves once the server is ready. Calls made while a start is under way
+/// wait for that start rather than beginning another.
+#[tauri::command]
+pub async fn ensure_sidecar_running(app: AppHandle) -> Result<(), String> {
+    let state: State<SidecarState> = app.state();
+    let _starting = state.start_lock.lock().await;
+
+    match state.status() {
+        SidecarStatus::Ready | SidecarStatus::Unhealthy => Ok(()),
+        SidecarStatus::Starting => wait_for_sidecar_ready(&app)
+            .await
+            .map_err(|e| e.to_string()),
+        // The restart supervisor is already bringing it back
+        SidecarStatus::Restarting | SidecarStatus::Crashed => {
+            Err("Server is restarting, try again shortly".to_string())
+        }
+        SidecarStatus::Stopped => {
+            log::info!("[Sidecar] Starting on first use");
+            start_sidecar(&app)?;
+            if !complete_sidecar_startup(&app).await {
+                return Err("Server is not compatible with this app".to_string());
+            }
+            match state.status() {
+                SidecarStatus::Ready => Ok(()),
+                _ => Err("Server failed to become ready".to_string()),
+            }
+        }
+    }
+}
+
+/// Move the sidecar to another port and remember it for future launches
+/// (exposed to frontend)
+///
+/// The sidecar is restarted on the new port, then `sidecar://port-changed`
+/// tells the webview its new base URL.
+#[tauri::command]
+pub async fn set_sidecar_port(app: AppHandle, port: u16) -> Result<(), String> {
+    let state: State<SidecarState> = app.state();
+    if state.external {
+        return Err("Can't change the port of an external server".to_string());
+    }
+    if port_pinned_by_env() {
+        return Err("The port is set by PIPALI_PORT, which takes precedence".to_string());
+    }
+    if port < 1024 {
+        return Err(format!(
+            "Port {} is reserved, choose one from 1024 up",
+            port
+        ));
+    }
+
+    let unchanged = port == state.port() && state.child.lock().unwrap().is_some();
+    if !unchanged && !is_port_available(&state.host, port) {
+        return Err(format!("Port {} is already in use", port));
+    }
+    if !unchanged {
+        reserve_restart(&app)?;
+    }
+    app.state::<SettingsState>()
+        .update(|settings| settings.set_saved_sidecar_port(port))?;
+    if unchanged {
+        return Ok(());
+    }
+
+    restart(&app).await?;
+    let port = state.port();
+    let _ = app.emit(
+        "sidecar://port-changed",
+        SidecarPortChangedPayload {
+            port,
+            base_url: match state.tls_port() {
+                Some(tls_port) => format!("https://{}:{}", state.host, tls_port),
+                None => format!("http://{}:{}", state.host, port),
+            },
+        },
+    );
+    Ok(())
+}
+
+/// Get the extra environment variables passed to the sidecar (exposed to frontend)
+#[tauri::command]
+pub fn get_sidecar_env(settings: State<'_, SettingsState>) -> BTreeMap<String, String> {
+    settings.get().env
+}
+
+/// Replace the sidecar's extra environment variables (exposed to frontend)
+///
+/// They take effect the next time the sidecar starts; pass `restart` to
+/// restart it now so they apply straight away.
+#[tauri::command]
+pub async fn set_sidecar_env(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    env: BTreeMap<String, String>,
+    restart: Option<bool>,
+) -> Result<(), String> {
+    if let Some(name) = env
+        .keys()
+        .find(|name| name.is_empty() || name.contains(['=', '\0']))
+    {
+        return Err(format!("Invalid environment variable name: {:?}", name));
+    }
+    if restart.unwrap_or(false) {
+        reserve_restart(&app)?;
+    }
+    settings.update(|settings| settings.env = env)?;
+
+    if restart.unwrap_or(false) {
+        self::restart(&app).await?;
+    }
+    Ok(())
+}
+
+/// Choose whether Pipali starts in the tray without showing its window
+/// (exposed to frontend)
+#[tauri::command]
+pub fn set_start_hidden(settings: State<'_, SettingsState>, enabled: bool) -> Result<(), String> {
+    settings.update(|settings| settings.start_hidden = enabled)
+}
+
+/// Choose whether the shell raises OS notifications for automations and
+/// server errors (exposed to frontend)
+#[tauri::command]
+pub fn set_native_notifications(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<(), String> {
+    settings.update(|settings| settings.native_notifications = enabled)
+}
+
+/// Whether Pipali starts when the user logs in (exposed to frontend)
+#[tauri::command]
+pub fn get_autostart(app: AppHandle) -> Result<bool, String> {
+    use tauri_plugin_autostart::ManagerExt;
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| format!("Failed to read autostart state: {}", e))
+}
+
+/// Start Pipali, hidden in the tray, when the user logs in (exposed to frontend)
+#[tauri::command]
+pub fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
+    use tauri_plugin_autostart::ManagerExt;
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| format!("Failed to update autostart: {}", e))?;
+    log::info!(
+        "[App] Autostart {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Choose whether closing the main window hides it to the tray or quits
+/// (exposed to frontend)
+#[tauri::command]
+pub fn set_close_to_tray(settings: State<'_, SettingsState>, enabled: bool) -> Result<(), String> {
+    settings.update(|settings| settings.close_to_tray = enabled)
 }
 
 /// Show the app window and add it to the dock (exposed to frontend)
@@ -45,3 +343,15 @@ pub async fn restart_sidecar(app: AppHandle) -> Result<(), String> {
 pub fn focus_window(app: AppHandle) {
     show_window(&app);
 }
+
+/// Open the active profile's data directory, with its database and
+/// attachments, in the system file manager (exposed to frontend)
+#[tauri::command]
+pub fn open_data_dir(app: AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+    let dir = crate::normalize_windows_path(crate::get_sidecar_data_dir(&app)?);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open data directory: {}", e))
+}
@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow, Wry};
+
+use crate::clipboard::ClipboardState;
+
+const COPY: &str = "context_copy";
+const COPY_MARKDOWN: &str = "context_copy_markdown";
+/// Followed by the index of the attachment
+const REVEAL_PREFIX: &str = "context_reveal_";
+const RETRY: &str = "context_retry";
+
+/// The chat message right-clicked in the webview
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextMenuTarget {
+    message_id: String,
+    /// The selected text, or the whole message as shown
+    text: String,
+    /// The message as written
+    markdown: String,
+    /// Paths of files attached to the message
+    attachments: Vec<String>,
+    can_retry: bool,
+}
+
+/// The target of the open context menu, with the label of its window
+#[derive(Default)]
+pub struct ContextMenuState {
+    target: Mutex<Option<(String, ContextMenuTarget)>>,
+}
+
+fn reveal_label(name: &str) -> String {
+    if cfg!(target_os = "macos") {
+        format!("Reveal {} in Finder", name)
+    } else if cfg!(target_os = "windows") {
+        format!("Show {} in Explorer", name)
+    } else {
+        format!("Show {} in Folder", name)
+    }
+}
+
+fn build(window: &WebviewWindow, target: &ContextMenuTarget) -> tauri::Result<Menu<Wry>> {
+    let mut menu = MenuBuilder::new(window)
+        .item(
+            &MenuItemBuilder::with_id(COPY, "Copy")
+                .enabled(!target.text.is_empty())
+                .build(window)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id(COPY_MARKDOWN, "Copy as Markdown")
+                .enabled(!target.markdown.is_empty())
+                .build(window)?,
+        );
+    if !target.attachments.is_empty() {
+        menu = menu.separator();
+        for (index, path) in target.attachments.iter().enumerate() {
+            let name = Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned());
+            menu = menu.text(format!("{}{}", REVEAL_PREFIX, index), reveal_label(&name));
+        }
+    }
+    if target.can_retry {
+        menu = menu.separator().text(RETRY, "Retry");
+    }
+    menu.build()
+}
+
+fn copy(app: &AppHandle, text: String) {
+    let result = app
+        .state::<ClipboardState>()
+        .with(|clipboard| clipboard.set_text(text));
+    if let Err(e) = result {
+        log::warn!("[App] Failed to copy message: {}", e);
+    }
+}
+
+fn handle(app: &AppHandle, id: &str) {
+    if !id.starts_with("context_") {
+        return;
+    }
+    let Some((label, target)) = app
+        .state::<ContextMenuState>()
+        .target
+        .lock()
+        .unwrap()
+        .take()
+    else {
+        return;
+    };
+    match id {
+        COPY => copy(app, target.text),
+        COPY_MARKDOWN => copy(app, target.markdown),
+        RETRY => {
+            let _ = app.emit_to(label.as_str(), "context-menu://retry", &target.message_id);
+        }
+        _ => {
+            let path = id
+                .strip_prefix(REVEAL_PREFIX)
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| target.attachments.get(index));
+            if let Some(path) = path {
+                if let Err(e) = crate::file_opener::reveal(app, path) {
+                    log::warn!("[App] Failed to reveal attachment: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Handle picks from chat context menus, which come in as app menu events
+pub fn setup(app: &AppHandle) {
+    app.on_menu_event(|app, event| handle(app, event.id().as_ref()));
+}
+
+/// Show a native context menu for a chat message, in place of the webview's
+/// own (exposed to frontend)
+///
+/// Copying and revealing attachments are done here. Retry is sent back to the
+/// window as `context-menu://retry`, with the message id.
+#[tauri::command]
+pub fn show_context_menu(
+    window: WebviewWindow,
+    state: State<'_, ContextMenuState>,
+    target: ContextMenuTarget,
+) -> Result<(), String> {
+    let menu = build(&window, &target).map_err(|e| format!("Failed to build menu: {}", e))?;
+    *state.target.lock().unwrap() = Some((window.label().to_string(), target));
+    window
+        .popup_menu(&menu)
+        .map_err(|e| format!("Failed to show menu: {}", e))
+}
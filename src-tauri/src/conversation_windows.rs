@@ -0,0 +1,80 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::window_state;
+
+/// Prefix of the labels of windows showing a single conversation, followed by
+/// its id
+const LABEL_PREFIX: &str = "conversation-";
+
+/// How far each conversation window opened is moved from the one before it
+const CASCADE_OFFSET: i32 = 32;
+
+pub fn is_conversation_window(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+/// Open a conversation in a window of its own, or bring forward the window
+/// already showing it
+pub fn open(app: &AppHandle, conversation_id: &str) -> Result<(), String> {
+    // Ids end up in the window label and URL
+    if conversation_id.is_empty()
+        || !conversation_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(format!("Invalid conversation id: {}", conversation_id));
+    }
+    if crate::app_lock::is_locked(app) {
+        crate::show_window(app);
+        return Err("Pipali is locked".to_string());
+    }
+    let label = format!("{}{}", LABEL_PREFIX, conversation_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    let open_windows = app
+        .webview_windows()
+        .keys()
+        .filter(|label| is_conversation_window(label))
+        .count() as i32;
+    let url = format!("index.html?conversationId={}", conversation_id);
+    let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+        .title("Pipali")
+        .inner_size(720.0, 800.0)
+        .min_inner_size(480.0, 400.0)
+        .visible(false)
+        .build()
+        .map_err(|e| format!("Failed to create conversation window: {}", e))?;
+    window_state::restore_conversation_window(&window, open_windows * CASCADE_OFFSET);
+    window.show().map_err(|e| e.to_string())?;
+    let _ = window.set_focus();
+    log::info!(
+        "[App] Opened conversation {} in a new window",
+        conversation_id
+    );
+    Ok(())
+}
+
+/// Save a conversation window's bounds as it closes
+pub fn on_close_requested(app: &AppHandle, label: &str) {
+    if let Some(window) = app.get_webview_window(label) {
+        window_state::save_conversation_window(&window);
+    }
+}
+
+/// Open a conversation in a separate window, to keep it in view while working
+/// in another (exposed to frontend)
+///
+/// Async since creating a window from a synchronous command deadlocks on
+/// Windows.
+#[tauri::command]
+pub async fn open_conversation_window(
+    app: AppHandle,
+    conversation_id: String,
+) -> Result<(), String> {
+    open(&app, &conversation_id)
+}
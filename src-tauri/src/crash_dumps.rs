@@ -0,0 +1,392 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::SettingsState;
+use crate::ServerEntry;
+
+/// Dumps larger than this are discarded rather than kept
+const MAX_DUMP_BYTES: u64 = 512 * 1024 * 1024;
+/// Only the newest dumps are kept, within both of these caps
+const MAX_DUMPS: usize = 5;
+const MAX_TOTAL_BYTES: u64 = 1024 * 1024 * 1024;
+/// Dumps and crash reports are written by the OS some time after the exit
+const COLLECT_ATTEMPTS: u32 = 10;
+const COLLECT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// A crash dump or crash report of the sidecar
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashDump {
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// When the dump was written, in milliseconds since the Unix epoch
+    pub created_at: u64,
+}
+
+pub fn crash_dumps_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::normalize_windows_path(crate::get_app_data_dir(app)?).join("crash-dumps"))
+}
+
+/// Let the OS write a dump if the sidecar crashes
+///
+/// On Linux the sidecar's core size limit is raised, on Windows Error
+/// Reporting is told to write minidumps of the server executable to our
+/// crash dumps dir. macOS always writes crash reports.
+pub fn enable(app: &AppHandle, server: &ServerEntry, pid: u32) {
+    if !app.state::<SettingsState>().get().capture_crash_dumps {
+        return;
+    }
+    let dir = match crash_dumps_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("[CrashDump] {}", e);
+            return;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("[CrashDump] Failed to create crash dumps dir: {}", e);
+        return;
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Err(e) = run("prlimit", &["--pid", &pid.to_string(), "--core=unlimited:"]) {
+        log::debug!("[CrashDump] Could not raise core size limit: {}", e);
+    }
+
+    #[cfg(windows)]
+    {
+        let exe = if server.run_with_bun {
+            "bun.exe".to_string()
+        } else {
+            server
+                .entry_point
+                .file_name()
+                .map_or("pipali-server.exe".to_string(), |name| {
+                    name.to_string_lossy().to_string()
+                })
+        };
+        if let Err(e) = windows::register_local_dumps(&exe, &dir) {
+            log::warn!("[CrashDump] Could not enable minidumps: {}", e);
+        }
+    }
+
+    let _ = (server, pid);
+}
+
+/// Whether an exit looks like a crash that may have left a dump
+pub fn is_crash(code: Option<i32>, signal: Option<i32>) -> bool {
+    // Signals we or the user send to stop the server don't dump
+    let crash_signal = signal.is_some_and(|signal| !matches!(signal, 1 | 2 | 9 | 15));
+    // NTSTATUS error codes, e.g. access violations
+    let crash_code = cfg!(windows) && code.is_some_and(|code| code as u32 >= 0xC000_0000);
+    crash_signal || crash_code
+}
+
+/// Look for a dump of the crashed sidecar and move it into the crash dumps
+/// dir, dropping old dumps beyond the caps. Emits `sidecar://crash-dump`.
+pub fn collect(app: &AppHandle, pid: u32, data_dir: &Path, started: SystemTime) {
+    if !app.state::<SettingsState>().get().capture_crash_dumps {
+        return;
+    }
+    let result = crash_dumps_dir(app).and_then(|dir| {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create crash dumps dir: {}", e))?;
+        let mut found = None;
+        for attempt in 1..=COLLECT_ATTEMPTS {
+            found = find_dump(app, pid, data_dir, started, &dir)?;
+            if found.is_some() || attempt == COLLECT_ATTEMPTS {
+                break;
+            }
+            std::thread::sleep(COLLECT_RETRY_DELAY);
+        }
+        let Some(path) = found else {
+            return Ok(None);
+        };
+
+        let size = std::fs::metadata(&path).map_or(0, |meta| meta.len());
+        if size > MAX_DUMP_BYTES {
+            let _ = std::fs::remove_file(&path);
+            return Err(format!(
+                "Discarded {}MB crash dump, above the {}MB cap",
+                size / 1024 / 1024,
+                MAX_DUMP_BYTES / 1024 / 1024
+            ));
+        }
+        prune(&dir);
+        Ok(describe(&path))
+    });
+
+    match result {
+        Ok(Some(dump)) => {
+            log::info!("[CrashDump] Saved sidecar crash dump to {:?}", dump.path);
+            let _ = app.emit("sidecar://crash-dump", &dump);
+        }
+        Ok(None) => log::info!("[CrashDump] No crash dump found for pid {}", pid),
+        Err(e) => log::warn!("[CrashDump] {}", e),
+    }
+}
+
+/// Name a collected dump after when and which sidecar crashed
+fn dump_path(dir: &Path, pid: u32, extension: &str) -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    dir.join(format!("sidecar-{}-{}.{}", secs, pid, extension))
+}
+
+/// Move a file, copying when the source is on another filesystem
+#[cfg(not(target_os = "macos"))]
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to).map_err(|e| format!("Failed to copy crash dump: {}", e))?;
+    let _ = std::fs::remove_file(from);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Core dumps go wherever `core_pattern` says: a file relative to the
+/// sidecar's working dir (its data dir), or systemd-coredump
+#[cfg(target_os = "linux")]
+fn find_dump(
+    _app: &AppHandle,
+    pid: u32,
+    data_dir: &Path,
+    _started: SystemTime,
+    dir: &Path,
+) -> Result<Option<PathBuf>, String> {
+    let pattern = std::fs::read_to_string("/proc/sys/kernel/core_pattern")
+        .map_err(|e| format!("Failed to read core_pattern: {}", e))?;
+    let pattern = pattern.trim();
+    let dest = dump_path(dir, pid, "core");
+
+    if let Some(handler) = pattern.strip_prefix('|') {
+        if !handler.contains("systemd-coredump") {
+            return Err(format!("Core dumps are handled by {}", handler));
+        }
+        let pid = pid.to_string();
+        let output = format!("--output={}", dest.to_string_lossy());
+        return Ok(run(
+            "coredumpctl",
+            &["--no-pager", "--quiet", &output, "dump", &pid],
+        )
+        .ok()
+        .filter(|_| dest.is_file())
+        .map(|_| dest));
+    }
+
+    if pattern.replace("%%", "").replace("%p", "").contains('%') {
+        return Err(format!("Unsupported core_pattern: {}", pattern));
+    }
+    let mut core = pattern.replace("%p", &pid.to_string()).replace("%%", "%");
+    let uses_pid = std::fs::read_to_string("/proc/sys/kernel/core_uses_pid")
+        .is_ok_and(|value| value.trim() == "1");
+    if !pattern.contains("%p") && uses_pid {
+        core = format!("{}.{}", core, pid);
+    }
+    let core = data_dir.join(core);
+    if !core.is_file() {
+        return Ok(None);
+    }
+    move_file(&core, &dest)?;
+    Ok(Some(dest))
+}
+
+/// Crash reports are written to DiagnosticReports and tagged with the pid
+#[cfg(target_os = "macos")]
+fn find_dump(
+    app: &AppHandle,
+    pid: u32,
+    _data_dir: &Path,
+    started: SystemTime,
+    dir: &Path,
+) -> Result<Option<PathBuf>, String> {
+    let reports_dir = app
+        .path()
+        .home_dir()
+        .map_err(|e| format!("Failed to get home dir: {}", e))?
+        .join("Library/Logs/DiagnosticReports");
+    let Ok(entries) = std::fs::read_dir(&reports_dir) else {
+        return Ok(None);
+    };
+
+    // .ips reports have `"pid" : 123,`, older .crash reports `Process: bun [123]`
+    let ips_tag = format!("\"pid\" : {},", pid);
+    let crash_tag = format!("[{}]", pid);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !matches!(extension.as_str(), "ips" | "crash") {
+            continue;
+        }
+        let recent = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified >= started);
+        if !recent {
+            continue;
+        }
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let matches = contents.contains(&ips_tag)
+            || contents
+                .lines()
+                .any(|line| line.starts_with("Process:") && line.ends_with(&crash_tag));
+        if matches {
+            // The report belongs to the system's crash reporter, so copy it
+            let dest = dump_path(dir, pid, &extension);
+            std::fs::write(&dest, contents)
+                .map_err(|e| format!("Failed to copy crash report: {}", e))?;
+            return Ok(Some(dest));
+        }
+    }
+    Ok(None)
+}
+
+/// Windows Error Reporting writes `<exe>.<pid>.dmp` into our crash dumps dir
+#[cfg(windows)]
+fn find_dump(
+    _app: &AppHandle,
+    pid: u32,
+    _data_dir: &Path,
+    _started: SystemTime,
+    dir: &Path,
+) -> Result<Option<PathBuf>, String> {
+    let suffix = format!(".{}.dmp", pid);
+    let dump = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read crash dumps dir: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with(&suffix))
+        });
+    let Some(dump) = dump else {
+        return Ok(None);
+    };
+    let dest = dump_path(dir, pid, "dmp");
+    move_file(&dump, &dest)?;
+    Ok(Some(dest))
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::os::windows::process::CommandExt;
+    use std::path::Path;
+    use windows_sys::Win32::System::Threading::CREATE_NO_WINDOW;
+
+    const LOCAL_DUMPS_KEY: &str =
+        r"HKCU\Software\Microsoft\Windows\Windows Error Reporting\LocalDumps";
+
+    /// Configure per-user WER LocalDumps for the executable
+    pub fn register_local_dumps(exe: &str, dir: &Path) -> Result<(), String> {
+        let key = format!(r"{}\{}", LOCAL_DUMPS_KEY, exe);
+        let folder = dir.to_string_lossy().to_string();
+        let count = super::MAX_DUMPS.to_string();
+        for (name, kind, value) in [
+            ("DumpFolder", "REG_EXPAND_SZ", folder.as_str()),
+            ("DumpCount", "REG_DWORD", count.as_str()),
+            // 1 is a minidump, much smaller than a full dump
+            ("DumpType", "REG_DWORD", "1"),
+        ] {
+            let status = std::process::Command::new("reg")
+                .args(["add", &key, "/v", name, "/t", kind, "/d", value, "/f"])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .creation_flags(CREATE_NO_WINDOW)
+                .status()
+                .map_err(|e| format!("Failed to run reg: {}", e))?;
+            if !status.success() {
+                return Err(format!("reg add {} exited with status {}", name, status));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn describe(path: &Path) -> Option<CrashDump> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some(CrashDump {
+        name: path.file_name()?.to_string_lossy().to_string(),
+        path: path.to_path_buf(),
+        size_bytes: meta.len(),
+        created_at: meta
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as u64),
+    })
+}
+
+/// Collected dumps, newest first
+fn list(dir: &Path) -> Vec<CrashDump> {
+    let mut dumps: Vec<CrashDump> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with("sidecar-"))
+                })
+                .filter_map(|path| describe(&path))
+                .collect()
+        })
+        .unwrap_or_default();
+    dumps.sort_by_key(|dump| std::cmp::Reverse(dump.created_at));
+    dumps
+}
+
+/// Remove the oldest dumps beyond the count and size caps
+fn prune(dir: &Path) {
+    let mut total = 0;
+    for (index, dump) in list(dir).into_iter().enumerate() {
+        total += dump.size_bytes;
+        if index >= MAX_DUMPS || total > MAX_TOTAL_BYTES {
+            log::info!("[CrashDump] Removing old crash dump {}", dump.name);
+            let _ = std::fs::remove_file(&dump.path);
+        }
+    }
+}
+
+/// List the sidecar crash dumps kept for debugging, newest first (exposed to
+/// frontend)
+#[tauri::command]
+pub fn list_crash_dumps(app: AppHandle) -> Result<Vec<CrashDump>, String> {
+    Ok(list(&crash_dumps_dir(&app)?))
+}
+
+/// Delete all kept sidecar crash dumps (exposed to frontend)
+#[tauri::command]
+pub fn clear_crash_dumps(app: AppHandle) -> Result<(), String> {
+    for dump in list(&crash_dumps_dir(&app)?) {
+        std::fs::remove_file(&dump.path)
+            .map_err(|e| format!("Failed to delete {}: {}", dump.name, e))?;
+    }
+    Ok(())
+}
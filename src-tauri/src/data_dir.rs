@@ -0,0 +1,235 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::SettingsState;
+use crate::{profiles, SidecarState};
+
+/// Entries of the app data dir that belong to the shell rather than the
+/// server's database, which stay where they are
+const SHELL_ENTRIES: [&str; 10] = [
+    "attachments",
+    "config",
+    "crash-dumps",
+    "logs",
+    "profiles",
+    "runtime",
+    "server-updates",
+    "sidecar.pid",
+    "tls",
+    "webview",
+];
+/// Least time between progress events while copying
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MigrationProgress {
+    copied_bytes: u64,
+    total_bytes: u64,
+}
+
+/// Database location the user chose with `--data-dir`, PIPALI_DATA_DIR or
+/// the `dataDir` setting, in that order
+///
+/// Relative paths are ignored, as they'd depend on where Pipali was launched.
+pub fn override_dir(app: &AppHandle) -> Option<PathBuf> {
+    let dir = launch_override().or_else(|| {
+        app.try_state::<SettingsState>()
+            .and_then(|settings| settings.get().data_dir)
+    })?;
+    let path = PathBuf::from(&dir);
+    if !path.is_absolute() {
+        log::warn!("[Sidecar] Ignoring relative data directory: {}", dir);
+        return None;
+    }
+    Some(crate::normalize_windows_path(path))
+}
+
+fn launch_override() -> Option<String> {
+    crate::cli_flag_value("data-dir")
+        .or_else(|| std::env::var("PIPALI_DATA_DIR").ok())
+        .filter(|dir| !dir.is_empty())
+}
+
+/// Files of the server's database in a data dir, skipping the shell's own
+pub fn database_entries(source: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    Ok(entries
+        .filter_map(Result::ok)
+        .filter(|entry| !SHELL_ENTRIES.contains(&entry.file_name().to_string_lossy().as_ref()))
+        .map(|entry| entry.path())
+        .collect())
+}
+
+/// Bytes taken by a file, or everything within a folder
+pub fn disk_size(path: &Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| disk_size(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+struct Copier<'a> {
+    app: &'a AppHandle,
+    progress: MigrationProgress,
+    last_emit: Instant,
+}
+
+impl Copier<'_> {
+    fn copy(&mut self, source: &Path, target: &Path) -> Result<(), String> {
+        let metadata = std::fs::symlink_metadata(source)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        if metadata.is_dir() {
+            std::fs::create_dir_all(target)
+                .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+            let entries = std::fs::read_dir(source)
+                .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+            for entry in entries.filter_map(Result::ok) {
+                self.copy(&entry.path(), &target.join(entry.file_name()))?;
+            }
+        } else {
+            self.progress.copied_bytes += std::fs::copy(source, target)
+                .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+            if self.last_emit.elapsed() >= PROGRESS_INTERVAL {
+                self.last_emit = Instant::now();
+                let _ = self
+                    .app
+                    .emit("data-dir://migration-progress", &self.progress);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_target(source: &Path, target: &Path) -> Result<(), String> {
+    if !target.is_absolute() {
+        return Err("Choose a full path for the new data directory".to_string());
+    }
+    let resolved_source = source
+        .canonicalize()
+        .unwrap_or_else(|_| source.to_path_buf());
+    let resolved_target = target
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .zip(target.file_name())
+        .map(|(parent, name)| parent.join(name))
+        .unwrap_or_else(|| target.to_path_buf());
+    if resolved_target == resolved_source {
+        return Err("Pipali already keeps its data there".to_string());
+    }
+    if resolved_target.starts_with(&resolved_source) {
+        return Err("The new data directory can't be inside the current one".to_string());
+    }
+    let is_empty = std::fs::read_dir(target).map_or(true, |mut entries| entries.next().is_none());
+    if !is_empty {
+        return Err(format!("{} isn't empty", target.display()));
+    }
+    Ok(())
+}
+
+/// Copy the database of the default profile to `target`, removing the copy
+/// if it fails part way
+fn copy_data(app: &AppHandle, source: &Path, target: &Path) -> Result<(), String> {
+    let created = !target.exists();
+    let entries = database_entries(source)?;
+    let mut copier = Copier {
+        app,
+        progress: MigrationProgress {
+            copied_bytes: 0,
+            total_bytes: entries.iter().map(|entry| disk_size(entry)).sum(),
+        },
+        last_emit: Instant::now(),
+    };
+    let result = std::fs::create_dir_all(target)
+        .map_err(|e| format!("Failed to create {}: {}", target.display(), e))
+        .and_then(|()| {
+            entries.iter().try_for_each(|entry| {
+                copier.copy(entry, &target.join(entry.file_name().unwrap_or_default()))
+            })
+        });
+    match result {
+        Ok(()) => {
+            let _ = app.emit("data-dir://migration-progress", &copier.progress);
+            Ok(())
+        }
+        Err(e) => {
+            if created {
+                let _ = std::fs::remove_dir_all(target);
+            } else if let Ok(copied) = std::fs::read_dir(target) {
+                for entry in copied.filter_map(Result::ok) {
+                    let _ = std::fs::remove_dir_all(entry.path())
+                        .or_else(|_| std::fs::remove_file(entry.path()));
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Move the default profile's database to another folder, e.g. a bigger
+/// drive (exposed to frontend)
+///
+/// The sidecar is stopped while its files are copied, with progress sent as
+/// `data-dir://migration-progress`, then restarted on the new folder. The old
+/// files are left in place until the user deletes them, and the sidecar
+/// keeps using them if the copy fails.
+#[tauri::command]
+pub async fn migrate_data_dir(app: AppHandle, new_path: String) -> Result<(), String> {
+    if app.state::<SidecarState>().external {
+        return Err("The data directory is managed by the external server".to_string());
+    }
+    if launch_override().is_some() {
+        return Err("The data directory is set with --data-dir or PIPALI_DATA_DIR".to_string());
+    }
+    let source = crate::get_default_data_dir(&app)?;
+    let target = crate::normalize_windows_path(PathBuf::from(new_path.trim()));
+    check_target(&source, &target)?;
+
+    // The default profile's sidecar has the database open, whether it runs
+    // as the main sidecar or in the background
+    let runs_default = app.state::<SettingsState>().get().active_profile.is_none();
+    if runs_default {
+        crate::commands::reserve_restart(&app)?;
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || crate::stop_sidecar(&handle))
+            .await
+            .map_err(|e| format!("Stop task failed: {}", e))??;
+    } else {
+        profiles::stop_background_sidecar(&app, profiles::DEFAULT_PROFILE).await;
+    }
+
+    log::info!("[Sidecar] Moving data from {:?} to {:?}", source, target);
+    let handle = app.clone();
+    let (copy_source, copy_target) = (source.clone(), target.clone());
+    let copied = tauri::async_runtime::spawn_blocking(move || {
+        copy_data(&handle, &copy_source, &copy_target)
+    })
+    .await
+    .map_err(|e| format!("Copy task failed: {}", e))
+    .and_then(|result| result)
+    .and_then(|()| {
+        app.state::<SettingsState>().update(|settings| {
+            settings.data_dir = Some(target.to_string_lossy().into_owned());
+        })
+    });
+    match copied {
+        Ok(()) => log::info!("[Sidecar] Moved data to {:?}", target),
+        Err(ref e) => log::error!("[Sidecar] Failed to move data: {}", e),
+    }
+
+    if runs_default {
+        crate::commands::restart(&app).await?;
+    }
+    copied
+}
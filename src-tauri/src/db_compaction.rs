@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use zeroize::Zeroizing;
+
+use crate::data_dir::{database_entries, disk_size};
+use crate::{SidecarState, SidecarStatus};
+
+/// VACUUM FULL rewrites every table, which takes a while on a large database
+const VACUUM_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Attachments newer than this may be waiting to be sent with a message, so
+/// are kept even if no conversation mentions them
+const ATTACHMENT_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Default)]
+pub struct CompactionState {
+    /// Held while compacting, so the server isn't vacuumed twice at once
+    running: tokio::sync::Mutex<()>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    pub database_bytes_before: u64,
+    pub database_bytes_after: u64,
+    /// Attachments no conversation referred to any more, which were deleted
+    pub attachments_removed: usize,
+    pub attachment_bytes_removed: u64,
+    /// Space freed in all, in bytes
+    pub reclaimed_bytes: u64,
+}
+
+#[derive(Deserialize)]
+struct ReferencedPaths {
+    referenced: Vec<String>,
+}
+
+fn database_bytes(app: &AppHandle) -> Result<u64, String> {
+    let entries = database_entries(&crate::get_sidecar_data_dir(app)?)?;
+    Ok(entries.iter().map(|entry| disk_size(entry)).sum())
+}
+
+/// Files under the attachments folder older than `ATTACHMENT_GRACE`, with
+/// their sizes
+fn old_attachments(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(folder) = pending.pop() {
+        let Ok(children) = std::fs::read_dir(&folder) else {
+            continue;
+        };
+        for child in children.flatten() {
+            let Ok(metadata) = child.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(child.path());
+                continue;
+            }
+            let old = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= ATTACHMENT_GRACE);
+            if old {
+                files.push((child.path(), metadata.len()));
+            }
+        }
+    }
+    files
+}
+
+/// Have the server vacuum the database, then delete attachments that no
+/// conversation mentions, filling in all but the database's size after
+fn vacuum_and_prune(
+    app: &AppHandle,
+    base_url: &str,
+    auth_token: &str,
+) -> Result<CompactionReport, String> {
+    let database_bytes_before = database_bytes(app)?;
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(5))
+        .timeout_read(VACUUM_TIMEOUT)
+        .build();
+    agent
+        .post(&format!("{}/vacuum", base_url))
+        .set("Authorization", &crate::bearer(auth_token))
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(409, _) => {
+                "Wait for running chats and automations to finish, then try again".to_string()
+            }
+            e => format!("The server couldn't vacuum the database: {}", e),
+        })?;
+
+    let candidates = old_attachments(&crate::get_attachments_dir(app)?);
+    let mut attachments_removed = 0;
+    let mut attachment_bytes_removed = 0;
+    if !candidates.is_empty() {
+        let paths: Vec<String> = candidates
+            .iter()
+            .map(|(path, _)| path.to_string_lossy().into_owned())
+            .collect();
+        let body = agent
+            .post(&format!("{}/attachments/referenced", base_url))
+            .set("Authorization", &crate::bearer(auth_token))
+            .set("Content-Type", "application/json")
+            .send_string(&serde_json::json!({ "paths": paths }).to_string())
+            .map_err(|e| format!("Failed to check which attachments are in use: {}", e))?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        let referenced: HashSet<String> = serde_json::from_str::<ReferencedPaths>(&body)
+            .map_err(|e| format!("Invalid response: {}", e))?
+            .referenced
+            .into_iter()
+            .collect();
+        for ((path, size), path_str) in candidates.iter().zip(&paths) {
+            if referenced.contains(path_str) {
+                continue;
+            }
+            match std::fs::remove_file(path) {
+                Ok(()) => {
+                    attachments_removed += 1;
+                    attachment_bytes_removed += size;
+                }
+                Err(e) => log::warn!("[Database] Failed to delete {:?}: {}", path, e),
+            }
+        }
+    }
+
+    Ok(CompactionReport {
+        database_bytes_before,
+        database_bytes_after: database_bytes_before,
+        attachments_removed,
+        attachment_bytes_removed,
+        reclaimed_bytes: attachment_bytes_removed,
+    })
+}
+
+/// Give the space deleted conversations took back to the disk, and delete
+/// attachments no conversation refers to (exposed to frontend)
+///
+/// The server vacuums its database while other requests wait, then restarts,
+/// which also rewrites an encrypted database's file at its new size. Refused
+/// while chats or automations are running.
+#[tauri::command]
+pub async fn compact_database(app: AppHandle) -> Result<CompactionReport, String> {
+    let state = app.state::<CompactionState>();
+    let Ok(_running) = state.running.try_lock() else {
+        return Err("The database is already being compacted".to_string());
+    };
+    let sidecar = app.state::<SidecarState>();
+    if sidecar.external {
+        return Err(
+            "Pipali is using an external server, whose database it can't compact".to_string(),
+        );
+    }
+    if sidecar.status() != SidecarStatus::Ready {
+        return Err("The server isn't running, so the database can't be compacted".to_string());
+    }
+    let base_url = format!("http://{}:{}/api", sidecar.host, sidecar.port());
+    let auth_token: Zeroizing<String> = sidecar.auth_token.get();
+
+    log::info!("[Database] Compacting the database");
+    let handle = app.clone();
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        vacuum_and_prune(&handle, &base_url, &auth_token)
+    })
+    .await
+    .map_err(|e| format!("Compaction task failed: {}", e))??;
+
+    log::info!(
+        "[Database] Vacuumed the database and deleted {} unused attachments, restarting the server",
+        report.attachments_removed
+    );
+    crate::commands::restart(&app).await.map_err(|e| {
+        format!(
+            "Deleted {} unused attachments, but the server failed to restart: {}",
+            report.attachments_removed, e
+        )
+    })?;
+    let handle = app.clone();
+    report.database_bytes_after =
+        tauri::async_runtime::spawn_blocking(move || database_bytes(&handle))
+            .await
+            .map_err(|e| format!("Compaction task failed: {}", e))??;
+    report.reclaimed_bytes += report
+        .database_bytes_before
+        .saturating_sub(report.database_bytes_after);
+    log::info!(
+        "[Database] Compacted the database from {} to {} bytes, and deleted {} unused attachments",
+        report.database_bytes_before,
+        report.database_bytes_after,
+        report.attachments_removed
+    );
+    Ok(report)
+}
@@ -0,0 +1,84 @@
+use tauri::{AppHandle, Manager};
+use zeroize::Zeroizing;
+
+use crate::settings::SettingsState;
+
+/// Keychain service and account the database key is filed under. Kept apart
+/// from the secrets service, so `get_secret` can't hand it to the webview.
+const SERVICE: &str = "ai.pipali.database";
+const ACCOUNT: &str = "key";
+
+fn entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, ACCOUNT).map_err(|e| format!("Failed to open keychain: {}", e))
+}
+
+fn read_key() -> Result<Option<Zeroizing<String>>, String> {
+    match entry()?.get_password() {
+        Ok(key) => Ok(Some(Zeroizing::new(key))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read the database key: {}", e)),
+    }
+}
+
+/// The keychain's database key, generating and storing one if there's none
+fn read_or_create_key() -> Result<Zeroizing<String>, String> {
+    if let Some(key) = read_key()? {
+        return Ok(key);
+    }
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    getrandom::getrandom(&mut *bytes)
+        .map_err(|e| format!("Failed to generate a database key: {}", e))?;
+    let key = Zeroizing::new(
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+    );
+    entry()?
+        .set_password(&key)
+        .map_err(|e| format!("Failed to save the database key: {}", e))?;
+    log::info!("[Database] Generated a database encryption key");
+    Ok(key)
+}
+
+/// Key the sidecar opens its database with, sent in its handshake, or `None`
+/// while encryption is off
+///
+/// The server refuses to start on an encrypted database without it, rather
+/// than starting afresh, so a locked keychain doesn't look like data loss.
+pub fn key_for_sidecar(app: &AppHandle) -> Option<Zeroizing<String>> {
+    if !app.state::<SettingsState>().get().encrypt_database {
+        return None;
+    }
+    match read_key() {
+        Ok(Some(key)) => Some(key),
+        Ok(None) => {
+            log::error!("[Database] Encryption is on but the keychain has no database key");
+            None
+        }
+        Err(e) => {
+            log::error!("[Database] {}", e);
+            None
+        }
+    }
+}
+
+/// Encrypt the database from now on (exposed to frontend)
+///
+/// Stores a new key in the keychain and restarts the sidecar, which encrypts
+/// its existing plaintext database in place before serving.
+#[tauri::command]
+pub async fn encrypt_database(app: AppHandle) -> Result<(), String> {
+    if app.state::<SettingsState>().get().encrypt_database {
+        return Ok(());
+    }
+    crate::commands::reserve_restart(&app)?;
+    tauri::async_runtime::spawn_blocking(read_or_create_key)
+        .await
+        .map_err(|e| format!("Keychain task failed: {}", e))??;
+    app.state::<SettingsState>().update(|settings| {
+        settings.encrypt_database = true;
+    })?;
+    log::info!("[Database] Turned on database encryption, restarting the server");
+    crate::commands::restart(&app).await
+}
@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+const SCHEME: &str = "pipali://";
+/// Longest text a link can pre-fill the prompt with
+const MAX_ASK_CHARS: usize = 20_000;
+
+/// Where a `pipali://` link takes the user
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "page", rename_all = "camelCase")]
+pub enum DeepLinkRoute {
+    Home,
+    /// `pipali://chat/<id>` or `pipali://conversation/<id>`
+    #[serde(rename_all = "camelCase")]
+    Chat {
+        conversation_id: String,
+    },
+    /// `pipali://automations` or `pipali://automation/<id>`
+    #[serde(rename_all = "camelCase")]
+    Automations {
+        automation_id: Option<String>,
+    },
+    Skills,
+    Tools,
+    Settings,
+    /// `pipali://ask?text=<text>`, a new conversation with the prompt
+    /// pre-filled
+    Ask {
+        text: String,
+    },
+}
+
+/// The link the webview hasn't navigated to yet
+#[derive(Default)]
+pub struct DeepLinkState {
+    pending: Mutex<Option<DeepLinkRoute>>,
+}
+
+/// Ids are UUIDs; refuse anything else rather than pass it to the webview
+fn parse_id(id: Option<&str>) -> Option<String> {
+    id.filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .map(str::to_string)
+}
+
+pub fn parse_route(url: &str) -> Option<DeepLinkRoute> {
+    let rest = url.strip_prefix(SCHEME)?;
+    let path = rest.split(['?', '#']).next().unwrap_or_default();
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    let route = match segments.next() {
+        None => DeepLinkRoute::Home,
+        Some("chat" | "conversation") => DeepLinkRoute::Chat {
+            conversation_id: parse_id(segments.next())?,
+        },
+        Some("automation" | "automations") => match segments.next() {
+            None => DeepLinkRoute::Automations {
+                automation_id: None,
+            },
+            id => DeepLinkRoute::Automations {
+                automation_id: Some(parse_id(id)?),
+            },
+        },
+        Some("skills") => DeepLinkRoute::Skills,
+        Some("tools") => DeepLinkRoute::Tools,
+        Some("settings") => DeepLinkRoute::Settings,
+        Some("ask") => {
+            let (_, query) = rest.split_once('?')?;
+            let query = query.split('#').next().unwrap_or_default();
+            let text = url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "text")?
+                .1;
+            DeepLinkRoute::Ask {
+                text: text.chars().take(MAX_ASK_CHARS).collect(),
+            }
+        }
+        Some(_) => return None,
+    };
+    Some(route)
+}
+
+/// Remember where a link goes, for the webview to pick up once it loads
+///
+/// Used for the link the app was launched with, before any window is shown.
+pub fn set_pending(app: &AppHandle, url: &str) -> bool {
+    let Some(route) = parse_route(url) else {
+        log::warn!("[App] Ignoring unknown deep link: {}", url);
+        return false;
+    };
+    // Links to ask about text can carry whatever the user selected
+    match route {
+        DeepLinkRoute::Ask { .. } => log::info!("[App] Deep link received: {}ask", SCHEME),
+        _ => log::info!("[App] Deep link received: {}", url),
+    }
+    *app.state::<DeepLinkState>().pending.lock().unwrap() = Some(route);
+    true
+}
+
+/// Navigate the main window to where a link goes and bring it to the front
+pub fn open(app: &AppHandle, url: &str) {
+    if set_pending(app, url) {
+        notify_webview(app);
+    }
+}
+
+/// Start a new conversation with the prompt pre-filled with `text`, like a
+/// `pipali://ask` link
+pub fn ask(app: &AppHandle, text: &str) {
+    let route = DeepLinkRoute::Ask {
+        text: text.chars().take(MAX_ASK_CHARS).collect(),
+    };
+    *app.state::<DeepLinkState>().pending.lock().unwrap() = Some(route);
+    notify_webview(app);
+}
+
+fn notify_webview(app: &AppHandle) {
+    // The webview takes the pending route when told, so a link that arrives
+    // before it is listening still gets followed
+    let _ = app.emit_to("main", "deep-link", ());
+    crate::show_window(app);
+}
+
+/// Take the route of the last link not yet navigated to (exposed to frontend)
+#[tauri::command]
+pub fn take_pending_deep_link(app: AppHandle) -> Option<DeepLinkRoute> {
+    app.state::<DeepLinkState>().pending.lock().unwrap().take()
+}
+
+/// Follow a `pipali://` link from within the app (exposed to frontend)
+#[tauri::command]
+pub fn open_deep_link(app: AppHandle, url: String) -> Result<(), String> {
+    if parse_route(&url).is_none() {
+        return Err(format!("Unknown Pipali link: {}", url));
+    }
+    open(&app, &url);
+    Ok(())
+}
@@ -0,0 +1,309 @@
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsState;
+use crate::SidecarState;
+
+/// How long Bun gets to print its version before it counts as hung
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but not as configured
+    Warn,
+    Fail,
+    /// Doesn't apply to this platform or setup
+    Skipped,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCheck {
+    /// Stable identifier, e.g. "sidecarBinary"
+    pub id: &'static str,
+    pub status: CheckStatus,
+    /// What was found, for the user or a bug report
+    pub detail: String,
+}
+
+impl DiagnosticCheck {
+    fn new(id: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn of(id: &'static str, result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Self::new(id, CheckStatus::Pass, detail),
+            Err(detail) => Self::new(id, CheckStatus::Fail, detail),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+/// Run `program --version`, killing it if it doesn't answer in time
+fn run_version(program: &Path) -> Result<String, String> {
+    let mut command = Command::new(program);
+    command
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        use windows_sys::Win32::System::Threading::CREATE_NO_WINDOW;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run {:?}: {}", program, e))?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => {
+                let mut version = String::new();
+                if let Some(mut stdout) = child.stdout.take() {
+                    let _ = stdout.read_to_string(&mut version);
+                }
+                return Ok(version.trim().to_string());
+            }
+            Ok(Some(status)) => return Err(format!("{:?} exited with {}", program, status)),
+            Ok(None) if started.elapsed() < RUN_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "{:?} hung, it didn't answer within {:?}",
+                    program, RUN_TIMEOUT
+                ));
+            }
+            Err(e) => return Err(format!("Failed to wait for {:?}: {}", program, e)),
+        }
+    }
+}
+
+/// The binary the sidecar runs is there, executable, signed and starts
+fn check_sidecar_binary(app: &AppHandle) -> (DiagnosticCheck, Option<std::path::PathBuf>) {
+    let server = match crate::ServerEntry::resolve(app) {
+        Ok(server) => server,
+        Err(e) => return (DiagnosticCheck::of("sidecarBinary", Err(e)), None),
+    };
+    let program = crate::sidecar_program(app, &server);
+    let result = (|| {
+        let metadata =
+            std::fs::metadata(&program).map_err(|e| format!("{:?} is missing: {}", program, e))?;
+        if !metadata.is_file() {
+            return Err(format!("{:?} isn't a file", program));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if metadata.permissions().mode() & 0o111 == 0 {
+                return Err(format!("{:?} isn't executable", program));
+            }
+        }
+        crate::code_signature::verify(&program)?;
+        if !server.run_with_bun {
+            return Ok(format!("{:?}", program));
+        }
+        if !server.entry_point.is_file() {
+            return Err(format!("Server script {:?} is missing", server.entry_point));
+        }
+        let version = run_version(&program)?;
+        Ok(format!("{:?} runs Bun {}", program, version))
+    })();
+    (DiagnosticCheck::of("sidecarBinary", result), Some(program))
+}
+
+/// Every shared library the sidecar binary links against can be found
+#[cfg(target_os = "linux")]
+fn check_shared_libraries(program: &Path) -> DiagnosticCheck {
+    const ID: &str = "sharedLibraries";
+    let output = match Command::new("ldd").arg(program).output() {
+        Ok(output) => output,
+        Err(e) => {
+            return DiagnosticCheck::new(ID, CheckStatus::Warn, format!("Couldn't run ldd: {}", e))
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let missing: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.contains("not found"))
+        .map(|line| line.split("=>").next().unwrap_or(line).trim())
+        .collect();
+    if !missing.is_empty() {
+        return DiagnosticCheck::new(
+            ID,
+            CheckStatus::Fail,
+            format!("Missing libraries: {}", missing.join(", ")),
+        );
+    }
+    // ldd exits with an error for static binaries, as Bun is on some builds
+    if !output.status.success() {
+        return DiagnosticCheck::new(ID, CheckStatus::Pass, "Not dynamically linked");
+    }
+    DiagnosticCheck::new(
+        ID,
+        CheckStatus::Pass,
+        format!("All {} libraries found", stdout.lines().count()),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_shared_libraries(_program: &Path) -> DiagnosticCheck {
+    DiagnosticCheck::new(
+        "sharedLibraries",
+        CheckStatus::Skipped,
+        "Only checked on Linux",
+    )
+}
+
+/// The port the sidecar prefers is free, or the sidecar already has it
+fn check_port(app: &AppHandle) -> DiagnosticCheck {
+    const ID: &str = "port";
+    let state = app.state::<SidecarState>();
+    if state.external {
+        return DiagnosticCheck::new(ID, CheckStatus::Skipped, "Using an external server");
+    }
+    let preferred = match app.state::<SettingsState>().get().saved_sidecar_port() {
+        Some(port) if !crate::port_pinned_by_env() => port,
+        _ => state.preferred_port,
+    };
+    let running = state.child.lock().unwrap().is_some();
+    if running && state.port() == preferred {
+        return DiagnosticCheck::new(
+            ID,
+            CheckStatus::Pass,
+            format!("The server is listening on port {}", preferred),
+        );
+    }
+    if crate::is_port_available(&state.host, preferred) {
+        return DiagnosticCheck::new(
+            ID,
+            CheckStatus::Pass,
+            format!("Port {} on {} is free", preferred, state.host),
+        );
+    }
+    DiagnosticCheck::new(
+        ID,
+        CheckStatus::Warn,
+        format!(
+            "Port {} on {} is taken by another app, so the server uses a free port instead",
+            preferred, state.host
+        ),
+    )
+}
+
+/// The database folder can be written to
+fn check_data_dir(app: &AppHandle) -> DiagnosticCheck {
+    let result = (|| {
+        let dir = crate::get_sidecar_data_dir(app)?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Can't create {:?}: {}", dir, e))?;
+        let probe = dir.join(".pipali-write-test");
+        std::fs::write(&probe, b"ok").map_err(|e| format!("Can't write to {:?}: {}", dir, e))?;
+        let _ = std::fs::remove_file(&probe);
+        Ok(format!("{:?} is writable", dir))
+    })();
+    DiagnosticCheck::of("dataDir", result)
+}
+
+/// The server answers on its health endpoint
+async fn check_health(app: &AppHandle) -> DiagnosticCheck {
+    const ID: &str = "health";
+    let state = app.state::<SidecarState>();
+    if !state.external && state.child.lock().unwrap().is_none() {
+        return DiagnosticCheck::new(ID, CheckStatus::Skipped, "The server isn't running");
+    }
+    let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+    let url = health_url.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        crate::fetch_server_info(&crate::health_check_agent(), &url)
+    })
+    .await
+    .map_err(|e| format!("Health check task failed: {}", e))
+    .and_then(|result| result.map_err(|e| format!("{} isn't responding: {}", health_url, e)))
+    .map(|info| match info.version {
+        Some(version) => format!("Server {} is responding", version),
+        None => "The server is responding".to_string(),
+    });
+    DiagnosticCheck::of(ID, result)
+}
+
+/// Which webview renders the app, and its version
+fn check_webview() -> DiagnosticCheck {
+    let engine = if cfg!(target_os = "macos") {
+        "WebKit"
+    } else if cfg!(windows) {
+        "WebView2"
+    } else {
+        "WebKitGTK"
+    };
+    let result = tauri::webview_version()
+        .map(|version| format!("{} {}", engine, version))
+        .map_err(|e| format!("Couldn't find {}: {}", engine, e));
+    DiagnosticCheck::of("webview", result)
+}
+
+/// Check what the app needs to run its server and show its window (exposed
+/// to frontend)
+///
+/// Every check runs even if an earlier one fails, so the report says
+/// everything found at once, e.g. for a bug report.
+#[tauri::command]
+pub async fn run_diagnostics(app: AppHandle) -> Result<DiagnosticsReport, String> {
+    let handle = app.clone();
+    let mut checks = tauri::async_runtime::spawn_blocking(move || {
+        let (binary, program) = check_sidecar_binary(&handle);
+        let libraries = match program {
+            Some(ref program) if program.is_file() => check_shared_libraries(program),
+            _ => DiagnosticCheck::new(
+                "sharedLibraries",
+                CheckStatus::Skipped,
+                "There's no sidecar binary to check",
+            ),
+        };
+        vec![
+            binary,
+            libraries,
+            check_port(&handle),
+            check_data_dir(&handle),
+        ]
+    })
+    .await
+    .map_err(|e| format!("Diagnostics task failed: {}", e))?;
+    checks.push(check_health(&app).await);
+    checks.push(check_webview());
+
+    for check in checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+    {
+        log::warn!("[Diagnostics] {} failed: {}", check.id, check.detail);
+    }
+    Ok(DiagnosticsReport {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        checks,
+    })
+}
@@ -0,0 +1,53 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::data_dir::{database_entries, disk_size};
+
+/// Space Pipali takes on disk, in bytes, for the storage panel in settings
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    /// The active profile's database
+    pub database_bytes: u64,
+    /// Images pasted into chats and screenshots
+    pub attachments_bytes: u64,
+    pub logs_bytes: u64,
+    /// The webview's caches, which are rebuilt as needed
+    pub caches_bytes: u64,
+    /// Server updates and the runtime unpacked from AppImages
+    pub downloads_bytes: u64,
+    pub crash_dumps_bytes: u64,
+}
+
+fn measure(app: &AppHandle) -> Result<DiskUsage, String> {
+    let app_data_dir = crate::normalize_windows_path(crate::get_app_data_dir(app)?);
+    let cache_dir = match crate::portable::data_dir() {
+        Some(dir) => dir.join("webview"),
+        None => app
+            .path()
+            .app_cache_dir()
+            .map_err(|e| format!("Failed to get app cache dir: {}", e))?,
+    };
+    Ok(DiskUsage {
+        database_bytes: database_entries(&crate::get_sidecar_data_dir(app)?)
+            .map(|entries| entries.iter().map(|entry| disk_size(entry)).sum())
+            .unwrap_or(0),
+        attachments_bytes: disk_size(&crate::get_attachments_dir(app)?),
+        logs_bytes: disk_size(&crate::get_logs_dir(app)?),
+        caches_bytes: disk_size(&cache_dir),
+        downloads_bytes: disk_size(&crate::server_update::updates_dir(app)?)
+            + disk_size(&app_data_dir.join("runtime")),
+        crash_dumps_bytes: disk_size(&crate::crash_dumps::crash_dumps_dir(app)?),
+    })
+}
+
+/// Break down the space Pipali takes on disk (exposed to frontend)
+///
+/// Folders are walked on a blocking thread, as a large database can take a
+/// while to measure.
+#[tauri::command]
+pub async fn get_disk_usage(app: AppHandle) -> Result<DiskUsage, String> {
+    tauri::async_runtime::spawn_blocking(move || measure(&app))
+        .await
+        .map_err(|e| format!("Disk usage task failed: {}", e))?
+}
@@ -0,0 +1,33 @@
+use tauri::{Manager, WebviewWindow};
+
+/// Shown under the cursor while a file is dragged out of the window
+const DRAG_ICON: &[u8] = include_bytes!("../icons/32x32.png");
+
+/// Start dragging a file out of the window, so a file the agent wrote can be
+/// dropped into Finder, Explorer or another app (exposed to frontend)
+///
+/// Call from the webview's dragstart handler while the mouse button is still
+/// down. Sync so it runs on the main thread, which the OS drag session needs.
+/// Only files in the folders `open_path` may open can be dragged.
+#[tauri::command]
+pub fn start_drag(window: WebviewWindow, path: String) -> Result<(), String> {
+    let path = crate::file_opener::validate(window.app_handle(), &path)?;
+    if !path.is_file() {
+        return Err(format!("Only files can be dragged: {}", path.display()));
+    }
+
+    #[cfg(target_os = "linux")]
+    let handle = window.gtk_window().map_err(|e| e.to_string())?;
+    #[cfg(not(target_os = "linux"))]
+    let handle = window;
+
+    log::info!("[App] Dragging {} out of the window", path.display());
+    drag::start_drag(
+        &handle,
+        drag::DragItem::Files(vec![path]),
+        drag::Image::Raw(DRAG_ICON.to_vec()),
+        |result, _| log::debug!("[App] Drag out finished: {:?}", result),
+        drag::Options::default(),
+    )
+    .map_err(|e| format!("Failed to start drag: {}", e))
+}
@@ -0,0 +1,414 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+use zeroize::Zeroizing;
+
+use crate::{SidecarState, SidecarStatus};
+
+/// Export folders are named this followed by a UTC timestamp
+const FOLDER_PREFIX: &str = "pipali-export-";
+/// Long conversations take the server a while to convert
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest title kept in a conversation's file name
+const MAX_TITLE_CHARS: usize = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Readable transcripts of the messages
+    Markdown,
+    /// The full trajectory, in the same ATIF format the app exports one
+    /// conversation in
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ExportState {
+    /// Held while an export runs, so two never write at once
+    running: tokio::sync::Mutex<()>,
+    /// Set by `cancel_export`, checked between conversations
+    cancelled: AtomicBool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConversationSummary {
+    id: String,
+    title: Option<String>,
+    created_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ConversationList {
+    conversations: Vec<ConversationSummary>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportProgress {
+    exported: usize,
+    total: usize,
+    /// Title of the conversation just written
+    title: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportInfo {
+    /// Folder the export was written to
+    pub path: PathBuf,
+    pub format: &'static str,
+    pub conversation_count: usize,
+    pub attachment_count: usize,
+}
+
+fn title_of(conversation: &ConversationSummary) -> &str {
+    conversation
+        .title
+        .as_deref()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or("Untitled conversation")
+}
+
+/// File name for a conversation, without its extension, e.g.
+/// `2026-01-31-plan-the-trip-1a2b3c4d`
+///
+/// The id keeps conversations with the same title and day apart.
+fn file_stem(conversation: &ConversationSummary) -> String {
+    let mut slug = String::new();
+    for c in title_of(conversation).chars() {
+        if slug.chars().count() >= MAX_TITLE_CHARS {
+            break;
+        }
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let id: String = conversation.id.chars().take(8).collect();
+    match conversation
+        .created_at
+        .as_deref()
+        .and_then(|at| at.get(..10))
+    {
+        Some(date) => format!("{}-{}-{}", date, slug, id),
+        None => format!("{}-{}", slug, id),
+    }
+}
+
+/// Text of every message in a trajectory
+fn messages(trajectory: &Value) -> impl Iterator<Item = &str> {
+    trajectory["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|step| step["message"].as_str())
+}
+
+/// Files under the attachments folder that a conversation's messages refer
+/// to, keyed by the path as written in the message, with their path relative
+/// to the attachments folder
+///
+/// Files attached from elsewhere on disk are the user's own and aren't copied.
+fn referenced_attachments(trajectory: &Value, attachments_dir: &Path) -> BTreeMap<String, PathBuf> {
+    let mut found = BTreeMap::new();
+    let prefix = attachments_dir.to_string_lossy();
+    let Ok(canonical_dir) = attachments_dir.canonicalize() else {
+        return found;
+    };
+    for message in messages(trajectory) {
+        for (start, _) in message.match_indices(prefix.as_ref()) {
+            let end = message[start..]
+                .find(|c: char| c.is_whitespace() || "\"'`()[]<>,".contains(c))
+                .map_or(message.len(), |offset| start + offset);
+            let written = &message[start..end];
+            let Ok(path) = Path::new(written).canonicalize() else {
+                continue;
+            };
+            // Keep `..` in a message from reaching outside the folder
+            if let Ok(relative) = path.strip_prefix(&canonical_dir) {
+                if path.is_file() {
+                    found.insert(written.to_string(), relative.to_path_buf());
+                }
+            }
+        }
+    }
+    found
+}
+
+/// `attachments/<path>` with `/` separators, as linked from a transcript
+fn attachment_link(relative: &Path) -> String {
+    let parts: Vec<_> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect();
+    format!("attachments/{}", parts.join("/"))
+}
+
+/// A readable transcript of a conversation, with attachments linked from the
+/// export's `attachments` folder
+fn render_markdown(
+    title: &str,
+    trajectory: &Value,
+    attachments: &BTreeMap<String, PathBuf>,
+) -> String {
+    // Longest paths first, so one that starts with another is replaced whole
+    let mut replacements: Vec<(&String, String)> = attachments
+        .iter()
+        .map(|(written, relative)| (written, attachment_link(relative)))
+        .collect();
+    replacements.sort_by_key(|(written, _)| std::cmp::Reverse(written.len()));
+
+    let mut markdown = format!("# {}\n", title);
+    for step in trajectory["steps"].as_array().into_iter().flatten() {
+        let author = match step["source"].as_str() {
+            Some("user") => "You",
+            Some("agent") => "Pipali",
+            _ => continue,
+        };
+        let message = step["message"].as_str().unwrap_or("").trim();
+        let tools: Vec<&str> = step["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|call| call["function_name"].as_str())
+            .collect();
+        if message.is_empty() && tools.is_empty() {
+            continue;
+        }
+
+        // e.g. "2026-01-31T09:30:00.000Z" as "2026-01-31 09:30"
+        let timestamp = step["timestamp"]
+            .as_str()
+            .and_then(|at| at.get(..16))
+            .map(|at| at.replacen('T', " ", 1));
+        match timestamp {
+            Some(at) => markdown.push_str(&format!("\n## {} · {}\n\n", author, at)),
+            None => markdown.push_str(&format!("\n## {}\n\n", author)),
+        }
+        if !tools.is_empty() {
+            let tools: Vec<String> = tools.iter().map(|tool| format!("`{}`", tool)).collect();
+            markdown.push_str(&format!("_Used {}_\n\n", tools.join(", ")));
+        }
+        if !message.is_empty() {
+            let mut message = message.to_string();
+            for (written, link) in &replacements {
+                message = message.replace(written.as_str(), link);
+            }
+            markdown.push_str(&message);
+            markdown.push('\n');
+        }
+    }
+    markdown
+}
+
+fn fetch(agent: &ureq::Agent, url: &str, auth_token: &str) -> Result<String, String> {
+    agent
+        .get(url)
+        .set("Authorization", &crate::bearer(auth_token))
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())
+}
+
+/// Fetch conversations from the server one at a time, writing each to a new
+/// timestamped folder under `dest` along with the attachments it refers to
+fn write_export(
+    app: &AppHandle,
+    base_url: &str,
+    auth_token: &str,
+    format: ExportFormat,
+    conversation_ids: Option<Vec<String>>,
+    dest: &Path,
+) -> Result<ExportInfo, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(5))
+        .timeout_read(REQUEST_TIMEOUT)
+        .build();
+    let body = fetch(&agent, &format!("{}/conversations", base_url), auth_token)
+        .map_err(|e| format!("Failed to list conversations: {}", e))?;
+    let mut conversations = serde_json::from_str::<ConversationList>(&body)
+        .map_err(|e| format!("Invalid response: {}", e))?
+        .conversations;
+    if let Some(ids) = conversation_ids {
+        conversations.retain(|conversation| ids.contains(&conversation.id));
+    }
+    if conversations.is_empty() {
+        return Err("There are no conversations to export".to_string());
+    }
+
+    let folder = dest.join(crate::backup::timestamped_name(
+        FOLDER_PREFIX,
+        time::OffsetDateTime::now_utc(),
+    ));
+    std::fs::create_dir_all(dest).map_err(|e| format!("Failed to create {:?}: {}", dest, e))?;
+    std::fs::create_dir(&folder).map_err(|e| format!("Failed to create {:?}: {}", folder, e))?;
+
+    let result = (|| {
+        let state = app.state::<ExportState>();
+        let attachments_dir = crate::get_attachments_dir(app)?;
+        let total = conversations.len();
+        let mut copied = BTreeSet::new();
+        for (index, conversation) in conversations.iter().enumerate() {
+            if state.cancelled.load(Ordering::SeqCst) {
+                return Err("The export was cancelled".to_string());
+            }
+            let title = title_of(conversation);
+            let url = format!("{}/conversations/{}/export/atif", base_url, conversation.id);
+            let atif = fetch(&agent, &url, auth_token)
+                .map_err(|e| format!("Failed to export \"{}\": {}", title, e))?;
+            let trajectory: Value = serde_json::from_str(&atif)
+                .map_err(|e| format!("Invalid export of \"{}\": {}", title, e))?;
+
+            let attachments = referenced_attachments(&trajectory, &attachments_dir);
+            let contents = match format {
+                ExportFormat::Markdown => render_markdown(title, &trajectory, &attachments),
+                ExportFormat::Json => atif,
+            };
+            let path = folder.join(format!(
+                "{}.{}",
+                file_stem(conversation),
+                format.extension()
+            ));
+            std::fs::write(&path, contents)
+                .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+            for relative in attachments.into_values() {
+                if !copied.insert(relative.clone()) {
+                    continue;
+                }
+                let target = folder.join("attachments").join(&relative);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+                }
+                std::fs::copy(attachments_dir.join(&relative), &target)
+                    .map_err(|e| format!("Failed to copy attachment {:?}: {}", relative, e))?;
+            }
+
+            let progress = ExportProgress {
+                exported: index + 1,
+                total,
+                title: title.to_string(),
+            };
+            let _ = app.emit("export://progress", progress);
+        }
+        Ok(ExportInfo {
+            path: folder.clone(),
+            format: format.extension(),
+            conversation_count: total,
+            attachment_count: copied.len(),
+        })
+    })();
+    // A cancelled or failed export isn't left looking like a complete one
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+    result
+}
+
+/// Export conversations as Markdown or JSON files, one per conversation, to
+/// a timestamped folder under `destPath` (exposed to frontend)
+///
+/// Without `destPath` the user picks the folder with the native picker, and
+/// the command resolves to `None` if they cancel it. Exports every
+/// conversation unless `conversationIds` is given. Attachments the
+/// conversations refer to are copied into an `attachments` folder beside
+/// them. Emits `export://progress` after each conversation is written.
+#[tauri::command]
+pub async fn export_conversations(
+    app: AppHandle,
+    format: ExportFormat,
+    conversation_ids: Option<Vec<String>>,
+    dest_path: Option<String>,
+) -> Result<Option<ExportInfo>, String> {
+    let state = app.state::<ExportState>();
+    let Ok(_running) = state.running.try_lock() else {
+        return Err("An export is already running".to_string());
+    };
+    let sidecar = app.state::<SidecarState>();
+    if sidecar.status() != SidecarStatus::Ready {
+        return Err("The server isn't running, so conversations can't be exported".to_string());
+    }
+
+    let dest = match dest_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let handle = app.clone();
+            let picked = tauri::async_runtime::spawn_blocking(move || {
+                handle
+                    .dialog()
+                    .file()
+                    .set_title("Export conversations to")
+                    .blocking_pick_folder()
+            })
+            .await
+            .map_err(|e| format!("Folder picker failed: {}", e))?;
+            match picked.and_then(|path| path.into_path().ok()) {
+                Some(path) => path,
+                None => return Ok(None),
+            }
+        }
+    };
+    if !dest.is_absolute() {
+        return Err("The export folder must be an absolute path".to_string());
+    }
+
+    state.cancelled.store(false, Ordering::SeqCst);
+    let base_url = format!("http://{}:{}/api", sidecar.host, sidecar.port());
+    let auth_token: Zeroizing<String> = sidecar.auth_token.get();
+    log::info!(
+        "[Export] Exporting conversations as {:?} to {:?}",
+        format,
+        dest
+    );
+    let handle = app.clone();
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        write_export(
+            &handle,
+            &base_url,
+            &auth_token,
+            format,
+            conversation_ids,
+            &dest,
+        )
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))??;
+    log::info!(
+        "[Export] Exported {} conversations and {} attachments to {:?}",
+        info.conversation_count,
+        info.attachment_count,
+        info.path
+    );
+    Ok(Some(info))
+}
+
+/// Stop a running export after the conversation it's writing (exposed to
+/// frontend)
+///
+/// The export then fails and its partly written folder is removed.
+#[tauri::command]
+pub fn cancel_export(export: State<'_, ExportState>) {
+    if export.running.try_lock().is_err() {
+        log::info!("[Export] Cancelling the export");
+        export.cancelled.store(true, Ordering::SeqCst);
+    }
+}
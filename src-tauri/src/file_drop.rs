@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager, State};
+
+use crate::{SidecarState, SidecarStatus};
+
+/// Files the sidecar's `/api/attachments` endpoint checks per request
+const MAX_ATTACHMENTS: usize = 20;
+
+/// A file the sidecar accepted as an attachment
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub path: String,
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// A path that can't be attached, and why
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rejected {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Payload of `file-drop://attached`
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct DroppedFiles {
+    pub attachments: Vec<Attachment>,
+    pub rejected: Vec<Rejected>,
+}
+
+impl DroppedFiles {
+    fn rejected_all(paths: &[String], reason: &str) -> Self {
+        Self {
+            attachments: Vec::new(),
+            rejected: paths
+                .iter()
+                .map(|path| Rejected {
+                    path: path.clone(),
+                    reason: reason.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Ask the sidecar to check files for attaching against its sensitive path
+/// rules
+///
+/// Files past the first [`MAX_ATTACHMENTS`] are rejected rather than sent,
+/// so a large drop still attaches the files that fit.
+pub fn register(state: &SidecarState, paths: &[String]) -> Result<DroppedFiles, String> {
+    let (paths, excess) = paths.split_at(paths.len().min(MAX_ATTACHMENTS));
+    let mut dropped = request_attachments(state, paths)?;
+    dropped.rejected.extend(
+        DroppedFiles::rejected_all(
+            excess,
+            &format!("Only {} files can be attached at once", MAX_ATTACHMENTS),
+        )
+        .rejected,
+    );
+    Ok(dropped)
+}
+
+fn request_attachments(state: &SidecarState, paths: &[String]) -> Result<DroppedFiles, String> {
+    if paths.is_empty() {
+        return Ok(DroppedFiles::default());
+    }
+    let url = format!("http://{}:{}/api/attachments", state.host, state.port());
+    let body = serde_json::json!({ "paths": paths }).to_string();
+    let response = crate::health_check_agent()
+        .post(&url)
+        .set("Authorization", &crate::bearer(&state.auth_token.get()))
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    serde_json::from_str(&response).map_err(|e| format!("Invalid response: {}", e))
+}
+
+fn attach(app: &AppHandle, paths: Vec<PathBuf>) {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state: State<SidecarState> = app.state();
+        let dropped = if state.status() != SidecarStatus::Ready {
+            DroppedFiles::rejected_all(&paths, "Pipali server isn't running")
+        } else {
+            register(&state, &paths).unwrap_or_else(|e| {
+                log::warn!("[App] Failed to attach dropped files: {}", e);
+                DroppedFiles::rejected_all(&paths, "Couldn't attach the file")
+            })
+        };
+        log::info!(
+            "[App] Dropped {} file(s), {} attached",
+            paths.len(),
+            dropped.attachments.len()
+        );
+        let _ = app.emit_to("main", "file-drop://attached", dropped);
+    });
+}
+
+/// Attach files dropped onto the main window to the current conversation
+///
+/// The webview doesn't see the paths of files dropped onto it, so the shell
+/// registers them with the sidecar and hands the frontend the result.
+pub fn on_main_window_drag_drop(app: &AppHandle, event: DragDropEvent) {
+    match event {
+        DragDropEvent::Enter { .. } => {
+            let _ = app.emit_to("main", "file-drop://hover", true);
+        }
+        DragDropEvent::Leave => {
+            let _ = app.emit_to("main", "file-drop://hover", false);
+        }
+        DragDropEvent::Drop { paths, .. } => {
+            let _ = app.emit_to("main", "file-drop://hover", false);
+            attach(app, paths);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+/// Pipali's temp dir, where the agent writes intermediate files and charts
+const TEMP_DIR: &str = "/tmp/pipali";
+
+/// Extensions of files that run code when opened, including scripts for an
+/// installed interpreter and shortcuts that can point at anything
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "app",
+    "appimage",
+    "applescript",
+    "appx",
+    "appxbundle",
+    "bat",
+    "cmd",
+    "com",
+    "command",
+    "cpl",
+    "desktop",
+    "exe",
+    "hta",
+    "inf",
+    "jar",
+    "js",
+    "jse",
+    "lnk",
+    "msc",
+    "msi",
+    "msix",
+    "msixbundle",
+    "msp",
+    "pif",
+    "pkg",
+    "pl",
+    "ps1",
+    "psm1",
+    "py",
+    "pyw",
+    "rb",
+    "reg",
+    "run",
+    "scpt",
+    "scptd",
+    "scr",
+    "settingcontent-ms",
+    "sh",
+    "terminal",
+    "url",
+    "vbe",
+    "vbs",
+    "webloc",
+    "workflow",
+    "wsf",
+    "wsh",
+];
+
+/// Folders the webview may open and reveal files in: the user's home, Pipali's
+/// temp dir and the locations granted with the file picker
+fn allowed_roots(app: &AppHandle) -> Vec<PathBuf> {
+    let mut roots = vec![PathBuf::from(TEMP_DIR), std::env::temp_dir().join("pipali")];
+    roots.extend(crate::get_home_dir());
+    roots.extend(crate::access_grants::granted_paths(app));
+    roots
+        .into_iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .collect()
+}
+
+/// Resolve a path from the webview, which must be absolute and exist within
+/// the allowed folders once symlinks and `..` are resolved
+pub fn validate(app: &AppHandle, path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(format!("Not an absolute path: {}", path.display()));
+    }
+    let resolved = path
+        .canonicalize()
+        .map_err(|_| format!("{} doesn't exist", path.display()))?;
+    if !allowed_roots(app)
+        .iter()
+        .any(|root| resolved.starts_with(root))
+    {
+        return Err(format!(
+            "Pipali can only open files in your home folder: {}",
+            path.display()
+        ));
+    }
+    Ok(crate::normalize_windows_path(resolved))
+}
+
+fn runs_code(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| EXECUTABLE_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Show a file selected in Finder, Explorer or the Linux file manager
+pub fn reveal(app: &AppHandle, path: &str) -> Result<(), String> {
+    let path = validate(app, path)?;
+    app.opener()
+        .reveal_item_in_dir(&path)
+        .map_err(|e| format!("Failed to show {}: {}", path.display(), e))
+}
+
+/// Open a file, such as a PDF or spreadsheet the agent wrote, in its default
+/// app (exposed to frontend)
+///
+/// Programs and scripts aren't opened, so a file the agent wrote can't be run
+/// with one click.
+#[tauri::command]
+pub fn open_path(app: AppHandle, path: String) -> Result<(), String> {
+    let path = validate(&app, &path)?;
+    if runs_code(&path) {
+        return Err(format!(
+            "Pipali doesn't open programs or scripts: {}",
+            path.display()
+        ));
+    }
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    log::info!("[App] Opened {}", path.display());
+    Ok(())
+}
+
+/// Show a file selected in its folder, for "Show in folder" on attachments
+/// and written files (exposed to frontend)
+#[tauri::command]
+pub fn reveal_in_file_manager(app: AppHandle, path: String) -> Result<(), String> {
+    reveal(&app, &path)
+}
@@ -0,0 +1,183 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::settings::{Settings, SettingsState};
+
+/// Shortcut that summons the main window when none is configured
+pub const DEFAULT_SUMMON_SHORTCUT: &str = "Alt+Space";
+/// Shortcut that toggles the quick-ask prompt bar when none is configured
+pub const DEFAULT_QUICK_ASK_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+/// Shortcut that asks about the selected text when none is configured, off
+/// on macOS where the Ask Pipali service does this
+#[cfg(not(target_os = "macos"))]
+pub const DEFAULT_ASK_SELECTION_SHORTCUT: Option<&str> = Some("Alt+Shift+Space");
+#[cfg(target_os = "macos")]
+pub const DEFAULT_ASK_SELECTION_SHORTCUT: Option<&str> = None;
+
+/// What a global shortcut does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyAction {
+    /// Show and focus the main window
+    #[default]
+    Summon,
+    /// Toggle the quick-ask prompt bar
+    QuickAsk,
+    /// Ask about the text selected in the focused app
+    AskSelection,
+}
+
+impl HotkeyAction {
+    const ALL: [HotkeyAction; 3] = [
+        HotkeyAction::Summon,
+        HotkeyAction::QuickAsk,
+        HotkeyAction::AskSelection,
+    ];
+
+    fn saved(self, settings: &Settings) -> Option<String> {
+        match self {
+            HotkeyAction::Summon => settings.summon_shortcut.clone(),
+            HotkeyAction::QuickAsk => settings.quick_ask_shortcut.clone(),
+            HotkeyAction::AskSelection => settings.ask_selection_shortcut.clone(),
+        }
+    }
+
+    fn save(self, settings: &mut Settings, shortcut: Option<String>) {
+        match self {
+            HotkeyAction::Summon => settings.summon_shortcut = shortcut,
+            HotkeyAction::QuickAsk => settings.quick_ask_shortcut = shortcut,
+            HotkeyAction::AskSelection => settings.ask_selection_shortcut = shortcut,
+        }
+    }
+}
+
+/// Global shortcuts currently registered with the OS
+#[derive(Default)]
+pub struct HotkeysState {
+    registered: Mutex<HashMap<HotkeyAction, Shortcut>>,
+}
+
+/// Handle a global shortcut press, registered as the plugin's handler
+pub fn handle(app: &AppHandle, shortcut: &Shortcut, state: ShortcutState) {
+    if state != ShortcutState::Pressed {
+        return;
+    }
+    let action = app
+        .state::<HotkeysState>()
+        .registered
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(_, registered)| *registered == shortcut)
+        .map(|(action, _)| *action);
+    match action {
+        Some(HotkeyAction::Summon) => {
+            log::info!("[App] Global shortcut triggered");
+            summon_window(app);
+        }
+        Some(HotkeyAction::QuickAsk) => crate::quick_ask::toggle(app),
+        Some(HotkeyAction::AskSelection) => crate::selection::ask_about_selection(app),
+        None => {}
+    }
+}
+
+/// Bring the main window to the front, or hide it if it already is
+fn summon_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(false);
+    if visible && window.is_focused().unwrap_or(false) {
+        let _ = window.hide();
+        crate::hide_from_dock(app);
+    } else {
+        crate::show_window(app);
+    }
+}
+
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator
+        .parse()
+        .map_err(|e| format!("Invalid shortcut {:?}: {}", accelerator, e))
+}
+
+/// Swap the shortcut registered for an action for another, or none
+fn register(
+    app: &AppHandle,
+    action: HotkeyAction,
+    shortcut: Option<Shortcut>,
+) -> Result<(), String> {
+    let hotkeys = app.state::<HotkeysState>();
+    // Not held while registering, since presses are handled under the lock
+    let (current, taken) = {
+        let registered = hotkeys.registered.lock().unwrap();
+        let taken = registered
+            .iter()
+            .any(|(other, registered)| *other != action && Some(*registered) == shortcut);
+        (registered.get(&action).copied(), taken)
+    };
+    if current == shortcut {
+        return Ok(());
+    }
+    if taken {
+        return Err("Shortcut is already used for another action".to_string());
+    }
+    if let Some(new) = shortcut {
+        app.global_shortcut()
+            .register(new)
+            .map_err(|e| format!("Failed to register shortcut (may already be in use): {}", e))?;
+    }
+    if let Some(old) = current {
+        let _ = app.global_shortcut().unregister(old);
+    }
+
+    let mut registered = hotkeys.registered.lock().unwrap();
+    match shortcut {
+        Some(shortcut) => registered.insert(action, shortcut),
+        None => registered.remove(&action),
+    };
+    Ok(())
+}
+
+/// Register the shortcuts saved in settings
+pub fn register_from_settings(app: &AppHandle) {
+    let settings = app.state::<SettingsState>().get();
+    for action in HotkeyAction::ALL {
+        let Some(accelerator) = action.saved(&settings) else {
+            log::info!("[App] Global shortcut for {:?} disabled", action);
+            continue;
+        };
+        match parse(&accelerator).and_then(|shortcut| register(app, action, Some(shortcut))) {
+            Ok(()) => log::info!("[App] Global shortcut {} registered", accelerator),
+            Err(e) => log::warn!("[App] Global shortcut {}: {}", accelerator, e),
+        }
+    }
+}
+
+/// Get the shortcut bound to an action, by default summoning the main window
+/// (exposed to frontend)
+#[tauri::command]
+pub fn get_global_shortcut(app: AppHandle, action: Option<HotkeyAction>) -> Option<String> {
+    action
+        .unwrap_or_default()
+        .saved(&app.state::<SettingsState>().get())
+}
+
+/// Rebind the shortcut for an action, by default summoning the main window,
+/// e.g. to `CommandOrControl+Shift+Space`, or pass `None` to turn it off
+/// (exposed to frontend)
+#[tauri::command]
+pub fn set_global_shortcut(
+    app: AppHandle,
+    shortcut: Option<String>,
+    action: Option<HotkeyAction>,
+) -> Result<(), String> {
+    let action = action.unwrap_or_default();
+    let parsed = shortcut.as_deref().map(parse).transpose()?;
+    register(&app, action, parsed)?;
+    app.state::<SettingsState>()
+        .update(|settings| action.save(settings, shortcut))
+}
@@ -0,0 +1,65 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// How often the sidecar is checked for idleness
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Stop the sidecar once it has been idle for `idleShutdownMins` while the
+/// window is hidden
+///
+/// The server reports its idle time on /api/health, counting chats and
+/// running automations as activity, and asks to be kept alive while it has
+/// scheduled automations. The frontend restarts it via `ensure_sidecar_running`
+/// when the user comes back.
+pub fn spawn_idle_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = crate::health_check_agent();
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+            let Some(limit) = app
+                .state::<SettingsState>()
+                .get()
+                .idle_shutdown_mins
+                .filter(|mins| *mins > 0)
+                .map(|mins| mins * 60)
+            else {
+                continue;
+            };
+            let state: State<SidecarState> = app.state();
+            if state.external || state.status() != SidecarStatus::Ready {
+                continue;
+            }
+            let hidden = app
+                .get_webview_window("main")
+                .is_some_and(|window| !window.is_visible().unwrap_or(true));
+            if !hidden {
+                continue;
+            }
+
+            let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+            let Ok(info) = crate::check_sidecar_health(&agent, &health_url).await else {
+                // Unresponsive servers are the watchdog's business
+                continue;
+            };
+            if info.keep_alive == Some(true) || info.idle_secs.is_none_or(|idle| idle < limit) {
+                continue;
+            }
+
+            log::info!(
+                "[Sidecar] Idle for {}s while hidden, stopping until next use",
+                info.idle_secs.unwrap_or_default()
+            );
+            let _ = app.emit("sidecar://idle-stopped", ());
+            let handle = app.clone();
+            let stopped =
+                tauri::async_runtime::spawn_blocking(move || crate::stop_sidecar(&handle)).await;
+            if let Ok(Err(e)) = stopped {
+                log::error!("[Sidecar] Failed to stop idle sidecar: {}", e);
+            }
+        }
+    });
+}
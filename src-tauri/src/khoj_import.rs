@@ -0,0 +1,503 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
+use zeroize::Zeroizing;
+
+use crate::{SidecarState, SidecarStatus};
+
+/// ATIF version the sidecar's importer accepts
+const ATIF_SCHEMA_VERSION: &str = "ATIF-v1.4";
+/// A Khoj server pages its chat export and file list, so stop at this many
+/// pages in case it never returns an empty one
+const MAX_PAGES: u32 = 1000;
+/// Khoj builds each page of its chat export from the database on request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Where to import from
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum KhojSource {
+    /// The JSON Khoj's "Export chats" setting saves, or a folder of them
+    /// unzipped from its export. The user picks the file if there's no path.
+    Export { path: Option<String> },
+    /// A Khoj server, signed in with an API key from its settings
+    Server {
+        url: String,
+        #[serde(rename = "apiKey")]
+        api_key: Option<String>,
+    },
+}
+
+#[derive(Default)]
+pub struct KhojImportState {
+    /// Held while an import runs, so the same chats aren't imported twice
+    running: tokio::sync::Mutex<()>,
+}
+
+/// A conversation as Khoj exports it
+#[derive(Deserialize)]
+struct KhojConversation {
+    title: Option<String>,
+    created_at: Option<String>,
+    #[serde(default)]
+    conversation_log: KhojConversationLog,
+}
+
+#[derive(Default, Deserialize)]
+struct KhojConversationLog {
+    #[serde(default)]
+    chat: Vec<KhojMessage>,
+}
+
+#[derive(Deserialize)]
+struct KhojMessage {
+    /// `you` or `khoj`
+    by: String,
+    #[serde(default)]
+    message: Value,
+    /// e.g. `2024-05-01 10:03:22`
+    created: Option<String>,
+}
+
+/// A file a Khoj server indexed
+#[derive(Deserialize)]
+struct KhojFile {
+    file_name: String,
+    #[serde(default)]
+    raw_text: String,
+}
+
+#[derive(Deserialize)]
+struct KhojFilePage {
+    #[serde(default)]
+    files: Vec<KhojFile>,
+    num_pages: Option<u32>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ImportStage {
+    Conversations,
+    Files,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportProgress {
+    stage: ImportStage,
+    done: usize,
+    total: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KhojImportSummary {
+    pub conversations_imported: usize,
+    /// Conversations the sidecar refused, with why
+    pub conversations_failed: Vec<String>,
+    pub files_imported: usize,
+    /// Indexed files that are still where Khoj found them, which Pipali
+    /// reads in place
+    pub files_on_disk: usize,
+    /// Folder the other indexed files were saved to, if there were any
+    pub files_dir: Option<PathBuf>,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(5))
+        .timeout_read(REQUEST_TIMEOUT)
+        .build()
+}
+
+/// Read conversations from a Khoj export file, or every `.json` file in a
+/// folder of them
+fn read_export(path: &Path) -> Result<Vec<KhojConversation>, String> {
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut conversations = Vec::new();
+    for file in files {
+        let contents = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+        let page: Vec<KhojConversation> = serde_json::from_str(&contents)
+            .map_err(|e| format!("{:?} isn't a Khoj chat export: {}", file, e))?;
+        conversations.extend(page);
+    }
+    Ok(conversations)
+}
+
+/// A Khoj server's `/api` URL, checked to be https, or http for a server on
+/// this machine, so the API key is never sent in the clear
+fn server_api_url(url: &str) -> Result<String, String> {
+    let parsed = url::Url::parse(url.trim()).map_err(|e| format!("Invalid Khoj URL: {}", e))?;
+    let local = parsed.host_str().is_some_and(crate::bind_host::is_loopback);
+    if !(parsed.scheme() == "https" || (parsed.scheme() == "http" && local)) {
+        return Err(
+            "The Khoj URL must start with https://, or http:// for Khoj on this machine"
+                .to_string(),
+        );
+    }
+    Ok(format!("{}/api", parsed.as_str().trim_end_matches('/')))
+}
+
+fn get_json<T: serde::de::DeserializeOwned>(
+    agent: &ureq::Agent,
+    url: &str,
+    api_key: Option<&str>,
+) -> Result<T, String> {
+    let mut request = agent.get(url);
+    if let Some(key) = api_key {
+        request = request.set("Authorization", &crate::bearer(key));
+    }
+    let body = request
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(401 | 403, _) => {
+                "Khoj refused the API key, create one in Khoj's settings".to_string()
+            }
+            e => e.to_string(),
+        })?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    serde_json::from_str(&body).map_err(|e| format!("Invalid response: {}", e))
+}
+
+/// Page through a Khoj server's chat export
+fn fetch_conversations(
+    agent: &ureq::Agent,
+    api_url: &str,
+    api_key: Option<&str>,
+) -> Result<Vec<KhojConversation>, String> {
+    let mut conversations = Vec::new();
+    for page in 0..MAX_PAGES {
+        let url = format!("{}/chat/export?page={}", api_url, page);
+        let batch: Vec<KhojConversation> = get_json(agent, &url, api_key)
+            .map_err(|e| format!("Failed to export chats from Khoj: {}", e))?;
+        if batch.is_empty() {
+            break;
+        }
+        conversations.extend(batch);
+    }
+    Ok(conversations)
+}
+
+/// Page through the files a Khoj server indexed
+///
+/// Servers from before Khoj listed indexed files have nothing to page
+/// through, so their files are skipped rather than failing the import.
+fn fetch_files(agent: &ureq::Agent, api_url: &str, api_key: Option<&str>) -> Vec<KhojFile> {
+    let mut files = Vec::new();
+    let mut num_pages = MAX_PAGES;
+    let mut page = 0;
+    while page < num_pages {
+        let url = format!("{}/content/files?page={}", api_url, page);
+        let batch: KhojFilePage = match get_json(agent, &url, api_key) {
+            Ok(batch) => batch,
+            Err(e) => {
+                log::warn!("[Khoj Import] Couldn't list indexed files: {}", e);
+                break;
+            }
+        };
+        if batch.files.is_empty() {
+            break;
+        }
+        num_pages = batch.num_pages.unwrap_or(MAX_PAGES).min(MAX_PAGES);
+        files.extend(batch.files);
+        page += 1;
+    }
+    files
+}
+
+/// A Khoj timestamp in RFC 3339, e.g. `2024-05-01 10:03:22` as
+/// `2024-05-01T10:03:22Z`. Khoj stores them in UTC without saying so.
+fn to_rfc3339(khoj_time: &str) -> Option<String> {
+    let trimmed = khoj_time.trim();
+    let (date, time) = trimmed.split_once([' ', 'T'])?;
+    if date.len() != 10 || time.len() < 8 {
+        return None;
+    }
+    let time = time.trim_end_matches('Z');
+    if time
+        .get(8..)
+        .is_some_and(|offset| offset.contains(['+', '-']))
+    {
+        Some(format!("{}T{}", date, time))
+    } else {
+        Some(format!("{}T{}Z", date, time))
+    }
+}
+
+/// Convert a Khoj conversation into an ATIF trajectory the sidecar imports
+fn to_atif(conversation: &KhojConversation, index: usize) -> Value {
+    let fallback_time = conversation
+        .created_at
+        .as_deref()
+        .and_then(to_rfc3339)
+        .unwrap_or_else(|| {
+            time::OffsetDateTime::now_utc()
+                .format(&time::format_description::well_known::Rfc3339)
+                .unwrap_or_default()
+        });
+    let steps: Vec<Value> = conversation
+        .conversation_log
+        .chat
+        .iter()
+        .filter_map(|message| {
+            // Khoj keeps some messages, e.g. generated images, as objects
+            let text = match &message.message {
+                Value::String(text) => text.clone(),
+                Value::Null => return None,
+                other => other.to_string(),
+            };
+            if text.trim().is_empty() {
+                return None;
+            }
+            let source = if message.by == "you" { "user" } else { "agent" };
+            let timestamp = message
+                .created
+                .as_deref()
+                .and_then(to_rfc3339)
+                .unwrap_or_else(|| fallback_time.clone());
+            Some((source, timestamp, text))
+        })
+        .enumerate()
+        .map(|(i, (source, timestamp, text))| {
+            json!({
+                "step_id": i + 1,
+                "timestamp": timestamp,
+                "source": source,
+                "message": text,
+            })
+        })
+        .collect();
+    json!({
+        "schema_version": ATIF_SCHEMA_VERSION,
+        "session_id": format!("khoj-import-{}", index + 1),
+        "agent": {
+            "name": "khoj",
+            "version": "unknown",
+            "model_name": "unknown",
+        },
+        "steps": steps,
+        "extra": { "importedFrom": "khoj" },
+    })
+}
+
+/// Where an indexed file is saved under `dir`, keeping the folders Khoj
+/// recorded it under but nothing that would lead outside `dir`
+fn file_target(dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let mut target = dir.to_path_buf();
+    let mut any = false;
+    for component in Path::new(&file_name.replace('\\', "/")).components() {
+        if let Component::Normal(part) = component {
+            target.push(part);
+            any = true;
+        }
+    }
+    any.then_some(target)
+}
+
+/// Import the conversations into the sidecar one at a time, then save the
+/// indexed files that aren't on this computer to `files_dir`
+fn run_import(
+    app: &AppHandle,
+    sidecar_url: &str,
+    auth_token: &str,
+    conversations: Vec<KhojConversation>,
+    files: Vec<KhojFile>,
+    files_dir: &Path,
+) -> Result<KhojImportSummary, String> {
+    let agent = agent();
+    let import_url = format!("{}/conversations/import/atif", sidecar_url);
+    let mut summary = KhojImportSummary {
+        conversations_imported: 0,
+        conversations_failed: Vec::new(),
+        files_imported: 0,
+        files_on_disk: 0,
+        files_dir: None,
+    };
+
+    let total = conversations.len();
+    for (index, conversation) in conversations.iter().enumerate() {
+        let title = conversation
+            .title
+            .clone()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| "Imported from Khoj".to_string());
+        let trajectory = to_atif(conversation, index);
+        // Khoj keeps chats that were opened but never written in
+        let empty = trajectory["steps"]
+            .as_array()
+            .is_none_or(|steps| steps.is_empty());
+        let response = if empty {
+            Ok(())
+        } else {
+            agent
+                .post(&import_url)
+                .set("Authorization", &crate::bearer(auth_token))
+                .set("Content-Type", "application/json")
+                .send_string(
+                    &json!({ "atifData": trajectory.to_string(), "title": title }).to_string(),
+                )
+                .map(|_| summary.conversations_imported += 1)
+        };
+        match response {
+            Ok(()) => {}
+            // The server went away, e.g. the user quit mid-import
+            Err(ureq::Error::Transport(e)) => {
+                return Err(format!("Lost the connection to the server: {}", e))
+            }
+            // The server refused this conversation, so carry on with the rest
+            Err(e) => {
+                log::warn!("[Khoj Import] Failed to import \"{}\": {}", title, e);
+                summary
+                    .conversations_failed
+                    .push(format!("{}: {}", title, e));
+            }
+        }
+        let progress = ImportProgress {
+            stage: ImportStage::Conversations,
+            done: index + 1,
+            total,
+        };
+        let _ = app.emit("khoj-import://progress", progress);
+    }
+
+    let total = files.len();
+    for (index, file) in files.iter().enumerate() {
+        let original = Path::new(&file.file_name);
+        if original.is_absolute() && original.is_file() {
+            summary.files_on_disk += 1;
+        } else if let Some(target) = file_target(files_dir, &file.file_name) {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            std::fs::write(&target, &file.raw_text)
+                .map_err(|e| format!("Failed to save {:?}: {}", target, e))?;
+            summary.files_imported += 1;
+            summary.files_dir = Some(files_dir.to_path_buf());
+        }
+        let progress = ImportProgress {
+            stage: ImportStage::Files,
+            done: index + 1,
+            total,
+        };
+        let _ = app.emit("khoj-import://progress", progress);
+    }
+    Ok(summary)
+}
+
+/// Import conversations, and a server's indexed files, from Khoj (exposed to
+/// frontend)
+///
+/// Conversations are converted to ATIF and imported through the sidecar, so
+/// they show up like any other. Indexed files still at the path Khoj indexed
+/// them from are read there; the rest are saved to a `khoj` folder in the
+/// app data dir, which Pipali is granted access to. Resolves to `None` if
+/// the user cancels picking the export. Emits `khoj-import://progress` after
+/// each conversation and file.
+#[tauri::command]
+pub async fn import_from_khoj(
+    app: AppHandle,
+    source: KhojSource,
+) -> Result<Option<KhojImportSummary>, String> {
+    let state = app.state::<KhojImportState>();
+    let Ok(_running) = state.running.try_lock() else {
+        return Err("An import from Khoj is already running".to_string());
+    };
+    let sidecar = app.state::<SidecarState>();
+    if sidecar.status() != SidecarStatus::Ready {
+        return Err("The server isn't running, so nothing can be imported".to_string());
+    }
+
+    let (conversations, files) = match source {
+        KhojSource::Export { path } => {
+            let path = match path {
+                Some(path) => PathBuf::from(path),
+                None => {
+                    let handle = app.clone();
+                    let picked = tauri::async_runtime::spawn_blocking(move || {
+                        handle
+                            .dialog()
+                            .file()
+                            .set_title("Choose a Khoj chat export")
+                            .add_filter("Khoj chat export", &["json"])
+                            .blocking_pick_file()
+                    })
+                    .await
+                    .map_err(|e| format!("File picker failed: {}", e))?;
+                    match picked.and_then(|path| path.into_path().ok()) {
+                        Some(path) => path,
+                        None => return Ok(None),
+                    }
+                }
+            };
+            log::info!("[Khoj Import] Importing the Khoj export at {:?}", path);
+            let conversations = tauri::async_runtime::spawn_blocking(move || read_export(&path))
+                .await
+                .map_err(|e| format!("Import task failed: {}", e))??;
+            (conversations, Vec::new())
+        }
+        KhojSource::Server { url, api_key } => {
+            let api_url = server_api_url(&url)?;
+            let api_key = api_key
+                .filter(|key| !key.trim().is_empty())
+                .map(Zeroizing::new);
+            log::info!("[Khoj Import] Importing from the Khoj server at {}", url);
+            tauri::async_runtime::spawn_blocking(move || {
+                let agent = agent();
+                let key = api_key.as_ref().map(|key| key.as_str());
+                let conversations = fetch_conversations(&agent, &api_url, key)?;
+                Ok::<_, String>((conversations, fetch_files(&agent, &api_url, key)))
+            })
+            .await
+            .map_err(|e| format!("Import task failed: {}", e))??
+        }
+    };
+    if conversations.is_empty() && files.is_empty() {
+        return Err("Found nothing to import from Khoj".to_string());
+    }
+
+    let files_dir = crate::normalize_windows_path(crate::get_app_data_dir(&app)?).join("khoj");
+    let sidecar_url = format!("http://{}:{}/api", sidecar.host, sidecar.port());
+    let auth_token: Zeroizing<String> = sidecar.auth_token.get();
+    let handle = app.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        run_import(
+            &handle,
+            &sidecar_url,
+            &auth_token,
+            conversations,
+            files,
+            &files_dir,
+        )
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))??;
+
+    if let Some(ref dir) = summary.files_dir {
+        crate::access_grants::grant(&app, std::slice::from_ref(dir))?;
+    }
+    log::info!(
+        "[Khoj Import] Imported {} conversations ({} failed) and {} files, {} more already on disk",
+        summary.conversations_imported,
+        summary.conversations_failed.len(),
+        summary.files_imported,
+        summary.files_on_disk
+    );
+    Ok(Some(summary))
+}
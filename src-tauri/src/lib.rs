@@ -1,61 +1,80 @@
+mod access_grants;
+mod always_on_top;
+mod api_protocol;
+mod app_lock;
+#[cfg(target_os = "macos")]
+mod app_menu;
+mod app_update;
+mod appimage;
+mod audit_log;
+mod backup;
+mod badge;
+mod battery;
+mod bind_host;
+mod clipboard;
+mod code_signature;
+mod command_scope;
 mod commands;
+mod context_menu;
+mod conversation_windows;
+mod crash_dumps;
+mod data_dir;
+mod db_compaction;
+mod db_encryption;
+mod deep_links;
+mod diagnostics;
+mod disk_usage;
+mod drag_out;
+mod export;
+mod file_drop;
+mod file_opener;
+mod hotkeys;
+mod idle_shutdown;
+mod khoj_import;
+mod local_tls;
+mod native_confirm;
+mod network;
+mod notification_actions;
+mod notifications;
+mod ocr;
+mod onboarding;
+mod pid_file;
+mod portable;
+mod power_events;
+mod privacy_mode;
+mod process_priority;
+mod process_tree;
+mod profiles;
+mod proxy;
+mod quick_ask;
+mod recent_conversations;
+mod resource_limits;
+mod resource_usage;
+mod screen_capture;
+mod secrets;
+mod selection;
+mod server_update;
+mod settings;
+mod sidecar_log;
+mod theme;
+mod tray;
 mod wake_lock;
-
-use std::sync::Mutex;
-use std::time::Duration;
-use std::time::Instant;
-use tauri::menu::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder};
-use tauri::tray::{MouseButton, MouseButtonState, TrayIconEvent};
+#[cfg(target_os = "linux")]
+mod wayland;
+mod window_state;
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager, State};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
-use tauri_plugin_shell::{process::CommandChild, ShellExt};
-
-/// Check for app updates and prompt user to install
-#[cfg(desktop)]
-async fn check_for_updates(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
-    use tauri_plugin_updater::UpdaterExt;
-
-    if cfg!(debug_assertions) {
-        log::info!("[Updater] Skipping update check in debug build");
-        return Ok(());
-    }
-
-    let Some(updater) = app.updater_builder().build().ok() else {
-        return Ok(());
-    };
-
-    let update = updater.check().await?;
-
-    if let Some(update) = update {
-        let version = update.version.clone();
-        let body = update.body.clone().unwrap_or_default();
-
-        let should_update = app
-            .dialog()
-            .message(format!(
-                "Update to {} is available!\n\nRelease notes:\n{}",
-                version, body
-            ))
-            .title("New Version Available")
-            .buttons(MessageDialogButtons::OkCancelCustom(
-                "Update".to_string(),
-                "Later".to_string(),
-            ))
-            .blocking_show();
-
-        if should_update {
-            log::info!("[Updater] Downloading and installing update...");
-            update.download_and_install(|_, _| {}, || {}).await?;
-            log::info!("[Updater] Update installed, restarting...");
-            app.restart();
-        }
-    } else {
-        log::info!("[Updater] No updates available");
-    }
+use tauri_plugin_shell::ShellExt;
+use zeroize::Zeroizing;
 
-    Ok(())
-}
+use settings::SettingsState;
+use sidecar_log::{
+    SidecarLog, SidecarLogBuffer, SidecarLogLevel, SidecarLogLine, SidecarLogStream,
+};
 
 /// Show the app in the dock and Cmd+Tab switcher (macOS)
 #[cfg(target_os = "macos")]
@@ -87,8 +106,13 @@ fn normalize_windows_path(path: std::path::PathBuf) -> std::path::PathBuf {
     path
 }
 
-/// Show the main window and emit an event to focus the chat input
+/// Show the main window and emit an event to focus the chat input, once the
+/// user has unlocked the app if it's locked
 fn show_window(app: &AppHandle) {
+    if app_lock::is_locked(app) {
+        app_lock::unlock_then_show(app);
+        return;
+    }
     show_in_dock(app);
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.unminimize();
@@ -111,30 +135,442 @@ fn toggle_window(app: &AppHandle) {
     }
 }
 
+/// Lifecycle status of the sidecar process
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarStatus {
+    /// Not running, either not started yet or stopped on request
+    Stopped,
+    /// Process spawned, waiting for the health check to pass
+    Starting,
+    /// Health check passed, server is accepting requests
+    Ready,
+    /// Process is running but not responding to health checks
+    Unhealthy,
+    /// Process exited unexpectedly
+    Crashed,
+    /// Waiting to restart after a crash
+    Restarting,
+}
+
+impl SidecarStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SidecarStatus::Stopped => "stopped",
+            SidecarStatus::Starting => "starting",
+            SidecarStatus::Ready => "ready",
+            SidecarStatus::Unhealthy => "unhealthy",
+            SidecarStatus::Crashed => "crashed",
+            SidecarStatus::Restarting => "restarting",
+        }
+    }
+}
+
+/// Payload for sidecar:// lifecycle events
+#[derive(Clone, Serialize)]
+struct SidecarLifecyclePayload {
+    status: SidecarStatus,
+    port: u16,
+}
+
+/// Payload for the sidecar://terminated event
+#[derive(Clone, Debug, Serialize)]
+struct SidecarTerminatedPayload {
+    pid: u32,
+    code: Option<i32>,
+    signal: Option<i32>,
+    /// False when the exit was requested via stop_sidecar
+    unexpected: bool,
+}
+
+/// Payload for the event forwarding a second launch's command line
+#[derive(Clone, Serialize)]
+struct SecondInstancePayload {
+    /// Arguments after the executable path
+    args: Vec<String>,
+    cwd: String,
+}
+
+/// Get the value of a `--name value` or `--name=value` command-line flag
+fn cli_flag_value(name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let prefix = format!("{}=", flag);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Whether a `--name` command-line switch was passed
+fn cli_switch(name: &str) -> bool {
+    let flag = format!("--{}", name);
+    std::env::args().skip(1).any(|arg| arg == flag)
+}
+
+/// Whether to start in the tray without showing the window, via `--hidden`
+/// (as passed on login) or the `startHidden` setting. Never before the user
+/// has been through onboarding, which needs the window.
+fn start_hidden(app: &AppHandle) -> bool {
+    (cli_switch("hidden") || app.state::<SettingsState>().get().start_hidden)
+        && !onboarding::is_pending(app)
+}
+
+/// Parse a `host:port` server address, optionally prefixed with `http://`
+fn parse_server_address(address: &str) -> Option<(String, u16)> {
+    let address = address
+        .trim()
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    let (host, port) = address.rsplit_once(':')?;
+    Some((host.to_string(), port.parse().ok()?))
+}
+
 /// Sidecar state management
 pub struct SidecarState {
-    pub child: Mutex<Option<CommandChild>>,
+    pub child: Mutex<Option<process_tree::SidecarChild>>,
     pub host: String,
-    pub port: u16,
+    /// Connect to an already running server instead of spawning the sidecar.
+    /// Set via `--external-server host:port` or PIPALI_EXTERNAL_SERVER.
+    pub external: bool,
+    /// Port requested via PIPALI_PORT (defaults to 6464). A port saved in the
+    /// settings replaces the default.
+    pub preferred_port: u16,
+    /// Port the sidecar was actually started on
+    pub port: Mutex<u16>,
+    /// Port of the sidecar's TLS listener, when the local TLS setting is on
+    tls_port: Mutex<Option<u16>>,
+    /// Shared secret the server requires on API and WebSocket requests, so
+    /// other local processes can't drive the agent. Fresh for every launch
+    /// unless pinned with PIPALI_AUTH_TOKEN (e.g. for an external server).
+    pub auth_token: secrets::Secret,
+    /// Consecutive automatic restarts since the sidecar was last healthy
+    pub restart_attempts: Mutex<u32>,
+    pub status: Mutex<SidecarStatus>,
+    /// Pid of the most recently exited sidecar, signalled via `exited`
+    exited_pid: Mutex<Option<u32>>,
+    exited: Condvar,
+    /// Pid of the most recent sidecar to print its READY marker, signalled via `ready`
+    ready_pid: Mutex<Option<u32>>,
+    ready: tokio::sync::Notify,
+    /// Held while the frontend has a sidecar start in progress
+    start_lock: tokio::sync::Mutex<()>,
+    /// Recent sidecar output, for the frontend's server output panel
+    pub logs: SidecarLogBuffer,
+    /// What the running server last reported about itself via /api/health
+    server_info: Mutex<Option<ServerInfo>>,
+    /// Version of the downloaded server update the sidecar runs, if any
+    running_update: Mutex<Option<String>>,
+    /// Exit details of the most recent unexpected sidecar exit
+    last_exit: Mutex<Option<SidecarTerminatedPayload>>,
+    /// When recent automatic restarts happened, for crash-loop detection
+    recent_restarts: Mutex<VecDeque<Instant>>,
 }
 
 impl Default for SidecarState {
     fn default() -> Self {
+        let external_server = cli_flag_value("external-server")
+            .or_else(|| std::env::var("PIPALI_EXTERNAL_SERVER").ok())
+            .and_then(|address| {
+                let parsed = parse_server_address(&address);
+                if parsed.is_none() {
+                    log::warn!(
+                        "[Sidecar] Ignoring invalid external server address: {}",
+                        address
+                    );
+                }
+                parsed
+            });
+        let external = external_server.is_some();
+        let (host, preferred_port) = external_server.unwrap_or_else(|| {
+            let host = bind_host::requested();
+            let port = std::env::var("PIPALI_PORT")
+                .unwrap_or_else(|_| "6464".to_string())
+                .parse()
+                .unwrap_or(6464);
+            (host, port)
+        });
         Self {
             child: Mutex::new(None),
-            host: std::env::var("PIPALI_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: std::env::var("PIPALI_PORT").unwrap_or_else(|_| "6464".to_string()).parse().unwrap_or(6464),
+            host,
+            external,
+            preferred_port,
+            port: Mutex::new(preferred_port),
+            tls_port: Mutex::new(None),
+            auth_token: secrets::Secret::new(
+                std::env::var("PIPALI_AUTH_TOKEN")
+                    .ok()
+                    .filter(|token| !token.is_empty())
+                    .unwrap_or_else(generate_auth_token),
+            ),
+            restart_attempts: Mutex::new(0),
+            status: Mutex::new(SidecarStatus::Stopped),
+            exited_pid: Mutex::new(None),
+            exited: Condvar::new(),
+            ready_pid: Mutex::new(None),
+            ready: tokio::sync::Notify::new(),
+            start_lock: tokio::sync::Mutex::new(()),
+            logs: SidecarLogBuffer::default(),
+            server_info: Mutex::new(None),
+            running_update: Mutex::new(None),
+            last_exit: Mutex::new(None),
+            recent_restarts: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl SidecarState {
+    /// Get the port the sidecar is currently bound to
+    pub fn port(&self) -> u16 {
+        *self.port.lock().unwrap()
+    }
+
+    /// Get the port of the sidecar's TLS listener, if it has one
+    pub fn tls_port(&self) -> Option<u16> {
+        *self.tls_port.lock().unwrap()
+    }
+
+    /// Get the current lifecycle status
+    pub fn status(&self) -> SidecarStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// Record an automatic restart. Returns the number of restarts within
+    /// CRASH_LOOP_WINDOW, including this one.
+    fn record_restart(&self) -> usize {
+        let now = Instant::now();
+        let mut restarts = self.recent_restarts.lock().unwrap();
+        restarts.retain(|at| now.duration_since(*at) < CRASH_LOOP_WINDOW);
+        restarts.push_back(now);
+        restarts.len()
+    }
+
+    /// Clear restart bookkeeping, e.g. when the user asks to retry
+    fn reset_restarts(&self) {
+        *self.restart_attempts.lock().unwrap() = 0;
+        self.recent_restarts.lock().unwrap().clear();
+    }
+
+    /// Get what the running server last reported about itself
+    pub fn server_info(&self) -> Option<ServerInfo> {
+        self.server_info.lock().unwrap().clone()
+    }
+
+    /// Get the version of the downloaded server update the sidecar runs, if any
+    pub fn running_update(&self) -> Option<String> {
+        self.running_update.lock().unwrap().clone()
+    }
+
+    fn set_server_info(&self, info: ServerInfo) {
+        *self.server_info.lock().unwrap() = Some(info);
+    }
+
+    /// Record that the sidecar process with the given pid has exited
+    fn mark_exited(&self, pid: u32) {
+        *self.server_info.lock().unwrap() = None;
+        *self.exited_pid.lock().unwrap() = Some(pid);
+        self.exited.notify_all();
+        // Stop anyone waiting for this process to become ready
+        self.ready.notify_waiters();
+    }
+
+    /// Block until the sidecar process with the given pid has exited.
+    /// Returns false if it is still running after the timeout.
+    fn wait_for_exit(&self, pid: u32, timeout: Duration) -> bool {
+        let exited_pid = self.exited_pid.lock().unwrap();
+        let (_exited_pid, result) = self
+            .exited
+            .wait_timeout_while(exited_pid, timeout, |exited| *exited != Some(pid))
+            .unwrap();
+        !result.timed_out()
+    }
+
+    /// Record that the sidecar process with the given pid reported it is ready
+    fn mark_ready(&self, pid: u32) {
+        *self.ready_pid.lock().unwrap() = Some(pid);
+        self.ready.notify_waiters();
+    }
+
+    /// Wait until the sidecar process with the given pid reports it is ready,
+    /// failing early if it exits first
+    async fn wait_for_ready_marker(&self, pid: u32, timeout: Duration) -> Result<(), String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register before checking so a marker in between isn't missed
+            let notified = self.ready.notified();
+            if *self.ready_pid.lock().unwrap() == Some(pid) {
+                return Ok(());
+            }
+            if *self.exited_pid.lock().unwrap() == Some(pid) {
+                return Err("server exited before it was ready".to_string());
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Err("server did not report ready".to_string());
+            }
+        }
+    }
+
+    /// Transition to a new lifecycle status. Returns false if already in it.
+    fn set_status(&self, status: SidecarStatus) -> bool {
+        let mut current = self.status.lock().unwrap();
+        if *current == status {
+            return false;
+        }
+        log::info!("[Sidecar] Status: {:?} -> {:?}", *current, status);
+        *current = status;
+        true
+    }
+}
+
+/// Update the sidecar status and emit the matching sidecar://<status> event
+fn set_sidecar_status(app: &AppHandle, status: SidecarStatus) {
+    let state: State<SidecarState> = app.state();
+    if state.set_status(status) {
+        let event = format!("sidecar://{}", status.as_str());
+        let _ = app.emit(
+            &event,
+            SidecarLifecyclePayload {
+                status,
+                port: state.port(),
+            },
+        );
+        tray::update_status(app, status);
+        if status == SidecarStatus::Ready {
+            recent_conversations::refresh(app);
+        }
+    }
+}
+
+/// Whether PIPALI_PORT is set, which takes precedence over the saved port
+fn port_pinned_by_env() -> bool {
+    std::env::var_os("PIPALI_PORT").is_some()
+}
+
+/// Check whether a port is free to bind on the given host
+fn is_port_available(host: &str, port: u16) -> bool {
+    std::net::TcpListener::bind((host, port)).is_ok()
+}
+
+/// Generate a random hex token for authenticating with the sidecar
+fn generate_auth_token() -> String {
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    getrandom::getrandom(&mut *bytes).expect("Failed to generate sidecar auth token");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `Authorization` header value for the sidecar's auth token
+fn bearer(auth_token: &str) -> Zeroizing<String> {
+    Zeroizing::new(format!("Bearer {}", auth_token))
+}
+
+/// Check whether the server on a port is a Pipali sidecar left behind by a
+/// previous shell, i.e. one that reports a shell pid other than ours
+fn is_stale_sidecar(host: &str, port: u16) -> bool {
+    let health_url = format!("http://{}:{}/api/health", host, port);
+    fetch_server_info(&health_check_agent(), &health_url)
+        .ok()
+        .and_then(|info| info.shell_pid)
+        .is_some_and(|shell_pid| shell_pid != std::process::id())
+}
+
+/// Shut down a stale sidecar via its API and wait for it to release the port.
+/// Only works for sidecars started before auth tokens were introduced, as the
+/// others only accept the request with their token.
+fn take_over_stale_sidecar(host: &str, port: u16) -> bool {
+    let shutdown_url = format!("http://{}:{}/api/shutdown", host, port);
+    if let Err(e) = health_check_agent().post(&shutdown_url).call() {
+        log::warn!("[Sidecar] Failed to shut down stale sidecar: {}", e);
+        return false;
+    }
+    wait_for_port_release(host, port)
+}
+
+/// Wait for a sidecar that's shutting down to release its port
+fn wait_for_port_release(host: &str, port: u16) -> bool {
+    let deadline = Instant::now() + SIDECAR_STOP_TIMEOUT * 2;
+    while Instant::now() < deadline {
+        if is_port_available(host, port) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    false
+}
+
+/// Pick a port for the sidecar to listen on
+///
+/// Prefers the configured port so the server address stays predictable. If a
+/// stale sidecar from a previous run holds it, that sidecar is shut down and
+/// the port reused; if anything else owns it, an OS-assigned free port is used.
+fn allocate_sidecar_port(host: &str, preferred: u16) -> Result<u16, String> {
+    if is_port_available(host, preferred) {
+        return Ok(preferred);
+    }
+
+    if is_stale_sidecar(host, preferred) {
+        log::warn!(
+            "[Sidecar] Port {} is held by a stale sidecar, shutting it down",
+            preferred
+        );
+        if take_over_stale_sidecar(host, preferred) {
+            log::info!("[Sidecar] Took over port {} from stale sidecar", preferred);
+            return Ok(preferred);
         }
     }
+
+    let listener = std::net::TcpListener::bind((host, 0))
+        .map_err(|e| format!("Failed to find a free port on {}: {}", host, e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read allocated port: {}", e))?
+        .port();
+    log::warn!(
+        "[Sidecar] Port {} is already in use, using free port {} instead",
+        preferred,
+        port
+    );
+    Ok(port)
 }
 
 /// Get the app data directory for storing the database
 fn get_app_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    if let Some(dir) = portable::data_dir() {
+        return Ok(dir);
+    }
     app.path()
         .app_data_dir()
         .map_err(|e| format!("Failed to get app data dir: {}", e))
 }
 
+/// Get the directory shell settings and window state are saved to
+fn get_app_config_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    if let Some(dir) = portable::data_dir() {
+        return Ok(dir.join("config"));
+    }
+    app.path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config dir: {}", e))
+}
+
+/// Get the directory sidecar log files are written to
+fn get_logs_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(normalize_windows_path(get_app_data_dir(app)?).join("logs"))
+}
+
+/// Get the directory images pasted into the chat and screenshots are saved
+/// to, which the agent reads them from
+///
+/// Kept with the active profile's database, as only its conversations refer
+/// to them.
+fn get_attachments_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(normalize_windows_path(get_sidecar_data_dir(app)?).join("attachments"))
+}
+
 #[cfg(target_os = "windows")]
 fn get_home_dir() -> Option<std::path::PathBuf> {
     std::env::var_os("USERPROFILE").map(std::path::PathBuf::from)
@@ -145,6 +581,9 @@ fn get_home_dir() -> Option<std::path::PathBuf> {
     std::env::var_os("HOME").map(std::path::PathBuf::from)
 }
 
+// Each platform's block returns, which reads more evenly than making the
+// last one the tail expression
+#[allow(clippy::needless_return)]
 fn get_legacy_data_dir() -> Option<std::path::PathBuf> {
     #[cfg(target_os = "macos")]
     {
@@ -174,6 +613,54 @@ fn has_existing_data_dir(dir: &std::path::Path) -> bool {
     dir.join("db").exists() || dir.join("pipali.db").exists()
 }
 
+/// Get the data directory the sidecar stores its database in, which depends
+/// on the active profile
+fn get_sidecar_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    match app
+        .try_state::<SettingsState>()
+        .and_then(|settings| settings.get().active_profile)
+    {
+        Some(name) => profiles::profile_data_dir(app, &name),
+        None => get_default_data_dir(app),
+    }
+}
+
+/// Get the data directory of the default profile
+///
+/// Uses the folder the user moved it to, if any. Otherwise the legacy data
+/// directory if it already holds data, else the app data dir. Portable
+/// installs only use their own data folder.
+fn get_default_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    if let Some(dir) = data_dir::override_dir(app) {
+        return Ok(dir);
+    }
+    let app_data_dir = normalize_windows_path(get_app_data_dir(app)?);
+    let legacy_data_dir = get_legacy_data_dir().filter(|_| portable::data_dir().is_none());
+    let data_dir = legacy_data_dir
+        .as_ref()
+        .filter(|dir| has_existing_data_dir(dir))
+        .cloned()
+        .unwrap_or(app_data_dir);
+    Ok(normalize_windows_path(data_dir))
+}
+
+/// Server build to run instead of the bundled one, from `PIPALI_SIDECAR_PATH`.
+/// Takes either a JS/TS entry point for the bundled Bun or a server binary.
+fn sidecar_path_override() -> Option<std::path::PathBuf> {
+    std::env::var_os("PIPALI_SIDECAR_PATH")
+        .filter(|path| !path.is_empty())
+        .map(std::path::PathBuf::from)
+        .map(normalize_windows_path)
+}
+
+/// Whether a server override is a script for Bun rather than an executable
+fn is_script_path(path: &std::path::Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("js" | "mjs" | "cjs" | "ts")
+    )
+}
+
 /// Get the path to the bundled server source directory
 fn get_server_resource_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
     app.path()
@@ -182,55 +669,186 @@ fn get_server_resource_dir(app: &AppHandle) -> Result<std::path::PathBuf, String
         .map_err(|e| format!("Failed to get resource dir: {}", e))
 }
 
-/// Start the sidecar process
+/// Record a sidecar output line to the app log, log file and in-memory buffer,
+/// and stream it to the frontend as a sidecar://log event
+fn record_sidecar_output(
+    app: &AppHandle,
+    file_log: &mut SidecarLog,
+    stream: SidecarLogStream,
+    level: SidecarLogLevel,
+    line: &str,
+) {
+    let line = SidecarLogLine::new(stream, level, line);
+    log::log!(log::Level::from(level), "[Sidecar] {}", line.line);
+    file_log.write_line(&line);
+    let _ = app.emit("sidecar://log", &line);
+    if let Some(state) = app.try_state::<SidecarState>() {
+        state.logs.push(line);
+    }
+}
+
+/// Parse the `READY port=<port>` line the server prints once it is listening
+fn parse_ready_marker(line: &str) -> Option<u16> {
+    line.trim().strip_prefix("READY port=")?.parse().ok()
+}
+
+/// Terminate a sidecar left running by a previous shell, as recorded in the PID file
 ///
-/// This starts the Pipali server using the bundled Bun runtime.
-/// The server source code is bundled in the resources directory,
-/// and we use the bundled Bun binary to run it.
-pub fn start_sidecar(app: &AppHandle) -> Result<(), String> {
-    let state: State<SidecarState> = app.state();
-    let host = state.host.clone();
-    let port = state.port;
+/// Only a process that still has the recorded pid and start time is touched,
+/// so a pid the OS has since reused is left alone. On Unix its command line
+/// must also show it is our server. If the server on the recorded port
+/// reports the recorded token hash, the port is waited on so it can be reused.
+fn cleanup_orphaned_sidecar(data_dir: &std::path::Path, host: &str, entry_point: &std::path::Path) {
+    let Some(record) = pid_file::read(data_dir) else {
+        return;
+    };
 
-    // Check if already running
-    if state.child.lock().unwrap().is_some() {
-        log::info!("[Sidecar] Already running");
-        return Ok(());
+    #[cfg(unix)]
+    let is_orphan = record.is_running()
+        && process_command_line(record.pid)
+            .is_some_and(|cmd| cmd.contains(&*entry_point.to_string_lossy()));
+    #[cfg(not(unix))]
+    let is_orphan = {
+        let _ = entry_point;
+        record.is_running()
+    };
+
+    if is_orphan {
+        let health_url = format!("http://{}:{}/api/health", host, record.port);
+        let holds_port = record.auth_token_sha256.is_some()
+            && fetch_server_info(&health_check_agent(), &health_url)
+                .is_ok_and(|info| info.auth_token_sha256 == record.auth_token_sha256);
+        log::warn!(
+            "[Sidecar] Found orphaned sidecar (pid={}, port={}), terminating it",
+            record.pid,
+            record.port
+        );
+        terminate_process(record.pid);
+        if holds_port && !wait_for_port_release(host, record.port) {
+            log::warn!(
+                "[Sidecar] Orphaned sidecar did not release port {}",
+                record.port
+            );
+        }
     }
 
-    // Get and create the app data directory for the database
-    let app_data_dir = normalize_windows_path(get_app_data_dir(app)?);
-    let legacy_data_dir = get_legacy_data_dir();
-    let data_dir = legacy_data_dir
-        .as_ref()
-        .filter(|dir| has_existing_data_dir(dir))
-        .cloned()
-        .unwrap_or(app_data_dir);
-    let data_dir = normalize_windows_path(data_dir);
+    pid_file::remove(data_dir);
+}
 
-    if legacy_data_dir.as_ref().is_some_and(|dir| dir == &data_dir) {
-        log::info!("[Sidecar] Using legacy data directory: {:?}", data_dir);
-    }
+/// The server build a sidecar runs
+struct ServerEntry {
+    /// Server resources root, holding migrations and assets
+    server_dir: std::path::PathBuf,
+    /// Script run with the bundled Bun, or a server binary
+    entry_point: std::path::PathBuf,
+    run_with_bun: bool,
+    /// Version of the downloaded server update, when running one
+    update: Option<String>,
+}
 
-    std::fs::create_dir_all(&data_dir)
-        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+impl ServerEntry {
+    /// Use the build PIPALI_SIDECAR_PATH points at, else a downloaded server
+    /// update, else the bundled server
+    fn resolve(app: &AppHandle) -> Result<Self, String> {
+        // Developers can point at a server build outside the bundle
+        let server_override = sidecar_path_override();
+        let run_with_bun = server_override.as_deref().is_none_or(is_script_path);
+
+        let (server_dir, entry_point) = match server_override {
+            Some(path) => {
+                log::info!(
+                    "[Sidecar] Using server override from PIPALI_SIDECAR_PATH: {:?}",
+                    path
+                );
+                if !path.exists() {
+                    return Err(format!("PIPALI_SIDECAR_PATH does not exist: {:?}", path));
+                }
+                // A script at <dir>/dist/index.js keeps the bundle layout, so
+                // migrations are found in <dir>/drizzle as they are when bundled
+                let server_dir = path
+                    .parent()
+                    .map(|dir| match dir.file_name() {
+                        Some(name) if name == "dist" => dir.parent().unwrap_or(dir),
+                        _ => dir,
+                    })
+                    .map(|dir| dir.to_path_buf())
+                    .unwrap_or_default();
+                (server_dir, path)
+            }
+            None => {
+                // Get the bundled server directory
+                let server_dir = normalize_windows_path(get_server_resource_dir(app)?);
+
+                // Update binaries embed their migrations, so only the bundled
+                // resources are needed alongside them
+                if let Some((version, binary)) = server_update::installed_server(app) {
+                    log::info!(
+                        "[Sidecar] Using downloaded server {}: {:?}",
+                        version,
+                        binary
+                    );
+                    return Ok(Self {
+                        server_dir,
+                        entry_point: binary,
+                        run_with_bun: false,
+                        update: Some(version),
+                    });
+                }
+
+                // Verify the server entry point exists
+                // The server is bundled into a single JS file at dist/index.js
+                let entry_point = server_dir.join("dist").join("index.js");
+                if !entry_point.exists() {
+                    return Err(format!(
+                        "Server entry point not found: {:?}. The app bundle may be corrupted.",
+                        entry_point
+                    ));
+                }
+                (server_dir, entry_point)
+            }
+        };
+        Ok(Self {
+            server_dir,
+            entry_point,
+            run_with_bun,
+            update: None,
+        })
+    }
+}
 
-    // Get the bundled server directory
-    let server_dir = normalize_windows_path(get_server_resource_dir(app)?);
-    log::info!("[Sidecar] Server directory: {:?}", server_dir);
+/// Get the directory containing the bundled binaries (sidecars)
+///
+/// Tauri places sidecars next to the main executable.
+fn sidecar_binaries_dir() -> std::path::PathBuf {
+    let binaries_dir = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_default();
+    normalize_windows_path(binaries_dir)
+}
 
-    // Verify the server entry point exists
-    // The server is bundled into a single JS file at dist/index.js
-    let entry_point = server_dir.join("dist").join("index.js");
-    if !entry_point.exists() {
-        return Err(format!(
-            "Server entry point not found: {:?}. The app bundle may be corrupted.",
-            entry_point
-        ));
+/// The binary that runs a server: the bundled Bun, the copy extracted from
+/// an AppImage, or the server binary itself
+fn sidecar_program(app: &AppHandle, server: &ServerEntry) -> std::path::PathBuf {
+    match (appimage::bun_path(app), server.run_with_bun) {
+        (Some(bun), true) => bun,
+        (None, true) => sidecar_binaries_dir().join(format!("bun{}", std::env::consts::EXE_SUFFIX)),
+        (_, false) => server.entry_point.clone(),
     }
+}
 
-    log::info!("[Sidecar] Starting on {}:{}...", host, port);
-    log::info!("[Sidecar] Data directory: {:?}", data_dir);
+/// Build the command that runs a server on the given data directory and port
+///
+/// The auth token and keychain secrets aren't part of it, but are written to
+/// the server's stdin once spawned, see [`secrets::handshake`].
+fn build_sidecar_command(
+    app: &AppHandle,
+    server: &ServerEntry,
+    data_dir: &std::path::Path,
+    host: &str,
+    port: u16,
+) -> Result<tauri_plugin_shell::process::Command, String> {
+    bind_host::check(app, host)?;
 
     // Use NODE_USE_SYSTEM_CA=1 to ensure Bun uses the OS certificate store for SSL verification.
     // This handles corporate proxies, custom CAs, and system-trusted certificates properly.
@@ -241,85 +859,278 @@ pub fn start_sidecar(app: &AppHandle) -> Result<(), String> {
 
     // Build args for the server
     // The bundled Bun will run: bun run dist/index.js --port ... --host ...
-    let mut args = vec![
-        "run".to_string(),
-        entry_point.to_string_lossy().to_string(),
+    // A server binary override is run directly with just the server flags.
+    let mut args = Vec::new();
+    if server.run_with_bun {
+        args.push("run".to_string());
+        args.push(server.entry_point.to_string_lossy().to_string());
+    }
+    args.extend([
         "--port".to_string(),
         port.to_string(),
         "--host".to_string(),
-        host.clone(),
-    ];
+        host.to_string(),
+    ]);
     if let Some(ref url) = platform_url {
         log::info!("[Sidecar] Using platform URL: {}", url);
         args.push("--platform-url".to_string());
         args.push(url.clone());
     }
 
-    // Get the directory containing the bundled binaries (sidecars)
-    // Tauri places sidecars next to the main executable
-    let binaries_dir = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-        .unwrap_or_default();
-    let binaries_dir = normalize_windows_path(binaries_dir);
+    let binaries_dir = sidecar_binaries_dir();
 
     // Use the bundled Bun runtime to start the server
     // The "bun" sidecar is registered in tauri.conf.json
-    let sidecar_command = app
-        .shell()
-        .sidecar("bun")
-        .map_err(|e| format!("Failed to create Bun sidecar command: {}", e))?
+    let extracted_bun = appimage::bun_path(app);
+    // Check whichever binary will run is one we shipped
+    code_signature::verify(&sidecar_program(app, server))?;
+    let server_command = if server.run_with_bun {
+        match extracted_bun {
+            Some(ref bun) => app.shell().command(bun),
+            None => app
+                .shell()
+                .sidecar("bun")
+                .map_err(|e| format!("Failed to create Bun sidecar command: {}", e))?,
+        }
+    } else {
+        app.shell().command(&server.entry_point)
+    };
+    // User-configured variables go first so the ones the shell relies on win
+    let extra_env = app.state::<SettingsState>().get().env;
+    if !extra_env.is_empty() {
+        let names: Vec<&str> = extra_env.keys().map(String::as_str).collect();
+        log::info!(
+            "[Sidecar] Passing configured environment: {}",
+            names.join(", ")
+        );
+    }
+    let proxy_env = proxy::sidecar_env(app);
+    // Lets skills run the same untouched Bun as the server
+    let bun_env =
+        extracted_bun.map(|bun| ("PIPALI_BUNDLED_BUN", bun.to_string_lossy().to_string()));
+
+    Ok(server_command
         .args(&args)
+        .envs(extra_env)
+        // Proxies from the OS settings, unless the user configured their own
+        .envs(proxy_env)
+        .envs(bun_env)
         .env("NODE_USE_SYSTEM_CA", "1")
         .env("NODE_ENV", "production")
         .env("PIPALI_DATA_DIR", data_dir.to_string_lossy().to_string())
+        // The auth token and secrets follow on stdin
+        .env("PIPALI_HANDSHAKE_STDIN", "1")
+        // The webview loads the frontend bundled with the shell instead
+        .env("PIPALI_API_ONLY", "1")
+        // Lets a later shell recognise this sidecar if it outlives us
+        .env("PIPALI_SHELL_PID", std::process::id().to_string())
         // Set PIPALI_BUNDLED_RUNTIMES_DIR so the server knows where to find bundled uv/uvx
         .env("PIPALI_BUNDLED_RUNTIMES_DIR", binaries_dir.to_string_lossy().to_string())
         // Provide the server resources root for migrations/assets
-        .env("PIPALI_SERVER_RESOURCE_DIR", server_dir.to_string_lossy().to_string())
-        .current_dir(data_dir);
+        .env("PIPALI_SERVER_RESOURCE_DIR", server.server_dir.to_string_lossy().to_string())
+        .current_dir(data_dir))
+}
+
+/// Start the sidecar process
+///
+/// This starts the Pipali server using the bundled Bun runtime.
+/// The server source code is bundled in the resources directory,
+/// and we use the bundled Bun binary to run it.
+pub fn start_sidecar(app: &AppHandle) -> Result<(), String> {
+    let state: State<SidecarState> = app.state();
+    let host = state.host.clone();
+
+    // Check if already running
+    if state.child.lock().unwrap().is_some() {
+        log::info!("[Sidecar] Already running");
+        return Ok(());
+    }
 
-    let (mut rx, child) = sidecar_command
-        .spawn()
+    // In external server mode, just wait for the user-run server to be ready
+    if state.external {
+        log::info!(
+            "[Sidecar] Using external server at {}:{}, not spawning sidecar",
+            host,
+            state.preferred_port
+        );
+        set_sidecar_status(app, SidecarStatus::Starting);
+        return Ok(());
+    }
+
+    // Get and create the app data directory for the database
+    let data_dir = get_sidecar_data_dir(app)?;
+    if get_legacy_data_dir().is_some_and(|dir| dir == data_dir) {
+        log::info!("[Sidecar] Using legacy data directory: {:?}", data_dir);
+    }
+    std::fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    let server = ServerEntry::resolve(app)?;
+    log::info!("[Sidecar] Server directory: {:?}", server.server_dir);
+    *state.running_update.lock().unwrap() = server.update.clone();
+
+    // A sidecar orphaned by a killed shell would fight us over the database
+    cleanup_orphaned_sidecar(&data_dir, &host, &server.entry_point);
+
+    // Bind to the preferred port if available, else an OS-assigned free port.
+    // The listener is released before spawning so the server can bind to it.
+    let preferred_port = match app.state::<SettingsState>().get().saved_sidecar_port() {
+        Some(port) if !port_pinned_by_env() => port,
+        _ => state.preferred_port,
+    };
+    let port = allocate_sidecar_port(&host, preferred_port)?;
+    *state.port.lock().unwrap() = port;
+
+    log::info!("[Sidecar] Starting on {}:{}...", host, port);
+    log::info!("[Sidecar] Data directory: {:?}", data_dir);
+
+    let mut sidecar_command = build_sidecar_command(app, &server, &data_dir, &host, port)?;
+    let tls = local_tls::prepare(app, &host, state.tls_port());
+    *state.tls_port.lock().unwrap() = tls.as_ref().map(|tls| tls.port);
+    if let Some(tls) = tls {
+        log::info!("[Sidecar] Serving TLS on {}:{}", host, tls.port);
+        sidecar_command = sidecar_command.envs(tls.env());
+    }
+
+    // Run it in its own process tree so stopping it also stops its children
+    let started = std::time::SystemTime::now();
+    let handshake = secrets::handshake(app, &state.auth_token.get());
+    let (mut rx, child) = process_tree::spawn(sidecar_command, &handshake)
         .map_err(|e| format!("Failed to spawn Bun sidecar: {}", e))?;
+    crash_dumps::enable(app, &server, child.pid());
+
+    let limits =
+        resource_limits::ResourceLimits::from_settings(&app.state::<SettingsState>().get());
+    resource_limits::apply(&child, &limits);
+    process_priority::apply_to_new(app, &child);
 
     // Store the child process
+    let pid = child.pid();
     *state.child.lock().unwrap() = Some(child);
+    let record = pid_file::SidecarPidRecord::new(pid, port, &state.auth_token.get());
+    if let Err(e) = pid_file::write(&data_dir, &record) {
+        log::warn!("[Sidecar] {}", e);
+    }
+    set_sidecar_status(app, SidecarStatus::Starting);
+
+    // Persist output so startup failures can be debugged after the app closes
+    let mut sidecar_log = get_logs_dir(app)
+        .map(|dir| SidecarLog::open(&dir))
+        .unwrap_or_default();
+    record_sidecar_output(
+        app,
+        &mut sidecar_log,
+        SidecarLogStream::Shell,
+        SidecarLogLevel::Info,
+        &format!("Started sidecar (pid={}, port={})", pid, port),
+    );
 
     // Spawn a task to handle stdout/stderr
     let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
-        use tauri_plugin_shell::process::CommandEvent;
+        use process_tree::SidecarEvent;
         while let Some(event) = rx.recv().await {
             match event {
-                CommandEvent::Stdout(line) => {
-                    log::info!("[Sidecar] {}", String::from_utf8_lossy(&line));
+                SidecarEvent::Stdout(line) => {
+                    let line = String::from_utf8_lossy(&line);
+                    if let Some(ready_port) = parse_ready_marker(&line) {
+                        let state = app_handle.state::<SidecarState>();
+                        if ready_port != port {
+                            log::warn!(
+                                "[Sidecar] Listening on port {} instead of {}",
+                                ready_port,
+                                port
+                            );
+                            *state.port.lock().unwrap() = ready_port;
+                        }
+                        state.mark_ready(pid);
+                    }
+                    record_sidecar_output(
+                        &app_handle,
+                        &mut sidecar_log,
+                        SidecarLogStream::Stdout,
+                        SidecarLogLevel::Info,
+                        &line,
+                    );
                 }
-                CommandEvent::Stderr(line) => {
-                    log::warn!("[Sidecar] {}", String::from_utf8_lossy(&line));
+                SidecarEvent::Stderr(line) => {
+                    record_sidecar_output(
+                        &app_handle,
+                        &mut sidecar_log,
+                        SidecarLogStream::Stderr,
+                        SidecarLogLevel::Warn,
+                        &String::from_utf8_lossy(&line),
+                    );
                 }
-                CommandEvent::Error(err) => {
-                    log::error!("[Sidecar] Error: {}", err);
+                SidecarEvent::Error(err) => {
+                    record_sidecar_output(
+                        &app_handle,
+                        &mut sidecar_log,
+                        SidecarLogStream::Shell,
+                        SidecarLogLevel::Error,
+                        &format!("Error: {}", err),
+                    );
                 }
-                CommandEvent::Terminated(payload) => {
-                    log::info!(
-                        "[Sidecar] Terminated with code: {:?}, signal: {:?}",
-                        payload.code,
-                        payload.signal
+                SidecarEvent::Terminated { code, signal } => {
+                    record_sidecar_output(
+                        &app_handle,
+                        &mut sidecar_log,
+                        SidecarLogStream::Shell,
+                        SidecarLogLevel::Info,
+                        &format!("Terminated with code: {:?}, signal: {:?}", code, signal),
                     );
                     #[cfg(target_os = "windows")]
-                    if payload.code == Some(-1073741795) {
+                    if code == Some(-1073741795) {
                         log::error!(
                             "[Sidecar] Bun crashed with illegal instruction. This usually indicates an unsupported CPU instruction set."
                         );
                     }
-                    // Clear the child state
                     if let Some(state) = app_handle.try_state::<SidecarState>() {
-                        *state.child.lock().unwrap() = None;
+                        state.mark_exited(pid);
+                    }
+                    if pid_file::read(&data_dir).is_some_and(|record| record.pid == pid) {
+                        pid_file::remove(&data_dir);
+                    }
+                    // Clear the child state, which also kills any processes the
+                    // server left behind. If stop_sidecar already took the child
+                    // (or a newer process replaced it), this exit was expected.
+                    let unexpected = app_handle.try_state::<SidecarState>().is_some_and(|state| {
+                        let mut child = state.child.lock().unwrap();
+                        if child.as_ref().is_some_and(|c| c.pid() == pid) {
+                            *child = None;
+                            true
+                        } else {
+                            false
+                        }
+                    });
+                    let terminated = SidecarTerminatedPayload {
+                        pid,
+                        code,
+                        signal,
+                        unexpected,
+                    };
+                    let _ = app_handle.emit("sidecar://terminated", &terminated);
+                    if unexpected {
+                        *app_handle.state::<SidecarState>().last_exit.lock().unwrap() =
+                            Some(terminated);
+                        log::warn!("[Sidecar] Exited unexpectedly (pid={})", pid);
+                        set_sidecar_status(&app_handle, SidecarStatus::Crashed);
+                        notifications::notify_error(
+                            &app_handle,
+                            "Pipali server stopped",
+                            "The server exited unexpectedly and is being restarted",
+                        );
+                        if crash_dumps::is_crash(code, signal) {
+                            let dump_handle = app_handle.clone();
+                            let dump_data_dir = data_dir.clone();
+                            tauri::async_runtime::spawn_blocking(move || {
+                                crash_dumps::collect(&dump_handle, pid, &dump_data_dir, started)
+                            });
+                        }
+                        schedule_sidecar_restart(app_handle.clone());
                     }
                     break;
                 }
-                _ => {}
             }
         }
     });
@@ -328,70 +1139,735 @@ pub fn start_sidecar(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Wait for the sidecar to be ready by polling the health endpoint
-pub fn wait_for_sidecar_ready(host: &str, port: u16) -> Result<(), String> {
-    let health_url = format!("http://{}:{}/api/health", host, port);
-    let max_attempts = 50; // 10 seconds total (50 * 200ms)
+/// Maximum consecutive automatic restarts before giving up
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+/// Delay before the first automatic restart, doubled on each further attempt
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Upper bound for the delay between automatic restarts
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+/// Window over which restarts are counted for crash-loop detection
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(60);
+/// Restarts within CRASH_LOOP_WINDOW that count as a crash loop
+const CRASH_LOOP_THRESHOLD: usize = 4;
+
+/// Payload for the sidecar://restart-attempt event
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SidecarRestartPayload {
+    attempt: u32,
+    max_attempts: u32,
+    delay_ms: u64,
+}
 
-    // Create a ureq agent with a short timeout for health checks
-    let agent = ureq::AgentBuilder::new()
+/// Backoff delay for the given restart attempt (1-based)
+fn restart_backoff(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    RESTART_BASE_DELAY
+        .saturating_mul(1 << exponent)
+        .min(RESTART_MAX_DELAY)
+}
+
+/// Restart the sidecar after an unexpected exit, with exponential backoff
+///
+/// Gives up after MAX_RESTART_ATTEMPTS consecutive failures, or sooner if the
+/// sidecar is crash-looping, and shows a native error dialog instead. The
+/// attempt counter is reset once a restarted sidecar passes its health check.
+fn schedule_sidecar_restart(app: AppHandle) {
+    let state: State<SidecarState> = app.state();
+    let attempt = {
+        let mut attempts = state.restart_attempts.lock().unwrap();
+        *attempts += 1;
+        *attempts
+    };
+    let recent_restarts = state.record_restart();
+
+    if attempt > MAX_RESTART_ATTEMPTS || recent_restarts > CRASH_LOOP_THRESHOLD {
+        log::error!(
+            "[Sidecar] Giving up after {} restart attempts ({} in the last {:?})",
+            attempt - 1,
+            recent_restarts - 1,
+            CRASH_LOOP_WINDOW
+        );
+        // A downloaded server that keeps crashing is dropped in favour of
+        // the one it replaced, rather than leaving the user stuck
+        if let Some(update) = state.running_update() {
+            log::error!(
+                "[ServerUpdate] Server {} keeps crashing, rolling back",
+                update
+            );
+            roll_back_server_update(&app, &update);
+            state.reset_restarts();
+            tauri::async_runtime::spawn(async move {
+                start_supervised_sidecar(&app).await;
+            });
+            return;
+        }
+        let _ = app.emit("sidecar://restart-failed", attempt - 1);
+        show_crash_loop_dialog(&app);
+        return;
+    }
+
+    let delay = restart_backoff(attempt);
+    set_sidecar_status(&app, SidecarStatus::Restarting);
+    log::info!(
+        "[Sidecar] Restarting in {:?} (attempt {}/{})",
+        delay,
+        attempt,
+        MAX_RESTART_ATTEMPTS
+    );
+    let _ = app.emit(
+        "sidecar://restart-attempt",
+        SidecarRestartPayload {
+            attempt,
+            max_attempts: MAX_RESTART_ATTEMPTS,
+            delay_ms: delay.as_millis() as u64,
+        },
+    );
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        start_supervised_sidecar(&app).await;
+    });
+}
+
+/// Start the sidecar and wait for it to become ready, as part of a supervised restart
+async fn start_supervised_sidecar(app: &AppHandle) {
+    if let Err(e) = start_sidecar(app) {
+        log::error!("[Sidecar] Restart failed: {}", e);
+        schedule_sidecar_restart(app.clone());
+        return;
+    }
+
+    let state: State<SidecarState> = app.state();
+    match wait_for_sidecar_ready(app).await {
+        Ok(()) => {
+            log::info!("[Sidecar] Restarted successfully");
+            *state.restart_attempts.lock().unwrap() = 0;
+            set_sidecar_status(app, SidecarStatus::Ready);
+            let _ = app.emit("sidecar-ready", ());
+        }
+        // If the process died, its Terminated handler schedules the next attempt
+        Err(e) => {
+            log::error!("[Sidecar] Restarted sidecar not ready: {}", e);
+            set_sidecar_status(app, SidecarStatus::Unhealthy);
+        }
+    }
+}
+
+/// Payload for the server-update://rolled-back event
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerRollbackPayload {
+    failed_version: String,
+    /// Downloaded server now in use, or `None` for the bundled server
+    restored_version: Option<String>,
+}
+
+/// Stop using a downloaded server that failed, going back to the one before
+/// it, and tell the user. The caller restarts the sidecar.
+fn roll_back_server_update(app: &AppHandle, version: &str) {
+    let restored_version = server_update::mark_failed(app, version);
+    let restored = restored_version
+        .as_deref()
+        .map_or("the bundled server".to_string(), |v| {
+            format!("server {}", v)
+        });
+    log::info!(
+        "[ServerUpdate] Rolled back from server {} to {}",
+        version,
+        restored
+    );
+    notifications::notify_error(
+        app,
+        "Pipali server update rolled back",
+        &format!(
+            "Server {} failed to start, so Pipali went back to {}",
+            version, restored
+        ),
+    );
+    let _ = app.emit(
+        "server-update://rolled-back",
+        ServerRollbackPayload {
+            failed_version: version.to_string(),
+            restored_version,
+        },
+    );
+}
+
+/// Tell the user the sidecar keeps crashing, with options to retry or inspect the logs
+fn show_crash_loop_dialog(app: &AppHandle) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let state: State<SidecarState> = app.state();
+    let exit = match state.last_exit.lock().unwrap().as_ref() {
+        Some(exit) => format!("Exit code: {:?}, signal: {:?}", exit.code, exit.signal),
+        None => "The server stopped responding.".to_string(),
+    };
+    let recent_errors: Vec<String> = state
+        .logs
+        .recent(200)
+        .into_iter()
+        .filter(|line| matches!(line.stream, SidecarLogStream::Stderr))
+        .map(|line| line.line)
+        .collect();
+    let recent_errors = match recent_errors.len() {
+        0 => "(none)".to_string(),
+        len => recent_errors[len.saturating_sub(8)..].join("\n"),
+    };
+
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "The Pipali server keeps crashing and has been stopped.\n\n{}\n\nRecent errors:\n{}",
+            exit, recent_errors
+        ))
+        .title("Pipali Server Error")
+        .kind(MessageDialogKind::Error)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Retry".to_string(),
+            "Open Logs".to_string(),
+        ))
+        .show(move |retry| {
+            if retry {
+                log::info!("[Sidecar] Retrying after crash loop");
+                let state: State<SidecarState> = app_handle.state();
+                state.reset_restarts();
+                tauri::async_runtime::spawn(async move {
+                    start_supervised_sidecar(&app_handle).await;
+                });
+            } else {
+                open_logs_dir(&app_handle);
+            }
+        });
+}
+
+/// Open the sidecar logs directory in the system file manager
+fn open_logs_dir(app: &AppHandle) {
+    use tauri_plugin_opener::OpenerExt;
+
+    match get_logs_dir(app) {
+        Ok(dir) => {
+            if let Err(e) = app.opener().open_path(dir.to_string_lossy(), None::<&str>) {
+                log::error!("[App] Failed to open logs directory: {}", e);
+            }
+        }
+        Err(e) => log::error!("[App] {}", e),
+    }
+}
+
+/// Create a ureq agent with a short timeout for health checks
+fn health_check_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
         .timeout_connect(Duration::from_millis(500))
         .timeout(Duration::from_secs(2))
-        .build();
-
-    for attempt in 1..=max_attempts {
-        // Use native Rust HTTP client (no console windows on Windows)
-        match agent.get(&health_url).call() {
-            Ok(response) => {
-                if response.status() == 200 {
-                    log::info!("[Sidecar] Server ready after {} attempts", attempt);
-                    return Ok(());
+        .build()
+}
+
+/// What the server reports about itself on /api/health, for the About dialog
+/// and diagnostics
+///
+/// Every field is optional since older servers report less.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ServerInfo {
+    pub version: Option<String>,
+    /// Tag of the newest database migration the server has applied
+    pub schema_version: Option<String>,
+    /// Names of the configured AI model providers
+    pub providers: Option<Vec<String>>,
+    /// Pid of the shell that started the server, if any
+    pub shell_pid: Option<u32>,
+    /// SHA-256 of the auth token the server was started with, if any
+    pub auth_token_sha256: Option<String>,
+    /// Seconds since the server last handled a request or ran a task
+    pub idle_secs: Option<u64>,
+    /// Scheduled automations need the server running even while idle
+    pub keep_alive: Option<bool>,
+    /// Chats and automations running right now
+    pub active_tasks: Option<u32>,
+}
+
+/// Request the health endpoint and parse the server's report
+fn fetch_server_info(agent: &ureq::Agent, health_url: &str) -> Result<ServerInfo, String> {
+    // Use native Rust HTTP client (no console windows on Windows)
+    let response = match agent.get(health_url).call() {
+        Ok(response) if response.status() == 200 => response,
+        Ok(response) => return Err(format!("unexpected status {}", response.status())),
+        Err(ureq::Error::Status(code, _)) => return Err(format!("unexpected status {}", code)),
+        Err(ureq::Error::Transport(transport)) => return Err(transport.to_string()),
+    };
+    let body = response
+        .into_string()
+        .map_err(|e| format!("Failed to read health response: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("Invalid health response: {}", e))
+}
+
+/// Probe the sidecar health endpoint once
+///
+/// The request runs on the blocking thread pool so it never ties up an
+/// async runtime worker.
+async fn check_sidecar_health(agent: &ureq::Agent, health_url: &str) -> Result<ServerInfo, String> {
+    let agent = agent.clone();
+    let health_url = health_url.to_string();
+    tauri::async_runtime::spawn_blocking(move || fetch_server_info(&agent, &health_url))
+        .await
+        .map_err(|e| format!("Health check task failed: {}", e))?
+}
+
+/// GET a sidecar API endpoint and parse its JSON response
+async fn get_sidecar_json<T: serde::de::DeserializeOwned + Send + 'static>(
+    agent: &ureq::Agent,
+    url: String,
+    auth_token: Zeroizing<String>,
+) -> Result<T, String> {
+    let agent = agent.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let body = agent
+            .get(&url)
+            .set("Authorization", &bearer(&auth_token))
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&body).map_err(|e| format!("Invalid response: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Request task failed: {}", e))?
+}
+
+/// POST a JSON body to an authenticated sidecar endpoint
+///
+/// The body is serialized into a buffer that's wiped once sent, as it may
+/// hold secrets.
+async fn post_sidecar_json<B: Serialize + Send + 'static>(
+    agent: &ureq::Agent,
+    url: String,
+    auth_token: Zeroizing<String>,
+    body: B,
+) -> Result<(), String> {
+    let agent = agent.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let body = secrets::to_json_bytes(&body, 0).map_err(|e| e.to_string())?;
+        agent
+            .post(&url)
+            .set("Authorization", &bearer(&auth_token))
+            .set("Content-Type", "application/json")
+            .send_bytes(&body)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Request task failed: {}", e))?
+}
+
+/// The sidecar didn't pass a health check within the startup timeout
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarStartupTimeout {
+    pub timeout_ms: u64,
+    pub health_url: String,
+    pub last_error: String,
+}
+
+impl std::fmt::Display for SidecarStartupTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Sidecar failed to become ready within {}ms (last error from {}: {})",
+            self.timeout_ms, self.health_url, self.last_error
+        )
+    }
+}
+
+/// Wait for the sidecar to be ready
+///
+/// A sidecar we spawned prints a READY line once it is listening, which the
+/// output task picks up. An external server can't tell us, so its health
+/// endpoint is polled instead. The timeout and poll interval come from the
+/// shell settings.
+pub async fn wait_for_sidecar_ready(app: &AppHandle) -> Result<(), SidecarStartupTimeout> {
+    let state: State<SidecarState> = app.state();
+    let settings = app.state::<SettingsState>().get();
+    let timeout = settings.startup_timeout();
+    let agent = health_check_agent();
+
+    let spawned_pid = state
+        .child
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|child| child.pid());
+    if let Some(pid) = spawned_pid {
+        let marker = state.wait_for_ready_marker(pid, timeout).await;
+        // The marker can move the port, so only build the URL afterwards
+        let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+        let health = check_sidecar_health(&agent, &health_url).await;
+        let last_error = match marker {
+            Ok(()) => {
+                log::info!("[Sidecar] Server reported ready");
+                match health {
+                    Ok(info) => state.set_server_info(info),
+                    Err(e) => log::warn!("[Sidecar] Could not read server info: {}", e),
                 }
+                return Ok(());
             }
-            Err(_) => {}
+            Err(e) => e,
+        };
+
+        // Server builds without the marker (e.g. via PIPALI_SIDECAR_PATH) may
+        // still be up, so check once before giving up
+        if let Ok(info) = health {
+            log::info!("[Sidecar] Server ready without reporting it");
+            state.set_server_info(info);
+            return Ok(());
         }
+        return Err(SidecarStartupTimeout {
+            timeout_ms: timeout.as_millis() as u64,
+            health_url,
+            last_error,
+        });
+    }
+
+    let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+    let poll_interval = settings.startup_poll_interval();
+    let deadline = Instant::now() + timeout;
+
+    let mut attempts = 0;
+    let last_error = loop {
+        attempts += 1;
+        let error = match check_sidecar_health(&agent, &health_url).await {
+            Ok(info) => {
+                log::info!("[Sidecar] Server ready after {} attempts", attempts);
+                state.set_server_info(info);
+                return Ok(());
+            }
+            Err(e) => e,
+        };
 
-        if attempt < max_attempts {
-            std::thread::sleep(Duration::from_millis(200));
+        if Instant::now() + poll_interval >= deadline {
+            break error;
         }
+        tokio::time::sleep(poll_interval).await;
+    };
+
+    Err(SidecarStartupTimeout {
+        timeout_ms: timeout.as_millis() as u64,
+        health_url,
+        last_error,
+    })
+}
+
+/// Server version this shell was built for, read from the package.json the
+/// bundled server is built from
+const EXPECTED_SERVER_VERSION: &str = env!("PIPALI_SERVER_VERSION");
+
+/// Where users get a fresh install when the app bundle needs repairing
+const DOWNLOAD_URL: &str = "https://pipali.ai/download";
+
+/// Shell and server versions disagree, e.g. after a partial update
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarVersionMismatch {
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+impl std::fmt::Display for SidecarVersionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Pipali server version {} is not compatible with this app (version {})",
+            self.actual.as_deref().unwrap_or("unknown"),
+            self.expected
+        )
     }
+}
 
-    Err("Sidecar failed to become ready within timeout".to_string())
+/// Versions are compatible when their major and minor components match;
+/// patch releases don't change the interface between the shell and server
+fn is_compatible_server_version(actual: &str, expected: &str) -> bool {
+    fn major_minor(version: &str) -> Option<(&str, &str)> {
+        let mut parts = version.trim().trim_start_matches('v').split('.');
+        Some((parts.next()?, parts.next()?))
+    }
+    major_minor(actual).is_some_and(|actual| major_minor(expected) == Some(actual))
 }
 
-/// Stop the sidecar process gracefully
-pub fn stop_sidecar(app: &AppHandle) -> Result<(), String> {
-    let state: State<SidecarState> = app.state();
-    let mut child_guard = state.child.lock().unwrap();
+/// Check the running server's version against the one this shell expects
+///
+/// Only a definite mismatch is an error. If the server info couldn't be read
+/// the sidecar's readiness checks deal with it, so the gate lets startup go on.
+fn check_sidecar_version(state: &SidecarState) -> Result<(), SidecarVersionMismatch> {
+    let Some(info) = state.server_info() else {
+        log::warn!("[Sidecar] Could not read server info to check its version");
+        return Ok(());
+    };
 
-    if let Some(child) = child_guard.take() {
-        log::info!("[Sidecar] Stopping...");
+    let actual = info.version;
+    match actual {
+        Some(ref version) if is_compatible_server_version(version, EXPECTED_SERVER_VERSION) => {
+            log::info!("[Sidecar] Server version {} is compatible", version);
+            Ok(())
+        }
+        // Servers from before the handshake don't report a version at all
+        _ => Err(SidecarVersionMismatch {
+            expected: EXPECTED_SERVER_VERSION.to_string(),
+            actual,
+        }),
+    }
+}
 
-        let pid = child.pid();
+/// Tell the user the app bundle is inconsistent and offer to reinstall, then quit
+fn show_version_mismatch_dialog(app: &AppHandle, mismatch: &SidecarVersionMismatch) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+    use tauri_plugin_opener::OpenerExt;
 
-        #[cfg(unix)]
-        {
-            if let Err(e) = send_sigterm(pid) {
-                log::warn!("[Sidecar] Failed to send SIGTERM (pid={}): {}", pid, e);
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "{}.\n\nThis can happen after an interrupted update. \
+             Reinstall Pipali to repair it; your data will be kept.",
+            mismatch
+        ))
+        .title("Pipali Needs Repair")
+        .kind(MessageDialogKind::Error)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Reinstall".to_string(),
+            "Quit".to_string(),
+        ))
+        .show(move |reinstall| {
+            if reinstall {
+                if let Err(e) = app_handle.opener().open_url(DOWNLOAD_URL, None::<&str>) {
+                    log::error!("[App] Failed to open download page: {}", e);
+                }
             }
+            app_handle.exit(1);
+        });
+}
+
+/// Wait for a freshly started sidecar, check its version and tell the
+/// frontend it can start fetching data
+///
+/// Returns false if the server is incompatible, in which case it is stopped
+/// and the user is asked to reinstall.
+async fn complete_sidecar_startup(app: &AppHandle) -> bool {
+    let state: State<SidecarState> = app.state();
+    let version_check = loop {
+        // Wait for sidecar to be ready
+        let ready = match wait_for_sidecar_ready(app).await {
+            Ok(()) => {
+                set_sidecar_status(app, SidecarStatus::Ready);
+                true
+            }
+            Err(e) => {
+                log::error!("Sidecar not ready: {}", e);
+                // Don't fail - the UI will show connection error
+                let _ = app.emit("sidecar://startup-timeout", &e);
+                set_sidecar_status(app, SidecarStatus::Unhealthy);
+                false
+            }
+        };
+        let version_check = check_sidecar_version(&state);
+
+        // A downloaded server that doesn't come up properly is dropped in
+        // favour of the bundled one
+        let Some(update) = state
+            .running_update()
+            .filter(|_| !ready || version_check.is_err())
+        else {
+            break version_check;
+        };
+        log::error!(
+            "[ServerUpdate] Server {} failed its startup checks, rolling back",
+            update
+        );
+        roll_back_server_update(app, &update);
+        let handle = app.clone();
+        let _ = tauri::async_runtime::spawn_blocking(move || stop_sidecar(&handle)).await;
+        if let Err(e) = start_sidecar(app) {
+            log::error!("[Sidecar] Failed to start bundled server: {}", e);
+            return true;
+        }
+    };
 
-            let deadline = Instant::now() + Duration::from_secs(3);
-            while Instant::now() < deadline {
-                if !is_process_alive(pid) {
-                    log::info!("[Sidecar] Stopped gracefully (pid={})", pid);
-                    return Ok(());
+    // A mismatched server could corrupt data or break the UI, so
+    // refuse to go on. Servers run by developers are exempt.
+    let developer_server = state.external || sidecar_path_override().is_some();
+    if let Err(mismatch) = version_check {
+        if developer_server {
+            log::warn!("[Sidecar] {}, continuing with developer server", mismatch);
+        } else {
+            log::error!("[Sidecar] {}", mismatch);
+            let _ = app.emit("sidecar://version-mismatch", &mismatch);
+            let handle = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = stop_sidecar(&handle) {
+                    log::error!("[Sidecar] Failed to stop: {}", e);
+                }
+            })
+            .await;
+            show_version_mismatch_dialog(app, &mismatch);
+            return false;
+        }
+    }
+
+    // The API must not be reachable from other devices without LAN mode
+    if !state.external {
+        let exposed = bind_host::verify_not_exposed(app, &state.host, state.port()).await;
+        if !exposed.is_empty() {
+            let addresses: Vec<String> = exposed.iter().map(|addr| addr.to_string()).collect();
+            log::error!(
+                "[Sidecar] Server is reachable from other devices at {}, stopping it",
+                addresses.join(", ")
+            );
+            let _ = app.emit("sidecar://exposed", &addresses);
+            let handle = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = stop_sidecar(&handle) {
+                    log::error!("[Sidecar] Failed to stop: {}", e);
                 }
-                std::thread::sleep(Duration::from_millis(100));
+            })
+            .await;
+            return false;
+        }
+    }
+
+    // Emit sidecar-ready event so frontend can start fetching data
+    log::info!("[App] Emitting sidecar-ready event");
+    let _ = app.emit("sidecar-ready", ());
+    true
+}
+
+/// How often the watchdog checks on a running sidecar
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(15);
+/// Consecutive failed health checks before the sidecar is restarted
+const WATCHDOG_MAX_FAILURES: u32 = 3;
+
+/// Periodically health-check the running sidecar and restart it if it hangs
+///
+/// Only Ready and Unhealthy sidecars are checked; startup, restarts and
+/// crashes are handled by the readiness wait and the restart supervisor.
+fn spawn_sidecar_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = health_check_agent();
+        let mut failures = 0;
+
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+
+            let state: State<SidecarState> = app.state();
+            let status = state.status();
+            if !matches!(status, SidecarStatus::Ready | SidecarStatus::Unhealthy) {
+                failures = 0;
+                continue;
             }
 
-            log::warn!("[Sidecar] Graceful stop timed out, forcing kill (pid={})", pid);
+            let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+            match check_sidecar_health(&agent, &health_url).await {
+                Ok(info) => {
+                    failures = 0;
+                    state.set_server_info(info);
+                    if status == SidecarStatus::Unhealthy {
+                        log::info!("[Watchdog] Sidecar recovered");
+                        set_sidecar_status(&app, SidecarStatus::Ready);
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    log::warn!(
+                        "[Watchdog] Health check failed ({}/{}): {}",
+                        failures,
+                        WATCHDOG_MAX_FAILURES,
+                        e
+                    );
+                    set_sidecar_status(&app, SidecarStatus::Unhealthy);
+
+                    // We can't restart a server we didn't start
+                    if failures >= WATCHDOG_MAX_FAILURES && !state.external {
+                        log::error!("[Watchdog] Sidecar unresponsive, restarting");
+                        failures = 0;
+                        let stop_handle = app.clone();
+                        let stopped = tauri::async_runtime::spawn_blocking(move || {
+                            stop_sidecar(&stop_handle)
+                        })
+                        .await;
+                        if let Ok(Err(e)) = stopped {
+                            log::error!("[Watchdog] Failed to stop sidecar: {}", e);
+                        }
+                        schedule_sidecar_restart(app.clone());
+                    }
+                }
+            }
         }
+    });
+}
+
+/// How long to wait for the sidecar to exit on its own before force-killing it
+const SIDECAR_STOP_TIMEOUT: Duration = Duration::from_secs(3);
+/// Longer wait for a server saving its encrypted database as it stops, past
+/// the 8 seconds it gives itself to shut down
+const ENCRYPTED_SIDECAR_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a sidecar is given to exit on its own, which is longer while the
+/// database is encrypted so stopping doesn't cut its last save short
+fn sidecar_stop_timeout(app: &AppHandle) -> Duration {
+    if app.state::<SettingsState>().get().encrypt_database {
+        ENCRYPTED_SIDECAR_STOP_TIMEOUT
+    } else {
+        SIDECAR_STOP_TIMEOUT
+    }
+}
+
+/// Stop the sidecar process gracefully
+///
+/// Asks the server to shut down (SIGTERM on Unix, the shutdown endpoint on
+/// Windows) so it can close the database cleanly, and only force-kills it if
+/// it hasn't exited within `sidecar_stop_timeout`.
+pub fn stop_sidecar(app: &AppHandle) -> Result<(), String> {
+    let state: State<SidecarState> = app.state();
+
+    // Release the lock before waiting so the output task can observe the exit
+    let Some(child) = state.child.lock().unwrap().take() else {
+        return Ok(());
+    };
+
+    log::info!("[Sidecar] Stopping...");
+    set_sidecar_status(app, SidecarStatus::Stopped);
+    if let Ok(data_dir) = get_sidecar_data_dir(app) {
+        pid_file::remove(&data_dir);
+    }
 
-        child
-            .kill()
-            .map_err(|e| format!("Failed to kill sidecar: {}", e))?;
-        log::info!("[Sidecar] Stopped");
+    let pid = child.pid();
+
+    #[cfg(unix)]
+    if let Err(e) = send_sigterm(pid) {
+        log::warn!("[Sidecar] Failed to send SIGTERM (pid={}): {}", pid, e);
+    }
+
+    #[cfg(not(unix))]
+    if let Err(e) = request_sidecar_shutdown(&state.host, state.port(), &state.auth_token.get()) {
+        log::warn!("[Sidecar] Failed to request shutdown (pid={}): {}", pid, e);
+    }
+
+    // Only the server itself is asked to stop, so it can shut down its own
+    // children; dropping `child` afterwards kills any that are left
+    if state.wait_for_exit(pid, sidecar_stop_timeout(app)) {
+        log::info!("[Sidecar] Stopped gracefully (pid={})", pid);
+        return Ok(());
+    }
+
+    log::warn!(
+        "[Sidecar] Graceful stop timed out, forcing kill (pid={})",
+        pid
+    );
+    child
+        .kill()
+        .map_err(|e| format!("Failed to kill sidecar: {}", e))?;
+    // Wait for the exit to be reported, so a restart doesn't race the old process
+    if !state.wait_for_exit(pid, SIDECAR_STOP_TIMEOUT) {
+        log::warn!("[Sidecar] No exit reported after kill (pid={})", pid);
     }
+    log::info!("[Sidecar] Stopped");
 
     Ok(())
 }
@@ -418,60 +1894,171 @@ fn is_process_alive(pid: u32) -> bool {
         .is_ok_and(|s| s.success())
 }
 
+#[cfg(unix)]
+fn process_command_line(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// SIGTERM a process, escalating to SIGKILL if it doesn't exit in time
+#[cfg(unix)]
+fn terminate_process(pid: u32) {
+    if let Err(e) = send_sigterm(pid) {
+        log::warn!("[Sidecar] Failed to send SIGTERM (pid={}): {}", pid, e);
+    }
+
+    let deadline = Instant::now() + SIDECAR_STOP_TIMEOUT;
+    while Instant::now() < deadline {
+        if !is_process_alive(pid) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    log::warn!(
+        "[Sidecar] Process did not exit, sending SIGKILL (pid={})",
+        pid
+    );
+    let _ = std::process::Command::new("kill")
+        .args(["-KILL", &pid.to_string()])
+        .status();
+}
+
+/// Kill a process outright, for platforms without SIGTERM
+#[cfg(not(unix))]
+fn terminate_process(pid: u32) {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    if !system.process(pid).is_some_and(|process| process.kill()) {
+        log::warn!("[Sidecar] Failed to kill process (pid={})", pid);
+    }
+}
+
+/// Ask the server to shut down via its API, for platforms without SIGTERM
+#[cfg(not(unix))]
+fn request_sidecar_shutdown(host: &str, port: u16, auth_token: &str) -> Result<(), String> {
+    let url = format!("http://{}:{}/api/shutdown", host, port);
+    ureq::post(&url)
+        .set("Authorization", &bearer(auth_token))
+        .timeout(Duration::from_secs(1))
+        .call()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Pin the type of a `generate_handler!` closure, whose argument can't be
+/// inferred once it's bound to a variable rather than passed to the builder
+fn command_handler<F>(handler: F) -> F
+where
+    F: Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static,
+{
+    handler
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    // GTK reads its backend flags when it starts
+    #[cfg(target_os = "linux")]
+    wayland::setup();
+    portable::setup();
+
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before starting
+        // its own sidecar on the same database
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            // When a second instance is launched, focus the existing window
+            log::info!("[App] Second instance detected");
+            // Check if the second instance was launched with a deep link URL
+            // The deep-link feature of single-instance plugin passes URLs in argv
+            for arg in argv.iter().skip(1) {
+                if arg.starts_with("pipali://") {
+                    deep_links::open(app, arg);
+                }
+            }
+            // A login launch while already running shouldn't pop up the window
+            let hidden = argv.iter().skip(1).any(|arg| arg == "--hidden");
+            let _ = app.emit(
+                "second-instance",
+                SecondInstancePayload {
+                    args: argv.into_iter().skip(1).collect(),
+                    cwd,
+                },
+            );
+            if !hidden {
+                show_window(app);
+            }
+        }))
+        // The webview's API requests, which get the auth token added here
+        .register_asynchronous_uri_scheme_protocol(api_protocol::SCHEME, api_protocol::handle)
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_dialog::init())
+        // Login launches start in the tray rather than over the user's workspace
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            Some(vec!["--hidden"]),
+        ))
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app, _shortcut, event| {
-                    if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
-                        log::info!("[App] Global shortcut triggered");
-                        toggle_window(app);
-                    }
-                })
+                .with_handler(|app, shortcut, event| hotkeys::handle(app, shortcut, event.state()))
                 .build(),
         )
         .plugin(tauri_plugin_deep_link::init())
-        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
-            // When a second instance is launched, focus the existing window
-            log::info!("[App] Second instance detected, focusing existing window");
-            // Check if the second instance was launched with a deep link URL
-            // The deep-link feature of single-instance plugin passes URLs in argv
-            for arg in argv.iter().skip(1) {
-                if arg.starts_with("pipali://") {
-                    log::info!("[App] Deep link from second instance: {}", arg);
-                    let _ = app.emit("deep-link", arg.clone());
-                }
-            }
-            show_window(app);
-        }))
         .manage(SidecarState::default())
         .manage(wake_lock::WakeLockState::default())
+        .manage(resource_usage::ResourceMonitorState::default())
+        .manage(profiles::ProfilesState::default())
+        .manage(process_priority::PriorityState::default())
+        .manage(hotkeys::HotkeysState::default())
+        .manage(clipboard::ClipboardState::default())
+        .manage(deep_links::DeepLinkState::default())
+        .manage(notifications::NotificationsState::default())
+        .manage(recent_conversations::RecentConversationsState::default())
+        .manage(theme::ThemeState::default())
+        .manage(always_on_top::AlwaysOnTopState::default())
+        .manage(context_menu::ContextMenuState::default())
+        .manage(access_grants::AccessGrantsState::default())
+        .manage(proxy::ProxyState::default())
+        .manage(network::NetworkState::default())
+        .manage(battery::BatteryState::default())
+        .manage(audit_log::AuditLogState::default())
+        .manage(commands::RestartState::default())
+        .manage(app_lock::AppLockState::default())
+        .manage(privacy_mode::PrivacyModeState::default())
+        .manage(app_update::AppUpdateState::default())
+        .manage(backup::BackupState::default())
+        .manage(export::ExportState::default())
+        .manage(khoj_import::KhojImportState::default())
+        .manage(db_compaction::CompactionState::default())
         .setup(|app| {
             // Initialize updater plugin
             #[cfg(desktop)]
             {
-                let handle = app.handle().clone();
                 app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
-
-                // Check for updates on startup (non-blocking)
-                tauri::async_runtime::spawn(async move {
-                    if let Err(e) = check_for_updates(&handle).await {
-                        log::warn!("[Updater] Failed to check for updates: {}", e);
-                    }
-                });
+                app_update::spawn_update_monitor(app.handle().clone());
             }
 
             let handle = app.handle().clone();
-            let state: State<SidecarState> = app.state();
-            let host = state.host.clone();
-            let port = state.port;
+
+            // Load shell settings before anything reads them
+            app.manage(SettingsState::load(&handle));
+            app.manage(onboarding::OnboardingState::load(&handle));
+
+            // Regain access to picked folders before the sidecar reads them
+            access_grants::setup(&handle);
+
+            // Relay the OS proxy settings so the sidecar can reach providers
+            proxy::setup(&handle);
 
             // Show app in dock immediately
             show_in_dock(&handle);
@@ -479,41 +2066,82 @@ pub fn run() {
             // Splash window is defined in tauri.conf.json and shown automatically
             log::info!("[App] Splash window should be visible");
 
-            // Start sidecar during setup
-            if let Err(e) = start_sidecar(&handle) {
+            // Start sidecar during setup, unless it should wait until it's needed
+            let lazy_start = handle.state::<SettingsState>().get().lazy_sidecar_start
+                && !handle.state::<SidecarState>().external;
+            if lazy_start {
+                log::info!("[Sidecar] Lazy start enabled, waiting for first use");
+            } else if let Err(e) = start_sidecar(&handle) {
                 log::error!("Failed to start sidecar: {}", e);
                 return Err(e.into());
             }
 
+            // Keep checking on the sidecar once it's up
+            spawn_sidecar_watchdog(handle.clone());
+            resource_limits::spawn_resource_limit_monitor(handle.clone());
+            power_events::spawn_power_monitor(handle.clone());
+            idle_shutdown::spawn_idle_monitor(handle.clone());
+            app_lock::spawn_lock_monitor(handle.clone());
+            wake_lock::spawn_task_monitor(handle.clone());
+            proxy::spawn_proxy_monitor(handle.clone());
+            network::spawn_network_monitor(handle.clone());
+            battery::spawn_battery_monitor(handle.clone());
+            backup::spawn_backup_monitor(handle.clone());
+            notifications::spawn_notification_monitor(handle.clone());
+
+            window_state::restore(&handle);
+
+            // Follow the link the app was launched with once the webview loads
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    for url in urls {
+                        deep_links::set_pending(&handle, url.as_str());
+                    }
+                }
+            }
+
+            let hidden = start_hidden(&handle);
+            if hidden {
+                log::info!("[App] Starting hidden in the tray");
+                if let Some(splash) = handle.get_webview_window("splashscreen") {
+                    let _ = splash.close();
+                }
+                hide_from_dock(&handle);
+            }
+
             // Spawn async task to wait for sidecar and transition windows
             // This allows the event loop to start so windows can render
             let app_handle = handle.clone();
             tauri::async_runtime::spawn(async move {
-                // Wait for sidecar to be ready
-                if let Err(e) = wait_for_sidecar_ready(&host, port) {
-                    log::error!("Sidecar not ready: {}", e);
-                    // Don't fail - the UI will show connection error
-                }
+                // A lazily started sidecar has nothing to wait for yet
+                if !lazy_start {
+                    if !complete_sidecar_startup(&app_handle).await {
+                        return;
+                    }
 
-                // Emit sidecar-ready event so frontend can start fetching data
-                log::info!("[App] Emitting sidecar-ready event");
-                let _ = app_handle.emit("sidecar-ready", ());
+                    // Signal splash screen to start transformation animation
+                    if !hidden {
+                        log::info!("[App] Server ready, triggering splash animation");
+                        if let Some(splash) = app_handle.get_webview_window("splashscreen") {
+                            // Call start() directly via JavaScript eval - more reliable than events
+                            let _ = splash.eval("start()");
+                        }
 
-                // Signal splash screen to start transformation animation
-                log::info!("[App] Server ready, triggering splash animation");
-                if let Some(splash) = app_handle.get_webview_window("splashscreen") {
-                    // Call start() directly via JavaScript eval - more reliable than events
-                    let _ = splash.eval("start()");
+                        // Wait for animation to complete (~2 seconds for the transformation)
+                        tokio::time::sleep(Duration::from_millis(2000)).await;
+                    }
                 }
 
-                // Wait for animation to complete (~2 seconds for the transformation)
-                std::thread::sleep(Duration::from_millis(2000));
-
-                // Close splash and show main window
+                // Close splash and show main window, which waits for the tray
+                // or hotkey when starting hidden
                 if let Some(splash) = app_handle.get_webview_window("splashscreen") {
                     let _ = splash.close();
                     log::info!("[App] Splash window closed");
                 }
+                if hidden {
+                    return;
+                }
                 if let Some(main_window) = app_handle.get_webview_window("main") {
                     let _ = main_window.show();
                     let _ = main_window.set_focus();
@@ -521,71 +2149,16 @@ pub fn run() {
                 }
             });
 
-            // Setup system tray menu
-            let show_item = MenuItemBuilder::with_id("show", "Show Pipali").build(app)?;
-            let keep_awake_item = CheckMenuItemBuilder::with_id("keep_awake", "Keep Device Awake")
-                .checked(false)
-                .build(app)?;
-            let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
-            let tray_menu = MenuBuilder::new(app)
-                .item(&show_item)
-                .separator()
-                .item(&keep_awake_item)
-                .separator()
-                .item(&quit_item)
-                .build()?;
-
-            // Get the tray icon created from tauri.conf.json and set its menu
-            if let Some(tray) = app.tray_by_id("main-tray") {
-                tray.set_menu(Some(tray_menu))?;
-
-                // Handle tray icon click - toggle window visibility
-                let app_handle = app.handle().clone();
-                tray.on_tray_icon_event(move |_tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        if let Some(window) = app_handle.get_webview_window("main") {
-                            if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                                hide_from_dock(&app_handle);
-                            } else {
-                                show_window(&app_handle);
-                            }
-                        }
-                    }
-                });
+            tray::setup(app)?;
+            theme::setup(app.handle());
 
-                // Handle tray menu item clicks
-                let app_handle = app.handle().clone();
-                tray.on_menu_event(move |_tray, event| {
-                    match event.id().as_ref() {
-                        "show" => {
-                            show_window(&app_handle);
-                        }
-                        "keep_awake" => {
-                            let state: State<wake_lock::WakeLockState> = app_handle.state();
-                            let is_checked = state.user_toggle();
-                            log::info!("[WakeLock] User toggled keep awake: {}", is_checked);
-                        }
-                        "quit" => {
-                            log::info!("[App] Quit requested from tray menu");
-                            app_handle.exit(0);
-                        }
-                        _ => {}
-                    }
-                });
-            }
+            #[cfg(target_os = "macos")]
+            app_menu::setup(app)?;
+            always_on_top::setup(app.handle());
+            context_menu::setup(app.handle());
 
-            // Register global shortcut: Alt+Space
-            let shortcut: Shortcut = "Alt+Space".parse().unwrap();
-            match app.global_shortcut().register(shortcut) {
-                Ok(_) => log::info!("[App] Global shortcut Alt+Space registered"),
-                Err(e) => log::warn!("[App] Failed to register Alt+Space shortcut (may already be in use): {e}"),
-            }
+            hotkeys::register_from_settings(app.handle());
+            selection::setup(app.handle());
 
             // Handle deep links when app is already running (macOS)
             #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -594,24 +2167,120 @@ pub fn run() {
                 let app_handle = app.handle().clone();
                 app.deep_link().on_open_url(move |event| {
                     for url in event.urls() {
-                        log::info!("[App] Deep link received: {}", url);
-                        let _ = app_handle.emit("deep-link", url.to_string());
-                        show_window(&app_handle);
+                        deep_links::open(&app_handle, url.as_str());
                     }
                 });
             }
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            commands::get_sidecar_port,
-            commands::get_sidecar_host,
-            commands::get_sidecar_config,
-            commands::restart_sidecar,
-            commands::focus_window,
-            wake_lock::acquire_wake_lock,
-            wake_lock::release_wake_lock
-        ])
+        .invoke_handler({
+            let handler = command_handler(tauri::generate_handler![
+                commands::get_sidecar_port,
+                commands::get_sidecar_host,
+                commands::get_sidecar_config,
+                api_protocol::get_websocket_url,
+                secrets::set_secret,
+                secrets::get_secret,
+                secrets::delete_secret,
+                native_confirm::confirm_natively,
+                db_encryption::encrypt_database,
+                commands::get_sidecar_status,
+                commands::get_sidecar_logs,
+                commands::get_server_info,
+                commands::restart_sidecar,
+                commands::set_sidecar_port,
+                commands::ensure_sidecar_running,
+                server_update::check_for_server_update,
+                server_update::get_server_update_status,
+                server_update::apply_server_update,
+                profiles::list_profiles,
+                profiles::create_profile,
+                profiles::start_profile,
+                profiles::stop_profile,
+                profiles::switch_profile,
+                data_dir::migrate_data_dir,
+                commands::get_sidecar_env,
+                commands::set_sidecar_env,
+                commands::focus_window,
+                commands::open_data_dir,
+                backup::backup_database,
+                backup::get_backup_schedule,
+                backup::set_backup_schedule,
+                export::export_conversations,
+                export::cancel_export,
+                khoj_import::import_from_khoj,
+                db_compaction::compact_database,
+                diagnostics::run_diagnostics,
+                onboarding::get_onboarding_status,
+                onboarding::complete_onboarding_step,
+                onboarding::reset_onboarding,
+                disk_usage::get_disk_usage,
+                commands::set_close_to_tray,
+                commands::set_start_hidden,
+                always_on_top::get_always_on_top,
+                always_on_top::set_always_on_top,
+                commands::set_native_notifications,
+                commands::get_autostart,
+                commands::set_autostart,
+                hotkeys::get_global_shortcut,
+                hotkeys::set_global_shortcut,
+                quick_ask::show_quick_ask,
+                quick_ask::hide_quick_ask,
+                conversation_windows::open_conversation_window,
+                deep_links::take_pending_deep_link,
+                deep_links::open_deep_link,
+                badge::set_badge_count,
+                clipboard::read_clipboard_image,
+                clipboard::write_clipboard_image,
+                clipboard::read_clipboard_html,
+                clipboard::write_clipboard_html,
+                drag_out::start_drag,
+                file_opener::open_path,
+                file_opener::reveal_in_file_manager,
+                context_menu::show_context_menu,
+                access_grants::pick_files,
+                access_grants::pick_folder,
+                access_grants::list_access_grants,
+                access_grants::revoke_access_grant,
+                proxy::get_system_proxy,
+                network::get_network_status,
+                battery::get_power_source,
+                screen_capture::list_capture_targets,
+                screen_capture::capture_screen,
+                ocr::ocr_image,
+                theme::get_system_theme,
+                resource_usage::get_sidecar_resource_usage,
+                resource_limits::set_sidecar_resource_limits,
+                process_priority::set_sidecar_priority,
+                process_priority::get_sidecar_priority,
+                crash_dumps::list_crash_dumps,
+                crash_dumps::clear_crash_dumps,
+                wake_lock::acquire_wake_lock,
+                wake_lock::release_wake_lock,
+                audit_log::export_audit_log,
+                app_lock::lock_app,
+                app_lock::unlock_app,
+                privacy_mode::get_privacy_mode,
+                privacy_mode::set_privacy_mode,
+                app_update::check_for_updates,
+                app_update::get_update_channel,
+                app_update::set_update_channel,
+                app_update::set_install_updates_on_exit,
+                app_update::download_update,
+                app_update::install_update
+            ]);
+            // Privileged commands are recorded to the audit log, and calls
+            // outside the window's scope or blocked by privacy mode turned
+            // away, before they run
+            move |invoke| {
+                audit_log::record(&invoke);
+                match command_scope::check(invoke).and_then(privacy_mode::check) {
+                    Some(invoke) => handler(invoke),
+                    None => true,
+                }
+            }
+        })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app_handle, event| {
@@ -624,19 +2293,65 @@ pub fn run() {
                     // Only hide main window to tray, let splashscreen close normally
                     if label == "main" {
                         api.prevent_close();
-                        if let Some(window) = app_handle.get_webview_window(&label) {
-                            let _ = window.hide();
+                        window_state::save(app_handle);
+                        if !app_handle.state::<SettingsState>().get().close_to_tray {
+                            // Exiting goes through ExitRequested, which stops the sidecar
+                            log::info!("[App] Main window closed, quitting");
+                            app_handle.exit(0);
+                        } else {
+                            if let Some(window) = app_handle.get_webview_window(&label) {
+                                let _ = window.hide();
+                            }
+                            hide_from_dock(app_handle);
+                            log::info!("[App] Window '{}' hidden to tray", label);
                         }
-                        hide_from_dock(app_handle);
-                        log::info!("[App] Window '{}' hidden to tray", label);
+                    } else if conversation_windows::is_conversation_window(&label) {
+                        conversation_windows::on_close_requested(app_handle, &label);
                     }
                 }
+                tauri::RunEvent::WindowEvent {
+                    label,
+                    event: tauri::WindowEvent::Focused(focused),
+                    ..
+                } if label == "main" => {
+                    process_priority::on_main_window_focus(app_handle, focused);
+                    notifications::on_main_window_focus(app_handle, focused);
+                    badge::on_main_window_focus(app_handle, focused);
+                    theme::on_main_window_focus(app_handle, focused);
+                    if !focused {
+                        recent_conversations::refresh(app_handle);
+                    }
+                }
+                tauri::RunEvent::WindowEvent {
+                    label,
+                    event: tauri::WindowEvent::ThemeChanged(new_theme),
+                    ..
+                } if label == "main" => {
+                    theme::on_theme_changed(app_handle, new_theme);
+                }
+                tauri::RunEvent::WindowEvent {
+                    label,
+                    event: tauri::WindowEvent::DragDrop(drag_drop),
+                    ..
+                } if label == "main" => {
+                    file_drop::on_main_window_drag_drop(app_handle, drag_drop);
+                }
+                tauri::RunEvent::WindowEvent {
+                    label,
+                    event: tauri::WindowEvent::Focused(focused),
+                    ..
+                } if label == quick_ask::LABEL => {
+                    quick_ask::on_focus_changed(app_handle, focused);
+                }
                 tauri::RunEvent::ExitRequested { .. } => {
+                    window_state::save(app_handle);
                     // Graceful shutdown on app exit (Cmd+Q, etc.)
                     log::info!("[App] Exit requested, stopping sidecar...");
                     if let Err(e) = stop_sidecar(app_handle) {
                         log::error!("Error stopping sidecar on exit: {}", e);
                     }
+                    profiles::stop_all(app_handle);
+                    app_update::install_on_exit(app_handle);
                 }
                 tauri::RunEvent::Exit => {
                     // Final cleanup when app is exiting (best-effort).
@@ -644,10 +2359,15 @@ pub fn run() {
                     if let Err(e) = stop_sidecar(app_handle) {
                         log::error!("Error stopping sidecar on exit: {}", e);
                     }
+                    profiles::stop_all(app_handle);
                     // Release wake lock on exit
                     if let Some(state) = app_handle.try_state::<wake_lock::WakeLockState>() {
                         state.release_all();
                     }
+                    // The process exits without dropping managed state
+                    if let Some(state) = app_handle.try_state::<SidecarState>() {
+                        state.auth_token.wipe();
+                    }
                 }
                 _ => {}
             }
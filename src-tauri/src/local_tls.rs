@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsState;
+
+/// Names the certificate is issued for, as the webview may reach the
+/// loopback server by any of them
+const HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+/// macOS rejects TLS certificates valid for more than 825 days
+const CERT_VALIDITY_DAYS: i64 = 800;
+/// Replace the certificate a month before it expires
+const CERT_RENEW_AFTER: Duration = Duration::from_secs(770 * 24 * 60 * 60);
+
+/// The sidecar's TLS listener, alongside the plain one the shell itself uses
+pub struct TlsListener {
+    pub port: u16,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl TlsListener {
+    /// Environment telling the server to serve TLS on a second port. Only
+    /// paths are passed, the key stays on disk.
+    pub fn env(&self) -> [(&'static str, String); 3] {
+        [
+            ("PIPALI_TLS_PORT", self.port.to_string()),
+            (
+                "PIPALI_TLS_CERT",
+                self.cert_path.to_string_lossy().to_string(),
+            ),
+            (
+                "PIPALI_TLS_KEY",
+                self.key_path.to_string_lossy().to_string(),
+            ),
+        ]
+    }
+}
+
+fn tls_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::get_app_data_dir(app)?.join("tls"))
+}
+
+fn needs_renewal(cert_path: &Path) -> bool {
+    std::fs::metadata(cert_path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_none_or(|age| age > CERT_RENEW_AFTER)
+}
+
+fn generate(cert_path: &Path, key_path: &Path) -> Result<(), String> {
+    let hosts = HOSTS
+        .iter()
+        .map(|host| host.to_string())
+        .collect::<Vec<_>>();
+    let mut params = rcgen::CertificateParams::new(hosts)
+        .map_err(|e| format!("Invalid certificate names: {}", e))?;
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, "Pipali local server");
+    params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth];
+    let now = time::OffsetDateTime::now_utc();
+    // A day's grace for clocks that are slightly behind
+    params.not_before = now - time::Duration::days(1);
+    params.not_after = now + time::Duration::days(CERT_VALIDITY_DAYS);
+
+    let key = rcgen::KeyPair::generate().map_err(|e| format!("Failed to generate key: {}", e))?;
+    let cert = params
+        .self_signed(&key)
+        .map_err(|e| format!("Failed to sign certificate: {}", e))?;
+
+    write_private(key_path, key.serialize_pem().as_bytes())?;
+    std::fs::write(cert_path, cert.pem()).map_err(|e| format!("Failed to write certificate: {}", e))
+}
+
+/// Write the key readable only by the user
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|e| format!("Failed to write key: {}", e))
+}
+
+/// Add the certificate to the user's trust store, so the webview accepts it
+#[cfg(target_os = "macos")]
+fn trust(cert_path: &Path) -> Result<(), String> {
+    // Goes in the user's default keychain, so no admin rights are needed
+    let status = std::process::Command::new("security")
+        .args(["add-trusted-cert", "-r", "trustRoot", "-p", "ssl"])
+        .arg(cert_path)
+        .status()
+        .map_err(|e| format!("Failed to run security: {}", e))?;
+    if !status.success() {
+        return Err(format!("security add-trusted-cert exited with {}", status));
+    }
+    Ok(())
+}
+
+/// Add the certificate to the user's trust store, so the webview accepts it
+#[cfg(windows)]
+fn trust(cert_path: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let status = std::process::Command::new("certutil")
+        .args(["-user", "-addstore", "Root"])
+        .arg(cert_path)
+        .stdout(std::process::Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| format!("Failed to run certutil: {}", e))?;
+    if !status.success() {
+        return Err(format!("certutil -addstore exited with {}", status));
+    }
+    Ok(())
+}
+
+/// WebKitGTK doesn't read a user trust store, so each webview is told to
+/// accept the certificate for the loopback hosts instead
+#[cfg(target_os = "linux")]
+fn trust_in_webviews(app: &AppHandle, cert_path: &Path) -> Result<(), String> {
+    let pem = std::fs::read_to_string(cert_path)
+        .map_err(|e| format!("Failed to read certificate: {}", e))?;
+    for window in app.webview_windows().into_values() {
+        let pem = pem.clone();
+        let result = window.with_webview(move |webview| {
+            use webkit2gtk::{WebContextExt, WebViewExt};
+
+            let Some(context) = webview.inner().context() else {
+                return;
+            };
+            match gio::TlsCertificate::from_pem(&pem) {
+                Ok(cert) => {
+                    for host in HOSTS {
+                        context.allow_tls_certificate_for_host(&cert, host);
+                    }
+                }
+                Err(e) => log::warn!("[TLS] Failed to load certificate: {}", e),
+            }
+        });
+        if let Err(e) = result {
+            log::warn!("[TLS] Failed to reach webview {}: {}", window.label(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Make sure the certificate exists, is current and is trusted
+///
+/// Trusting a new certificate on macOS and Windows asks the user to confirm,
+/// so a marker records that it's been done.
+fn ensure_certificate(app: &AppHandle) -> Result<(PathBuf, PathBuf), String> {
+    let dir = tls_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create TLS dir: {}", e))?;
+    let cert_path = dir.join("cert.pem");
+    let key_path = dir.join("key.pem");
+    let trusted_marker = dir.join("trusted");
+
+    if needs_renewal(&cert_path) || !key_path.exists() {
+        log::info!("[TLS] Generating a self-signed certificate for the local server");
+        generate(&cert_path, &key_path)?;
+        let _ = std::fs::remove_file(&trusted_marker);
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    if !trusted_marker.exists() {
+        log::info!("[TLS] Adding the local server's certificate to the trust store");
+        trust(&cert_path)?;
+        std::fs::write(&trusted_marker, b"")
+            .map_err(|e| format!("Failed to record trusted certificate: {}", e))?;
+    }
+    #[cfg(target_os = "linux")]
+    trust_in_webviews(app, &cert_path)?;
+
+    Ok((cert_path, key_path))
+}
+
+/// Set up the sidecar's TLS listener if the user turned it on
+///
+/// The previous port is reused when it's free, so URLs the webview already
+/// holds keep working across restarts. TLS is optional, so on failure this
+/// logs and the webview keeps using plain http/ws.
+pub fn prepare(app: &AppHandle, host: &str, previous_port: Option<u16>) -> Option<TlsListener> {
+    if !app.state::<SettingsState>().get().local_tls {
+        return None;
+    }
+    let result = ensure_certificate(app).and_then(|(cert_path, key_path)| {
+        let port = previous_port
+            .and_then(|port| std::net::TcpListener::bind((host, port)).ok())
+            .map_or_else(|| std::net::TcpListener::bind((host, 0)), Ok)
+            .and_then(|listener| listener.local_addr())
+            .map_err(|e| format!("Failed to find a free port on {}: {}", host, e))?
+            .port();
+        Ok(TlsListener {
+            port,
+            cert_path,
+            key_path,
+        })
+    });
+    match result {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            log::warn!("[TLS] Serving without TLS: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::SidecarState;
+
+/// Option ids the server's confirmation flow expects
+const APPROVE: &str = "yes";
+const DECLINE: &str = "no";
+
+/// What the server says a pending confirmation is for. Read from the server
+/// rather than the webview, so a compromised page can't dress it up.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfirmationRequest {
+    title: String,
+    message: Option<String>,
+    context: Option<ConfirmationContext>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfirmationContext {
+    affected_files: Option<Vec<String>>,
+    command_info: Option<CommandInfo>,
+}
+
+#[derive(Deserialize)]
+struct CommandInfo {
+    command: String,
+    reason: String,
+    workdir: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeConfirmation {
+    pub selected_option_id: String,
+    /// HMAC-SHA256 of `requestId:selectedOptionId` keyed with the auth token
+    pub signature: String,
+}
+
+fn describe(request: &ConfirmationRequest) -> String {
+    let mut lines = Vec::new();
+    if let Some(message) = &request.message {
+        lines.push(message.clone());
+    }
+    let context = request.context.as_ref();
+    if let Some(info) = context.and_then(|context| context.command_info.as_ref()) {
+        lines.push(format!("Command: {}", info.command));
+        lines.push(format!("In: {}", info.workdir));
+        lines.push(format!("Reason: {}", info.reason));
+    } else if let Some(files) = context.and_then(|context| context.affected_files.as_ref()) {
+        lines.extend(files.iter().map(|file| format!("File: {}", file)));
+    }
+    lines.join("\n\n")
+}
+
+fn sign(auth_token: &str, request_id: &str, option_id: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(auth_token.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}:{}", request_id, option_id).as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Ask the user to approve a high-risk tool call in a native dialog, and sign
+/// their answer for the server (exposed to frontend)
+///
+/// The server only accepts approvals of native confirmations with this
+/// signature, which the webview can't produce as it never sees the token.
+#[tauri::command]
+pub async fn confirm_natively(
+    app: AppHandle,
+    request_id: String,
+) -> Result<NativeConfirmation, String> {
+    let state: State<SidecarState> = app.state();
+    let url = format!(
+        "http://{}:{}/api/confirmations/native/{}",
+        state.host,
+        state.port(),
+        request_id
+    );
+    let request: ConfirmationRequest =
+        crate::get_sidecar_json(&crate::health_check_agent(), url, state.auth_token.get()).await?;
+
+    let handle = app.clone();
+    let approved = tauri::async_runtime::spawn_blocking(move || {
+        let mut dialog = handle
+            .dialog()
+            .message(describe(&request))
+            .title(request.title)
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Allow".to_string(),
+                "Deny".to_string(),
+            ));
+        if let Some(window) = handle.get_webview_window("main") {
+            dialog = dialog.parent(&window);
+        }
+        dialog.blocking_show()
+    })
+    .await
+    .map_err(|e| format!("Confirmation dialog failed: {}", e))?;
+
+    let option_id = if approved { APPROVE } else { DECLINE };
+    log::info!(
+        "[Confirm] User chose {:?} for confirmation {}",
+        option_id,
+        request_id
+    );
+    Ok(NativeConfirmation {
+        selected_option_id: option_id.to_string(),
+        signature: sign(&state.auth_token.get(), &request_id, option_id),
+    })
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{SidecarState, SidecarStatus};
+
+/// How often reachability is checked while online
+const ONLINE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Checked more often while offline, so the connection coming back is noticed
+/// quickly
+const OFFLINE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Failed checks in a row before we call it offline, so one dropped probe on
+/// a flaky network doesn't flash the banner
+const OFFLINE_AFTER_FAILURES: u32 = 2;
+/// Public DNS resolvers, which answer on 443 and need no lookup to reach
+const PROBE_ADDRESSES: [&str; 2] = ["1.1.1.1:443", "8.8.8.8:443"];
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStatus {
+    pub online: bool,
+    /// RFC 3339 time the status last changed, if it has
+    pub changed_at: Option<String>,
+}
+
+impl Default for NetworkStatus {
+    fn default() -> Self {
+        Self {
+            online: true,
+            changed_at: None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct NetworkState {
+    status: Mutex<NetworkStatus>,
+}
+
+fn connects(address: &str) -> bool {
+    let addresses: Vec<SocketAddr> = match address.to_socket_addrs() {
+        Ok(addresses) => addresses.collect(),
+        Err(_) => return false,
+    };
+    addresses
+        .iter()
+        .any(|address| TcpStream::connect_timeout(address, PROBE_TIMEOUT).is_ok())
+}
+
+/// Whether the internet can be reached, directly or through the system proxy
+fn reachable(app: &AppHandle) -> bool {
+    crate::proxy::proxy_address(app)
+        .into_iter()
+        .chain(PROBE_ADDRESSES.iter().map(|address| address.to_string()))
+        .any(|address| connects(&address))
+}
+
+/// Tell the sidecar whether to attempt cloud calls
+async fn notify_sidecar(app: &AppHandle, agent: &ureq::Agent, online: bool) {
+    let state: State<SidecarState> = app.state();
+    if state.status() != SidecarStatus::Ready {
+        return;
+    }
+    let url = format!("http://{}:{}/api/network", state.host, state.port());
+    let body = serde_json::json!({ "online": online });
+    if let Err(e) = crate::post_sidecar_json(agent, url, state.auth_token.get(), body).await {
+        log::debug!("[Network] Failed to update server: {}", e);
+    }
+}
+
+fn set_online(app: &AppHandle, online: bool) {
+    let changed_at = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    *app.state::<NetworkState>().status.lock().unwrap() = NetworkStatus {
+        online,
+        changed_at: Some(changed_at),
+    };
+    if online {
+        log::info!("[Network] Back online");
+        let _ = app.emit("network://online", ());
+    } else {
+        log::warn!("[Network] Offline");
+        let _ = app.emit("network://offline", ());
+    }
+}
+
+/// Watch for the internet going away and coming back
+///
+/// Changes are emitted as `network://online` and `network://offline`. While
+/// offline the sidecar is reminded on every check, so one restarted in the
+/// meantime also holds off its cloud calls.
+pub fn spawn_network_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = crate::health_check_agent();
+        let mut failures = 0;
+        loop {
+            let online = app.state::<NetworkState>().status.lock().unwrap().online;
+            tokio::time::sleep(if online {
+                ONLINE_CHECK_INTERVAL
+            } else {
+                OFFLINE_CHECK_INTERVAL
+            })
+            .await;
+
+            let handle = app.clone();
+            let Ok(reached) =
+                tauri::async_runtime::spawn_blocking(move || reachable(&handle)).await
+            else {
+                continue;
+            };
+            failures = if reached { 0 } else { failures + 1 };
+
+            if reached && !online {
+                set_online(&app, true);
+                notify_sidecar(&app, &agent, true).await;
+            } else if !reached && failures >= OFFLINE_AFTER_FAILURES {
+                if online {
+                    set_online(&app, false);
+                }
+                notify_sidecar(&app, &agent, false).await;
+            }
+        }
+    });
+}
+
+/// Whether the internet is reachable, for the offline banner (exposed to
+/// frontend)
+#[tauri::command]
+pub fn get_network_status(state: State<'_, NetworkState>) -> NetworkStatus {
+    state.status.lock().unwrap().clone()
+}
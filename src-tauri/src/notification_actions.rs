@@ -0,0 +1,144 @@
+use tauri::AppHandle;
+
+/// A button on an actionable notification
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+pub struct Action {
+    pub id: String,
+    pub label: String,
+}
+
+/// What the user did with an actionable notification
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+pub enum Interaction {
+    /// Pressed the button with this action id
+    Action(String),
+    /// Typed a reply inline, which only Notification Center offers
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    Reply(String),
+    /// Clicked the notification itself
+    Clicked,
+    /// Closed it, or it expired
+    Dismissed,
+}
+
+/// Show a notification with buttons, and an inline reply field if `reply`
+/// is set and the platform has one, and wait for the user's response
+///
+/// The notification plugin only supports actions on mobile, so this talks to
+/// the OS notification service directly. Blocks until the user responds, so
+/// call from a blocking task. Returns `None` where actionable notifications
+/// aren't supported.
+pub fn show(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    actions: &[Action],
+    reply: bool,
+) -> Option<Result<Interaction, String>> {
+    platform::show(app, title, body, actions, reply)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{Action, Interaction};
+    use tauri::AppHandle;
+
+    /// Freedesktop notifications have buttons, but no standard inline reply
+    pub fn show(
+        _app: &AppHandle,
+        title: &str,
+        body: &str,
+        actions: &[Action],
+        _reply: bool,
+    ) -> Option<Result<Interaction, String>> {
+        let mut notification = notify_rust::Notification::new();
+        notification
+            .appname("Pipali")
+            .summary(title)
+            .body(body)
+            // Invoked by clicking the notification body
+            .action("default", "Open");
+        for action in actions {
+            notification.action(&action.id, &action.label);
+        }
+        let handle = match notification.show() {
+            Ok(handle) => handle,
+            Err(e) => return Some(Err(e.to_string())),
+        };
+        let mut interaction = Interaction::Dismissed;
+        handle.wait_for_action(|action| {
+            interaction = match action {
+                "default" => Interaction::Clicked,
+                "__closed" => Interaction::Dismissed,
+                id => Interaction::Action(id.to_string()),
+            }
+        });
+        Some(Ok(interaction))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{Action, Interaction};
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+    use std::sync::Once;
+    use tauri::AppHandle;
+
+    static SET_APPLICATION: Once = Once::new();
+
+    /// Notification Center has one main button, which can be a dropdown of
+    /// actions or a reply field, besides the close button
+    pub fn show(
+        app: &AppHandle,
+        title: &str,
+        body: &str,
+        actions: &[Action],
+        reply: bool,
+    ) -> Option<Result<Interaction, String>> {
+        // Notifications are attributed to the app with this bundle id
+        SET_APPLICATION.call_once(|| {
+            let _ = mac_notification_sys::set_application(&app.config().identifier);
+        });
+        let labels: Vec<&str> = actions.iter().map(|action| action.label.as_str()).collect();
+        let main_button = match labels.as_slice() {
+            _ if reply => MainButton::Response("Reply"),
+            [] => return None,
+            [label] => MainButton::SingleAction(label),
+            labels => MainButton::DropdownActions("Respond", labels),
+        };
+        let response = Notification::new()
+            .title(title)
+            .message(body)
+            .main_button(main_button)
+            .close_button("Dismiss")
+            .send();
+        let interaction = match response {
+            Ok(NotificationResponse::ActionButton(label)) => actions
+                .iter()
+                .find(|action| action.label == label)
+                .map(|action| Interaction::Action(action.id.clone()))
+                .unwrap_or(Interaction::Dismissed),
+            Ok(NotificationResponse::Reply(text)) => Interaction::Reply(text),
+            Ok(NotificationResponse::Click) => Interaction::Clicked,
+            Ok(_) => Interaction::Dismissed,
+            Err(e) => return Some(Err(e.to_string())),
+        };
+        Some(Ok(interaction))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod platform {
+    use super::{Action, Interaction};
+    use tauri::AppHandle;
+
+    pub fn show(
+        _app: &AppHandle,
+        _title: &str,
+        _body: &str,
+        _actions: &[Action],
+        _reply: bool,
+    ) -> Option<Result<Interaction, String>> {
+        None
+    }
+}
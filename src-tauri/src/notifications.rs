@@ -0,0 +1,309 @@
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use zeroize::Zeroizing;
+
+use crate::notification_actions::{self, Action, Interaction};
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// How often the sidecar is asked about automations
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Most buttons put on a notification, as some platforms hide the rest
+const MAX_ACTIONS: usize = 3;
+
+/// Conversation of the last notification raised, opened once the user comes
+/// back to the window since desktop notifications don't report clicks
+#[derive(Default)]
+pub struct NotificationsState {
+    target: Mutex<Option<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingConfirmation {
+    id: String,
+    automation_name: Option<String>,
+    conversation_id: Option<String>,
+    request: ConfirmationRequest,
+}
+
+#[derive(Deserialize)]
+struct ConfirmationRequest {
+    title: String,
+    operation: String,
+    #[serde(default)]
+    options: Vec<ConfirmationOption>,
+}
+
+#[derive(Deserialize)]
+struct ConfirmationOption {
+    id: String,
+    label: String,
+}
+
+#[derive(Deserialize)]
+struct PendingConfirmations {
+    confirmations: Vec<PendingConfirmation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinishedExecution {
+    automation_name: String,
+    conversation_id: Option<String>,
+    status: String,
+    error_message: Option<String>,
+    completed_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FinishedExecutions {
+    executions: Vec<FinishedExecution>,
+}
+
+/// Whether the user can already see what the frontend shows them
+fn window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false)
+    })
+}
+
+fn enabled(app: &AppHandle) -> bool {
+    app.state::<SettingsState>().get().native_notifications
+}
+
+/// Collapse whitespace and cut text down to fit a notification
+fn truncate(text: &str, max_chars: usize) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.chars().count() <= max_chars {
+        return normalized;
+    }
+    let mut truncated: String = normalized.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Raise a native notification, opening `conversation_id` when the user
+/// next focuses the window
+fn notify(app: &AppHandle, title: &str, body: &str, conversation_id: Option<&str>) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(truncate(body, 100))
+        .show()
+    {
+        log::warn!("[App] Failed to show notification: {}", e);
+        return;
+    }
+    if let Some(id) = conversation_id {
+        *app.state::<NotificationsState>().target.lock().unwrap() = Some(id.to_string());
+    }
+}
+
+/// Let the user know about a problem while they aren't looking at the window
+pub fn notify_error(app: &AppHandle, title: &str, body: &str) {
+    if enabled(app) && !window_focused(app) {
+        notify(app, title, body, None);
+    }
+}
+
+/// Open the conversation of the last notification once the window is focused
+pub fn on_main_window_focus(app: &AppHandle, focused: bool) {
+    if !focused {
+        return;
+    }
+    let target = app
+        .state::<NotificationsState>()
+        .target
+        .lock()
+        .unwrap()
+        .take();
+    if let Some(id) = target {
+        crate::deep_links::open(app, &format!("pipali://chat/{}", id));
+    }
+}
+
+/// Raise a notification for an automation confirmation request that can be
+/// answered from the notification itself, sending the answer to the sidecar
+///
+/// Falls back to a plain notification where buttons aren't supported.
+fn notify_confirmation(
+    app: &AppHandle,
+    agent: &ureq::Agent,
+    confirmation: PendingConfirmation,
+    title: &'static str,
+    body: String,
+    base_url: String,
+    auth_token: Zeroizing<String>,
+) {
+    let (app, agent) = (app.clone(), agent.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let conversation_id = confirmation.conversation_id.as_deref();
+        let actions: Vec<Action> = confirmation
+            .request
+            .options
+            .iter()
+            .take(MAX_ACTIONS)
+            .map(|option| Action {
+                id: option.id.clone(),
+                label: option.label.clone(),
+            })
+            .collect();
+        // Questions without choices take a typed answer
+        let reply = confirmation.request.operation == "ask_user" && actions.is_empty();
+        let response =
+            match notification_actions::show(&app, title, &truncate(&body, 100), &actions, reply) {
+                None => return notify(&app, title, &body, conversation_id),
+                Some(Err(e)) => {
+                    log::warn!("[App] Failed to show actionable notification: {}", e);
+                    return notify(&app, title, &body, conversation_id);
+                }
+                Some(Ok(Interaction::Action(id))) => serde_json::json!({ "selectedOptionId": id }),
+                Some(Ok(Interaction::Reply(text))) => {
+                    serde_json::json!({ "selectedOptionId": "guidance", "guidance": text })
+                }
+                Some(Ok(Interaction::Clicked)) => {
+                    match conversation_id {
+                        Some(id) => crate::deep_links::open(&app, &format!("pipali://chat/{}", id)),
+                        None => crate::show_window(&app),
+                    }
+                    return;
+                }
+                Some(Ok(Interaction::Dismissed)) => return,
+            };
+
+        let url = format!("{}/confirmations/{}/respond", base_url, confirmation.id);
+        let result = agent
+            .post(&url)
+            .set("Authorization", &crate::bearer(&auth_token))
+            .set("Content-Type", "application/json")
+            .send_string(&response.to_string());
+        match result {
+            Ok(_) => {
+                log::info!(
+                    "[App] Responded to confirmation {} from notification",
+                    confirmation.id
+                );
+                // Lets the frontend drop its toast for the confirmation
+                let _ = app.emit("automation://confirmation-responded", &confirmation.id);
+            }
+            Err(e) => {
+                log::warn!("[App] Failed to respond to confirmation: {}", e);
+                notify_error(
+                    &app,
+                    "Couldn't send your response",
+                    "Open Pipali to respond to the request",
+                );
+            }
+        }
+    });
+}
+
+/// Raise native notifications for automation confirmation requests and
+/// finished automation runs while the window isn't focused
+///
+/// The frontend shows toasts for these itself while focused. Notifying from
+/// the shell means nothing is missed while the webview is hidden to the tray
+/// and throttled.
+pub fn spawn_notification_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = crate::health_check_agent();
+        let mut seen_confirmations: HashSet<String> = HashSet::new();
+        let mut since = humantime::format_rfc3339_millis(SystemTime::now()).to_string();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            let state: State<SidecarState> = app.state();
+            if !enabled(&app) || state.status() != SidecarStatus::Ready {
+                continue;
+            }
+            let base_url = format!("http://{}:{}/api/automations", state.host, state.port());
+            let auth_token = state.auth_token.get();
+            let notify_now = !window_focused(&app);
+
+            match crate::get_sidecar_json::<PendingConfirmations>(
+                &agent,
+                format!("{}/confirmations/pending", base_url),
+                auth_token.clone(),
+            )
+            .await
+            {
+                Ok(pending) => {
+                    let mut pending_ids = HashSet::new();
+                    for confirmation in pending.confirmations {
+                        pending_ids.insert(confirmation.id.clone());
+                        if seen_confirmations.contains(&confirmation.id) || !notify_now {
+                            continue;
+                        }
+                        let title = if confirmation.request.operation == "ask_user" {
+                            "Question from Pipali"
+                        } else {
+                            "Action Required"
+                        };
+                        let body = format!(
+                            "{}: {}",
+                            confirmation.automation_name.as_deref().unwrap_or("Routine"),
+                            confirmation.request.title
+                        );
+                        notify_confirmation(
+                            &app,
+                            &agent,
+                            confirmation,
+                            title,
+                            body,
+                            base_url.clone(),
+                            auth_token.clone(),
+                        );
+                    }
+                    seen_confirmations = pending_ids;
+                }
+                Err(e) => log::debug!("[App] Failed to check automation confirmations: {}", e),
+            }
+
+            match crate::get_sidecar_json::<FinishedExecutions>(
+                &agent,
+                format!("{}/executions?since={}", base_url, since),
+                auth_token,
+            )
+            .await
+            {
+                Ok(finished) => {
+                    // Newest first
+                    if let Some(latest) = finished.executions.first() {
+                        if let Some(ref completed_at) = latest.completed_at {
+                            since = completed_at.clone();
+                        }
+                    }
+                    if !notify_now {
+                        continue;
+                    }
+                    for execution in finished.executions.iter().rev() {
+                        let body = if execution.status == "failed" {
+                            format!(
+                                "Failed: {}",
+                                execution
+                                    .error_message
+                                    .as_deref()
+                                    .unwrap_or("Unknown error")
+                            )
+                        } else {
+                            "Finished running".to_string()
+                        };
+                        notify(
+                            &app,
+                            &truncate(&execution.automation_name, 50),
+                            &body,
+                            execution.conversation_id.as_deref(),
+                        );
+                    }
+                }
+                Err(e) => log::debug!("[App] Failed to check finished automations: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+/// Read the text in an image on-device, so screenshots can be understood
+/// without sending the image to a remote model (exposed to frontend)
+///
+/// Uses Vision on macOS, Windows.Media.Ocr on Windows and the Tesseract CLI
+/// on Linux. Lines of text are separated by newlines.
+#[tauri::command]
+pub async fn ocr_image(path: String) -> Result<String, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() || !path.is_file() {
+        return Err(format!("Not an image file: {}", path.display()));
+    }
+    let text = tauri::async_runtime::spawn_blocking(move || platform::recognize(&path))
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))??;
+    Ok(text.trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::rc::{autoreleasepool, Allocated, Retained};
+    use objc2::runtime::{AnyClass, AnyObject};
+    use objc2::{class, msg_send};
+    use std::ffi::{c_char, CStr, CString};
+    use std::path::Path;
+
+    #[link(name = "Vision", kind = "framework")]
+    extern "C" {}
+
+    /// VNRequestTextRecognitionLevelAccurate
+    const RECOGNITION_LEVEL_ACCURATE: isize = 0;
+
+    unsafe fn to_string(ns_string: *mut AnyObject) -> Option<String> {
+        if ns_string.is_null() {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![ns_string, UTF8String];
+        (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+
+    pub fn recognize(path: &Path) -> Result<String, String> {
+        let path = CString::new(path.to_string_lossy().into_owned()).map_err(|e| e.to_string())?;
+        let request_class = AnyClass::get(c"VNRecognizeTextRequest")
+            .ok_or("Text recognition needs macOS 10.15 or later")?;
+        let handler_class = AnyClass::get(c"VNImageRequestHandler")
+            .ok_or("Text recognition needs macOS 10.15 or later")?;
+        autoreleasepool(|_| unsafe {
+            let path: Retained<AnyObject> =
+                msg_send![class!(NSString), stringWithUTF8String: path.as_ptr()];
+            let url: Retained<AnyObject> = msg_send![class!(NSURL), fileURLWithPath: &*path];
+            let options: Retained<AnyObject> = msg_send![class!(NSDictionary), dictionary];
+            let handler: Allocated<AnyObject> = msg_send![handler_class, alloc];
+            let handler: Retained<AnyObject> =
+                msg_send![handler, initWithURL: &*url, options: &*options];
+
+            let request: Retained<AnyObject> = msg_send![request_class, new];
+            let _: () = msg_send![&request, setRecognitionLevel: RECOGNITION_LEVEL_ACCURATE];
+            let _: () = msg_send![&request, setUsesLanguageCorrection: true];
+            let requests: Retained<AnyObject> =
+                msg_send![class!(NSArray), arrayWithObject: &*request];
+
+            let mut error: *mut AnyObject = std::ptr::null_mut();
+            let performed: bool =
+                msg_send![&handler, performRequests: &*requests, error: &mut error];
+            if !performed {
+                let description: *mut AnyObject = if error.is_null() {
+                    std::ptr::null_mut()
+                } else {
+                    msg_send![error, localizedDescription]
+                };
+                return Err(format!(
+                    "Failed to recognize text: {}",
+                    to_string(description).unwrap_or_else(|| "unknown error".to_string())
+                ));
+            }
+
+            let results: *mut AnyObject = msg_send![&request, results];
+            let count: usize = if results.is_null() {
+                0
+            } else {
+                msg_send![results, count]
+            };
+            let mut lines = Vec::with_capacity(count);
+            for i in 0..count {
+                let observation: *mut AnyObject = msg_send![results, objectAtIndex: i];
+                let candidates: *mut AnyObject = msg_send![observation, topCandidates: 1usize];
+                let candidate: *mut AnyObject = msg_send![candidates, firstObject];
+                if candidate.is_null() {
+                    continue;
+                }
+                let text: *mut AnyObject = msg_send![candidate, string];
+                if let Some(text) = to_string(text) {
+                    lines.push(text);
+                }
+            }
+            Ok(lines.join("\n"))
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::path::Path;
+    use windows::core::HSTRING;
+    use windows::Graphics::Imaging::BitmapDecoder;
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Storage::{FileAccessMode, StorageFile};
+
+    fn recognize_lines(path: &Path) -> windows::core::Result<Vec<String>> {
+        let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(path))?.get()?;
+        let stream = file.OpenAsync(FileAccessMode::Read)?.get()?;
+        let bitmap = BitmapDecoder::CreateAsync(&stream)?
+            .get()?
+            .GetSoftwareBitmapAsync()?
+            .get()?;
+        let engine = OcrEngine::TryCreateFromUserProfileLanguages()?;
+        let result = engine.RecognizeAsync(&bitmap)?.get()?;
+        let mut lines = Vec::new();
+        for line in result.Lines()? {
+            lines.push(line.Text()?.to_string());
+        }
+        Ok(lines)
+    }
+
+    /// Recognizes text in the languages of the user's profile
+    pub fn recognize(path: &Path) -> Result<String, String> {
+        recognize_lines(path)
+            .map(|lines| lines.join("\n"))
+            .map_err(|e| format!("Failed to recognize text: {}", e))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Tesseract isn't bundled, as its language data is large
+    pub fn recognize(path: &Path) -> Result<String, String> {
+        let output = Command::new("tesseract")
+            .arg(path)
+            .arg("stdout")
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    "Install Tesseract (the tesseract-ocr package) to read text in images"
+                        .to_string()
+                }
+                _ => format!("Failed to run tesseract: {}", e),
+            })?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to recognize text: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
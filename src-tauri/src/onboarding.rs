@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+/// Steps of the first-run flow, in the order they're shown
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OnboardingStep {
+    Welcome,
+    SignIn,
+    /// Screen recording and accessibility access (macOS only)
+    Permissions,
+    /// Offer to start Pipali at login
+    Autostart,
+}
+
+impl OnboardingStep {
+    const ALL: [OnboardingStep; 4] = [
+        OnboardingStep::Welcome,
+        OnboardingStep::SignIn,
+        OnboardingStep::Permissions,
+        OnboardingStep::Autostart,
+    ];
+
+    /// Whether the step has anything to do on this platform
+    fn applies(self) -> bool {
+        self != OnboardingStep::Permissions || cfg!(target_os = "macos")
+    }
+}
+
+/// Onboarding progress, persisted to `onboarding.json` in the app config dir
+///
+/// Kept by the shell rather than the webview's storage, which can be cleared
+/// and isn't shared between windows.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Progress {
+    /// When Pipali was first launched, in seconds since the Unix epoch
+    first_run_at: Option<u64>,
+    /// Steps the user finished or skipped
+    completed_steps: BTreeSet<OnboardingStep>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingStatus {
+    /// Whether this is the launch `onboarding.json` was created on
+    pub first_run: bool,
+    pub completed_steps: BTreeSet<OnboardingStep>,
+    /// Step to show next, or `None` once onboarding is done
+    pub next_step: Option<OnboardingStep>,
+}
+
+pub struct OnboardingState {
+    path: Option<PathBuf>,
+    progress: Mutex<Progress>,
+    first_run: bool,
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl OnboardingState {
+    /// Load onboarding progress, starting it on the first launch
+    ///
+    /// Installs from before onboarding was tracked already have a database,
+    /// so they count as onboarded rather than being walked through it again.
+    pub fn load(app: &AppHandle) -> Self {
+        let path = crate::get_app_config_dir(app)
+            .ok()
+            .map(|dir| dir.join("onboarding.json"));
+        let saved = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str::<Progress>(&contents).ok());
+        if let Some(progress) = saved {
+            return Self {
+                path,
+                progress: Mutex::new(progress),
+                first_run: false,
+            };
+        }
+
+        let existing_install = crate::get_sidecar_data_dir(app)
+            .ok()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .is_some_and(|mut entries| entries.next().is_some());
+        let progress = Progress {
+            first_run_at: Some(unix_now()),
+            completed_steps: if existing_install {
+                OnboardingStep::ALL.into_iter().collect()
+            } else {
+                BTreeSet::new()
+            },
+        };
+        let state = Self {
+            path,
+            progress: Mutex::new(progress),
+            first_run: !existing_install,
+        };
+        // Saved straight away, so the next launch isn't a first run too
+        if let Err(e) = state.update(|_| {}) {
+            log::warn!("[Onboarding] {}", e);
+        }
+        if state.first_run {
+            log::info!("[Onboarding] First run, starting onboarding");
+        }
+        state
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Progress)) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("Config directory is unavailable")?;
+        let mut progress = self.progress.lock().unwrap();
+        let mut updated = progress.clone();
+        change(&mut updated);
+
+        let contents = serde_json::to_string_pretty(&updated).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to save onboarding progress: {}", e))?;
+
+        *progress = updated;
+        Ok(())
+    }
+
+    pub fn status(&self) -> OnboardingStatus {
+        let progress = self.progress.lock().unwrap();
+        OnboardingStatus {
+            first_run: self.first_run,
+            completed_steps: progress.completed_steps.clone(),
+            next_step: OnboardingStep::ALL
+                .into_iter()
+                .find(|step| step.applies() && !progress.completed_steps.contains(step)),
+        }
+    }
+}
+
+/// Whether the user still has onboarding steps to go through, during which
+/// the main window is shown even if Pipali was set to start hidden
+pub fn is_pending(app: &AppHandle) -> bool {
+    app.state::<OnboardingState>().status().next_step.is_some()
+}
+
+/// Get onboarding progress and the step to show next (exposed to frontend)
+#[tauri::command]
+pub fn get_onboarding_status(onboarding: State<'_, OnboardingState>) -> OnboardingStatus {
+    onboarding.status()
+}
+
+/// Mark an onboarding step finished or skipped, resolving with the step to
+/// show next (exposed to frontend)
+#[tauri::command]
+pub fn complete_onboarding_step(
+    onboarding: State<'_, OnboardingState>,
+    step: OnboardingStep,
+) -> Result<OnboardingStatus, String> {
+    onboarding.update(|progress| {
+        progress.completed_steps.insert(step);
+    })?;
+    let status = onboarding.status();
+    if status.next_step.is_none() {
+        log::info!("[Onboarding] Onboarding complete");
+    }
+    Ok(status)
+}
+
+/// Go through onboarding again from the start (exposed to frontend)
+#[tauri::command]
+pub fn reset_onboarding(
+    onboarding: State<'_, OnboardingState>,
+) -> Result<OnboardingStatus, String> {
+    onboarding.update(|progress| progress.completed_steps.clear())?;
+    log::info!("[Onboarding] Onboarding reset");
+    Ok(onboarding.status())
+}
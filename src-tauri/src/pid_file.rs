@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// Record of the running sidecar, written to the data directory so that a
+/// sidecar orphaned by a killed shell can be found and cleaned up on next launch
+#[derive(Serialize, Deserialize)]
+pub struct SidecarPidRecord {
+    pub pid: u32,
+    pub port: u16,
+    /// When the process started, in seconds since the epoch, so a pid the OS
+    /// has since handed to another process isn't mistaken for the sidecar
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    /// SHA-256 of the sidecar's auth token, matched against the one a server
+    /// reports from its health endpoint. The token itself is never written.
+    #[serde(default)]
+    pub auth_token_sha256: Option<String>,
+}
+
+impl SidecarPidRecord {
+    pub fn new(pid: u32, port: u16, auth_token: &str) -> Self {
+        Self {
+            pid,
+            port,
+            started_at: process_start_time(pid),
+            auth_token_sha256: Some(token_sha256(auth_token)),
+        }
+    }
+
+    /// Whether the recorded process is still running under its pid
+    pub fn is_running(&self) -> bool {
+        self.started_at
+            .is_some_and(|started_at| process_start_time(self.pid) == Some(started_at))
+    }
+}
+
+/// Hex SHA-256 of an auth token, as the server reports it
+pub fn token_sha256(auth_token: &str) -> String {
+    Sha256::digest(auth_token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// When a process started, in seconds since the epoch
+fn process_start_time(pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::new(),
+    );
+    system.process(pid).map(|process| process.start_time())
+}
+
+fn pid_file_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("sidecar.pid")
+}
+
+pub fn write(data_dir: &Path, record: &SidecarPidRecord) -> Result<(), String> {
+    let contents = serde_json::to_string(record).map_err(|e| e.to_string())?;
+
+    // The token hash identifies the sidecar, so keep the record private to the user
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options
+        .open(pid_file_path(data_dir))
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| format!("Failed to write sidecar PID file: {}", e))
+}
+
+pub fn read(data_dir: &Path) -> Option<SidecarPidRecord> {
+    let contents = std::fs::read_to_string(pid_file_path(data_dir)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn remove(data_dir: &Path) {
+    let path = pid_file_path(data_dir);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("[Sidecar] Failed to remove PID file {:?}: {}", path, e);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Files that make an install portable when placed beside the executable
+const MARKERS: [&str; 2] = ["portable.txt", ".portable"];
+
+static DATA_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// The `data` folder beside the executable, if Pipali runs in portable mode
+/// (Windows only), e.g. from a USB stick
+pub fn data_dir() -> Option<PathBuf> {
+    DATA_DIR
+        .get_or_init(|| {
+            if !cfg!(target_os = "windows") {
+                return None;
+            }
+            let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+            MARKERS
+                .iter()
+                .any(|marker| exe_dir.join(marker).exists())
+                .then(|| exe_dir.join("data"))
+        })
+        .clone()
+}
+
+/// Keep the webview's cache and storage in the portable data folder too,
+/// before the first webview is created
+pub fn setup() {
+    let Some(dir) = data_dir() else {
+        return;
+    };
+    log::info!("[App] Portable mode, keeping data in {:?}", dir);
+    if std::env::var_os("WEBVIEW2_USER_DATA_FOLDER").is_none() {
+        std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", dir.join("webview"));
+    }
+}
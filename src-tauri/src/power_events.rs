@@ -0,0 +1,219 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[cfg(target_os = "linux")]
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// How often the wall clock is checked for jumps caused by sleep
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Wall clock time missing from a check interval before we call it a sleep
+const SLEEP_GAP_THRESHOLD: Duration = Duration::from_secs(30);
+/// Both wake detectors can fire for the same wake; handle it once
+const WAKE_DEBOUNCE: Duration = Duration::from_secs(15);
+/// The network can take a moment to return after wake
+const WAKE_HEALTH_ATTEMPTS: u32 = 3;
+const WAKE_HEALTH_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WakePayload {
+    /// Roughly how long the system slept, if known
+    slept_secs: Option<u64>,
+}
+
+#[derive(Default)]
+struct PowerMonitor {
+    last_wake: Mutex<Option<Instant>>,
+    /// Pid of the sidecar we stopped for sleep, to resume on wake
+    #[cfg(target_os = "linux")]
+    paused_pid: Mutex<Option<u32>>,
+}
+
+/// Watch for the system going to sleep and waking up
+///
+/// Wake is detected everywhere by the wall clock jumping ahead of our timer.
+/// On Linux, logind also announces sleep beforehand, which lets the sidecar
+/// be paused if `pauseSidecarOnSleep` is set. On wake the sidecar is resumed,
+/// health-checked (and restarted if it doesn't recover), and the frontend is
+/// told to reconnect via `power://wake`.
+pub fn spawn_power_monitor(app: AppHandle) {
+    let monitor = Arc::new(PowerMonitor::default());
+
+    let clock_app = app.clone();
+    let clock_monitor = monitor.clone();
+    std::thread::spawn(move || {
+        let mut last_check = SystemTime::now();
+        loop {
+            std::thread::sleep(CLOCK_CHECK_INTERVAL);
+            let now = SystemTime::now();
+            let elapsed = now.duration_since(last_check).unwrap_or_default();
+            last_check = now;
+            if elapsed > CLOCK_CHECK_INTERVAL + SLEEP_GAP_THRESHOLD {
+                handle_wake(
+                    &clock_app,
+                    &clock_monitor,
+                    Some(elapsed - CLOCK_CHECK_INTERVAL),
+                );
+            }
+        }
+    });
+
+    #[cfg(target_os = "linux")]
+    std::thread::spawn(move || logind::watch(&app, &monitor));
+}
+
+#[cfg(target_os = "linux")]
+mod logind {
+    use super::{handle_sleep, handle_wake, PowerMonitor};
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use tauri::AppHandle;
+
+    /// Follow logind's PrepareForSleep signal, sent with `true` before sleep
+    /// and `false` after wake
+    pub fn watch(app: &AppHandle, monitor: &PowerMonitor) {
+        let child = Command::new("gdbus")
+            .args([
+                "monitor",
+                "--system",
+                "--dest",
+                "org.freedesktop.login1",
+                "--object-path",
+                "/org/freedesktop/login1",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let Some(stdout) = child.ok().and_then(|mut child| child.stdout.take()) else {
+            log::info!("[Power] logind is unavailable, detecting wake from the clock only");
+            return;
+        };
+
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if line.contains("PrepareForSleep (true") {
+                handle_sleep(app, monitor);
+            } else if line.contains("PrepareForSleep (false") {
+                handle_wake(app, monitor, None);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn handle_sleep(app: &AppHandle, monitor: &PowerMonitor) {
+    log::info!("[Power] System is going to sleep");
+    let _ = app.emit("power://sleep", ());
+
+    if !app.state::<SettingsState>().get().pause_sidecar_on_sleep {
+        return;
+    }
+    let state: State<SidecarState> = app.state();
+    let Some(pid) = state
+        .child
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|child| child.pid())
+    else {
+        return;
+    };
+    // The sidecar leads its own process group, so this pauses its children too
+    match signal_process_group(pid, "-STOP") {
+        Ok(()) => {
+            log::info!("[Power] Paused sidecar (pid={})", pid);
+            *monitor.paused_pid.lock().unwrap() = Some(pid);
+        }
+        Err(e) => log::warn!("[Power] Failed to pause sidecar: {}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn signal_process_group(pgid: u32, signal: &str) -> Result<(), String> {
+    let status = std::process::Command::new("kill")
+        .args([signal, "--", &format!("-{}", pgid)])
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("kill exited with status {}", status))
+    }
+}
+
+fn handle_wake(app: &AppHandle, monitor: &PowerMonitor, slept: Option<Duration>) {
+    {
+        let mut last_wake = monitor.last_wake.lock().unwrap();
+        if last_wake.is_some_and(|at| at.elapsed() < WAKE_DEBOUNCE) {
+            return;
+        }
+        *last_wake = Some(Instant::now());
+    }
+    log::info!(
+        "[Power] System woke up{}",
+        slept.map_or(String::new(), |d| format!(" after ~{}s", d.as_secs()))
+    );
+
+    #[cfg(target_os = "linux")]
+    if let Some(pid) = monitor.paused_pid.lock().unwrap().take() {
+        match signal_process_group(pid, "-CONT") {
+            Ok(()) => log::info!("[Power] Resumed sidecar (pid={})", pid),
+            Err(e) => log::warn!("[Power] Failed to resume sidecar: {}", e),
+        }
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        recheck_sidecar(&app).await;
+        let _ = app.emit(
+            "power://wake",
+            WakePayload {
+                slept_secs: slept.map(|d| d.as_secs()),
+            },
+        );
+    });
+}
+
+/// Health-check the sidecar straight away instead of waiting for the watchdog,
+/// restarting it if it stays unresponsive
+async fn recheck_sidecar(app: &AppHandle) {
+    let state: State<SidecarState> = app.state();
+    if !matches!(
+        state.status(),
+        SidecarStatus::Ready | SidecarStatus::Unhealthy
+    ) {
+        return;
+    }
+
+    let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+    let agent = crate::health_check_agent();
+    let mut last_error = String::new();
+    for attempt in 1..=WAKE_HEALTH_ATTEMPTS {
+        match crate::check_sidecar_health(&agent, &health_url).await {
+            Ok(info) => {
+                crate::set_sidecar_status(app, SidecarStatus::Ready);
+                state.set_server_info(info);
+                return;
+            }
+            Err(e) => last_error = e,
+        }
+        if attempt < WAKE_HEALTH_ATTEMPTS {
+            tokio::time::sleep(WAKE_HEALTH_RETRY_DELAY).await;
+        }
+    }
+
+    log::warn!("[Power] Sidecar unhealthy after wake: {}", last_error);
+    crate::set_sidecar_status(app, SidecarStatus::Unhealthy);
+    if state.external {
+        return;
+    }
+    let stop_handle = app.clone();
+    let stopped =
+        tauri::async_runtime::spawn_blocking(move || crate::stop_sidecar(&stop_handle)).await;
+    if let Ok(Err(e)) = stopped {
+        log::error!("[Power] Failed to stop sidecar: {}", e);
+    }
+    crate::schedule_sidecar_restart(app.clone());
+}
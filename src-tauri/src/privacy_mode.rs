@@ -0,0 +1,89 @@
+use std::sync::Mutex;
+use tauri::ipc::Invoke;
+use tauri::menu::{CheckMenuItem, CheckMenuItemBuilder};
+use tauri::{AppHandle, Emitter, Manager, State, Wry};
+
+use crate::settings::SettingsState;
+
+/// Commands that read the screen, the clipboard, what other apps are showing
+/// or the text in images, which privacy mode turns away whoever calls them
+const CAPTURE_COMMANDS: [&str; 5] = [
+    "capture_screen",
+    "list_capture_targets",
+    "ocr_image",
+    "read_clipboard_html",
+    "read_clipboard_image",
+];
+
+/// Menu items showing whether privacy mode is on
+#[derive(Default)]
+pub struct PrivacyModeState {
+    items: Mutex<Vec<CheckMenuItem<Wry>>>,
+}
+
+pub fn is_enabled(app: &AppHandle) -> bool {
+    app.state::<SettingsState>().get().privacy_mode
+}
+
+/// A "Privacy Mode" item for the tray menu, kept checked while it's on
+pub fn menu_item(app: &tauri::App, id: &str) -> tauri::Result<CheckMenuItem<Wry>> {
+    let item = CheckMenuItemBuilder::with_id(id, "Privacy Mode")
+        .checked(app.state::<SettingsState>().get().privacy_mode)
+        .build(app)?;
+    app.state::<PrivacyModeState>()
+        .items
+        .lock()
+        .unwrap()
+        .push(item.clone());
+    Ok(item)
+}
+
+/// Turn privacy mode on or off
+pub fn set(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    app.state::<SettingsState>()
+        .update(|settings| settings.privacy_mode = enabled)?;
+    for item in app.state::<PrivacyModeState>().items.lock().unwrap().iter() {
+        let _ = item.set_checked(enabled);
+    }
+    let _ = app.emit("privacy-mode://changed", enabled);
+    log::info!(
+        "[Privacy] Privacy mode {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Flip the setting from a menu item
+pub fn toggle(app: &AppHandle) {
+    if let Err(e) = set(app, !is_enabled(app)) {
+        log::error!("[Privacy] {}", e);
+    }
+}
+
+/// Turn away capture commands while privacy mode is on, handing any other
+/// call back
+pub fn check(invoke: Invoke) -> Option<Invoke> {
+    let command = invoke.message.command();
+    if !CAPTURE_COMMANDS.contains(&command) || !is_enabled(invoke.message.webview().app_handle()) {
+        return Some(invoke);
+    }
+    log::info!("[Privacy] Blocked {} in privacy mode", command);
+    let error = format!("{} is turned off in privacy mode", command);
+    invoke.resolver.reject(error);
+    None
+}
+
+/// Whether privacy mode is on (exposed to frontend)
+#[tauri::command]
+pub fn get_privacy_mode(settings: State<'_, SettingsState>) -> bool {
+    settings.get().privacy_mode
+}
+
+/// Turn privacy mode on or off (exposed to frontend)
+///
+/// While it's on, screenshots, clipboard reads and the selection shortcut
+/// are refused by the shell, whatever the agent asks for.
+#[tauri::command]
+pub fn set_privacy_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set(&app, enabled)
+}
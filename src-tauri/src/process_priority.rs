@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::process_tree::SidecarChild;
+use crate::settings::SettingsState;
+use crate::SidecarState;
+
+/// How long the window has to stay unfocused before the sidecar is lowered,
+/// so switching windows briefly doesn't slow down a running chat
+const UNFOCUSED_DELAY: Duration = Duration::from_secs(10);
+
+/// Scheduling priority of the sidecar's process tree
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarPriority {
+    #[default]
+    Normal,
+    /// Yields CPU to interactive apps
+    Low,
+    /// Only runs when the machine is otherwise idle, for CPU and disk
+    Background,
+}
+
+#[derive(Default)]
+pub struct PriorityState {
+    /// Priority currently applied, and the sidecar pid it was applied to
+    applied: Mutex<Option<(u32, SidecarPriority)>>,
+    /// Bumped on every focus change, to cancel pending auto-lowering
+    focus_generation: AtomicU64,
+}
+
+/// Apply a priority to the whole sidecar process tree
+fn apply(child: &SidecarChild, priority: SidecarPriority) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        unix::apply(child.pid(), priority)
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Threading::{
+            BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+        };
+        child.set_priority_class(match priority {
+            SidecarPriority::Normal => None,
+            SidecarPriority::Low => Some(BELOW_NORMAL_PRIORITY_CLASS),
+            SidecarPriority::Background => Some(IDLE_PRIORITY_CLASS),
+        })
+    }
+}
+
+/// Whether a lowered priority can be raised back to normal
+///
+/// Unprivileged processes can only lower their nice value on Unix, so the
+/// sidecar is only lowered automatically if it can be restored afterwards.
+fn can_restore() -> bool {
+    #[cfg(unix)]
+    {
+        unix::can_restore()
+    }
+    #[cfg(windows)]
+    {
+        true
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::SidecarPriority;
+    use std::process::{Command, Stdio};
+
+    fn run(program: &str, args: &[&str]) -> Result<(), String> {
+        let output = Command::new(program)
+            .args(args)
+            .stdout(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn can_restore() -> bool {
+        let field = |file: &str, prefix: &str| {
+            std::fs::read_to_string(file).ok().and_then(|contents| {
+                contents
+                    .lines()
+                    .find_map(|line| line.strip_prefix(prefix))
+                    .and_then(|rest| rest.split_whitespace().next().map(str::to_string))
+            })
+        };
+        // RLIMIT_NICE allows nice values down to 20 minus the limit
+        let is_root = field("/proc/self/status", "Uid:").as_deref() == Some("0");
+        let nice_limit = field("/proc/self/limits", "Max nice priority")
+            .and_then(|limit| limit.parse::<u32>().ok())
+            .unwrap_or(0);
+        is_root || nice_limit >= 20
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn can_restore() -> bool {
+        Command::new("id")
+            .arg("-u")
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+    }
+
+    pub fn apply(pid: u32, priority: SidecarPriority) -> Result<(), String> {
+        let pgid = pid.to_string();
+        let nice = match priority {
+            SidecarPriority::Normal => "0",
+            SidecarPriority::Low => "10",
+            SidecarPriority::Background => "19",
+        };
+        // The sidecar leads its own process group, so -g covers its children
+        run("renice", &["-n", nice, "-g", &pgid])?;
+
+        #[cfg(target_os = "linux")]
+        {
+            let class: &[&str] = match priority {
+                SidecarPriority::Normal => &["-c", "2", "-n", "4"],
+                SidecarPriority::Low => &["-c", "2", "-n", "7"],
+                SidecarPriority::Background => &["-c", "3"],
+            };
+            let args: Vec<&str> = class.iter().copied().chain(["-P", &pgid]).collect();
+            if let Err(e) = run("ionice", &args) {
+                log::warn!("[Sidecar] Failed to set I/O priority: {}", e);
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            // The background policy throttles disk access as well as CPU
+            let flag = match priority {
+                SidecarPriority::Background => "-b",
+                _ => "-B",
+            };
+            if let Err(e) = run("taskpolicy", &[flag, "-p", &pgid]) {
+                log::warn!("[Sidecar] Failed to set task policy: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Priority the user chose for the sidecar, and whether to lower it further
+/// while the window is unfocused
+fn preferred_priority(app: &AppHandle, focused: bool) -> SidecarPriority {
+    let settings = app.state::<SettingsState>().get();
+    if !focused && settings.lower_sidecar_priority_when_unfocused && can_restore() {
+        settings.sidecar_priority.max(SidecarPriority::Low)
+    } else {
+        settings.sidecar_priority
+    }
+}
+
+/// Bring the running sidecar to the given priority, if it isn't already
+fn update(app: &AppHandle, priority: SidecarPriority) -> Result<(), String> {
+    let priorities = app.state::<PriorityState>();
+    let sidecar = app.state::<SidecarState>();
+    let child = sidecar.child.lock().unwrap();
+    let Some(child) = child.as_ref() else {
+        return Ok(());
+    };
+    let pid = child.pid();
+
+    let mut applied = priorities.applied.lock().unwrap();
+    let current = applied
+        .filter(|(applied_pid, _)| *applied_pid == pid)
+        .map_or(SidecarPriority::Normal, |(_, priority)| priority);
+    if current == priority {
+        return Ok(());
+    }
+
+    apply(child, priority).map_err(|e| format!("Failed to set sidecar priority: {}", e))?;
+    log::info!("[Sidecar] Priority: {:?} -> {:?}", current, priority);
+    *applied = Some((pid, priority));
+    Ok(())
+}
+
+/// Give a freshly spawned sidecar the user's chosen priority
+pub fn apply_to_new(app: &AppHandle, child: &SidecarChild) {
+    let priority = app.state::<SettingsState>().get().sidecar_priority;
+    if priority == SidecarPriority::Normal {
+        return;
+    }
+    match apply(child, priority) {
+        Ok(()) => {
+            *app.state::<PriorityState>().applied.lock().unwrap() = Some((child.pid(), priority))
+        }
+        Err(e) => log::warn!("[Sidecar] Failed to set priority: {}", e),
+    }
+}
+
+/// Lower the sidecar a while after the main window loses focus and restore
+/// it as soon as the window is focused again
+pub fn on_main_window_focus(app: &AppHandle, focused: bool) {
+    let priorities = app.state::<PriorityState>();
+    let generation = priorities.focus_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if focused {
+        if let Err(e) = update(app, preferred_priority(app, true)) {
+            log::warn!("[Sidecar] {}", e);
+        }
+        return;
+    }
+
+    if !app
+        .state::<SettingsState>()
+        .get()
+        .lower_sidecar_priority_when_unfocused
+    {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(UNFOCUSED_DELAY).await;
+        let priorities = app.state::<PriorityState>();
+        if priorities.focus_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Err(e) = update(&app, preferred_priority(&app, false)) {
+            log::warn!("[Sidecar] {}", e);
+        }
+    });
+}
+
+/// Set the sidecar's scheduling priority and remember it (exposed to frontend)
+///
+/// `lowerWhenUnfocused` additionally lowers it while the window isn't
+/// focused. Raising the priority of a running sidecar may need privileges on
+/// Unix; it then takes effect from the next sidecar start.
+#[tauri::command]
+pub fn set_sidecar_priority(
+    app: AppHandle,
+    priority: SidecarPriority,
+    lower_when_unfocused: Option<bool>,
+) -> Result<(), String> {
+    app.state::<SettingsState>().update(|settings| {
+        settings.sidecar_priority = priority;
+        if let Some(lower) = lower_when_unfocused {
+            settings.lower_sidecar_priority_when_unfocused = lower;
+        }
+    })?;
+
+    let focused = app
+        .get_webview_window("main")
+        .is_some_and(|window| window.is_focused().unwrap_or(true));
+    update(&app, preferred_priority(&app, focused))
+}
+
+/// Get the sidecar's current scheduling priority (exposed to frontend)
+#[tauri::command]
+pub fn get_sidecar_priority(app: AppHandle) -> SidecarPriority {
+    let pid = app
+        .state::<SidecarState>()
+        .child
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|child| child.pid());
+    app.state::<PriorityState>()
+        .applied
+        .lock()
+        .unwrap()
+        .filter(|(applied_pid, _)| Some(*applied_pid) == pid)
+        .map_or(SidecarPriority::Normal, |(_, priority)| priority)
+}
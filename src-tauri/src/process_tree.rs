@@ -0,0 +1,368 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::Stdio;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Output and exit events from a sidecar started with [`spawn`]
+pub enum SidecarEvent {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    Error(String),
+    Terminated {
+        code: Option<i32>,
+        signal: Option<i32>,
+    },
+}
+
+/// Handle to the sidecar and every process it launched
+///
+/// The server starts browsers, MCP servers and tool processes of its own, and
+/// those outlive it if only the direct child is killed. On Unix the sidecar
+/// leads a new process group; on Windows it is placed in a Job Object.
+/// Dropping the handle kills whatever is left of the tree.
+pub struct SidecarChild {
+    pid: u32,
+    #[cfg(windows)]
+    job: job::Job,
+}
+
+impl SidecarChild {
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Cap the memory and CPU of every process in the tree via the job object
+    #[cfg(windows)]
+    pub fn limit_job(
+        &self,
+        memory_bytes: Option<u64>,
+        cpu_percent: Option<u32>,
+    ) -> Result<(), String> {
+        self.job.set_limits(memory_bytes, cpu_percent)
+    }
+
+    /// Run every process in the tree at the given priority class, or at the
+    /// normal priority if `None`
+    #[cfg(windows)]
+    pub fn set_priority_class(&self, class: Option<u32>) -> Result<(), String> {
+        self.job.set_priority_class(class)
+    }
+
+    /// Force-kill the sidecar together with all of its descendants
+    pub fn kill(&self) -> Result<(), String> {
+        #[cfg(unix)]
+        {
+            kill_process_group(self.pid)
+        }
+        #[cfg(windows)]
+        {
+            self.job.terminate()
+        }
+    }
+}
+
+impl Drop for SidecarChild {
+    fn drop(&mut self) {
+        // Windows closes the job handle on drop, which kills its processes
+        #[cfg(unix)]
+        let _ = kill_process_group(self.pid);
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pgid: u32) -> Result<(), String> {
+    // A negative pid addresses the whole process group
+    let status = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pgid)])
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| e.to_string())?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("kill exited with status {}", status))
+    }
+}
+
+/// Spawn the sidecar command as the root of its own process tree
+///
+/// Takes the command built by the shell plugin, so sidecar path resolution and
+/// environment handling stay the same, but runs it directly to set up the tree.
+/// `handshake` is written to the sidecar's stdin, which is then closed.
+pub fn spawn(
+    command: tauri_plugin_shell::process::Command,
+    handshake: &[u8],
+) -> Result<(UnboundedReceiver<SidecarEvent>, SidecarChild), String> {
+    let mut command: std::process::Command = command.into();
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    // Started suspended so it can't launch anything before it's in the job.
+    // Setting the flags replaces the shell plugin's, so CREATE_NO_WINDOW is kept.
+    #[cfg(windows)]
+    std::os::windows::process::CommandExt::creation_flags(
+        &mut command,
+        job::CREATE_NO_WINDOW | job::CREATE_SUSPENDED,
+    );
+
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    let pid = child.id();
+
+    #[cfg(windows)]
+    let job = match job::Job::assign(&child).and_then(|job| job::resume(pid).map(|()| job)) {
+        Ok(job) => job,
+        Err(e) => {
+            let _ = child.kill();
+            return Err(e);
+        }
+    };
+
+    // Dropping stdin once written tells the server the handshake is complete
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(handshake) {
+            let _ = child.kill();
+            return Err(format!("Failed to send handshake: {}", e));
+        }
+    }
+
+    let (tx, rx) = unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, tx.clone(), SidecarEvent::Stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, tx.clone(), SidecarEvent::Stderr);
+    }
+
+    std::thread::spawn(move || {
+        let event = match child.wait() {
+            Ok(status) => SidecarEvent::Terminated {
+                code: status.code(),
+                #[cfg(unix)]
+                signal: std::os::unix::process::ExitStatusExt::signal(&status),
+                #[cfg(not(unix))]
+                signal: None,
+            },
+            Err(e) => SidecarEvent::Error(format!("Failed to wait for sidecar: {}", e)),
+        };
+        let _ = tx.send(event);
+    });
+
+    Ok((
+        rx,
+        SidecarChild {
+            pid,
+            #[cfg(windows)]
+            job,
+        },
+    ))
+}
+
+fn forward_lines(
+    output: impl Read + Send + 'static,
+    tx: UnboundedSender<SidecarEvent>,
+    event: fn(Vec<u8>) -> SidecarEvent,
+) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(output).split(b'\n') {
+            let message = match line {
+                Ok(line) => event(line),
+                Err(e) => SidecarEvent::Error(e.to_string()),
+            };
+            if tx.send(message).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(windows)]
+mod job {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+        JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
+        TerminateJobObject, JOBOBJECTINFOCLASS, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_CPU_RATE_CONTROL_ENABLE,
+        JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP, JOB_OBJECT_LIMIT_JOB_MEMORY,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE, JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+    };
+    use windows_sys::Win32::System::Threading::{OpenThread, ResumeThread, THREAD_SUSPEND_RESUME};
+    pub use windows_sys::Win32::System::Threading::{CREATE_NO_WINDOW, CREATE_SUSPENDED};
+
+    /// Let a process started with CREATE_SUSPENDED run, by resuming its threads
+    pub fn resume(pid: u32) -> Result<(), String> {
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err(format!(
+                    "Failed to list the sidecar's threads: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            let mut entry: THREADENTRY32 = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+            let mut resumed = false;
+            let mut found = Thread32First(snapshot, &mut entry) != 0;
+            while found {
+                if entry.th32OwnerProcessID == pid {
+                    let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                    if !thread.is_null() {
+                        resumed |= ResumeThread(thread) != u32::MAX;
+                        CloseHandle(thread);
+                    }
+                }
+                found = Thread32Next(snapshot, &mut entry) != 0;
+            }
+            CloseHandle(snapshot);
+            if !resumed {
+                return Err(format!(
+                    "Failed to resume sidecar: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    /// Job Object whose processes are killed when its last handle is closed,
+    /// including when the shell itself crashes
+    pub struct Job(HANDLE);
+
+    // The handle is only used through thread-safe Win32 calls
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    impl Job {
+        pub fn assign(child: &std::process::Child) -> Result<Self, String> {
+            unsafe {
+                let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if handle.is_null() {
+                    return Err(format!(
+                        "Failed to create job object: {}",
+                        std::io::Error::last_os_error()
+                    ));
+                }
+                let job = Job(handle);
+                job.set_limits(None, None)?;
+
+                if AssignProcessToJobObject(handle, child.as_raw_handle() as HANDLE) == 0 {
+                    return Err(format!(
+                        "Failed to assign sidecar to job object: {}",
+                        std::io::Error::last_os_error()
+                    ));
+                }
+                Ok(job)
+            }
+        }
+
+        /// Set the job's limits. Killing the tree on close and the priority
+        /// class are always kept.
+        pub fn set_limits(
+            &self,
+            memory_bytes: Option<u64>,
+            cpu_percent: Option<u32>,
+        ) -> Result<(), String> {
+            unsafe {
+                let current = self.extended_limits()?.BasicLimitInformation;
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE
+                    | (current.LimitFlags & JOB_OBJECT_LIMIT_PRIORITY_CLASS);
+                info.BasicLimitInformation.PriorityClass = current.PriorityClass;
+                if let Some(bytes) = memory_bytes {
+                    info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+                    info.JobMemoryLimit = bytes as usize;
+                }
+                self.set_information(JobObjectExtendedLimitInformation, &info)?;
+
+                let mut rate: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = std::mem::zeroed();
+                if let Some(percent) = cpu_percent {
+                    // CpuRate is in hundredths of a percent of all processors,
+                    // while the limit is relative to a single core
+                    let cores = std::thread::available_parallelism().map_or(1, |n| n.get()) as u32;
+                    rate.ControlFlags =
+                        JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+                    rate.Anonymous.CpuRate = (percent * 100 / cores).clamp(1, 10_000);
+                }
+                self.set_information(JobObjectCpuRateControlInformation, &rate)
+            }
+        }
+
+        pub fn set_priority_class(&self, class: Option<u32>) -> Result<(), String> {
+            unsafe {
+                let mut info = self.extended_limits()?;
+                match class {
+                    Some(class) => {
+                        info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+                        info.BasicLimitInformation.PriorityClass = class;
+                    }
+                    None => {
+                        info.BasicLimitInformation.LimitFlags &= !JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+                        info.BasicLimitInformation.PriorityClass = 0;
+                    }
+                }
+                self.set_information(JobObjectExtendedLimitInformation, &info)
+            }
+        }
+
+        unsafe fn extended_limits(&self) -> Result<JOBOBJECT_EXTENDED_LIMIT_INFORMATION, String> {
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            if QueryInformationJobObject(
+                self.0,
+                JobObjectExtendedLimitInformation,
+                &mut info as *mut _ as *mut std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                std::ptr::null_mut(),
+            ) == 0
+            {
+                return Err(format!(
+                    "Failed to query job object: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(info)
+        }
+
+        unsafe fn set_information<T>(
+            &self,
+            class: JOBOBJECTINFOCLASS,
+            info: &T,
+        ) -> Result<(), String> {
+            if SetInformationJobObject(
+                self.0,
+                class,
+                info as *const T as *const std::ffi::c_void,
+                std::mem::size_of::<T>() as u32,
+            ) == 0
+            {
+                return Err(format!(
+                    "Failed to configure job object: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(())
+        }
+
+        pub fn terminate(&self) -> Result<(), String> {
+            if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{oneshot, watch};
+
+use crate::pid_file;
+use crate::process_tree::{self, SidecarChild, SidecarEvent};
+use crate::settings::{ProfileSettings, SettingsState};
+use crate::{sidecar_stop_timeout, SidecarState};
+
+/// Name the frontend uses for the profile that existed before profiles did
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A profile's sidecar running alongside the main one
+///
+/// Only the active profile gets the main sidecar's supervision: restarts,
+/// the watchdog and resource limits. Background sidecars are simply stopped
+/// when the shell exits.
+struct BackgroundSidecar {
+    child: SidecarChild,
+    port: u16,
+    exited: watch::Receiver<bool>,
+}
+
+/// Sidecars of profiles other than the active one, by profile name
+#[derive(Default)]
+pub struct ProfilesState {
+    running: Mutex<BTreeMap<String, BackgroundSidecar>>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub name: String,
+    pub data_dir: PathBuf,
+    /// Port its sidecar is running on, else the one it will start on
+    pub port: Option<u16>,
+    pub running: bool,
+    pub active: bool,
+}
+
+/// Profile names become directory names, so keep them portable
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        && name.trim() == name;
+    if !valid {
+        return Err(format!(
+            "Invalid profile name {:?}; use letters, digits, spaces, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Get the data directory of a profile. Each profile has its own database.
+pub fn profile_data_dir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    if name == DEFAULT_PROFILE {
+        return crate::get_default_data_dir(app);
+    }
+    Ok(crate::normalize_windows_path(crate::get_app_data_dir(app)?)
+        .join("profiles")
+        .join(name))
+}
+
+/// Look up a profile's settings. The default profile has none of its own.
+fn find_profile(app: &AppHandle, name: &str) -> Result<Option<ProfileSettings>, String> {
+    if name == DEFAULT_PROFILE {
+        return Ok(None);
+    }
+    app.state::<SettingsState>()
+        .get()
+        .profiles
+        .get(name)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| format!("No profile named {:?}", name))
+}
+
+fn active_profile_name(app: &AppHandle) -> String {
+    app.state::<SettingsState>()
+        .get()
+        .active_profile
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// List the default profile followed by the user's own (exposed to frontend)
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    let settings = app.state::<SettingsState>().get();
+    let sidecar: State<SidecarState> = app.state();
+    let active = active_profile_name(&app);
+    let main_running = sidecar.child.lock().unwrap().is_some();
+    let profiles: State<ProfilesState> = app.state();
+    let running = profiles.running.lock().unwrap();
+
+    let default = (DEFAULT_PROFILE.to_string(), settings.sidecar_port);
+    let others = settings
+        .profiles
+        .iter()
+        .map(|(name, profile)| (name.clone(), profile.port));
+    std::iter::once(default)
+        .chain(others)
+        .map(|(name, saved_port)| {
+            let is_active = name == active;
+            let background_port = running.get(&name).map(|sidecar| sidecar.port);
+            let port = if is_active && main_running {
+                Some(sidecar.port())
+            } else {
+                background_port.or(saved_port)
+            };
+            Ok(ProfileInfo {
+                data_dir: profile_data_dir(&app, &name)?,
+                port,
+                running: background_port.is_some() || (is_active && main_running),
+                active: is_active,
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Create a profile with its own data directory and port (exposed to frontend)
+#[tauri::command]
+pub fn create_profile(app: AppHandle, name: String) -> Result<ProfileInfo, String> {
+    validate_profile_name(&name)?;
+    let settings = app.state::<SettingsState>();
+    if name.eq_ignore_ascii_case(DEFAULT_PROFILE)
+        || settings
+            .get()
+            .profiles
+            .keys()
+            .any(|existing| existing.eq_ignore_ascii_case(&name))
+    {
+        return Err(format!("A profile named {:?} already exists", name));
+    }
+
+    // Give it a port of its own so it can run next to the other profiles
+    let sidecar: State<SidecarState> = app.state();
+    let current = settings.get();
+    let taken: Vec<u16> = std::iter::once(sidecar.port())
+        .chain(current.sidecar_port)
+        .chain(current.profiles.values().filter_map(|profile| profile.port))
+        .collect();
+    let port = (sidecar.preferred_port.saturating_add(1)..u16::MAX)
+        .find(|port| !taken.contains(port) && crate::is_port_available(&sidecar.host, *port))
+        .ok_or("No free port for the profile")?;
+
+    let data_dir = profile_data_dir(&app, &name)?;
+    std::fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create profile data dir: {}", e))?;
+    settings.update(|settings| {
+        settings
+            .profiles
+            .insert(name.clone(), ProfileSettings { port: Some(port) });
+    })?;
+    log::info!("[Profiles] Created profile {:?} on port {}", name, port);
+
+    Ok(ProfileInfo {
+        name,
+        data_dir,
+        port: Some(port),
+        running: false,
+        active: false,
+    })
+}
+
+/// Start a profile's sidecar in the background, e.g. to use two brains at
+/// once (exposed to frontend)
+///
+/// Resolves with the port once the server reports it is ready. Starting the
+/// active profile is a no-op, as the main sidecar already runs it.
+#[tauri::command]
+pub async fn start_profile(app: AppHandle, name: String) -> Result<u16, String> {
+    let profile = find_profile(&app, &name)?;
+    let sidecar: State<SidecarState> = app.state();
+    if name == active_profile_name(&app) {
+        return Ok(sidecar.port());
+    }
+    if let Some(running) = app
+        .state::<ProfilesState>()
+        .running
+        .lock()
+        .unwrap()
+        .get(&name)
+    {
+        return Ok(running.port);
+    }
+
+    let data_dir = profile_data_dir(&app, &name)?;
+    std::fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create profile data dir: {}", e))?;
+    let preferred_port = match profile {
+        Some(profile) => profile.port,
+        None => app.state::<SettingsState>().get().sidecar_port,
+    }
+    .unwrap_or(sidecar.preferred_port);
+    let server = crate::ServerEntry::resolve(&app)?;
+    crate::cleanup_orphaned_sidecar(&data_dir, &sidecar.host, &server.entry_point);
+    if !crate::is_port_available(&sidecar.host, preferred_port) {
+        return Err(format!("Port {} is already in use", preferred_port));
+    }
+
+    let command =
+        crate::build_sidecar_command(&app, &server, &data_dir, &sidecar.host, preferred_port)?;
+    let handshake = crate::secrets::handshake(&app, &sidecar.auth_token.get());
+    let (rx, child) = process_tree::spawn(command, &handshake)
+        .map_err(|e| format!("Failed to spawn sidecar for profile {:?}: {}", name, e))?;
+    log::info!(
+        "[Profiles] Started {:?} (pid={}, port={})",
+        name,
+        child.pid(),
+        preferred_port
+    );
+
+    let (ready_tx, ready_rx) = oneshot::channel();
+    let (exited_tx, exited) = watch::channel(false);
+    let pid = child.pid();
+    let record = pid_file::SidecarPidRecord::new(pid, preferred_port, &sidecar.auth_token.get());
+    if let Err(e) = pid_file::write(&data_dir, &record) {
+        log::warn!("[Profiles] {}", e);
+    }
+    app.state::<ProfilesState>().running.lock().unwrap().insert(
+        name.clone(),
+        BackgroundSidecar {
+            child,
+            port: preferred_port,
+            exited,
+        },
+    );
+    forward_output(app.clone(), name.clone(), pid, rx, ready_tx, exited_tx);
+
+    let timeout = app.state::<SettingsState>().get().startup_timeout();
+    match tokio::time::timeout(timeout, ready_rx).await {
+        Ok(Ok(port)) => {
+            if let Some(running) = app
+                .state::<ProfilesState>()
+                .running
+                .lock()
+                .unwrap()
+                .get_mut(&name)
+            {
+                running.port = port;
+            }
+            Ok(port)
+        }
+        Ok(Err(_)) => Err(format!("Server for profile {:?} exited on startup", name)),
+        Err(_) => {
+            stop_background_sidecar(&app, &name).await;
+            Err(format!(
+                "Server for profile {:?} did not report ready",
+                name
+            ))
+        }
+    }
+}
+
+/// Log a background sidecar's output and clean up when it exits
+fn forward_output(
+    app: AppHandle,
+    name: String,
+    pid: u32,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<SidecarEvent>,
+    ready_tx: oneshot::Sender<u16>,
+    exited_tx: watch::Sender<bool>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut ready_tx = Some(ready_tx);
+        while let Some(event) = rx.recv().await {
+            match event {
+                SidecarEvent::Stdout(line) => {
+                    let line = String::from_utf8_lossy(&line);
+                    if let Some(port) = crate::parse_ready_marker(&line) {
+                        if let Some(tx) = ready_tx.take() {
+                            let _ = tx.send(port);
+                        }
+                    }
+                    log::info!("[Profile {}] {}", name, line.trim_end());
+                }
+                SidecarEvent::Stderr(line) => {
+                    log::warn!(
+                        "[Profile {}] {}",
+                        name,
+                        String::from_utf8_lossy(&line).trim_end()
+                    );
+                }
+                SidecarEvent::Error(err) => log::error!("[Profile {}] Error: {}", name, err),
+                SidecarEvent::Terminated { code, signal } => {
+                    log::info!(
+                        "[Profile {}] Terminated with code: {:?}, signal: {:?}",
+                        name,
+                        code,
+                        signal
+                    );
+                    let _ = exited_tx.send(true);
+                    if let Ok(data_dir) = profile_data_dir(&app, &name) {
+                        if pid_file::read(&data_dir).is_some_and(|record| record.pid == pid) {
+                            pid_file::remove(&data_dir);
+                        }
+                    }
+                    let profiles: State<ProfilesState> = app.state();
+                    let mut running = profiles.running.lock().unwrap();
+                    if running
+                        .get(&name)
+                        .is_some_and(|sidecar| sidecar.child.pid() == pid)
+                    {
+                        running.remove(&name);
+                    }
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Ask a background sidecar to shut down, killing it if it doesn't in time
+pub async fn stop_background_sidecar(app: &AppHandle, name: &str) {
+    let Some(mut sidecar) = app
+        .state::<ProfilesState>()
+        .running
+        .lock()
+        .unwrap()
+        .remove(name)
+    else {
+        return;
+    };
+    let pid = sidecar.child.pid();
+
+    #[cfg(unix)]
+    let requested = crate::send_sigterm(pid);
+    #[cfg(not(unix))]
+    let requested = {
+        let state: State<SidecarState> = app.state();
+        let (host, port, token) = (state.host.clone(), sidecar.port, state.auth_token.get());
+        tauri::async_runtime::spawn_blocking(move || {
+            crate::request_sidecar_shutdown(&host, port, &token)
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()))
+    };
+    if let Err(e) = requested {
+        log::warn!("[Profiles] Failed to stop {:?} gracefully: {}", name, e);
+    }
+
+    let exited = tokio::time::timeout(
+        sidecar_stop_timeout(app),
+        sidecar.exited.wait_for(|exited| *exited),
+    );
+    if exited.await.is_err() {
+        log::warn!(
+            "[Profiles] {:?} did not stop in time, killing it (pid={})",
+            name,
+            pid
+        );
+    }
+    // Dropping the handle kills whatever is left of its process tree
+    drop(sidecar);
+}
+
+/// Stop a profile's background sidecar (exposed to frontend)
+#[tauri::command]
+pub async fn stop_profile(app: AppHandle, name: String) -> Result<(), String> {
+    find_profile(&app, &name)?;
+    stop_background_sidecar(&app, &name).await;
+    Ok(())
+}
+
+/// Make a profile the active one, running it as the main sidecar
+/// (exposed to frontend)
+///
+/// The choice is remembered across launches. `profile://switched` tells the
+/// webview to reload against the other profile's server.
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<(), String> {
+    find_profile(&app, &name)?;
+    if app.state::<SidecarState>().external {
+        return Err("Profiles aren't available with an external server".to_string());
+    }
+    if name == active_profile_name(&app) {
+        return Ok(());
+    }
+    crate::commands::reserve_restart(&app)?;
+
+    // Both sidecars would otherwise open the same database
+    stop_background_sidecar(&app, &name).await;
+
+    // Stop under the old profile so its PID file is cleaned up
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || crate::stop_sidecar(&handle))
+        .await
+        .map_err(|e| format!("Stop task failed: {}", e))??;
+
+    app.state::<SettingsState>().update(|settings| {
+        settings.active_profile = (name != DEFAULT_PROFILE).then(|| name.clone());
+    })?;
+    log::info!("[Profiles] Switched to profile {:?}", name);
+
+    crate::commands::restart(&app).await?;
+    let _ = app.emit("profile://switched", &name);
+    Ok(())
+}
+
+/// Stop every background sidecar, e.g. when the shell exits
+pub fn stop_all(app: &AppHandle) {
+    let Some(state) = app.try_state::<ProfilesState>() else {
+        return;
+    };
+    let sidecars = std::mem::take(&mut *state.running.lock().unwrap());
+    if sidecars.is_empty() {
+        return;
+    }
+
+    // Give them the same grace period as the main sidecar to close their databases
+    #[cfg(not(unix))]
+    let (host, auth_token) = {
+        let state: State<SidecarState> = app.state();
+        (state.host.clone(), state.auth_token.get())
+    };
+    for (name, sidecar) in &sidecars {
+        #[cfg(unix)]
+        let requested = crate::send_sigterm(sidecar.child.pid());
+        #[cfg(not(unix))]
+        let requested = crate::request_sidecar_shutdown(&host, sidecar.port, &auth_token);
+        if let Err(e) = requested {
+            log::warn!("[Profiles] Failed to stop {:?} gracefully: {}", name, e);
+        }
+    }
+    let deadline = std::time::Instant::now() + sidecar_stop_timeout(app);
+    while std::time::Instant::now() < deadline
+        && sidecars.values().any(|sidecar| !*sidecar.exited.borrow())
+    {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    log::info!(
+        "[Profiles] Stopped {} background sidecar(s)",
+        sidecars.len()
+    );
+}
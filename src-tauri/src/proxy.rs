@@ -0,0 +1,360 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// How often the OS proxy settings are checked for changes
+const PROXY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Variables that configure the sidecar's proxy, which Bun's fetch reads
+const PROXY_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "no_proxy",
+];
+
+/// Hosts never sent through the proxy, so the sidecar can still reach local
+/// model servers
+const LOCAL_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+/// The proxies set in the OS network settings
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemProxy {
+    /// Proxy URL for plain HTTP requests
+    pub http: Option<String>,
+    /// Proxy URL for HTTPS requests
+    pub https: Option<String>,
+    /// Hosts to connect to directly, comma separated
+    pub no_proxy: Option<String>,
+}
+
+impl SystemProxy {
+    fn is_set(&self) -> bool {
+        self.http.is_some() || self.https.is_some()
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        if !self.is_set() {
+            return Vec::new();
+        }
+        let mut env = Vec::new();
+        if let Some(ref http) = self.http {
+            env.push(("HTTP_PROXY", http.clone()));
+        }
+        if let Some(ref https) = self.https {
+            env.push(("HTTPS_PROXY", https.clone()));
+        }
+        let mut no_proxy: Vec<&str> = LOCAL_HOSTS.to_vec();
+        if let Some(ref hosts) = self.no_proxy {
+            no_proxy.extend(hosts.split(',').filter(|host| !LOCAL_HOSTS.contains(host)));
+        }
+        env.push(("NO_PROXY", no_proxy.join(",")));
+        env
+    }
+}
+
+#[derive(Default)]
+pub struct ProxyState {
+    /// Proxies last read from the OS
+    detected: Mutex<SystemProxy>,
+    /// Proxies the running sidecar was started with
+    applied: Mutex<SystemProxy>,
+}
+
+/// Make a proxy URL from a host, which may already have a scheme, and port
+fn proxy_url(host: &str, port: Option<&str>) -> Option<String> {
+    let host = host.trim();
+    if host.is_empty() {
+        return None;
+    }
+    let url = if host.contains("://") {
+        host.to_string()
+    } else {
+        format!("http://{}", host)
+    };
+    Some(
+        match port
+            .map(str::trim)
+            .filter(|port| !port.is_empty() && *port != "0")
+        {
+            Some(port) => format!("{}:{}", url, port),
+            None => url,
+        },
+    )
+}
+
+/// Whether the user set proxy variables for the sidecar themselves, in
+/// Pipali's environment or its settings
+fn configured_manually(app: &AppHandle) -> bool {
+    let settings_env = app.state::<SettingsState>().get().env;
+    PROXY_VARS
+        .iter()
+        .any(|name| std::env::var_os(name).is_some() || settings_env.contains_key(*name))
+}
+
+fn detect(app: &AppHandle) -> SystemProxy {
+    let proxy = platform::detect().unwrap_or_else(|e| {
+        log::warn!("[App] Failed to read system proxy settings: {}", e);
+        SystemProxy::default()
+    });
+    *app.state::<ProxyState>().detected.lock().unwrap() = proxy.clone();
+    proxy
+}
+
+/// Proxy variables for a sidecar about to start
+///
+/// The OS settings are only relayed when the user hasn't configured a proxy
+/// for the sidecar some other way.
+pub fn sidecar_env(app: &AppHandle) -> Vec<(&'static str, String)> {
+    let state = app.state::<ProxyState>();
+    let proxy = if configured_manually(app) {
+        SystemProxy::default()
+    } else {
+        state.detected.lock().unwrap().clone()
+    };
+    *state.applied.lock().unwrap() = proxy.clone();
+    if proxy.is_set() {
+        log::info!(
+            "[Sidecar] Using system proxy {}",
+            proxy.https.as_ref().or(proxy.http.as_ref()).unwrap()
+        );
+    }
+    proxy.env()
+}
+
+/// `host:port` of the proxy outbound HTTPS requests go through, if any
+pub fn proxy_address(app: &AppHandle) -> Option<String> {
+    let proxy = app.state::<ProxyState>().detected.lock().unwrap().clone();
+    let url = proxy.https.or(proxy.http)?;
+    let address = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let address = address.rsplit_once('@').map_or(address, |(_, host)| host);
+    let address = address.trim_end_matches('/');
+    Some(if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:80", address)
+    })
+}
+
+/// Read the OS proxy settings before the sidecar first starts
+pub fn setup(app: &AppHandle) {
+    let proxy = detect(app);
+    if proxy.is_set() {
+        log::info!("[App] System proxy: {:?}", proxy);
+    }
+}
+
+/// Restart the sidecar with the new proxy when the OS settings change
+///
+/// The restart waits until the sidecar isn't running any chats or
+/// automations, so no task is cut off.
+pub fn spawn_proxy_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = crate::health_check_agent();
+        loop {
+            tokio::time::sleep(PROXY_CHECK_INTERVAL).await;
+
+            let handle = app.clone();
+            let Ok(proxy) = tauri::async_runtime::spawn_blocking(move || detect(&handle)).await
+            else {
+                continue;
+            };
+            let state: State<SidecarState> = app.state();
+            if state.external
+                || state.status() != SidecarStatus::Ready
+                || configured_manually(&app)
+                || *app.state::<ProxyState>().applied.lock().unwrap() == proxy
+            {
+                continue;
+            }
+            let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+            match crate::check_sidecar_health(&agent, &health_url).await {
+                Ok(info) if info.active_tasks.unwrap_or_default() == 0 => {}
+                _ => continue,
+            }
+
+            log::info!(
+                "[App] System proxy changed to {:?}, restarting server",
+                proxy
+            );
+            if let Err(e) = crate::commands::restart(&app).await {
+                log::error!("[App] Failed to restart server for proxy change: {}", e);
+            }
+        }
+    });
+}
+
+/// Get the proxies set in the OS network settings (exposed to frontend)
+#[tauri::command]
+pub fn get_system_proxy(state: State<'_, ProxyState>) -> SystemProxy {
+    state.detected.lock().unwrap().clone()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::collections::HashMap;
+    use std::process::Command;
+
+    use super::{proxy_url, SystemProxy};
+
+    /// Parse `scutil --proxy`, which prints the proxy settings as a
+    /// dictionary with the bypass list as a nested array
+    pub fn detect() -> Result<SystemProxy, String> {
+        let output = Command::new("scutil")
+            .arg("--proxy")
+            .output()
+            .map_err(|e| format!("Failed to run scutil: {}", e))?;
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        let mut values = HashMap::new();
+        let mut exceptions = Vec::new();
+        let mut in_exceptions = false;
+        for line in output.lines().map(str::trim) {
+            if in_exceptions {
+                match line.split_once(" : ") {
+                    Some((_, host)) => exceptions.push(host.to_string()),
+                    None => in_exceptions = false,
+                }
+            } else if line.starts_with("ExceptionsList") {
+                in_exceptions = true;
+            } else if let Some((key, value)) = line.split_once(" : ") {
+                values.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        let proxy = |kind: &str| {
+            if values.get(&format!("{}Enable", kind)).map(String::as_str) != Some("1") {
+                return None;
+            }
+            proxy_url(
+                values.get(&format!("{}Proxy", kind))?,
+                values.get(&format!("{}Port", kind)).map(String::as_str),
+            )
+        };
+        Ok(SystemProxy {
+            http: proxy("HTTP"),
+            https: proxy("HTTPS"),
+            no_proxy: (!exceptions.is_empty()).then(|| exceptions.join(",")),
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{GlobalFree, HGLOBAL};
+    use windows::Win32::Networking::WinHttp::{
+        WinHttpGetIEProxyConfigForCurrentUser, WINHTTP_CURRENT_USER_IE_PROXY_CONFIG,
+    };
+
+    use super::{proxy_url, SystemProxy};
+
+    /// Read a string WinHTTP allocated, and free it
+    fn take_string(value: PWSTR) -> Option<String> {
+        if value.is_null() {
+            return None;
+        }
+        let string = unsafe { value.to_string() }.ok();
+        unsafe {
+            let _ = GlobalFree(Some(HGLOBAL(value.0 as *mut _)));
+        }
+        string.filter(|string| !string.is_empty())
+    }
+
+    /// The proxy from Internet Options, written either as one `host:port`
+    /// for every scheme or as `http=host:port;https=host:port`
+    pub fn detect() -> Result<SystemProxy, String> {
+        let mut config = WINHTTP_CURRENT_USER_IE_PROXY_CONFIG::default();
+        unsafe { WinHttpGetIEProxyConfigForCurrentUser(&mut config) }
+            .map_err(|e| format!("Failed to read Internet Options: {}", e))?;
+        if take_string(config.lpszAutoConfigUrl).is_some() {
+            log::info!("[App] Proxy auto-config scripts aren't supported, only fixed proxies");
+        }
+        let servers = take_string(config.lpszProxy);
+        let bypass = take_string(config.lpszProxyBypass);
+
+        let mut proxy = SystemProxy::default();
+        if let Some(servers) = servers {
+            if servers.contains('=') {
+                for entry in servers.split(';') {
+                    match entry.split_once('=') {
+                        Some(("http", host)) => proxy.http = proxy_url(host, None),
+                        Some(("https", host)) => proxy.https = proxy_url(host, None),
+                        _ => {}
+                    }
+                }
+            } else {
+                proxy.http = proxy_url(&servers, None);
+                proxy.https = proxy.http.clone();
+            }
+        }
+        // <local> stands for hosts without a dot, which NO_PROXY can't express
+        proxy.no_proxy = bypass
+            .map(|bypass| {
+                bypass
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty() && *host != "<local>")
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .filter(|hosts| !hosts.is_empty());
+        Ok(proxy)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::process::Command;
+
+    use super::{proxy_url, SystemProxy};
+
+    fn gsetting(schema: &str, key: &str) -> Option<String> {
+        let output = Command::new("gsettings")
+            .args(["get", schema, key])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// GNOME's proxy settings. Elsewhere proxies are set with the environment
+    /// variables the sidecar inherits.
+    pub fn detect() -> Result<SystemProxy, String> {
+        let Some(mode) = gsetting("org.gnome.system.proxy", "mode") else {
+            return Ok(SystemProxy::default());
+        };
+        if mode.trim_matches('\'') != "manual" {
+            return Ok(SystemProxy::default());
+        }
+        let proxy = |kind: &str| {
+            let schema = format!("org.gnome.system.proxy.{}", kind);
+            let host = gsetting(&schema, "host")?;
+            let port = gsetting(&schema, "port");
+            proxy_url(host.trim_matches('\''), port.as_deref())
+        };
+        let no_proxy = gsetting("org.gnome.system.proxy", "ignore-hosts").map(|hosts| {
+            hosts
+                .trim_matches(['[', ']'])
+                .split(',')
+                .map(|host| host.trim().trim_matches('\''))
+                .filter(|host| !host.is_empty())
+                .collect::<Vec<_>>()
+                .join(",")
+        });
+        Ok(SystemProxy {
+            http: proxy("http"),
+            https: proxy("https"),
+            no_proxy: no_proxy.filter(|hosts| !hosts.is_empty()),
+        })
+    }
+}
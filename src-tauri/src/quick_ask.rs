@@ -0,0 +1,76 @@
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+/// Label of the quick-ask prompt bar window
+pub const LABEL: &str = "quick-ask";
+
+/// Get the prompt bar, creating it hidden on first use
+fn window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        return Ok(window);
+    }
+    WebviewWindowBuilder::new(app, LABEL, WebviewUrl::App("quick-ask.html".into()))
+        .title("Ask Pipali")
+        .inner_size(640.0, 360.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .center()
+        .build()
+        .map_err(|e| format!("Failed to create quick-ask window: {}", e))
+}
+
+/// Show the prompt bar over whatever app is active
+pub fn show(app: &AppHandle) -> Result<(), String> {
+    if crate::app_lock::is_locked(app) {
+        crate::show_window(app);
+        return Err("Pipali is locked".to_string());
+    }
+    let window = window(app)?;
+    let _ = window.center();
+    window.show().map_err(|e| e.to_string())?;
+    let _ = window.set_focus();
+    // Lets the page clear the last question and focus its input
+    let _ = app.emit_to(LABEL, "quick-ask://shown", ());
+    Ok(())
+}
+
+pub fn hide(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.hide();
+    }
+}
+
+pub fn toggle(app: &AppHandle) {
+    let visible = app
+        .get_webview_window(LABEL)
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    if visible {
+        hide(app);
+    } else if let Err(e) = show(app) {
+        log::error!("[App] {}", e);
+    }
+}
+
+/// Dismiss the prompt bar once the user clicks away from it
+pub fn on_focus_changed(app: &AppHandle, focused: bool) {
+    if !focused {
+        hide(app);
+    }
+}
+
+/// Show the quick-ask prompt bar (exposed to frontend)
+///
+/// Async since creating a window from a synchronous command deadlocks on
+/// Windows.
+#[tauri::command]
+pub async fn show_quick_ask(app: AppHandle) -> Result<(), String> {
+    show(&app)
+}
+
+/// Hide the quick-ask prompt bar (exposed to frontend)
+#[tauri::command]
+pub fn hide_quick_ask(app: AppHandle) {
+    hide(&app);
+}
@@ -0,0 +1,285 @@
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{SidecarState, SidecarStatus};
+
+/// How many conversations the dock menu and jump list offer
+const RECENT_LIMIT: usize = 5;
+/// Longest title shown before it's cut short
+const MAX_TITLE_CHARS: usize = 40;
+
+#[derive(Clone, PartialEq, Deserialize)]
+struct Conversation {
+    id: String,
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Conversations {
+    conversations: Vec<Conversation>,
+}
+
+/// Conversations currently in the dock menu or jump list
+#[derive(Default)]
+pub struct RecentConversationsState {
+    shown: Mutex<Vec<Conversation>>,
+}
+
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn label(conversation: &Conversation) -> String {
+    let title = conversation
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or("Untitled conversation");
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title.to_string();
+    }
+    let mut label: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    label.push('…');
+    label
+}
+
+/// Offer the most recent conversations from the macOS dock menu or Windows
+/// taskbar jump list, each opening the app straight to that conversation
+///
+/// Called when the sidecar becomes ready and whenever the main window loses
+/// focus, by which time any new conversation has its title.
+pub fn refresh(app: &AppHandle) {
+    if !platform::SUPPORTED {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state: State<SidecarState> = app.state();
+        if state.status() != SidecarStatus::Ready {
+            return;
+        }
+        let url = format!("http://{}:{}/api/conversations", state.host, state.port());
+        let agent = crate::health_check_agent();
+        let conversations =
+            match crate::get_sidecar_json::<Conversations>(&agent, url, state.auth_token.get())
+                .await
+            {
+                Ok(response) => response.conversations,
+                Err(e) => {
+                    log::debug!("[App] Failed to fetch recent conversations: {}", e);
+                    return;
+                }
+            };
+        let recent: Vec<Conversation> = conversations.into_iter().take(RECENT_LIMIT).collect();
+        {
+            let state: State<RecentConversationsState> = app.state();
+            let mut shown = state.shown.lock().unwrap();
+            if *shown == recent {
+                return;
+            }
+            *shown = recent.clone();
+        }
+
+        let handle = app.clone();
+        let updated =
+            tauri::async_runtime::spawn_blocking(move || platform::update(&handle, &recent)).await;
+        if let Ok(Err(e)) = updated {
+            log::warn!("[App] {}", e);
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::Conversation;
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::{AnyClass, AnyObject, Sel};
+    use objc2::{class, msg_send, sel};
+    use std::ffi::{c_char, c_void, CString};
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::{Mutex, Once, OnceLock};
+    use tauri::AppHandle;
+
+    pub const SUPPORTED: bool = true;
+
+    extern "C" {
+        fn object_getClass(obj: *const AnyObject) -> *const AnyClass;
+        fn class_addMethod(
+            cls: *const AnyClass,
+            name: Sel,
+            imp: *const c_void,
+            types: *const c_char,
+        ) -> bool;
+    }
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+    static INSTALL: Once = Once::new();
+    /// NSMenu handed to the dock, owned here
+    static DOCK_MENU: AtomicPtr<AnyObject> = AtomicPtr::new(std::ptr::null_mut());
+    /// Conversation ids of the dock menu items, by item tag
+    static DOCK_MENU_IDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    extern "C" fn application_dock_menu(
+        _this: *mut AnyObject,
+        _cmd: Sel,
+        _sender: *mut AnyObject,
+    ) -> *mut AnyObject {
+        DOCK_MENU.load(Ordering::Acquire)
+    }
+
+    extern "C" fn open_recent_conversation(
+        _this: *mut AnyObject,
+        _cmd: Sel,
+        sender: *mut AnyObject,
+    ) {
+        let tag: isize = unsafe { msg_send![sender, tag] };
+        let id = DOCK_MENU_IDS.lock().unwrap().get(tag as usize).cloned();
+        if let (Some(app), Some(id)) = (APP.get(), id) {
+            crate::deep_links::open(app, &format!("pipali://chat/{}", id));
+        }
+    }
+
+    fn ns_string(text: &str) -> Retained<AnyObject> {
+        let text = CString::new(text.replace('\0', "")).unwrap_or_default();
+        unsafe { msg_send![class!(NSString), stringWithUTF8String: text.as_ptr()] }
+    }
+
+    /// tao's app delegate has no dock menu, so add the methods for one to it
+    unsafe fn install(app: &AppHandle) {
+        let _ = APP.set(app.clone());
+        let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        let delegate: *mut AnyObject = msg_send![ns_app, delegate];
+        if delegate.is_null() {
+            log::warn!("[App] No app delegate to add the dock menu to");
+            return;
+        }
+        let cls = object_getClass(delegate);
+        class_addMethod(
+            cls,
+            sel!(applicationDockMenu:),
+            application_dock_menu as *const c_void,
+            c"@@:@".as_ptr(),
+        );
+        class_addMethod(
+            cls,
+            sel!(openRecentConversation:),
+            open_recent_conversation as *const c_void,
+            c"v@:@".as_ptr(),
+        );
+    }
+
+    /// Replace the dock menu's items with the given conversations
+    unsafe fn set_dock_menu(conversations: &[(String, String)]) {
+        let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        let delegate: *mut AnyObject = msg_send![ns_app, delegate];
+        let menu: Retained<AnyObject> = msg_send![class!(NSMenu), new];
+        for (tag, (_, title)) in conversations.iter().enumerate() {
+            let item: Allocated<AnyObject> = msg_send![class!(NSMenuItem), alloc];
+            let item: Retained<AnyObject> = msg_send![
+                item,
+                initWithTitle: &*ns_string(title),
+                action: sel!(openRecentConversation:),
+                keyEquivalent: &*ns_string("")
+            ];
+            let _: () = msg_send![&item, setTag: tag as isize];
+            let _: () = msg_send![&item, setTarget: delegate];
+            let _: () = msg_send![&menu, addItem: &*item];
+        }
+        *DOCK_MENU_IDS.lock().unwrap() = conversations.iter().map(|(id, _)| id.clone()).collect();
+        let previous = DOCK_MENU.swap(Retained::into_raw(menu), Ordering::AcqRel);
+        // Drops the NSMenu that was handed out before
+        drop(Retained::from_raw(previous));
+    }
+
+    pub fn update(app: &AppHandle, conversations: &[Conversation]) -> Result<(), String> {
+        let items: Vec<(String, String)> = conversations
+            .iter()
+            .map(|conversation| (conversation.id.clone(), super::label(conversation)))
+            .collect();
+        let handle = app.clone();
+        // AppKit is only safe to use from the main thread
+        app.run_on_main_thread(move || unsafe {
+            INSTALL.call_once(|| install(&handle));
+            set_dock_menu(&items);
+        })
+        .map_err(|e| format!("Failed to update dock menu: {}", e))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::Conversation;
+    use std::path::Path;
+    use tauri::AppHandle;
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::StructuredStorage::PROPVARIANT;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{
+        DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink,
+    };
+
+    pub const SUPPORTED: bool = true;
+
+    /// Each entry launches Pipali with a `pipali://` link, which a running
+    /// instance picks up through single-instance
+    unsafe fn build_jump_list(
+        exe: &Path,
+        conversations: &[(String, String)],
+    ) -> windows::core::Result<()> {
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut min_slots = 0;
+        let _removed: IObjectArray = list.BeginList(&mut min_slots)?;
+        let collection: IObjectCollection =
+            CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+        let exe = HSTRING::from(exe);
+        for (id, title) in conversations {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            link.SetPath(&exe)?;
+            link.SetArguments(&HSTRING::from(format!("pipali://chat/{}", id)))?;
+            link.SetIconLocation(&exe, 0)?;
+            let store: IPropertyStore = link.cast()?;
+            store.SetValue(&PKEY_Title, &PROPVARIANT::from(title.as_str()))?;
+            store.Commit()?;
+            collection.AddObject(&link)?;
+        }
+        let items: IObjectArray = collection.cast()?;
+        list.AppendCategory(&HSTRING::from("Recent Conversations"), &items)?;
+        list.CommitList()
+    }
+
+    pub fn update(_app: &AppHandle, conversations: &[Conversation]) -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let items: Vec<(String, String)> = conversations
+            .iter()
+            .map(|conversation| (conversation.id.clone(), super::label(conversation)))
+            .collect();
+        // The shell objects need a single-threaded COM apartment of their own
+        std::thread::spawn(move || unsafe {
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            let result = build_jump_list(&exe, &items);
+            CoUninitialize();
+            result
+        })
+        .join()
+        .map_err(|_| "Jump list update panicked".to_string())?
+        .map_err(|e| format!("Failed to update jump list: {}", e))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::Conversation;
+    use tauri::AppHandle;
+
+    pub const SUPPORTED: bool = false;
+
+    pub fn update(_app: &AppHandle, _conversations: &[Conversation]) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::process_tree::SidecarChild;
+use crate::resource_usage::{self, ResourceMonitorState};
+use crate::settings::{Settings, SettingsState};
+use crate::SidecarState;
+
+/// How often the sidecar's usage is compared against its limits
+const LIMIT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Consecutive samples over a limit before acting, so short spikes are allowed
+const LIMIT_EXCEEDED_SAMPLES: u32 = 3;
+
+/// Soft limits on the sidecar's process tree, from the shell settings
+#[derive(Clone, Copy)]
+pub struct ResourceLimits {
+    pub memory_bytes: Option<u64>,
+    /// Share of CPU, where 100 is one full core
+    pub cpu_percent: Option<u32>,
+}
+
+impl ResourceLimits {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            memory_bytes: settings
+                .sidecar_memory_limit_mb
+                .filter(|mb| *mb > 0)
+                .map(|mb| mb * 1024 * 1024),
+            cpu_percent: settings.sidecar_cpu_limit_percent.filter(|pct| *pct > 0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memory_bytes.is_none() && self.cpu_percent.is_none()
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LimitExceededPayload {
+    resource: &'static str,
+    limit: u64,
+    usage: u64,
+    action: &'static str,
+}
+
+/// Have the OS enforce the limits on a freshly spawned sidecar, where it can
+///
+/// Windows caps the sidecar's job object and Linux moves it into a cgroup v2
+/// group, when the user's cgroup tree is delegated. Elsewhere, or if that
+/// fails, the usage monitor enforces the limits instead.
+pub fn apply(child: &SidecarChild, limits: &ResourceLimits) {
+    #[cfg(windows)]
+    let result = child.limit_job(limits.memory_bytes, limits.cpu_percent);
+    #[cfg(target_os = "linux")]
+    let result = cgroup::apply(child.pid(), limits);
+    #[cfg(not(any(windows, target_os = "linux")))]
+    let result: Result<(), String> = {
+        let _ = (child, limits);
+        Ok(())
+    };
+
+    if let Err(e) = result {
+        log::warn!(
+            "[Sidecar] Could not apply OS resource limits, monitoring usage instead: {}",
+            e
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod cgroup {
+    use super::ResourceLimits;
+    use std::path::PathBuf;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    /// Period used for the cpu.max quota, the kernel default
+    const CPU_PERIOD_US: u64 = 100_000;
+
+    /// Move the sidecar into a `pipali-sidecar` group next to the shell's own
+    /// cgroup. Children it starts later inherit the group.
+    pub fn apply(pid: u32, limits: &ResourceLimits) -> Result<(), String> {
+        let own_cgroup = std::fs::read_to_string("/proc/self/cgroup")
+            .map_err(|e| format!("Failed to read own cgroup: {}", e))?;
+        let own_path = own_cgroup
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or("cgroup v2 is not available")?;
+        let parent = PathBuf::from(CGROUP_ROOT)
+            .join(own_path.trim().trim_start_matches('/'))
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .ok_or("Shell runs in the root cgroup")?;
+
+        // Nothing to do without limits, unless they need lifting from an earlier run
+        let group = parent.join("pipali-sidecar");
+        if limits.is_empty() && !group.exists() {
+            return Ok(());
+        }
+
+        let controllers = std::fs::read_to_string(parent.join("cgroup.subtree_control"))
+            .map_err(|e| format!("Failed to read cgroup controllers: {}", e))?;
+        let has_controller = |name: &str| controllers.split_whitespace().any(|c| c == name);
+        if limits.memory_bytes.is_some() && !has_controller("memory") {
+            return Err("memory controller is not delegated".to_string());
+        }
+        if limits.cpu_percent.is_some() && !has_controller("cpu") {
+            return Err("cpu controller is not delegated".to_string());
+        }
+
+        std::fs::create_dir_all(&group)
+            .map_err(|e| format!("Failed to create cgroup {:?}: {}", group, e))?;
+
+        let write = |file: &str, value: String| {
+            std::fs::write(group.join(file), value)
+                .map_err(|e| format!("Failed to write {}: {}", file, e))
+        };
+        if has_controller("memory") {
+            // memory.high throttles and reclaims rather than OOM-killing
+            write(
+                "memory.high",
+                limits
+                    .memory_bytes
+                    .map_or("max".to_string(), |bytes| bytes.to_string()),
+            )?;
+        }
+        if has_controller("cpu") {
+            let quota = limits.cpu_percent.map_or("max".to_string(), |pct| {
+                (pct as u64 * CPU_PERIOD_US / 100).to_string()
+            });
+            write("cpu.max", format!("{} {}", quota, CPU_PERIOD_US))?;
+        }
+        write("cgroup.procs", pid.to_string())?;
+
+        log::info!("[Sidecar] Applied resource limits via cgroup {:?}", group);
+        Ok(())
+    }
+}
+
+/// Lower the priority of the whole sidecar process group
+#[cfg(unix)]
+fn throttle(pid: u32) -> Result<(), String> {
+    let status = std::process::Command::new("renice")
+        .args(["-n", "10", "-g", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("renice exited with status {}", status));
+    }
+
+    // Also move the server to the background task policy, which throttles
+    // its CPU and disk access further
+    #[cfg(target_os = "macos")]
+    if let Err(e) = std::process::Command::new("taskpolicy")
+        .args(["-b", "-p", &pid.to_string()])
+        .status()
+    {
+        log::warn!("[Sidecar] Failed to apply background task policy: {}", e);
+    }
+    Ok(())
+}
+
+/// The job object already hard-caps CPU use on Windows
+#[cfg(windows)]
+fn throttle(_pid: u32) -> Result<(), String> {
+    Ok(())
+}
+
+/// Set the sidecar's memory limit in MB and CPU limit as a percentage of one
+/// core, or `None` to lift them (exposed to frontend)
+#[tauri::command]
+pub fn set_sidecar_resource_limits(
+    app: AppHandle,
+    memory_limit_mb: Option<u64>,
+    cpu_limit_percent: Option<u32>,
+) -> Result<(), String> {
+    let settings = app.state::<SettingsState>();
+    settings.update(|settings| {
+        settings.sidecar_memory_limit_mb = memory_limit_mb;
+        settings.sidecar_cpu_limit_percent = cpu_limit_percent;
+    })?;
+
+    // Apply to the running sidecar too; the monitor picks them up on its own
+    let limits = ResourceLimits::from_settings(&settings.get());
+    if let Some(child) = app.state::<SidecarState>().child.lock().unwrap().as_ref() {
+        apply(child, &limits);
+    }
+    Ok(())
+}
+
+/// Periodically check the sidecar's usage against the configured limits
+///
+/// The sidecar is killed, and restarted by the supervisor, if it stays over
+/// its memory limit, and throttled if it stays over its CPU limit. Limits are
+/// re-read each time, so changes apply without a restart.
+pub fn spawn_resource_limit_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut watched_pid = None;
+        let mut memory_strikes = 0;
+        let mut cpu_strikes = 0;
+        let mut throttled = false;
+
+        loop {
+            tokio::time::sleep(LIMIT_CHECK_INTERVAL).await;
+
+            let limits = ResourceLimits::from_settings(&app.state::<SettingsState>().get());
+            let state: State<SidecarState> = app.state();
+            let pid = state
+                .child
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|child| child.pid());
+            if watched_pid != pid {
+                watched_pid = pid;
+                memory_strikes = 0;
+                cpu_strikes = 0;
+                throttled = false;
+            }
+            let Some(pid) = pid else {
+                continue;
+            };
+            if limits.is_empty() {
+                continue;
+            }
+
+            let handle = app.clone();
+            let usage = tauri::async_runtime::spawn_blocking(move || {
+                resource_usage::sample(&handle.state::<ResourceMonitorState>(), pid)
+            })
+            .await;
+            let Ok(Ok(usage)) = usage else {
+                continue;
+            };
+
+            if let Some(limit) = limits.memory_bytes {
+                memory_strikes = if usage.tree_memory_bytes > limit {
+                    memory_strikes + 1
+                } else {
+                    0
+                };
+                if memory_strikes >= LIMIT_EXCEEDED_SAMPLES {
+                    log::error!(
+                        "[Sidecar] Using {} MB, over its {} MB limit, killing it",
+                        usage.tree_memory_bytes / 1024 / 1024,
+                        limit / 1024 / 1024
+                    );
+                    let _ = app.emit(
+                        "sidecar://limit-exceeded",
+                        LimitExceededPayload {
+                            resource: "memory",
+                            limit,
+                            usage: usage.tree_memory_bytes,
+                            action: "killed",
+                        },
+                    );
+                    // Leaving the child in place makes this an unexpected exit,
+                    // so the supervisor restarts it with its usual backoff
+                    if let Some(child) = state.child.lock().unwrap().as_ref() {
+                        if let Err(e) = child.kill() {
+                            log::error!("[Sidecar] Failed to kill: {}", e);
+                        }
+                    }
+                    memory_strikes = 0;
+                    continue;
+                }
+            }
+
+            if let Some(limit) = limits.cpu_percent {
+                cpu_strikes = if usage.tree_cpu_percent > limit as f32 {
+                    cpu_strikes + 1
+                } else {
+                    0
+                };
+                if cpu_strikes >= LIMIT_EXCEEDED_SAMPLES && !throttled {
+                    log::warn!(
+                        "[Sidecar] Using {:.0}% CPU, over its {}% limit, throttling it",
+                        usage.tree_cpu_percent,
+                        limit
+                    );
+                    if let Err(e) = throttle(pid) {
+                        log::warn!("[Sidecar] Failed to throttle: {}", e);
+                    }
+                    throttled = true;
+                    let _ = app.emit(
+                        "sidecar://limit-exceeded",
+                        LimitExceededPayload {
+                            resource: "cpu",
+                            limit: limit as u64,
+                            usage: usage.tree_cpu_percent as u64,
+                            action: "throttled",
+                        },
+                    );
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,138 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, MINIMUM_CPU_UPDATE_INTERVAL};
+use tauri::{AppHandle, Manager};
+
+use crate::SidecarState;
+
+/// Snapshot of the sidecar's resource use, for the UI's resource panel
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarResourceUsage {
+    pub pid: u32,
+    /// Resident memory of the server process, in bytes
+    pub memory_bytes: u64,
+    pub virtual_memory_bytes: u64,
+    /// CPU use since the previous sample, where 100 is one full core
+    pub cpu_percent: f32,
+    /// Open file descriptors, on platforms where they can be counted
+    pub open_files: Option<usize>,
+    /// Totals across the server and every process it launched
+    pub tree_memory_bytes: u64,
+    pub tree_cpu_percent: f32,
+    pub tree_process_count: usize,
+}
+
+/// Keeps the process table between samples, since CPU use is measured as
+/// the change since the previous refresh
+#[derive(Default)]
+pub struct ResourceMonitorState {
+    system: Mutex<System>,
+}
+
+fn refresh_processes(system: &mut System) {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::new().with_cpu().with_memory(),
+    );
+}
+
+/// Pids of the given process and all of its descendants
+fn process_tree(system: &System, root: Pid) -> Vec<Pid> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        // Linux lists threads as tasks of their process; only count processes
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+
+    let mut tree = vec![root];
+    let mut index = 0;
+    while let Some(pid) = tree.get(index).copied() {
+        tree.extend(children.get(&pid).into_iter().flatten().copied());
+        index += 1;
+    }
+    tree
+}
+
+#[cfg(target_os = "linux")]
+fn count_open_files(pid: u32) -> Option<usize> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()
+        .map(|entries| entries.count())
+}
+
+#[cfg(target_os = "macos")]
+fn count_open_files(pid: u32) -> Option<usize> {
+    // -F f prints one "f<fd>" line per open file
+    let output = std::process::Command::new("lsof")
+        .args(["-n", "-P", "-F", "f", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().filter(|line| line.starts_with('f')).count())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn count_open_files(_pid: u32) -> Option<usize> {
+    None
+}
+
+pub(crate) fn sample(
+    monitor: &ResourceMonitorState,
+    pid: u32,
+) -> Result<SidecarResourceUsage, String> {
+    let mut system = monitor.system.lock().unwrap();
+    let sidecar_pid = Pid::from_u32(pid);
+
+    // CPU use needs two samples, so wait a moment on the first one
+    let first_sample = system.process(sidecar_pid).is_none();
+    refresh_processes(&mut system);
+    if first_sample {
+        std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+        refresh_processes(&mut system);
+    }
+
+    let process = system
+        .process(sidecar_pid)
+        .ok_or_else(|| format!("Sidecar process {} not found", pid))?;
+
+    let tree: Vec<_> = process_tree(&system, sidecar_pid)
+        .into_iter()
+        .filter_map(|pid| system.process(pid))
+        .collect();
+
+    Ok(SidecarResourceUsage {
+        pid,
+        memory_bytes: process.memory(),
+        virtual_memory_bytes: process.virtual_memory(),
+        cpu_percent: process.cpu_usage(),
+        open_files: count_open_files(pid),
+        tree_memory_bytes: tree.iter().map(|p| p.memory()).sum(),
+        tree_cpu_percent: tree.iter().map(|p| p.cpu_usage()).sum(),
+        tree_process_count: tree.len(),
+    })
+}
+
+/// Sample the sidecar's memory, CPU and open file use (exposed to frontend)
+#[tauri::command]
+pub async fn get_sidecar_resource_usage(app: AppHandle) -> Result<SidecarResourceUsage, String> {
+    let pid = app
+        .state::<SidecarState>()
+        .child
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|child| child.pid())
+        .ok_or("Sidecar is not running")?;
+
+    tauri::async_runtime::spawn_blocking(move || sample(&app.state::<ResourceMonitorState>(), pid))
+        .await
+        .map_err(|e| format!("Resource sampling task failed: {}", e))?
+}
@@ -0,0 +1,168 @@
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::file_drop::{self, Attachment};
+use crate::{SidecarState, SidecarStatus};
+
+/// Time for the main window to disappear before the screen is captured
+const HIDE_DELAY: Duration = Duration::from_millis(250);
+
+/// A display or window that can be captured
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureTarget {
+    pub id: u32,
+    pub name: String,
+    /// Only set for displays
+    pub primary: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct CaptureTargets {
+    pub displays: Vec<CaptureTarget>,
+    pub windows: Vec<CaptureTarget>,
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+/// Screen recording needs the user's permission on macOS, without which the
+/// capture only shows the desktop wallpaper
+///
+/// The first request shows the system prompt. Once denied, only System
+/// Settings can grant it.
+#[cfg(target_os = "macos")]
+fn check_permission() -> Result<(), String> {
+    if unsafe { CGPreflightScreenCaptureAccess() || CGRequestScreenCaptureAccess() } {
+        return Ok(());
+    }
+    Err("Pipali needs screen recording permission. Allow it in System Settings > Privacy & Security > Screen Recording, then try again.".to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn check_permission() -> Result<(), String> {
+    Ok(())
+}
+
+/// Capture the window, or else the display, as width, height and RGBA pixels
+fn capture(display_id: Option<u32>, window_id: Option<u32>) -> Result<(u32, u32, Vec<u8>), String> {
+    let image = if let Some(id) = window_id {
+        let windows = xcap::Window::all().map_err(|e| format!("Failed to list windows: {}", e))?;
+        let window = windows
+            .into_iter()
+            .find(|window| window.id() == id)
+            .ok_or_else(|| format!("Window {} not found", id))?;
+        window
+            .capture_image()
+            .map_err(|e| format!("Failed to capture window: {}", e))?
+    } else {
+        let monitors =
+            xcap::Monitor::all().map_err(|e| format!("Failed to list displays: {}", e))?;
+        let monitor = match display_id {
+            Some(id) => monitors.into_iter().find(|monitor| monitor.id() == id),
+            None => monitors.into_iter().find(|monitor| monitor.is_primary()),
+        }
+        .ok_or_else(|| "Display not found".to_string())?;
+        monitor
+            .capture_image()
+            .map_err(|e| format!("Failed to capture display: {}", e))?
+    };
+    Ok((image.width(), image.height(), image.into_raw()))
+}
+
+/// List the displays and windows `capture_screen` can capture (exposed to
+/// frontend)
+#[tauri::command]
+pub async fn list_capture_targets() -> Result<CaptureTargets, String> {
+    check_permission()?;
+    let displays = xcap::Monitor::all()
+        .map_err(|e| format!("Failed to list displays: {}", e))?
+        .iter()
+        .map(|monitor| CaptureTarget {
+            id: monitor.id(),
+            name: monitor.name().to_string(),
+            primary: Some(monitor.is_primary()),
+        })
+        .collect();
+    let windows = xcap::Window::all()
+        .map_err(|e| format!("Failed to list windows: {}", e))?
+        .iter()
+        .filter(|window| !window.is_minimized() && !window.title().is_empty())
+        .map(|window| CaptureTarget {
+            id: window.id(),
+            name: format!("{} — {}", window.app_name(), window.title()),
+            primary: None,
+        })
+        .collect();
+    Ok(CaptureTargets { displays, windows })
+}
+
+/// Capture a display, the primary one by default, or a window to the
+/// attachments dir and attach it for the sidecar (exposed to frontend)
+///
+/// The main window is hidden while a display is captured, so the screenshot
+/// shows what the user is asking about rather than Pipali.
+#[tauri::command]
+pub async fn capture_screen(
+    app: AppHandle,
+    state: State<'_, SidecarState>,
+    display_id: Option<u32>,
+    window_id: Option<u32>,
+) -> Result<Attachment, String> {
+    if state.status() != SidecarStatus::Ready {
+        return Err("Pipali server isn't running".to_string());
+    }
+    check_permission()?;
+
+    let main_window = app
+        .get_webview_window("main")
+        .filter(|window| window_id.is_none() && window.is_visible().unwrap_or(false));
+    if let Some(ref window) = main_window {
+        let _ = window.hide();
+        tokio::time::sleep(HIDE_DELAY).await;
+    }
+    let captured = tauri::async_runtime::spawn_blocking(move || capture(display_id, window_id))
+        .await
+        .map_err(|e| format!("Capture task failed: {}", e));
+    if main_window.is_some() {
+        crate::show_window(&app);
+    }
+    let (width, height, rgba) = captured??;
+
+    let dir = crate::get_attachments_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachments dir: {}", e))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("screenshot-{}.png", timestamp));
+    crate::clipboard::write_png(&path, width as usize, height as usize, &rgba)?;
+    log::info!(
+        "[App] Captured {}x{} screenshot to {}",
+        width,
+        height,
+        path.display()
+    );
+
+    let path = path.to_string_lossy().into_owned();
+    let handle = app.clone();
+    let mut registered = tauri::async_runtime::spawn_blocking(move || {
+        file_drop::register(&handle.state::<SidecarState>(), &[path])
+    })
+    .await
+    .map_err(|e| format!("Attach task failed: {}", e))??;
+    match registered.attachments.pop() {
+        Some(attachment) => Ok(attachment),
+        None => Err(registered
+            .rejected
+            .pop()
+            .map(|rejected| rejected.reason)
+            .unwrap_or_else(|| "Failed to attach screenshot".to_string())),
+    }
+}
@@ -0,0 +1,205 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// Keychain service the secrets are filed under
+const SERVICE: &str = "ai.pipali";
+
+/// A token held for the life of the app, like the sidecar's auth token,
+/// wiped from memory when dropped or when the app exits
+#[derive(Default)]
+pub struct Secret(Mutex<Zeroizing<String>>);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(Mutex::new(Zeroizing::new(value)))
+    }
+
+    /// A copy to use, itself wiped when dropped
+    pub fn get(&self) -> Zeroizing<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn wipe(&self) {
+        self.0.lock().unwrap().zeroize();
+    }
+}
+
+/// Secrets for the sidecar, serialized straight into a wiped buffer rather
+/// than through a `serde_json::Value`, which would leave copies behind
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretsBody {
+    secrets: BTreeMap<String, Zeroizing<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Handshake<'a> {
+    auth_token: &'a str,
+    secrets: BTreeMap<String, Zeroizing<String>>,
+    database_key: Option<Zeroizing<String>>,
+}
+
+/// Counts the bytes written to it, to size a buffer before serializing into it
+struct ByteCounter(usize);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize to JSON in a wiped buffer with room for `spare` more bytes
+///
+/// The JSON is measured before the buffer is allocated, as growing the
+/// buffer while serializing would leave copies of the secrets behind.
+pub fn to_json_bytes<T: Serialize>(
+    value: &T,
+    spare: usize,
+) -> serde_json::Result<Zeroizing<Vec<u8>>> {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, value)?;
+    let mut bytes = Zeroizing::new(Vec::with_capacity(counter.0 + spare));
+    serde_json::to_writer(&mut *bytes, value)?;
+    Ok(bytes)
+}
+
+/// Secrets are named like the environment variables the server reads them
+/// from, e.g. OPENAI_API_KEY
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(format!("Invalid secret name: {:?}", name));
+    }
+    Ok(())
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("Failed to open keychain: {}", e))
+}
+
+fn read(name: &str) -> Result<Option<Zeroizing<String>>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(Zeroizing::new(value))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from the keychain: {}", name, e)),
+    }
+}
+
+/// Every secret in the keychain, by name
+///
+/// Keychains can't list what an app stored, so the names are kept in the
+/// settings. Reading may block while the OS asks the user to unlock them.
+fn read_all(app: &AppHandle) -> BTreeMap<String, Zeroizing<String>> {
+    let names = app.state::<SettingsState>().get().secret_names;
+    names
+        .into_iter()
+        .filter_map(|name| match read(&name) {
+            Ok(value) => value.map(|value| (name, value)),
+            Err(e) => {
+                log::warn!("[Secrets] {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// What a new sidecar is sent on stdin: its auth token, the keychain's
+/// secrets and the database key, which would show in `ps` or /proc as
+/// arguments or environment
+pub fn handshake(app: &AppHandle, auth_token: &str) -> Zeroizing<Vec<u8>> {
+    let handshake = Handshake {
+        auth_token,
+        secrets: read_all(app),
+        database_key: crate::db_encryption::key_for_sidecar(app),
+    };
+    let mut bytes = to_json_bytes(&handshake, 1).expect("Handshake serializes to JSON");
+    bytes.push(b'\n');
+    bytes
+}
+
+/// Hand the keychain's secrets to the running sidecar over its authenticated
+/// loopback API, after they're changed
+async fn send_to_sidecar(app: &AppHandle) {
+    let state: State<SidecarState> = app.state();
+    if state.status() != SidecarStatus::Ready {
+        return;
+    }
+    let handle = app.clone();
+    let Ok(secrets) = tauri::async_runtime::spawn_blocking(move || read_all(&handle)).await else {
+        return;
+    };
+    let url = format!("http://{}:{}/api/secrets", state.host, state.port());
+    let body = SecretsBody { secrets };
+    let agent = crate::health_check_agent();
+    if let Err(e) = crate::post_sidecar_json(&agent, url, state.auth_token.get(), body).await {
+        log::warn!("[Secrets] Failed to send secrets to the server: {}", e);
+    }
+}
+
+/// Store a secret, such as a provider API key, in the OS keychain
+/// (exposed to frontend)
+#[tauri::command]
+pub async fn set_secret(
+    app: AppHandle,
+    name: String,
+    value: Zeroizing<String>,
+) -> Result<(), String> {
+    validate_name(&name)?;
+    let key = name.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        entry(&key)?
+            .set_password(&value)
+            .map_err(|e| format!("Failed to save {} to the keychain: {}", key, e))
+    })
+    .await
+    .map_err(|e| format!("Keychain task failed: {}", e))??;
+    app.state::<SettingsState>().update(|settings| {
+        settings.secret_names.insert(name.clone());
+    })?;
+    log::info!("[Secrets] Saved {}", name);
+    send_to_sidecar(&app).await;
+    Ok(())
+}
+
+/// Read a secret from the OS keychain (exposed to frontend)
+#[tauri::command]
+pub async fn get_secret(name: String) -> Result<Option<Zeroizing<String>>, String> {
+    validate_name(&name)?;
+    tauri::async_runtime::spawn_blocking(move || read(&name))
+        .await
+        .map_err(|e| format!("Keychain task failed: {}", e))?
+}
+
+/// Remove a secret from the OS keychain (exposed to frontend)
+#[tauri::command]
+pub async fn delete_secret(app: AppHandle, name: String) -> Result<(), String> {
+    validate_name(&name)?;
+    let key = name.clone();
+    tauri::async_runtime::spawn_blocking(move || match entry(&key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete {} from the keychain: {}", key, e)),
+    })
+    .await
+    .map_err(|e| format!("Keychain task failed: {}", e))??;
+    app.state::<SettingsState>().update(|settings| {
+        settings.secret_names.remove(&name);
+    })?;
+    log::info!("[Secrets] Deleted {}", name);
+    send_to_sidecar(&app).await;
+    Ok(())
+}
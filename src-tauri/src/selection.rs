@@ -0,0 +1,319 @@
+use tauri::AppHandle;
+
+/// Ask Pipali about the text selected in the focused app, from the global
+/// shortcut
+///
+/// Opens a new conversation with the prompt pre-filled, so the user can say
+/// what they want to know about it before sending.
+pub fn ask_about_selection(app: &AppHandle) {
+    if crate::privacy_mode::is_enabled(app) {
+        log::info!("[Privacy] Not reading the selection in privacy mode");
+        crate::show_window(app);
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || match platform::read_selection(&app) {
+        Ok(Some(text)) if !text.trim().is_empty() => crate::deep_links::ask(&app, text.trim()),
+        Ok(_) => {
+            log::info!("[App] No text selected to ask about");
+            crate::show_window(&app);
+        }
+        Err(e) => {
+            log::warn!("[App] Failed to read selected text: {}", e);
+            crate::show_window(&app);
+        }
+    });
+}
+
+/// Offer Ask Pipali in the Services menu of other apps (macOS)
+///
+/// The service is declared under NSServices in Info.plist. Elsewhere the
+/// global shortcut is the only way in.
+pub fn setup(app: &AppHandle) {
+    platform::setup(app);
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::runtime::{AnyClass, AnyObject, Sel};
+    use objc2::{class, msg_send, sel};
+    use std::ffi::{c_char, c_void, CStr, CString};
+    use std::sync::OnceLock;
+    use tauri::{AppHandle, Manager};
+
+    use crate::clipboard::ClipboardState;
+
+    extern "C" {
+        fn object_getClass(obj: *const AnyObject) -> *const AnyClass;
+        fn class_addMethod(
+            cls: *const AnyClass,
+            name: Sel,
+            imp: *const c_void,
+            types: *const c_char,
+        ) -> bool;
+    }
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        fn NSUpdateDynamicServices();
+    }
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    /// `askPipali:userData:error:`, named by NSMessage in Info.plist
+    extern "C" fn ask_pipali(
+        _this: *mut AnyObject,
+        _cmd: Sel,
+        pasteboard: *mut AnyObject,
+        _user_data: *mut AnyObject,
+        _error: *mut *mut AnyObject,
+    ) {
+        let text = unsafe {
+            let plain_text = CString::new("public.utf8-plain-text").unwrap();
+            let kind: *mut AnyObject =
+                msg_send![class!(NSString), stringWithUTF8String: plain_text.as_ptr()];
+            let text: *mut AnyObject = msg_send![pasteboard, stringForType: kind];
+            if text.is_null() {
+                return;
+            }
+            let utf8: *const c_char = msg_send![text, UTF8String];
+            if utf8.is_null() {
+                return;
+            }
+            CStr::from_ptr(utf8).to_string_lossy().into_owned()
+        };
+        if let Some(app) = APP.get() {
+            if crate::privacy_mode::is_enabled(app) {
+                log::info!("[Privacy] Ignoring the Ask Pipali service in privacy mode");
+                return;
+            }
+            log::info!("[App] Ask Pipali service invoked");
+            crate::deep_links::ask(app, text.trim());
+        }
+    }
+
+    pub fn setup(app: &AppHandle) {
+        let _ = APP.set(app.clone());
+        let result = app.run_on_main_thread(|| unsafe {
+            let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+            let delegate: *mut AnyObject = msg_send![ns_app, delegate];
+            if delegate.is_null() {
+                log::warn!("[App] No app delegate to provide the Ask Pipali service");
+                return;
+            }
+            class_addMethod(
+                object_getClass(delegate),
+                sel!(askPipali:userData:error:),
+                ask_pipali as *const c_void,
+                c"v@:@@^@".as_ptr(),
+            );
+            let _: () = msg_send![ns_app, setServicesProvider: delegate];
+            NSUpdateDynamicServices();
+        });
+        if let Err(e) = result {
+            log::warn!("[App] Failed to set up the Ask Pipali service: {}", e);
+        }
+    }
+
+    /// Copying for the user would need accessibility access, so the
+    /// shortcut asks about what they last copied: select, press ⌘C, then
+    /// the shortcut. The Services menu item reads the selection itself.
+    pub fn read_selection(app: &AppHandle) -> Result<Option<String>, String> {
+        app.state::<ClipboardState>()
+            .with(|clipboard| match clipboard.get_text() {
+                Err(arboard::Error::ContentNotAvailable) => Ok(None),
+                text => text.map(Some),
+            })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::time::Duration;
+    use tauri::{AppHandle, Manager};
+    use windows::Win32::Foundation::{GlobalFree, HANDLE, HGLOBAL};
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, EnumClipboardFormats, GetClipboardData, OpenClipboard,
+        SetClipboardData,
+    };
+    use windows::Win32::System::Memory::{
+        GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+        VIRTUAL_KEY, VK_C, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+    };
+
+    use crate::clipboard::ClipboardState;
+
+    /// Time for the focused app to put its selection on the clipboard
+    const COPY_DELAY: Duration = Duration::from_millis(200);
+    /// Tries at opening the clipboard while another app has it open
+    const OPEN_ATTEMPTS: u32 = 10;
+
+    /// Clipboard formats held as GDI handles rather than global memory, which
+    /// can't be copied byte for byte: CF_BITMAP, CF_METAFILEPICT, CF_PALETTE,
+    /// CF_ENHMETAFILE, the owner display formats and private GDI objects.
+    /// Bitmaps come back all the same, as Windows renders them from CF_DIB.
+    fn is_gdi_format(format: u32) -> bool {
+        matches!(format, 2 | 3 | 9 | 14 | 0x80 | 0x82 | 0x83 | 0x8e)
+            || (0x300..=0x3ff).contains(&format)
+    }
+
+    /// The clipboard, open for this thread until dropped
+    struct OpenedClipboard;
+
+    impl OpenedClipboard {
+        fn open() -> Result<Self, String> {
+            for _ in 0..OPEN_ATTEMPTS {
+                if unsafe { OpenClipboard(None) }.is_ok() {
+                    return Ok(Self);
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            Err("The clipboard is in use by another app".to_string())
+        }
+    }
+
+    impl Drop for OpenedClipboard {
+        fn drop(&mut self) {
+            let _ = unsafe { CloseClipboard() };
+        }
+    }
+
+    /// Copy every format on the clipboard, so it can be put back as it was
+    fn snapshot() -> Result<Vec<(u32, Vec<u8>)>, String> {
+        let _clipboard = OpenedClipboard::open()?;
+        let mut formats = Vec::new();
+        let mut format = 0;
+        loop {
+            format = unsafe { EnumClipboardFormats(format) };
+            if format == 0 {
+                break;
+            }
+            if is_gdi_format(format) {
+                continue;
+            }
+            let Ok(handle) = (unsafe { GetClipboardData(format) }) else {
+                continue;
+            };
+            let memory = HGLOBAL(handle.0);
+            // SAFETY: the handle is global memory owned by the clipboard,
+            // which stays valid while it's open and the memory is locked
+            unsafe {
+                let data = GlobalLock(memory);
+                if data.is_null() {
+                    continue;
+                }
+                let bytes = std::slice::from_raw_parts(data.cast::<u8>(), GlobalSize(memory));
+                formats.push((format, bytes.to_vec()));
+                let _ = GlobalUnlock(memory);
+            }
+        }
+        Ok(formats)
+    }
+
+    /// Put back the formats [`snapshot`] copied, replacing what's there
+    fn restore(formats: &[(u32, Vec<u8>)]) -> Result<(), String> {
+        let _clipboard = OpenedClipboard::open()?;
+        unsafe { EmptyClipboard() }.map_err(|e| format!("Failed to clear the clipboard: {}", e))?;
+        for (format, bytes) in formats {
+            // SAFETY: the memory is allocated with the size copied into it,
+            // and the clipboard owns it once SetClipboardData succeeds
+            unsafe {
+                let Ok(memory) = GlobalAlloc(GMEM_MOVEABLE, bytes.len()) else {
+                    continue;
+                };
+                let data = GlobalLock(memory);
+                if data.is_null() {
+                    let _ = GlobalFree(Some(memory));
+                    continue;
+                }
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.cast::<u8>(), bytes.len());
+                let _ = GlobalUnlock(memory);
+                if SetClipboardData(*format, Some(HANDLE(memory.0))).is_err() {
+                    let _ = GlobalFree(Some(memory));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn key(vk: VIRTUAL_KEY, up: bool) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: 0,
+                    dwFlags: if up {
+                        KEYEVENTF_KEYUP
+                    } else {
+                        KEYBD_EVENT_FLAGS(0)
+                    },
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    pub fn setup(_app: &AppHandle) {}
+
+    /// Windows has no selection clipboard, so copy the selection by pressing
+    /// Ctrl+C in the focused app, then put back everything that was on the
+    /// clipboard, images and files included
+    pub fn read_selection(app: &AppHandle) -> Result<Option<String>, String> {
+        let state = app.state::<ClipboardState>();
+        let previous = snapshot()?;
+        state.with(|clipboard| clipboard.clear())?;
+
+        // Release the shortcut's modifiers, which would otherwise still be
+        // held down with the C
+        let inputs = [
+            key(VK_SHIFT, true),
+            key(VK_MENU, true),
+            key(VK_LWIN, true),
+            key(VK_RWIN, true),
+            key(VK_CONTROL, false),
+            key(VK_C, false),
+            key(VK_C, true),
+            key(VK_CONTROL, true),
+        ];
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        let selection = if sent as usize == inputs.len() {
+            std::thread::sleep(COPY_DELAY);
+            state.with(|clipboard| Ok(clipboard.get_text().ok()))
+        } else {
+            Err("Failed to send the copy shortcut".to_string())
+        };
+        if let Err(e) = restore(&previous) {
+            log::warn!("[App] Failed to restore the clipboard: {}", e);
+        }
+        selection
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+    use tauri::{AppHandle, Manager};
+
+    use crate::clipboard::ClipboardState;
+
+    pub fn setup(_app: &AppHandle) {}
+
+    /// Selected text is on the primary selection, without copying it
+    pub fn read_selection(app: &AppHandle) -> Result<Option<String>, String> {
+        app.state::<ClipboardState>().with(|clipboard| {
+            match clipboard
+                .get()
+                .clipboard(LinuxClipboardKind::Primary)
+                .text()
+            {
+                Err(arboard::Error::ContentNotAvailable) => Ok(None),
+                text => text.map(Some),
+            }
+        })
+    }
+}
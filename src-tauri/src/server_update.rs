@@ -0,0 +1,478 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{is_compatible_server_version, SidecarState, EXPECTED_SERVER_VERSION};
+
+/// Server binaries are large; refuse anything far beyond their usual size
+const MAX_ARTIFACT_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Server update manifest, in the same shape as the app's update.json
+#[derive(Deserialize)]
+struct UpdateManifest {
+    version: String,
+    platforms: HashMap<String, PlatformArtifact>,
+}
+
+#[derive(Deserialize)]
+struct PlatformArtifact {
+    url: String,
+    /// Base64-encoded minisign signature of the artifact
+    signature: String,
+}
+
+/// What has been downloaded, persisted next to the downloads
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct UpdateRecord {
+    installed: Option<InstalledServer>,
+    /// Download the installed server replaced, kept to roll back to
+    previous: Option<InstalledServer>,
+    /// Versions that failed their startup checks, never to be used again
+    failed_versions: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstalledServer {
+    version: String,
+    path: PathBuf,
+    /// Hex SHA-256 of the binary as verified, checked again before each
+    /// launch so a binary swapped on disk since is never run
+    #[serde(default)]
+    sha256: Option<String>,
+}
+
+impl InstalledServer {
+    /// Whether the binary is still the one whose signature was verified
+    fn is_intact(&self) -> bool {
+        let Some(ref expected) = self.sha256 else {
+            log::error!(
+                "[ServerUpdate] Server {} has no recorded checksum, ignoring it",
+                self.version
+            );
+            return false;
+        };
+        match std::fs::read(&self.path) {
+            Ok(data) if sha256_hex(&data) == *expected => true,
+            Ok(_) => {
+                log::error!(
+                    "[ServerUpdate] Server {} at {:?} changed since it was verified, ignoring it",
+                    self.version,
+                    self.path
+                );
+                false
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUpdateStatus {
+    /// Version of the server bundled with the app
+    pub bundled_version: String,
+    /// Downloaded server version used from the next start, if any
+    pub installed_version: Option<String>,
+    /// Downloaded server version running right now, if any
+    pub running_version: Option<String>,
+    /// Downloaded server version kept to roll back to, if any
+    pub previous_version: Option<String>,
+    pub failed_versions: Vec<String>,
+}
+
+pub fn updates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::normalize_windows_path(crate::get_app_data_dir(app)?).join("server-updates"))
+}
+
+fn read_record(app: &AppHandle) -> UpdateRecord {
+    updates_dir(app)
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join("state.json")).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_record(app: &AppHandle, record: &UpdateRecord) -> Result<(), String> {
+    let dir = updates_dir(app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create server update dir: {}", e))?;
+    let contents = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join("state.json"), contents)
+        .map_err(|e| format!("Failed to save server update state: {}", e))
+}
+
+/// Numeric version components, for ordering releases
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether a version is a plain `MAJOR.MINOR.PATCH` release, as it ends up
+/// in the download's path
+fn is_release_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn is_newer(version: &str, than: &str) -> bool {
+    version_key(version) > version_key(than)
+}
+
+/// Whether a downloaded server may replace the bundled one
+fn is_usable(version: &str, record: &UpdateRecord) -> bool {
+    is_compatible_server_version(version, EXPECTED_SERVER_VERSION)
+        && is_newer(version, EXPECTED_SERVER_VERSION)
+        && !record
+            .failed_versions
+            .iter()
+            .any(|failed| failed == version)
+}
+
+/// The downloaded server binary to run instead of the bundled server, if any
+///
+/// Updates built for an older app, superseded by the bundled server after an
+/// app update, or changed on disk since they were verified, are ignored.
+pub fn installed_server(app: &AppHandle) -> Option<(String, PathBuf)> {
+    let record = read_record(app);
+    let installed = record.installed.as_ref()?;
+    if !is_usable(&installed.version, &record) || !installed.is_intact() {
+        return None;
+    }
+    Some((installed.version.clone(), installed.path.clone()))
+}
+
+/// Stop using a server update that failed its startup checks or kept
+/// crashing, going back to the download it replaced if that's still usable
+///
+/// Resolves with the downloaded version used from the next start, or `None`
+/// for the bundled server.
+pub fn mark_failed(app: &AppHandle, version: &str) -> Option<String> {
+    let mut record = read_record(app);
+    if !record
+        .failed_versions
+        .iter()
+        .any(|failed| failed == version)
+    {
+        record.failed_versions.push(version.to_string());
+    }
+    if record
+        .installed
+        .as_ref()
+        .is_some_and(|installed| installed.version == version)
+    {
+        let previous = record.previous.take();
+        record.installed = previous
+            .filter(|previous| is_usable(&previous.version, &record) && previous.is_intact());
+    }
+    if let Err(e) = write_record(app, &record) {
+        log::error!("[ServerUpdate] {}", e);
+    }
+    record.installed.map(|installed| installed.version)
+}
+
+/// Stop using the downloaded servers, after moving to another release
+/// channel, deleting those that aren't running
+pub fn forget_downloads(app: &AppHandle) {
+    let mut record = read_record(app);
+    let downloads: Vec<InstalledServer> = record
+        .installed
+        .take()
+        .into_iter()
+        .chain(record.previous.take())
+        .collect();
+    if downloads.is_empty() {
+        return;
+    }
+    if let Err(e) = write_record(app, &record) {
+        log::error!("[ServerUpdate] {}", e);
+        return;
+    }
+    let running = app.state::<SidecarState>().running_update();
+    for download in downloads {
+        if running.as_ref() == Some(&download.version) {
+            continue;
+        }
+        if let Some(dir) = download.path.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Platform key used in update manifests, as in the app updater's
+fn platform_key() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+/// The key app updates are signed with, from the updater config
+fn update_public_key(app: &AppHandle) -> Result<minisign_verify::PublicKey, String> {
+    let encoded = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey")?.as_str())
+        .ok_or("No updater public key configured")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid updater public key: {}", e))?;
+    minisign_verify::PublicKey::decode(&String::from_utf8_lossy(&decoded))
+        .map_err(|e| format!("Invalid updater public key: {}", e))
+}
+
+/// Last segment of an artifact URL's path
+fn artifact_file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Whether a file name has a version among its parts, like
+/// `pipali-server_1.2.3_darwin-aarch64` has 1.2.3
+fn names_version(file_name: &str, version: &str) -> bool {
+    file_name
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .any(|part| part.trim_matches('.') == version)
+}
+
+/// Verify the artifact's signature, and that the trusted comment it signs
+/// names the file that was downloaded
+///
+/// The file name carries the version, so an older release's artifact can't be
+/// passed off as a newer one.
+fn verify_signature(
+    public_key: &minisign_verify::PublicKey,
+    data: &[u8],
+    signature: &str,
+    file_name: &str,
+) -> Result<(), String> {
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(signature.trim())
+        .map_err(|e| format!("Invalid signature encoding: {}", e))?;
+    let signature = minisign_verify::Signature::decode(&String::from_utf8_lossy(&decoded))
+        .map_err(|e| format!("Invalid signature: {}", e))?;
+    public_key
+        .verify(data, &signature, true)
+        .map_err(|e| format!("Signature verification failed: {}", e))?;
+    // Signed as `timestamp:<secs>\tfile:<name>`
+    let signed_file = signature
+        .trusted_comment()
+        .split('\t')
+        .find_map(|field| field.strip_prefix("file:"));
+    if signed_file != Some(file_name) {
+        return Err(format!(
+            "Signature is for {}, not {}",
+            signed_file.unwrap_or("an unnamed file"),
+            file_name
+        ));
+    }
+    Ok(())
+}
+
+/// Write the binary next to its final path and move it into place, so a
+/// partial download is never picked up
+fn install_binary(dir: &Path, data: &[u8]) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create server update dir: {}", e))?;
+    let name = if cfg!(windows) {
+        "pipali-server.exe"
+    } else {
+        "pipali-server"
+    };
+    let path = dir.join(name);
+    let partial = dir.join(format!("{}.partial", name));
+    std::fs::write(&partial, data).map_err(|e| format!("Failed to write server update: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make server update executable: {}", e))?;
+    }
+
+    std::fs::rename(&partial, &path)
+        .map_err(|e| format!("Failed to install server update: {}", e))?;
+    Ok(path)
+}
+
+/// Download and verify the latest server on the update channel, if there's a
+/// usable newer one. The manifest is overridable via PIPALI_SERVER_UPDATE_URL.
+fn download_update(app: &AppHandle) -> Result<Option<String>, String> {
+    let manifest_url = std::env::var("PIPALI_SERVER_UPDATE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| crate::app_update::channel(app).manifest_url("server"));
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(10))
+        .timeout_read(Duration::from_secs(60))
+        .build();
+
+    let manifest: UpdateManifest = agent
+        .get(&manifest_url)
+        .call()
+        .map_err(|e| format!("Failed to fetch server update manifest: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read server update manifest: {}", e))
+        .and_then(|body| {
+            serde_json::from_str(&body)
+                .map_err(|e| format!("Invalid server update manifest: {}", e))
+        })?;
+    if !is_release_version(&manifest.version) {
+        return Err(format!(
+            "Invalid server update version: {}",
+            manifest.version
+        ));
+    }
+
+    let mut record = read_record(app);
+    let current = record
+        .installed
+        .as_ref()
+        .filter(|installed| is_usable(&installed.version, &record))
+        .map_or(EXPECTED_SERVER_VERSION, |installed| {
+            installed.version.as_str()
+        });
+    if !is_newer(&manifest.version, current) || !is_usable(&manifest.version, &record) {
+        log::info!(
+            "[ServerUpdate] No usable server update (latest {}, current {})",
+            manifest.version,
+            current
+        );
+        return Ok(None);
+    }
+    let artifact = manifest
+        .platforms
+        .get(&platform_key())
+        .ok_or_else(|| format!("No server update for platform {}", platform_key()))?;
+    let file_name = artifact_file_name(&artifact.url);
+    if !names_version(file_name, &manifest.version) {
+        return Err(format!(
+            "Server update {} isn't for version {}",
+            file_name, manifest.version
+        ));
+    }
+
+    log::info!("[ServerUpdate] Downloading server {}", manifest.version);
+    let mut data = Vec::new();
+    agent
+        .get(&artifact.url)
+        .call()
+        .map_err(|e| format!("Failed to download server update: {}", e))?
+        .into_reader()
+        .take(MAX_ARTIFACT_BYTES + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to download server update: {}", e))?;
+    if data.len() as u64 > MAX_ARTIFACT_BYTES {
+        return Err("Server update is larger than expected".to_string());
+    }
+    verify_signature(
+        &update_public_key(app)?,
+        &data,
+        &artifact.signature,
+        file_name,
+    )?;
+
+    let path = install_binary(&updates_dir(app)?.join(&manifest.version), &data)?;
+    // Keep the superseded download to roll back to, if the new one fails
+    let superseded = record.installed.replace(InstalledServer {
+        version: manifest.version.clone(),
+        path,
+        sha256: Some(sha256_hex(&data)),
+    });
+    let dropped = match superseded {
+        Some(superseded) => record.previous.replace(superseded),
+        None => None,
+    };
+    write_record(app, &record)?;
+
+    // Clean up the download that was kept before, unless it's still running
+    let running = app.state::<SidecarState>().running_update();
+    if let Some(dropped) = dropped.filter(|dropped| running.as_ref() != Some(&dropped.version)) {
+        if let Some(dir) = dropped.path.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    log::info!(
+        "[ServerUpdate] Server {} verified, it will be used from the next start",
+        manifest.version
+    );
+    Ok(Some(manifest.version))
+}
+
+/// Download the latest server if there is a newer compatible one (exposed to
+/// frontend)
+///
+/// Resolves with the version that will be used from the next sidecar start,
+/// or `None` if already up to date. Emits `server-update://staged`.
+#[tauri::command]
+pub async fn check_for_server_update(app: AppHandle) -> Result<Option<String>, String> {
+    let handle = app.clone();
+    let version = tauri::async_runtime::spawn_blocking(move || download_update(&handle))
+        .await
+        .map_err(|e| format!("Server update task failed: {}", e))??;
+    if let Some(ref version) = version {
+        let _ = app.emit("server-update://staged", version);
+    }
+    Ok(version)
+}
+
+/// Restart the sidecar on the downloaded server now, rather than from its
+/// next start (exposed to frontend)
+///
+/// Does nothing if the downloaded server is already running.
+#[tauri::command]
+pub async fn apply_server_update(app: AppHandle) -> Result<(), String> {
+    let Some((version, _)) = installed_server(&app) else {
+        return Err("No server update has been downloaded".to_string());
+    };
+    if app.state::<SidecarState>().running_update().as_ref() == Some(&version) {
+        return Ok(());
+    }
+    crate::commands::reserve_restart(&app)?;
+    log::info!(
+        "[ServerUpdate] Restarting the sidecar on server {}",
+        version
+    );
+    crate::commands::restart(&app).await
+}
+
+/// Get the bundled, downloaded and running server versions (exposed to frontend)
+#[tauri::command]
+pub fn get_server_update_status(app: AppHandle) -> ServerUpdateStatus {
+    let record = read_record(&app);
+    ServerUpdateStatus {
+        bundled_version: EXPECTED_SERVER_VERSION.to_string(),
+        installed_version: record
+            .installed
+            .as_ref()
+            .filter(|installed| is_usable(&installed.version, &record) && installed.path.is_file())
+            .map(|installed| installed.version.clone()),
+        running_version: app.state::<SidecarState>().running_update(),
+        previous_version: record.previous.map(|previous| previous.version),
+        failed_versions: record.failed_versions,
+    }
+}
@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::app_update::UpdateChannel;
+use crate::backup::BackupFrequency;
+use crate::process_priority::SidecarPriority;
+
+/// Desktop shell settings, persisted to `settings.json` in the app config dir
+///
+/// Missing fields fall back to their defaults, so older files keep working.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    /// How long to wait for the sidecar to pass its first health check
+    pub startup_timeout_secs: u64,
+    /// Delay between startup health checks
+    pub startup_poll_interval_ms: u64,
+    /// Extra environment variables for the sidecar, e.g. proxies or log level
+    pub env: BTreeMap<String, String>,
+    /// Names of the secrets kept in the OS keychain, which can't list them
+    pub secret_names: BTreeSet<String>,
+    /// Soft memory limit for the sidecar and its children, in MB
+    pub sidecar_memory_limit_mb: Option<u64>,
+    /// Soft CPU limit for the sidecar and its children, where 100 is one core
+    pub sidecar_cpu_limit_percent: Option<u32>,
+    /// Pause the sidecar while the system sleeps (Linux only)
+    pub pause_sidecar_on_sleep: bool,
+    /// Run the window through XWayland on Wayland sessions, for compositors
+    /// where it misbehaves (Linux only, applied on the next launch)
+    pub force_x11: bool,
+    /// Folder the default profile keeps its database in, unless `--data-dir`
+    /// or PIPALI_DATA_DIR is set. Changed with `migrate_data_dir`.
+    pub data_dir: Option<String>,
+    /// Allow PIPALI_HOST to make the sidecar listen on addresses other devices
+    /// can reach. Off by default, so the server stays loopback-only.
+    pub lan_mode: bool,
+    /// Also serve the sidecar over TLS with a self-signed certificate the shell
+    /// generates and trusts, and point the webview at https/wss
+    pub local_tls: bool,
+    /// Keep the server's database encrypted on disk, with the key in the
+    /// OS keychain. Turned on by `encrypt_database`, which can't be undone.
+    pub encrypt_database: bool,
+    /// Port to start the default profile's sidecar on, unless PIPALI_PORT is set
+    pub sidecar_port: Option<u16>,
+    /// Profiles besides the default one, by name
+    pub profiles: BTreeMap<String, ProfileSettings>,
+    /// Profile the main sidecar runs, or `None` for the default profile
+    pub active_profile: Option<String>,
+    /// Open the window straight away and only start the sidecar once the
+    /// frontend first needs it
+    pub lazy_sidecar_start: bool,
+    /// Stop the sidecar after this many idle minutes while the window is
+    /// hidden, restarting it on the next interaction. Off if unset or 0.
+    pub idle_shutdown_mins: Option<u64>,
+    /// Hide the app's windows after this many minutes without input, until
+    /// the user authenticates with Touch ID, Windows Hello or their account
+    /// password. Off if unset or 0.
+    pub lock_after_idle_mins: Option<u64>,
+    /// Scheduling priority of the sidecar and its children
+    pub sidecar_priority: SidecarPriority,
+    /// Lower the sidecar's priority while the window is unfocused, restoring
+    /// it once the window is focused again
+    pub lower_sidecar_priority_when_unfocused: bool,
+    /// Keep the system from idle sleeping while the sidecar runs chats or
+    /// automations
+    pub keep_awake_during_tasks: bool,
+    /// Ask the sidecar to run fewer automations at once while on battery
+    /// below this charge. Off if unset or 0.
+    pub throttle_below_battery_percent: Option<u8>,
+    /// Keep OS crash dumps of the sidecar, under `crash-dumps` in the app data dir
+    pub capture_crash_dumps: bool,
+    /// Hide the main window to the tray on close, keeping the sidecar and its
+    /// automations running, rather than quitting
+    pub close_to_tray: bool,
+    /// Start in the tray without showing the window, like `--hidden`
+    pub start_hidden: bool,
+    /// Keep the main window above other windows
+    pub always_on_top: bool,
+    /// Refuse screenshots, clipboard reads and selection capture until
+    /// turned off again, e.g. for the length of a sensitive meeting
+    pub privacy_mode: bool,
+    /// Global shortcut that summons the main window, or `None` to disable it
+    pub summon_shortcut: Option<String>,
+    /// Global shortcut that toggles the quick-ask prompt bar, or `None`
+    pub quick_ask_shortcut: Option<String>,
+    /// Global shortcut that asks Pipali about the text selected in any app,
+    /// or `None`. On macOS it asks about the text last copied with ⌘C.
+    pub ask_selection_shortcut: Option<String>,
+    /// Raise OS notifications for automations and server errors while the
+    /// window isn't focused
+    pub native_notifications: bool,
+    /// Release channel the app and its server update from
+    pub update_channel: UpdateChannel,
+    /// Install downloaded updates when the app quits, rather than offering
+    /// to restart into them
+    pub install_updates_on_exit: bool,
+    /// Start the app again after installing an update on exit
+    pub relaunch_after_update: bool,
+    /// How often the database is backed up automatically
+    pub backup_frequency: BackupFrequency,
+    /// Folder automatic backups go in, or `None` for `backups` in the app
+    /// data dir
+    pub backup_dir: Option<String>,
+    /// Backups to keep in the backup folder, deleting older ones
+    pub backups_to_keep: u32,
+}
+
+/// A profile with its own data directory and sidecar
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProfileSettings {
+    /// Port to start the profile's sidecar on
+    pub port: Option<u16>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            startup_timeout_secs: 10,
+            startup_poll_interval_ms: 200,
+            env: BTreeMap::new(),
+            secret_names: BTreeSet::new(),
+            sidecar_memory_limit_mb: None,
+            sidecar_cpu_limit_percent: None,
+            pause_sidecar_on_sleep: false,
+            force_x11: false,
+            data_dir: None,
+            lan_mode: false,
+            local_tls: false,
+            encrypt_database: false,
+            sidecar_port: None,
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            lazy_sidecar_start: false,
+            idle_shutdown_mins: None,
+            lock_after_idle_mins: None,
+            sidecar_priority: SidecarPriority::Normal,
+            lower_sidecar_priority_when_unfocused: true,
+            keep_awake_during_tasks: true,
+            throttle_below_battery_percent: Some(20),
+            capture_crash_dumps: true,
+            close_to_tray: true,
+            start_hidden: false,
+            always_on_top: false,
+            privacy_mode: false,
+            summon_shortcut: Some(crate::hotkeys::DEFAULT_SUMMON_SHORTCUT.to_string()),
+            quick_ask_shortcut: Some(crate::hotkeys::DEFAULT_QUICK_ASK_SHORTCUT.to_string()),
+            ask_selection_shortcut: crate::hotkeys::DEFAULT_ASK_SELECTION_SHORTCUT
+                .map(str::to_string),
+            native_notifications: true,
+            update_channel: UpdateChannel::Stable,
+            install_updates_on_exit: false,
+            relaunch_after_update: false,
+            backup_frequency: BackupFrequency::Off,
+            backup_dir: None,
+            backups_to_keep: 7,
+        }
+    }
+}
+
+fn env_u64(name: &str) -> Option<u64> {
+    std::env::var(name).ok()?.parse().ok()
+}
+
+impl Settings {
+    /// Port saved for the active profile's sidecar
+    pub fn saved_sidecar_port(&self) -> Option<u16> {
+        match self.active_profile {
+            Some(ref name) => self.profiles.get(name).and_then(|profile| profile.port),
+            None => self.sidecar_port,
+        }
+    }
+
+    pub fn set_saved_sidecar_port(&mut self, port: u16) {
+        match self.active_profile {
+            Some(ref name) => self.profiles.entry(name.clone()).or_default().port = Some(port),
+            None => self.sidecar_port = Some(port),
+        }
+    }
+
+    /// Startup timeout, overridable via PIPALI_STARTUP_TIMEOUT_SECS
+    pub fn startup_timeout(&self) -> Duration {
+        Duration::from_secs(
+            env_u64("PIPALI_STARTUP_TIMEOUT_SECS").unwrap_or(self.startup_timeout_secs),
+        )
+    }
+
+    /// Startup poll interval, overridable via PIPALI_STARTUP_POLL_INTERVAL_MS
+    pub fn startup_poll_interval(&self) -> Duration {
+        Duration::from_millis(
+            env_u64("PIPALI_STARTUP_POLL_INTERVAL_MS")
+                .unwrap_or(self.startup_poll_interval_ms)
+                .max(10),
+        )
+    }
+}
+
+fn read_settings(path: Option<&Path>) -> Settings {
+    match path.map(std::fs::read_to_string) {
+        Some(Ok(contents)) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("[Settings] Invalid settings file, using defaults: {}", e);
+            Settings::default()
+        }),
+        _ => Settings::default(),
+    }
+}
+
+/// Bundle identifier from tauri.conf.json, which names the app config dir
+#[cfg(target_os = "linux")]
+const IDENTIFIER: &str = "ai.pipali";
+
+/// Read the settings before Tauri starts, for options that must be applied
+/// before GTK initializes (Linux only)
+///
+/// Looks where Tauri's app config dir resolves on Linux.
+#[cfg(target_os = "linux")]
+pub fn read_before_startup() -> Settings {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| crate::get_home_dir().map(|home| home.join(".config")));
+    let path = config_dir.map(|dir| dir.join(IDENTIFIER).join("settings.json"));
+    read_settings(path.as_deref())
+}
+
+pub struct SettingsState {
+    path: Option<PathBuf>,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsState {
+    /// Load settings from disk, falling back to defaults if missing or invalid
+    pub fn load(app: &AppHandle) -> Self {
+        let path = crate::get_app_config_dir(app)
+            .ok()
+            .map(|dir| dir.join("settings.json"));
+
+        Self {
+            settings: Mutex::new(read_settings(path.as_deref())),
+            path,
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Apply a change to the settings and write them back to disk
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or("Settings directory is unavailable")?;
+        let mut settings = self.settings.lock().unwrap();
+        let mut updated = settings.clone();
+        change(&mut updated);
+
+        let contents = serde_json::to_string_pretty(&updated).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        std::fs::write(path, contents).map_err(|e| format!("Failed to save settings: {}", e))?;
+
+        *settings = updated;
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Rotate the log once it grows past this size
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+/// Number of rotated logs to keep alongside the active one
+const MAX_ROTATED_LOGS: usize = 4;
+/// Number of recent lines kept in memory
+const LOG_BUFFER_CAPACITY: usize = 1000;
+
+/// Where a sidecar log line came from
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarLogStream {
+    Stdout,
+    Stderr,
+    /// Messages from the shell about the sidecar (spawn, exit, errors)
+    Shell,
+}
+
+impl SidecarLogStream {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SidecarLogStream::Stdout => "stdout",
+            SidecarLogStream::Stderr => "stderr",
+            SidecarLogStream::Shell => "shell",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarLogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<SidecarLogLevel> for log::Level {
+    fn from(level: SidecarLogLevel) -> Self {
+        match level {
+            SidecarLogLevel::Info => log::Level::Info,
+            SidecarLogLevel::Warn => log::Level::Warn,
+            SidecarLogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct SidecarLogLine {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub stream: SidecarLogStream,
+    pub level: SidecarLogLevel,
+    pub line: String,
+}
+
+impl SidecarLogLine {
+    pub fn new(stream: SidecarLogStream, level: SidecarLogLevel, line: &str) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            stream,
+            level,
+            line: line.trim_end().to_string(),
+        }
+    }
+}
+
+/// Bounded buffer of the most recent sidecar log lines
+#[derive(Default)]
+pub struct SidecarLogBuffer {
+    lines: Mutex<VecDeque<SidecarLogLine>>,
+}
+
+impl SidecarLogBuffer {
+    pub fn push(&self, line: SidecarLogLine) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() >= LOG_BUFFER_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Get up to `last_n` of the most recent lines, oldest first
+    pub fn recent(&self, last_n: usize) -> Vec<SidecarLogLine> {
+        let lines = self.lines.lock().unwrap();
+        let skip = lines.len().saturating_sub(last_n);
+        lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Persists sidecar stdout/stderr to `logs/sidecar.log` with size-based rotation
+///
+/// Rotated logs are kept as `sidecar.1.log` (newest) to `sidecar.N.log` (oldest).
+/// Writing is best-effort: if the log can't be opened, lines are dropped.
+#[derive(Default)]
+pub struct SidecarLog {
+    dir: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl SidecarLog {
+    pub fn open(logs_dir: &Path) -> Self {
+        let mut sidecar_log = Self {
+            dir: logs_dir.to_path_buf(),
+            file: None,
+            size: 0,
+        };
+        if let Err(e) = std::fs::create_dir_all(logs_dir)
+            .map_err(|e| format!("Failed to create logs dir: {}", e))
+            .and_then(|_| sidecar_log.open_active())
+        {
+            log::warn!("[Sidecar] Output will not be saved to disk: {}", e);
+        }
+        sidecar_log
+    }
+
+    fn active_path(&self) -> PathBuf {
+        self.dir.join("sidecar.log")
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("sidecar.{}.log", index))
+    }
+
+    fn open_active(&mut self) -> Result<(), String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.active_path())
+            .map_err(|e| format!("Failed to open sidecar log: {}", e))?;
+        self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Append a line to the active log file
+    pub fn write_line(&mut self, line: &SidecarLogLine) {
+        if self.size >= MAX_LOG_SIZE {
+            self.rotate();
+        }
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+
+        let timestamp = UNIX_EPOCH + std::time::Duration::from_millis(line.timestamp);
+        let entry = format!(
+            "{} [{}] {}\n",
+            humantime::format_rfc3339_millis(timestamp),
+            line.stream.as_str(),
+            line.line
+        );
+        if file.write_all(entry.as_bytes()).is_ok() {
+            self.size += entry.len() as u64;
+        }
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+
+        let _ = std::fs::remove_file(self.rotated_path(MAX_ROTATED_LOGS));
+        for index in (1..MAX_ROTATED_LOGS).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                let _ = std::fs::rename(&from, self.rotated_path(index + 1));
+            }
+        }
+        let _ = std::fs::rename(self.active_path(), self.rotated_path(1));
+
+        if let Err(e) = self.open_active() {
+            log::warn!("[Sidecar] {}", e);
+        }
+    }
+}
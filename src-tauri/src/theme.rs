@@ -0,0 +1,155 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::window::Color;
+use tauri::{AppHandle, Emitter, Manager, Theme};
+
+/// Background of the main window before and while the webview paints,
+/// matching --color-bg of the light and dark themes
+const LIGHT_BACKGROUND: Color = Color(0xfa, 0xfa, 0xfa, 0xff);
+const DARK_BACKGROUND: Color = Color(0x12, 0x12, 0x12, 0xff);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+}
+
+/// The OS appearance, payload of `system://theme-changed`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemTheme {
+    pub theme: ThemeMode,
+    pub high_contrast: bool,
+}
+
+impl Default for SystemTheme {
+    fn default() -> Self {
+        Self {
+            theme: ThemeMode::Light,
+            high_contrast: false,
+        }
+    }
+}
+
+/// The appearance last applied to the window chrome and tray
+#[derive(Default)]
+pub struct ThemeState {
+    current: Mutex<Option<SystemTheme>>,
+}
+
+fn detect(app: &AppHandle, theme: Option<Theme>) -> SystemTheme {
+    let theme = theme.or_else(|| {
+        app.get_webview_window("main")
+            .and_then(|window| window.theme().ok())
+    });
+    SystemTheme {
+        theme: match theme {
+            Some(Theme::Dark) => ThemeMode::Dark,
+            _ => ThemeMode::Light,
+        },
+        high_contrast: platform::high_contrast(),
+    }
+}
+
+/// Match the window chrome and tray icon to the OS appearance, and tell the
+/// webview if it changed
+fn apply(app: &AppHandle, theme: Option<Theme>) {
+    let system_theme = detect(app, theme);
+    {
+        let state = app.state::<ThemeState>();
+        let mut current = state.current.lock().unwrap();
+        if *current == Some(system_theme) {
+            return;
+        }
+        *current = Some(system_theme);
+    }
+    log::info!("[App] System theme is now {:?}", system_theme);
+
+    if let Some(window) = app.get_webview_window("main") {
+        let background = match system_theme.theme {
+            ThemeMode::Light => LIGHT_BACKGROUND,
+            ThemeMode::Dark => DARK_BACKGROUND,
+        };
+        let _ = window.set_background_color(Some(background));
+    }
+    crate::tray::set_theme(app, system_theme);
+    let _ = app.emit("system://theme-changed", system_theme);
+}
+
+/// Pick up the OS appearance at startup
+pub fn setup(app: &AppHandle) {
+    apply(app, None);
+}
+
+/// Follow a light/dark change reported by the OS
+pub fn on_theme_changed(app: &AppHandle, theme: Theme) {
+    apply(app, Some(theme));
+}
+
+/// High contrast changes aren't reported as theme changes, so check again
+/// whenever the user comes back to the window
+pub fn on_main_window_focus(app: &AppHandle, focused: bool) {
+    if focused {
+        apply(app, None);
+    }
+}
+
+/// Get the OS appearance (exposed to frontend)
+#[tauri::command]
+pub fn get_system_theme(app: AppHandle) -> SystemTheme {
+    let current = *app.state::<ThemeState>().current.lock().unwrap();
+    current.unwrap_or_else(|| detect(&app, None))
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    /// The Increase Contrast accessibility setting
+    pub fn high_contrast() -> bool {
+        unsafe {
+            let workspace: *mut AnyObject = msg_send![class!(NSWorkspace), sharedWorkspace];
+            msg_send![workspace, accessibilityDisplayShouldIncreaseContrast]
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    /// Whether a contrast theme is on
+    pub fn high_contrast() -> bool {
+        let mut info = HIGHCONTRASTW {
+            cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+            ..Default::default()
+        };
+        let result = unsafe {
+            SystemParametersInfoW(
+                SPI_GETHIGHCONTRAST,
+                info.cbSize,
+                Some(&mut info as *mut HIGHCONTRASTW as *mut _),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+        };
+        result.is_ok() && info.dwFlags.contains(HCF_HIGHCONTRASTON)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::process::Command;
+
+    /// GNOME's high contrast setting, which other desktops don't have
+    pub fn high_contrast() -> bool {
+        Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.a11y.interface", "high-contrast"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItemBuilder, MenuBuilder, MenuItem, MenuItemBuilder};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconEvent};
+use tauri::{AppHandle, Manager, State, Wry};
+
+use crate::theme::SystemTheme;
+use crate::SidecarStatus;
+
+const TRAY_ID: &str = "main-tray";
+/// The tray icon from tauri.conf.json, which status dots are drawn onto
+const TRAY_ICON: &[u8] = include_bytes!("../icons/32x32.png");
+
+/// Tray menu items that change with the sidecar status
+pub struct TrayState {
+    status_item: MenuItem<Wry>,
+    restart_item: MenuItem<Wry>,
+    /// OS appearance the icon is drawn for
+    theme: Mutex<SystemTheme>,
+}
+
+/// Set up the tray icon created from tauri.conf.json with its menu
+pub fn setup(app: &tauri::App) -> tauri::Result<()> {
+    let status_item = MenuItemBuilder::with_id("status", status_label(SidecarStatus::Stopped))
+        .enabled(false)
+        .build(app)?;
+    let show_item = MenuItemBuilder::with_id("show_hide", "Show/Hide Pipali").build(app)?;
+    let restart_item = MenuItemBuilder::with_id("restart_server", "Restart Server").build(app)?;
+    let logs_item = MenuItemBuilder::with_id("open_logs", "Open Logs").build(app)?;
+    let keep_awake_item = CheckMenuItemBuilder::with_id("keep_awake", "Keep Device Awake")
+        .checked(false)
+        .build(app)?;
+    let always_on_top_item = crate::always_on_top::menu_item(app, "always_on_top")?;
+    let privacy_mode_item = crate::privacy_mode::menu_item(app, "privacy_mode")?;
+    let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
+    let tray_menu = MenuBuilder::new(app)
+        .item(&status_item)
+        .separator()
+        .item(&show_item)
+        .item(&restart_item)
+        .item(&logs_item)
+        .separator()
+        .item(&keep_awake_item)
+        .item(&always_on_top_item)
+        .item(&privacy_mode_item)
+        .separator()
+        .item(&quit_item)
+        .build()?;
+
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    tray.set_menu(Some(tray_menu))?;
+
+    // Handle tray icon click - toggle window visibility
+    let app_handle = app.handle().clone();
+    tray.on_tray_icon_event(move |_tray, event| {
+        if let TrayIconEvent::Click {
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            ..
+        } = event
+        {
+            crate::toggle_window(&app_handle);
+        }
+    });
+
+    // Handle tray menu item clicks
+    let app_handle = app.handle().clone();
+    tray.on_menu_event(move |_tray, event| match event.id().as_ref() {
+        "show_hide" => crate::toggle_window(&app_handle),
+        // Only enabled while the server needs starting again
+        "status" | "restart_server" => {
+            log::info!("[App] Server restart requested from tray menu");
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::commands::restart(&app_handle).await {
+                    log::error!("[App] Failed to restart server: {}", e);
+                }
+            });
+        }
+        "open_logs" => crate::open_logs_dir(&app_handle),
+        "keep_awake" => {
+            let state: State<crate::wake_lock::WakeLockState> = app_handle.state();
+            let is_checked = state.user_toggle();
+            log::info!("[WakeLock] User toggled keep awake: {}", is_checked);
+        }
+        "always_on_top" => crate::always_on_top::toggle(&app_handle),
+        "privacy_mode" => crate::privacy_mode::toggle(&app_handle),
+        "quit" => {
+            log::info!("[App] Quit requested from tray menu");
+            app_handle.exit(0);
+        }
+        _ => {}
+    });
+
+    app.manage(TrayState {
+        status_item,
+        restart_item,
+        theme: Mutex::new(SystemTheme::default()),
+    });
+    let status = app.state::<crate::SidecarState>().status();
+    update_status(app.handle(), status);
+    Ok(())
+}
+
+fn status_label(status: SidecarStatus) -> &'static str {
+    match status {
+        SidecarStatus::Stopped => "Server stopped",
+        SidecarStatus::Starting => "Server starting…",
+        SidecarStatus::Ready => "Server running",
+        SidecarStatus::Unhealthy => "Server not responding",
+        SidecarStatus::Crashed => "Server crashed",
+        SidecarStatus::Restarting => "Server restarting…",
+    }
+}
+
+/// What the status menu item says, with the details needed to tell a server
+/// that's stuck from one that's still starting
+fn status_text(status: SidecarStatus, pid: Option<u32>, port: u16, external: bool) -> String {
+    let process = match (external, pid) {
+        (true, _) => format!("external, port {}", port),
+        (false, Some(pid)) => format!("pid {}, port {}", pid, port),
+        (false, None) => format!("port {}", port),
+    };
+    match status {
+        SidecarStatus::Stopped => "Server: Stopped — click to start".to_string(),
+        SidecarStatus::Starting => format!("Server: Starting… ({})", process),
+        SidecarStatus::Ready => format!("Server: Ready ({})", process),
+        SidecarStatus::Unhealthy => {
+            format!("Server: Not responding ({}) — click to restart", process)
+        }
+        SidecarStatus::Crashed => "Server: Crashed — click to restart".to_string(),
+        SidecarStatus::Restarting => "Server: Restarting…".to_string(),
+    }
+}
+
+/// Colour of the dot drawn onto the tray icon, if the status needs attention
+fn status_color(status: SidecarStatus) -> Option<[u8; 3]> {
+    match status {
+        SidecarStatus::Stopped | SidecarStatus::Ready => None,
+        SidecarStatus::Starting | SidecarStatus::Restarting => Some([0xf5, 0x9e, 0x0b]),
+        SidecarStatus::Unhealthy | SidecarStatus::Crashed => Some([0xef, 0x44, 0x44]),
+    }
+}
+
+/// The tray icon with a status dot in its bottom right corner
+///
+/// Contrast themes get a silhouette of the logo in the theme's text colour,
+/// as they're meant to replace app colours.
+fn status_icon(status: SidecarStatus, theme: SystemTheme) -> tauri::Result<Image<'static>> {
+    let base = Image::from_bytes(TRAY_ICON)?;
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    // The template icon on macOS is a silhouette already
+    if theme.high_contrast && !cfg!(target_os = "macos") {
+        let shade = match theme.theme {
+            crate::theme::ThemeMode::Light => 0x00,
+            crate::theme::ThemeMode::Dark => 0xff,
+        };
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel[..3].fill(shade);
+        }
+    }
+    let Some([r, g, b]) = status_color(status) else {
+        return Ok(Image::new_owned(rgba, width, height));
+    };
+
+    let radius = width.min(height) as f32 / 4.0;
+    let (cx, cy) = (width as f32 - radius, height as f32 - radius);
+    for y in 0..height {
+        for x in 0..width {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            let pixel = ((y * width + x) * 4) as usize;
+            if distance <= radius {
+                rgba[pixel..pixel + 4].copy_from_slice(&[r, g, b, 0xff]);
+            } else if distance <= radius + 1.5 {
+                // Clear a ring around the dot so it stands out from the logo
+                rgba[pixel + 3] = 0;
+            }
+        }
+    }
+    Ok(Image::new_owned(rgba, width, height))
+}
+
+/// Reflect the sidecar status in the tray icon, tooltip and menu
+pub fn update_status(app: &AppHandle, status: SidecarStatus) {
+    let Some(tray_state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let label = status_label(status);
+    let sidecar = app.state::<crate::SidecarState>();
+    let pid = sidecar
+        .child
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|child| child.pid());
+    let text = status_text(status, pid, sidecar.port(), sidecar.external);
+    let _ = tray_state.status_item.set_text(text);
+    let _ = tray_state.status_item.set_enabled(matches!(
+        status,
+        SidecarStatus::Stopped | SidecarStatus::Unhealthy | SidecarStatus::Crashed
+    ));
+    // Restarting while a start is already underway would only race it
+    let _ = tray_state.restart_item.set_enabled(!matches!(
+        status,
+        SidecarStatus::Starting | SidecarStatus::Restarting
+    ));
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("Pipali: {}", label)));
+        let theme = *tray_state.theme.lock().unwrap();
+        match status_icon(status, theme) {
+            Ok(icon) => {
+                let _ = tray.set_icon(Some(icon));
+                #[cfg(target_os = "macos")]
+                let _ = tray.set_icon_as_template(true);
+            }
+            Err(e) => log::warn!("[App] Failed to draw tray icon: {}", e),
+        }
+    }
+}
+
+/// Redraw the tray icon for a new OS appearance
+pub fn set_theme(app: &AppHandle, theme: SystemTheme) {
+    let Some(tray_state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    *tray_state.theme.lock().unwrap() = theme;
+    update_status(app, app.state::<crate::SidecarState>().status());
+}
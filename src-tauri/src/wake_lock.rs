@@ -1,10 +1,19 @@
 use std::sync::Mutex;
-use tauri::State;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{SidecarState, SidecarStatus};
+
+/// How often the sidecar is asked whether it's running tasks
+const TASK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 pub struct WakeLockState {
     count: Mutex<u32>,
     guard: Mutex<Option<keepawake::KeepAwake>>,
     user_enabled: Mutex<bool>,
+    /// Held for the chats and automations the sidecar is running
+    tasks_active: Mutex<bool>,
 }
 
 impl Default for WakeLockState {
@@ -13,6 +22,7 @@ impl Default for WakeLockState {
             count: Mutex::new(0),
             guard: Mutex::new(None),
             user_enabled: Mutex::new(false),
+            tasks_active: Mutex::new(false),
         }
     }
 }
@@ -21,6 +31,7 @@ impl WakeLockState {
     pub fn release_all(&self) {
         *self.count.lock().unwrap() = 0;
         *self.user_enabled.lock().unwrap() = false;
+        *self.tasks_active.lock().unwrap() = false;
         *self.guard.lock().unwrap() = None;
     }
 
@@ -37,6 +48,24 @@ impl WakeLockState {
         *enabled
     }
 
+    /// Hold a wake lock while the sidecar is running tasks
+    fn set_tasks_active(&self, active: bool) {
+        let mut tasks_active = self.tasks_active.lock().unwrap();
+        if *tasks_active == active {
+            return;
+        }
+        *tasks_active = active;
+        log::info!(
+            "[WakeLock] Sidecar tasks {}",
+            if active { "started" } else { "finished" }
+        );
+        if active {
+            self.increment();
+        } else {
+            self.decrement();
+        }
+    }
+
     fn increment(&self) {
         let mut count = self.count.lock().unwrap();
         if *count == 0 {
@@ -81,3 +110,31 @@ pub fn release_wake_lock(state: State<'_, WakeLockState>) -> Result<(), String>
     state.decrement();
     Ok(())
 }
+
+/// Keep the system awake while the sidecar runs chats or automations, so a
+/// long research task isn't killed by the laptop going to sleep
+///
+/// The server reports its running tasks on /api/health. Only idle sleep is
+/// prevented, so closing the lid still sleeps.
+pub fn spawn_task_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let agent = crate::health_check_agent();
+        loop {
+            tokio::time::sleep(TASK_CHECK_INTERVAL).await;
+
+            let state: State<SidecarState> = app.state();
+            let enabled = app.state::<SettingsState>().get().keep_awake_during_tasks;
+            let active = if enabled && state.status() == SidecarStatus::Ready {
+                let health_url = format!("http://{}:{}/api/health", state.host, state.port());
+                match crate::check_sidecar_health(&agent, &health_url).await {
+                    Ok(info) => info.active_tasks.is_some_and(|tasks| tasks > 0),
+                    // Keep what was decided until the server answers again
+                    Err(_) => continue,
+                }
+            } else {
+                false
+            };
+            app.state::<WakeLockState>().set_tasks_active(active);
+        }
+    });
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+/// Whether the desktop session runs on Wayland
+fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland")
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Set a variable unless the user already chose a value for it
+fn set_default(name: &str, value: &str) {
+    if std::env::var_os(name).is_none() {
+        log::info!("[App] Setting {}={}", name, value);
+        std::env::set_var(name, value);
+    }
+}
+
+/// Work around WebKitGTK's rendering and window management issues on
+/// Wayland, before GTK starts
+///
+/// With `forceX11` (or PIPALI_FORCE_X11=1) the window runs through XWayland,
+/// which avoids focus and scaling glitches on compositors GTK handles poorly.
+/// Otherwise the window stays native, only turning off WebKit's DMA-BUF
+/// renderer on NVIDIA's driver, where it leaves the window blank or
+/// transparent.
+pub fn setup() {
+    if !is_wayland_session() {
+        return;
+    }
+    let force_x11 = std::env::var("PIPALI_FORCE_X11").map_or_else(
+        |_| crate::settings::read_before_startup().force_x11,
+        |value| value == "1" || value.eq_ignore_ascii_case("true"),
+    );
+
+    if force_x11 {
+        if std::env::var_os("DISPLAY").is_none() {
+            log::warn!("[App] X11 fallback requested, but XWayland isn't running");
+        } else {
+            log::info!("[App] Wayland session, falling back to X11");
+            set_default("GDK_BACKEND", "x11");
+            // Compositing through XWayland flickers with the DMA-BUF renderer
+            set_default("WEBKIT_DISABLE_DMABUF_RENDERER", "1");
+            return;
+        }
+    }
+
+    log::info!("[App] Wayland session");
+    if Path::new("/proc/driver/nvidia/version").exists() {
+        set_default("WEBKIT_DISABLE_DMABUF_RENDERER", "1");
+    }
+}
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+/// How much of the title bar must land on a monitor for a saved position to
+/// be used, so the window can always be dragged back
+const MIN_VISIBLE_WIDTH: i32 = 100;
+const TITLE_BAR_HEIGHT: i32 = 40;
+
+/// Window bounds, in physical pixels
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+}
+
+/// Saved geometry, persisted to `window-state.json` in the app config dir
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct WindowStateFile {
+    /// Geometry for each monitor layout the window was used on
+    layouts: BTreeMap<String, WindowGeometry>,
+    /// Geometry last saved on any layout, for layouts not seen before
+    last: Option<WindowGeometry>,
+    /// Geometry of the conversation window closed last, for the next one
+    conversation: Option<WindowGeometry>,
+}
+
+fn state_path(app: &AppHandle) -> Option<PathBuf> {
+    crate::get_app_config_dir(app)
+        .ok()
+        .map(|dir| dir.join("window-state.json"))
+}
+
+fn read_state(app: &AppHandle) -> WindowStateFile {
+    state_path(app)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_state(app: &AppHandle, state: &WindowStateFile) {
+    let Some(path) = state_path(app) else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(state)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&path, contents).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        log::warn!("[App] Failed to save window state: {}", e);
+    }
+}
+
+/// Identifies the set of connected monitors and how they are arranged
+fn layout_key(window: &WebviewWindow) -> Option<String> {
+    let mut monitors: Vec<String> = window
+        .available_monitors()
+        .ok()?
+        .iter()
+        .map(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            format!(
+                "{}@{},{}:{}x{}",
+                monitor.name().map_or("", String::as_str),
+                position.x,
+                position.y,
+                size.width,
+                size.height
+            )
+        })
+        .collect();
+    monitors.sort();
+    Some(monitors.join(";"))
+}
+
+/// Whether the window's bounds are worth saving
+///
+/// Hidden windows were saved when hidden, and minimized ones report
+/// placeholder positions on Windows.
+fn saveable(window: &WebviewWindow) -> bool {
+    window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+}
+
+/// Current bounds of the window, keeping `previous` bounds to restore to
+/// when it is maximized
+fn current_geometry(
+    window: &WebviewWindow,
+    previous: Option<&WindowGeometry>,
+) -> Option<WindowGeometry> {
+    if window.is_maximized().unwrap_or(false) {
+        return previous.map(|previous| WindowGeometry {
+            maximized: true,
+            ..*previous
+        });
+    }
+    match (window.outer_position(), window.inner_size()) {
+        (Ok(position), Ok(size)) => Some(WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized: false,
+        }),
+        _ => None,
+    }
+}
+
+/// Remember the main window's bounds for the current monitor layout
+pub fn save(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if !saveable(&window) {
+        return;
+    }
+    let Some(key) = layout_key(&window) else {
+        return;
+    };
+    let mut state = read_state(app);
+    let previous = state.layouts.get(&key).or(state.last.as_ref());
+    let Some(geometry) = current_geometry(&window, previous) else {
+        return;
+    };
+    state.layouts.insert(key, geometry);
+    state.last = Some(geometry);
+    write_state(app, &state);
+}
+
+/// Remember a conversation window's bounds as it closes, for the next
+/// conversation window opened
+pub fn save_conversation_window(window: &WebviewWindow) {
+    if !saveable(window) {
+        return;
+    }
+    let app = window.app_handle();
+    let mut state = read_state(app);
+    let Some(geometry) = current_geometry(window, state.conversation.as_ref()) else {
+        return;
+    };
+    state.conversation = Some(geometry);
+    write_state(app, &state);
+}
+
+/// Whether enough of the window's title bar would be on the monitor
+fn title_bar_visible(geometry: &WindowGeometry, monitor: &Monitor) -> bool {
+    let (position, size) = (monitor.position(), monitor.size());
+    let left = geometry.x.max(position.x);
+    let right = (geometry.x + geometry.width as i32).min(position.x + size.width as i32);
+    let top = geometry.y.max(position.y);
+    let bottom = (geometry.y + TITLE_BAR_HEIGHT).min(position.y + size.height as i32);
+    right - left >= MIN_VISIBLE_WIDTH && bottom > top
+}
+
+/// Restore the main window's saved bounds before it is first shown
+///
+/// Geometry saved for the current monitor layout is preferred. Otherwise the
+/// last saved geometry is used if it still fits on a connected monitor, and
+/// failing that the window keeps its size, shrunk to fit, and is centered.
+pub fn restore(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let mut state = read_state(app);
+    let saved = layout_key(&window)
+        .and_then(|key| state.layouts.remove(&key))
+        .or(state.last);
+    if let Some(geometry) = saved {
+        place(&window, geometry);
+    }
+}
+
+/// Give a new conversation window the bounds the last one closed with,
+/// moved down and right by `offset` pixels so it doesn't cover one that's
+/// still open
+pub fn restore_conversation_window(window: &WebviewWindow, offset: i32) {
+    let Some(geometry) = read_state(window.app_handle()).conversation else {
+        let _ = window.center();
+        return;
+    };
+    place(
+        window,
+        WindowGeometry {
+            x: geometry.x + offset,
+            y: geometry.y + offset,
+            ..geometry
+        },
+    );
+}
+
+/// Move the window to saved bounds, or center it if they're off screen
+fn place(window: &WebviewWindow, geometry: WindowGeometry) {
+    let monitors = window.available_monitors().unwrap_or_default();
+
+    let on_screen = monitors
+        .iter()
+        .find(|monitor| title_bar_visible(&geometry, monitor));
+    let target = on_screen
+        .cloned()
+        .or_else(|| window.primary_monitor().ok().flatten());
+    let (mut width, mut height) = (geometry.width, geometry.height);
+    if let Some(ref monitor) = target {
+        width = width.min(monitor.size().width);
+        height = height.min(monitor.size().height);
+    }
+    let _ = window.set_size(PhysicalSize::new(width, height));
+
+    if on_screen.is_some() {
+        let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+    } else {
+        log::info!("[App] Saved window position is off screen, centering");
+        let _ = window.center();
+    }
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
@@ -20,15 +20,16 @@ import type {
 import type { PendingConfirmation } from "./types/confirmation";
 
 // Hooks
-import { useFocusManagement, useModels, useSidecar, useWebSocketChat } from "./hooks";
+import { useFocusManagement, useModels, useNetworkStatus, useSidecar, useWebSocketChat } from "./hooks";
 
 // Utils
 import { setApiBaseUrl, apiFetch } from "./utils/api";
-import { initNotifications, notifyConfirmationRequest, notifyTaskComplete, setNotificationClickHandler, setupFocusNavigationListener } from "./utils/notifications";
-import { isTauri, onWindowShown, onSidecarReady, listenForDeepLinks } from "./utils/tauri";
+import { initNotifications, isWindowFocused, notifyConfirmationRequest, notifyTaskComplete, setNotificationClickHandler, setupFocusNavigationListener } from "./utils/notifications";
+import { isTauri, onWindowShown, onSidecarReady, listenForDeepLinks, takePendingDeepLink, onConfirmationAnsweredFromNotification, setBadgeCount, onMenuAction, onFilesDropped, readClipboardImage, captureScreen, ocrImage, openConversationWindow, onRetryFromContextMenu } from "./utils/tauri";
+import type { DeepLinkRoute, MenuAction, FileAttachment } from "./utils/tauri";
 
 // Components
-import { Header, Sidebar, InputArea } from "./components/layout";
+import { Header, Sidebar, InputArea, OfflineBanner } from "./components/layout";
 import { MessageList } from "./components/messages";
 import { ToastContainer } from "./components/confirmation/ToastContainer";
 import { HomePage } from "./components/home";
@@ -103,6 +104,12 @@ const App = () => {
     // Billing alerts state
     const [billingAlerts, setBillingAlerts] = useState<BillingAlert[]>([]);
     const [platformFrontendUrl, setPlatformFrontendUrl] = useState<string>('https://pipali.ai');
+    // Automation a deep link asked to open, until the automations page shows it
+    const [deepLinkAutomationId, setDeepLinkAutomationId] = useState<string | null>(null);
+    // Files dropped or pasted into the window, sent with the next message
+    const [attachments, setAttachments] = useState<FileAttachment[]>([]);
+    const [attachmentError, setAttachmentError] = useState<string | null>(null);
+    const [isDraggingFiles, setIsDraggingFiles] = useState(false);
 
     // Refs
     const prevConversationIdRef = useRef<string | undefined>(undefined);
@@ -132,6 +139,10 @@ const App = () => {
     const messagesRef = useRef<Message[]>([]);
     // Track isConnected for callbacks that may close over stale state
     const isConnectedRef = useRef(false);
+    // Latest deep link handler, so the Tauri listener is only set up once
+    const handleDeepLinkRef = useRef<((route: DeepLinkRoute) => void) | null>(null);
+    const handleMenuActionRef = useRef<((action: MenuAction) => void) | null>(null);
+    const retryMessageRef = useRef<((messageId: string) => void) | null>(null);
 
     useEffect(() => {
         automationConfirmationsRef.current = automationConfirmations;
@@ -144,6 +155,7 @@ const App = () => {
     // Hooks
     const { textareaRef, scheduleTextareaFocus } = useFocusManagement();
     const { models, selectedModel, setSelectedModel, selectModel, showModelDropdown, setShowModelDropdown, refetchModels } = useModels();
+    const isOnline = useNetworkStatus();
     const wsUrl = `${wsBaseUrl}/ws/chat`;
 
     const {
@@ -276,6 +288,25 @@ const App = () => {
         isConnectedRef.current = isConnected;
     }, [isConnected]);
 
+    // Badge the dock icon with responses and confirmations the user hasn't seen
+    const unreadCount = useMemo(() => {
+        let count = automationConfirmations.length;
+        for (const state of conversationStates.values()) {
+            if (state.isCompleted) count++;
+        }
+        for (const confirmations of pendingConfirmations.values()) {
+            count += confirmations.length;
+        }
+        return count;
+    }, [automationConfirmations, conversationStates, pendingConfirmations]);
+
+    useEffect(() => {
+        // While focused the user can already see them, and the shell clears the badge
+        if (!isWindowFocused()) {
+            setBadgeCount(unreadCount);
+        }
+    }, [unreadCount]);
+
     // Initialize data fetching - wait for sidecar to be ready in desktop mode
     useEffect(() => {
         let unlisten: (() => void) | undefined;
@@ -306,8 +337,17 @@ const App = () => {
             unlisten = unlistenFn;
         });
 
+        // Drop toasts for confirmations answered from a desktop notification
+        let unlistenAnswered: (() => void) | undefined;
+        onConfirmationAnsweredFromNotification(() => {
+            fetchAutomationConfirmations();
+        }).then((unlistenFn) => {
+            unlistenAnswered = unlistenFn;
+        });
+
         return () => {
             unlisten?.();
+            unlistenAnswered?.();
             if (pollInterval) clearInterval(pollInterval);
             if (authStatusRetryTimeoutRef.current) {
                 clearTimeout(authStatusRetryTimeoutRef.current);
@@ -339,31 +379,23 @@ const App = () => {
         };
     }, [scheduleTextareaFocus]);
 
-    // Helper function to parse deep link URL and navigate to conversation
-    const handleDeepLink = useCallback((url: string) => {
-        // Parse pipali://chat/{conversationId} format
-        const match = url.match(/^pipali:\/\/chat\/([a-zA-Z0-9-]+)/);
-        if (match) {
-            const convId = match[1];
-            setCurrentPage('chat');
-            setChatConversationId(convId);
-            conversationIdRef.current = convId;
-            scheduleTextareaFocus();
-        }
-    }, [scheduleTextareaFocus]);
-
     // Listen for deep link events from Tauri (external pipali:// URLs)
     useEffect(() => {
         let unlisten: (() => void) | undefined;
+        const navigate = (route: DeepLinkRoute) => handleDeepLinkRef.current?.(route);
 
-        listenForDeepLinks(handleDeepLink).then((unlistenFn) => {
+        // Follow the link the app was launched with, if any
+        takePendingDeepLink().then((route) => {
+            if (route) navigate(route);
+        });
+        listenForDeepLinks(navigate).then((unlistenFn) => {
             unlisten = unlistenFn;
         });
 
         return () => {
             unlisten?.();
         };
-    }, [handleDeepLink]);
+    }, []);
 
     // Focus chat input and navigate to pending confirmations when window is shown via shortcut/tray (Tauri)
     useEffect(() => {
@@ -501,6 +533,59 @@ const App = () => {
         return () => window.removeEventListener('keydown', handleFindShortcut);
     }, []);
 
+    // Menu bar items (macOS), whose shortcuts don't reach the listeners above
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+
+        onMenuAction((action) => handleMenuActionRef.current?.(action)).then((unlistenFn) => {
+            unlisten = unlistenFn;
+        });
+
+        return () => {
+            unlisten?.();
+        };
+    }, []);
+
+    // Retry picked from a message's context menu (desktop app)
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+
+        onRetryFromContextMenu((messageId) => retryMessageRef.current?.(messageId)).then((unlistenFn) => {
+            unlisten = unlistenFn;
+        });
+
+        return () => {
+            unlisten?.();
+        };
+    }, []);
+
+    // Files dropped onto the window (desktop app)
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+
+        onFilesDropped(
+            ({ attachments: dropped, rejected }) => {
+                setAttachments(prev => [
+                    ...prev,
+                    ...dropped.filter(file => !prev.some(existing => existing.path === file.path)),
+                ]);
+                const [first] = rejected;
+                setAttachmentError(first
+                    ? `Couldn't attach ${first.path.split(/[\\/]/).pop()}: ${first.reason}`
+                    : null);
+                if (dropped.length > 0) setCurrentPage('chat');
+                scheduleTextareaFocus();
+            },
+            setIsDraggingFiles,
+        ).then((unlistenFn) => {
+            unlisten = unlistenFn;
+        });
+
+        return () => {
+            unlisten?.();
+        };
+    }, [scheduleTextareaFocus]);
+
     // Global Cmd/Ctrl+R listener for page reload in desktop app
     useEffect(() => {
         if (!isTauri()) return;
@@ -539,7 +624,8 @@ const App = () => {
                 setAutomationConfirmations(prev => {
                     const prevIds = new Set(prev.map(c => c.id));
                     for (const confirmation of newConfirmations) {
-                        if (!prevIds.has(confirmation.id)) {
+                        // The desktop shell raises OS notifications for these while unfocused
+                        if (!prevIds.has(confirmation.id) && (!isTauri() || isWindowFocused())) {
                             // New confirmation - send OS notification with conversation ID for navigation
                             notifyConfirmationRequest(
                                 confirmation.request,
@@ -975,6 +1061,54 @@ const App = () => {
         window.history.pushState({}, '', '/settings');
     };
 
+    // Navigate to where a pipali:// deep link points
+    const handleDeepLink = (route: DeepLinkRoute) => {
+        switch (route.page) {
+            case 'chat':
+                setCurrentPage('chat');
+                setChatConversationId(route.conversationId);
+                conversationIdRef.current = route.conversationId;
+                scheduleTextareaFocus();
+                break;
+            case 'automations':
+                setDeepLinkAutomationId(route.automationId);
+                goToAutomationsPage();
+                break;
+            case 'skills':
+                goToSkillsPage();
+                break;
+            case 'tools':
+                goToMcpToolsPage();
+                break;
+            case 'settings':
+                goToSettingsPage();
+                break;
+            case 'home':
+                goToHomePage();
+                break;
+            case 'ask':
+                startNewConversation();
+                setInput(route.text);
+                scheduleTextareaFocus();
+                break;
+        }
+    };
+    handleDeepLinkRef.current = handleDeepLink;
+
+    handleMenuActionRef.current = (action: MenuAction) => {
+        switch (action) {
+            case 'new-conversation':
+                startNewConversation();
+                break;
+            case 'settings':
+                goToSettingsPage();
+                break;
+            case 'find':
+                setShowFindInPage(prev => !prev);
+                break;
+        }
+    };
+
     const selectConversation = (id: string) => {
         setCurrentPage('chat');
         if (conversationId) {
@@ -1144,12 +1278,52 @@ const App = () => {
         }
     };
 
+    // Pasting a screenshot attaches it, as the webview can't read clipboard images itself
+    const handlePaste = (e: React.ClipboardEvent) => {
+        if (!isTauri() || e.clipboardData.getData('text/plain')) return;
+        e.preventDefault();
+        readClipboardImage().then((image) => {
+            if (!image) return;
+            setAttachments(prev => [...prev, image]);
+            setAttachmentError(null);
+        });
+    };
+
+    // Ask about my screen: attach a screenshot of the primary display, with
+    // its text read on-device so the agent needn't look at the image
+    const handleCaptureScreen = () => {
+        captureScreen()
+            .then(async (screenshot) => {
+                const text = await ocrImage(screenshot.path).catch((err) => {
+                    console.warn('[screen] Failed to read text in screenshot:', err);
+                    return '';
+                });
+                setAttachments(prev => [...prev, { ...screenshot, text: text || undefined }]);
+                setAttachmentError(null);
+            })
+            .catch((err) => setAttachmentError(`Couldn't capture the screen: ${err}`))
+            .finally(scheduleTextareaFocus);
+    };
+
+    // Point the agent at the attached files, which it reads with its file tools
+    const takeAttachments = (text: string): string => {
+        if (attachments.length === 0) return text;
+        setAttachments([]);
+        setAttachmentError(null);
+        const fileList = attachments.map(file => `- ${file.path}`).join('\n');
+        const fileTexts = attachments
+            .filter(file => file.text)
+            .map(file => `\n\nText in ${file.name}:\n\`\`\`\n${file.text}\n\`\`\``)
+            .join('');
+        return `${text}\n\nAttached files:\n${fileList}${fileTexts}`.trim();
+    };
+
     const sendMessage = async (e?: React.FormEvent) => {
         e?.preventDefault();
         if (!isConnected) return;
 
         const rawValue = textareaRef.current?.value ?? input;
-        const messageText = rawValue.trim();
+        const messageText = takeAttachments(rawValue.trim());
         if (!messageText) return;
         setInput("");
 
@@ -1176,6 +1350,21 @@ const App = () => {
         scheduleTextareaFocus();
     };
 
+    // Send the prompt behind a message again
+    const retryMessage = (messageId: string) => {
+        if (!isConnected || isProcessing || !conversationId) return;
+        const index = messages.findIndex(m => m.id === messageId);
+        if (index === -1) return;
+        const prompt = messages.slice(0, index + 1).findLast(m => m.role === 'user');
+        if (!prompt?.content) return;
+
+        clearConfirmations(conversationId);
+        const clientMessageId = generateUUID();
+        const runId = generateUUID();
+        sendWsMessage(prompt.content, conversationId, { clientMessageId, runId, optimistic: true });
+    };
+    retryMessageRef.current = retryMessage;
+
     const handleKeyDown = (e: React.KeyboardEvent) => {
         if (e.key === 'Enter' && !e.shiftKey) {
             e.preventDefault();
@@ -1188,7 +1377,7 @@ const App = () => {
         if (!isConnected) return;
 
         const rawValue = textareaRef.current?.value ?? input;
-        const userMsg = rawValue.trim();
+        const userMsg = takeAttachments(rawValue.trim());
         if (!userMsg) return;
         setInput("");
 
@@ -1254,6 +1443,7 @@ const App = () => {
                 onSelectConversation={selectConversation}
                 onDeleteConversation={deleteConversation}
                 onExportConversation={exportConversationAsATIF}
+                onOpenInNewWindow={isTauri() ? openConversationWindow : undefined}
                 onGoToSkills={goToSkillsPage}
                 onGoToAutomations={goToAutomationsPage}
                 onGoToMcpTools={goToMcpToolsPage}
@@ -1287,6 +1477,8 @@ const App = () => {
                     onGoHome={goToHomePage}
                 />
 
+                {!isOnline && <OfflineBanner />}
+
                 {currentPage === 'home' && (
                     <HomePage
                         activeTasks={getActiveTasks()}
@@ -1302,6 +1494,8 @@ const App = () => {
                         onConfirmationRespond={respondToAutomationConfirmation}
                         onConfirmationDismiss={dismissAutomationConfirmation}
                         onViewConversation={selectConversation}
+                        openAutomationId={deepLinkAutomationId}
+                        onAutomationOpened={() => setDeepLinkAutomationId(null)}
                     />
                 )}
                 {currentPage === 'mcp-tools' && (
@@ -1311,7 +1505,7 @@ const App = () => {
                     <SettingsPage />
                 )}
                 {currentPage === 'chat' && (
-                    <MessageList messages={messages} conversationId={conversationId} platformFrontendUrl={platformFrontendUrl} onDeleteMessage={deleteMessage} />
+                    <MessageList messages={messages} conversationId={conversationId} platformFrontendUrl={platformFrontendUrl} onDeleteMessage={deleteMessage} onRetryMessage={isProcessing ? undefined : retryMessage} />
                 )}
 
                 <InputArea
@@ -1328,6 +1522,12 @@ const App = () => {
                     onConfirmationRespond={sendCurrentConfirmationResponse}
                     textareaRef={textareaRef}
                     onBackgroundSend={sendAsBackgroundTask}
+                    attachments={attachments}
+                    onRemoveAttachment={(path) => setAttachments(prev => prev.filter(file => file.path !== path))}
+                    attachmentError={attachmentError}
+                    isDraggingFiles={isDraggingFiles}
+                    onPaste={handlePaste}
+                    onCaptureScreen={isTauri() ? handleCaptureScreen : undefined}
                 />
             </div>
 
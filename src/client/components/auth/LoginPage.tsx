@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback } from 'react';
 import { Loader2 } from 'lucide-react';
-import { apiFetch, getApiBaseUrl } from '../../utils/api';
+import { apiFetch, getServerUrl } from '../../utils/api';
 import { isDesktopMode, openInBrowser } from '../../utils/tauri';
 
 interface AuthCapabilities {
@@ -17,7 +17,7 @@ export function LoginPage({ onLoginSuccess }: LoginPageProps) {
     const [isWaitingForAuth, setIsWaitingForAuth] = useState(false);
     const [error, setError] = useState<string | null>(null);
     const [authCapabilities, setAuthCapabilities] = useState<AuthCapabilities | null>(null);
-    const logoUrl = `${getApiBaseUrl()}/icons/pipali_128.png`;
+    const logoUrl = '/icons/pipali_128.png';
     const isDesktop = isDesktopMode();
 
     // Fetch auth capabilities from platform on mount
@@ -69,7 +69,7 @@ export function LoginPage({ onLoginSuccess }: LoginPageProps) {
 
         try {
             // Build callback URL with desktop flag if in desktop mode
-            const baseUrl = getApiBaseUrl() || window.location.origin;
+            const baseUrl = getServerUrl() || window.location.origin;
             const callbackUrl = isDesktop
                 ? `${baseUrl}/api/auth/callback?desktop=1`
                 : `${baseUrl}/api/auth/callback`;
@@ -110,7 +110,7 @@ export function LoginPage({ onLoginSuccess }: LoginPageProps) {
             const { url } = await res.json();
 
             // Build callback URL with desktop flag if in desktop mode
-            const baseUrl = getApiBaseUrl() || window.location.origin;
+            const baseUrl = getServerUrl() || window.location.origin;
             const callbackUrl = isDesktop
                 ? `${baseUrl}/api/auth/callback?desktop=1`
                 : `${baseUrl}/api/auth/callback`;
@@ -14,6 +14,9 @@ interface AutomationsPageProps {
     onConfirmationRespond: (confirmationId: string, optionId: string, guidance?: string) => void;
     onConfirmationDismiss: (confirmationId: string) => void;
     onViewConversation: (conversationId: string) => void;
+    /** Automation to open once loaded, e.g. from a pipali://automation/<id> link */
+    openAutomationId?: string | null;
+    onAutomationOpened?: () => void;
 }
 
 export function AutomationsPage({
@@ -21,6 +24,8 @@ export function AutomationsPage({
     onConfirmationRespond,
     onConfirmationDismiss,
     onViewConversation,
+    openAutomationId,
+    onAutomationOpened,
 }: AutomationsPageProps) {
     const [automations, setAutomations] = useState<AutomationInfo[]>([]);
     const [isLoading, setIsLoading] = useState(true);
@@ -32,6 +37,17 @@ export function AutomationsPage({
         fetchAutomations();
     }, []);
 
+    useEffect(() => {
+        if (!openAutomationId || isLoading) return;
+        const automation = automations.find(a => a.id === openAutomationId);
+        if (automation) {
+            setSelectedAutomation(automation);
+        } else {
+            console.warn('Automation from deep link not found', openAutomationId);
+        }
+        onAutomationOpened?.();
+    }, [openAutomationId, automations, isLoading]);
+
     const fetchAutomations = async () => {
         try {
             const res = await apiFetch('/api/automations');
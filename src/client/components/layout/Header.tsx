@@ -3,7 +3,6 @@
 import React, { useRef, useEffect } from 'react';
 import { PanelLeftClose, PanelLeft, ChevronDown, Circle, Check } from 'lucide-react';
 import type { ChatModelInfo } from '../../types';
-import { getApiBaseUrl } from '../../utils/api';
 
 function formatCost(inputCost: number | null, outputCost: number | null): string {
     if (inputCost === null && outputCost === null) return '';
@@ -70,7 +69,7 @@ export function Header({
                             }
                         }}
                     >
-                        <img src={`${getApiBaseUrl()}/icons/pipali_64.png`} alt="Pipali" className="logo-icon" />
+                        <img src="/icons/pipali_64.png" alt="Pipali" className="logo-icon" />
                         <span className="logo-text">Pipali</span>
                     </div>
                 </div>
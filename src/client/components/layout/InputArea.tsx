@@ -1,8 +1,10 @@
 // Message input area with send/stop controls
 
 import React, { useEffect } from 'react';
-import { ArrowUp, Square } from 'lucide-react';
+import { ArrowUp, Square, Paperclip, X, ScreenShare, FolderOpen } from 'lucide-react';
 import type { ConfirmationRequest } from '../../types';
+import { isTauri, revealInFileManager } from '../../utils/tauri';
+import type { FileAttachment } from '../../utils/tauri';
 import { ConfirmationDialog } from '../confirmation/ConfirmationDialog';
 
 interface InputAreaProps {
@@ -19,6 +21,15 @@ interface InputAreaProps {
     onConfirmationRespond: (optionId: string, guidance?: string) => void;
     textareaRef: React.RefObject<HTMLTextAreaElement | null>;
     onBackgroundSend?: () => void;
+    /** Files dropped onto the window, sent with the next message */
+    attachments?: FileAttachment[];
+    onRemoveAttachment?: (path: string) => void;
+    /** Why dropped files couldn't be attached */
+    attachmentError?: string | null;
+    isDraggingFiles?: boolean;
+    onPaste?: (e: React.ClipboardEvent) => void;
+    /** Attach a screenshot of the screen (desktop app) */
+    onCaptureScreen?: () => void;
 }
 
 export function InputArea({
@@ -35,7 +46,15 @@ export function InputArea({
     onConfirmationRespond,
     textareaRef,
     onBackgroundSend,
+    attachments = [],
+    onRemoveAttachment,
+    attachmentError,
+    isDraggingFiles = false,
+    onPaste,
+    onCaptureScreen,
 }: InputAreaProps) {
+    const canSend = input.trim().length > 0 || attachments.length > 0;
+
     // Auto-resize textarea
     useEffect(() => {
         if (textareaRef.current) {
@@ -55,11 +74,43 @@ export function InputArea({
             )}
 
             <div className="input-container">
-                <form onSubmit={onSubmit} className="input-form">
+                {(attachments.length > 0 || attachmentError) && (
+                    <div className="input-attachments">
+                        {attachments.map(attachment => (
+                            <span key={attachment.path} className="input-attachment" title={attachment.path}>
+                                <Paperclip size={12} />
+                                <span className="input-attachment-name">{attachment.name}</span>
+                                {isTauri() && (
+                                    <button
+                                        type="button"
+                                        onClick={() => revealInFileManager(attachment.path)}
+                                        className="input-attachment-action"
+                                        title="Show in folder"
+                                    >
+                                        <FolderOpen size={12} />
+                                    </button>
+                                )}
+                                <button
+                                    type="button"
+                                    onClick={() => onRemoveAttachment?.(attachment.path)}
+                                    className="input-attachment-action"
+                                    title="Remove attachment"
+                                >
+                                    <X size={12} />
+                                </button>
+                            </span>
+                        ))}
+                        {attachmentError && (
+                            <span className="input-attachment-error">{attachmentError}</span>
+                        )}
+                    </div>
+                )}
+                <form onSubmit={onSubmit} className={`input-form${isDraggingFiles ? ' dragging-files' : ''}`}>
                     <textarea
                         ref={textareaRef}
                         value={input}
                         onChange={(e) => onInputChange(e.target.value)}
+                        onPaste={onPaste}
                         onKeyDown={(e) => {
                             // Cmd+Enter (Mac) or Ctrl+Enter (Windows/Linux): background task
                             if (e.key === 'Enter' && (e.metaKey || e.ctrlKey) && !e.shiftKey) {
@@ -75,16 +126,29 @@ export function InputArea({
                                 ? "Stopped. Type a new message..."
                                 : isProcessing
                                     ? "Type to interrupt with a message..."
-                                    : "Ask anything..."
+                                    : isDraggingFiles
+                                        ? "Drop files to attach them..."
+                                        : "Ask anything..."
                         }
                         rows={1}
                         disabled={!isConnected}
                         autoFocus
                     />
                     <div className="input-buttons">
+                        {onCaptureScreen && (
+                            <button
+                                type="button"
+                                onClick={onCaptureScreen}
+                                disabled={!isConnected}
+                                className="action-button capture"
+                                title="Attach a screenshot of your screen"
+                            >
+                                <ScreenShare size={18} />
+                            </button>
+                        )}
                         {/* Single action button: Send / Stop */}
                         {isProcessing ? (
-                            input.trim() ? (
+                            canSend ? (
                                 <button
                                     type="submit"
                                     disabled={!isConnected}
@@ -106,7 +170,7 @@ export function InputArea({
                         ) : (
                             <button
                                 type="submit"
-                                disabled={!input.trim() || !isConnected}
+                                disabled={!canSend || !isConnected}
                                 className="action-button send"
                             >
                                 <ArrowUp size={18} />
@@ -0,0 +1,13 @@
+// Banner shown below the header while the internet is unreachable
+
+import React from 'react';
+import { WifiOff } from 'lucide-react';
+
+export function OfflineBanner() {
+    return (
+        <div className="offline-banner" role="status">
+            <WifiOff size={14} />
+            <span>You're offline. Pipali will continue once your connection is back.</span>
+        </div>
+    );
+}
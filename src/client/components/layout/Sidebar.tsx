@@ -1,7 +1,7 @@
 // Sidebar with conversation list
 
 import React, { useState, useEffect, useRef } from 'react';
-import { Loader2, MessageSquare, AlertCircle, CheckCircle, Plus, MoreVertical, Download, Trash2, ChevronRight, Search, X, Zap, Clock, Hammer, Settings, User, LogOut, Shield, Sun, Moon, Monitor } from 'lucide-react';
+import { Loader2, MessageSquare, AlertCircle, CheckCircle, Plus, MoreVertical, Download, Trash2, ChevronRight, Search, X, Zap, Clock, Hammer, Settings, User, LogOut, Shield, Sun, Moon, Monitor, AppWindow } from 'lucide-react';
 import type { ConversationSummary, ConversationState, ConfirmationRequest, AuthStatus, BillingAlert } from '../../types';
 import { useTheme } from '../../hooks';
 import { BillingAlertBanner } from '../billing';
@@ -51,6 +51,8 @@ interface SidebarProps {
     onSelectConversation: (id: string) => void;
     onDeleteConversation: (id: string, e: React.MouseEvent) => void;
     onExportConversation: (id: string) => void;
+    /** Only available in the desktop app */
+    onOpenInNewWindow?: (id: string) => void;
     onGoToSkills?: () => void;
     onGoToAutomations?: () => void;
     onGoToMcpTools?: () => void;
@@ -75,6 +77,7 @@ export function Sidebar({
     onSelectConversation,
     onDeleteConversation,
     onExportConversation,
+    onOpenInNewWindow,
     onGoToSkills,
     onGoToAutomations,
     onGoToMcpTools,
@@ -306,6 +309,25 @@ export function Sidebar({
 
                     {openConversationMenuId === conv.id && openMenuContext === (inModal ? 'modal' : 'sidebar') && (
                         <div className="conversation-menu" role="menu">
+                            {onOpenInNewWindow && (
+                                <button
+                                    className="conversation-menu-item"
+                                    onClick={(e) => {
+                                        e.stopPropagation();
+                                        setOpenConversationMenuId(null);
+                                        setOpenMenuContext(null);
+                                        onOpenInNewWindow(conv.id);
+                                        if (inModal) {
+                                            closeModal();
+                                        }
+                                    }}
+                                    role="menuitem"
+                                >
+                                    <AppWindow size={14} />
+                                    <span>Open in new window</span>
+                                </button>
+                            )}
+
                             <button
                                 className="conversation-menu-item"
                                 onClick={(e) => {
@@ -3,3 +3,4 @@
 export * from './Header';
 export * from './Sidebar';
 export * from './InputArea';
+export * from './OfflineBanner';
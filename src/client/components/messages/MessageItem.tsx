@@ -1,6 +1,6 @@
 // Individual message component
 
-import { useState } from 'react';
+import React, { useState } from 'react';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
 import remarkMath from 'remark-math';
@@ -13,19 +13,48 @@ import { ExternalLink } from '../ExternalLink';
 import { safeMarkdownUrlTransform, localImageSrc } from '../../utils/markdown';
 import { getApiBaseUrl } from '../../utils/api';
 import { BillingMessage } from '../billing';
+import { isTauri, showMessageContextMenu } from '../../utils/tauri';
 
 interface MessageItemProps {
     message: Message;
     platformFrontendUrl?: string;
     onDelete?: (messageId: string, role: 'user' | 'assistant') => void;
+    onRetry?: (messageId: string) => void;
 }
 
-export function MessageItem({ message, platformFrontendUrl, onDelete }: MessageItemProps) {
+/**
+ * Paths listed under "Attached files:" in a message sent with attachments
+ */
+function attachedPaths(content: string): string[] {
+    const list = content.match(/(?:^|\n\n)Attached files:\n((?:- .+(?:\n|$))+)/)?.[1];
+    if (!list) return [];
+    return list.split('\n').filter(line => line.startsWith('- ')).map(line => line.slice(2));
+}
+
+export function MessageItem({ message, platformFrontendUrl, onDelete, onRetry }: MessageItemProps) {
     const isUser = message.role === 'user';
     const [isHovered, setIsHovered] = useState(false);
 
     const canDelete = onDelete && !message.isStreaming;
 
+    // Offer the desktop app's native menu in place of the webview's
+    const handleContextMenu = (e: React.MouseEvent<HTMLDivElement>) => {
+        if (!isTauri() || !message.content) return;
+        e.preventDefault();
+        const selection = window.getSelection();
+        const selectedText = selection && !selection.isCollapsed && e.currentTarget.contains(selection.anchorNode)
+            ? selection.toString()
+            : '';
+        const shownText = e.currentTarget.querySelector<HTMLElement>('.message-content')?.innerText ?? message.content;
+        showMessageContextMenu({
+            messageId: message.id,
+            text: selectedText || shownText,
+            markdown: message.content,
+            attachments: isUser ? attachedPaths(message.content) : [],
+            canRetry: !!onRetry && !message.isStreaming,
+        });
+    };
+
     // Render billing message if present
     if (message.billingInfo && platformFrontendUrl) {
         return (
@@ -44,6 +73,7 @@ export function MessageItem({ message, platformFrontendUrl, onDelete }: MessageI
             className={`message ${isUser ? 'user-message' : 'assistant-message'}`}
             onMouseEnter={() => setIsHovered(true)}
             onMouseLeave={() => setIsHovered(false)}
+            onContextMenu={handleContextMenu}
         >
             {isHovered && canDelete && (
                 <div className="message-actions">
@@ -10,9 +10,10 @@ interface MessageListProps {
     conversationId?: string;
     platformFrontendUrl?: string;
     onDeleteMessage?: (messageId: string, role: 'user' | 'assistant') => void;
+    onRetryMessage?: (messageId: string) => void;
 }
 
-export function MessageList({ messages, conversationId, platformFrontendUrl, onDeleteMessage }: MessageListProps) {
+export function MessageList({ messages, conversationId, platformFrontendUrl, onDeleteMessage, onRetryMessage }: MessageListProps) {
     const lastUserMessageRef = useRef<HTMLDivElement>(null);
     const mainContentRef = useRef<HTMLElement>(null);
     const previousConversationIdRef = useRef<string | undefined>(undefined);
@@ -108,7 +109,7 @@ export function MessageList({ messages, conversationId, platformFrontendUrl, onD
                     <div className="messages">
                         {messages.map((msg, index) => (
                             <div key={msg.stableId} ref={index === lastUserMessageIndex ? lastUserMessageRef : undefined}>
-                                <MessageItem message={msg} platformFrontendUrl={platformFrontendUrl} onDelete={onDeleteMessage} />
+                                <MessageItem message={msg} platformFrontendUrl={platformFrontendUrl} onDelete={onDeleteMessage} onRetry={onRetryMessage} />
                             </div>
                         ))}
                     </div>
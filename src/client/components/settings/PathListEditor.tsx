@@ -4,7 +4,8 @@
  */
 
 import React, { useState } from 'react';
-import { Plus, X, FolderOpen } from 'lucide-react';
+import { Plus, X, FolderOpen, FolderSearch } from 'lucide-react';
+import { isTauri, pickFolder } from '../../utils/tauri';
 
 interface PathListEditorProps {
     /** The list of paths */
@@ -33,6 +34,14 @@ export function PathListEditor({
         }
     };
 
+    // Pick with the native folder picker in the desktop app
+    const handleBrowse = async () => {
+        const picked = await pickFolder('Choose a folder');
+        if (picked && !paths.includes(picked)) {
+            onChange([...paths, picked]);
+        }
+    };
+
     const handleRemove = (index: number) => {
         const updated = paths.filter((_, i) => i !== index);
         onChange(updated);
@@ -90,6 +99,17 @@ export function PathListEditor({
                 >
                     <Plus size={16} />
                 </button>
+                {isTauri() && (
+                    <button
+                        type="button"
+                        onClick={handleBrowse}
+                        disabled={disabled}
+                        className="path-add-btn"
+                        title="Choose a folder"
+                    >
+                        <FolderSearch size={16} />
+                    </button>
+                )}
             </div>
         </div>
     );
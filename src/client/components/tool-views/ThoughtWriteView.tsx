@@ -1,6 +1,7 @@
 // Scrollable content preview for write operations in thoughts section
 
-import { FilePlus } from 'lucide-react';
+import { FilePlus, FolderOpen } from 'lucide-react';
+import { isTauri, startFileDrag, revealInFileManager } from '../../utils/tauri';
 
 interface ThoughtWriteViewProps {
     content: string;
@@ -12,10 +13,32 @@ export function ThoughtWriteView({ content, filePath }: ThoughtWriteViewProps) {
 
     // Get display filename from path
     const filename = filePath?.split('/').pop() || 'new file';
+    // The desktop app can drag the written file out into other apps
+    const canDragOut = isTauri() && !!filePath;
 
     return (
         <div className="thought-write">
-            <div className="write-file-header"><FilePlus size={12} /> {filename}</div>
+            <div
+                className={`write-file-header${canDragOut ? ' draggable' : ''}`}
+                draggable={canDragOut}
+                onDragStart={canDragOut ? (e) => {
+                    e.preventDefault();
+                    startFileDrag(filePath!);
+                } : undefined}
+                title={canDragOut ? 'Drag to copy the file into another app' : undefined}
+            >
+                <FilePlus size={12} /> {filename}
+                {canDragOut && (
+                    <button
+                        type="button"
+                        className="write-file-reveal"
+                        onClick={() => revealInFileManager(filePath!)}
+                        title="Show in folder"
+                    >
+                        <FolderOpen size={12} />
+                    </button>
+                )}
+            </div>
             <div className="write-file-content">
                 {lines.map((line, idx) => (
                     <div key={idx} className="write-file-line">
@@ -2,6 +2,7 @@
 
 export * from './useFocusManagement';
 export * from './useModels';
+export * from './useNetworkStatus';
 export * from './useSidecar';
 export * from './useTheme';
 export * from './useWebSocketChat';
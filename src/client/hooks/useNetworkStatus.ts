@@ -0,0 +1,38 @@
+import { useEffect, useState } from 'react';
+import { getNetworkStatus, isTauri, onNetworkStatusChanged } from '../utils/tauri';
+
+/**
+ * Whether the internet is reachable.
+ *
+ * The desktop shell probes reachability itself, since the webview's
+ * navigator.onLine only knows whether a network interface is up.
+ */
+export function useNetworkStatus(): boolean {
+  const [online, setOnline] = useState(() => isTauri() || navigator.onLine);
+
+  useEffect(() => {
+    if (isTauri()) {
+      let unlisten: (() => void) | undefined;
+      getNetworkStatus().then((status) => {
+        if (status) setOnline(status.online);
+      });
+      onNetworkStatusChanged(setOnline).then((unlistenFn) => {
+        unlisten = unlistenFn;
+      });
+      return () => {
+        unlisten?.();
+      };
+    }
+
+    const handleOnline = () => setOnline(true);
+    const handleOffline = () => setOnline(false);
+    window.addEventListener('online', handleOnline);
+    window.addEventListener('offline', handleOffline);
+    return () => {
+      window.removeEventListener('online', handleOnline);
+      window.removeEventListener('offline', handleOffline);
+    };
+  }, []);
+
+  return online;
+}
@@ -1,4 +1,6 @@
 import { useState, useEffect, useCallback } from 'react';
+import { getSystemTheme, onSystemThemeChanged } from '../utils/tauri';
+import type { SystemTheme } from '../utils/tauri';
 
 export type Theme = 'light' | 'dark' | 'system';
 
@@ -8,7 +10,8 @@ const THEME_STORAGE_KEY = 'pipali-theme';
  * Hook to manage theme (light/dark mode)
  *
  * Supports:
- * - System preference via prefers-color-scheme
+ * - System preference via prefers-color-scheme, or as reported by the
+ *   desktop app shell, which also tracks high contrast
  * - Manual toggle via user selection
  * - Persistence via localStorage
  */
@@ -23,14 +26,17 @@ export function useTheme() {
   });
 
   const [resolvedTheme, setResolvedTheme] = useState<'light' | 'dark'>('light');
+  // OS appearance from the desktop app shell, null in the browser
+  const [systemTheme, setSystemTheme] = useState<SystemTheme | null>(null);
 
   // Get the actual theme based on system preference
   const getResolvedTheme = useCallback((currentTheme: Theme): 'light' | 'dark' => {
     if (currentTheme === 'system') {
+      if (systemTheme) return systemTheme.theme;
       return window.matchMedia('(prefers-color-scheme: dark)').matches ? 'dark' : 'light';
     }
     return currentTheme;
-  }, []);
+  }, [systemTheme]);
 
   // Apply theme to document
   const applyTheme = useCallback((currentTheme: Theme) => {
@@ -40,13 +46,17 @@ export function useTheme() {
     // Remove both classes first
     root.classList.remove('light', 'dark');
 
-    // Only add class if not using system preference
+    // Only add class if not using system preference, unless the shell
+    // reports it, as some webviews don't update prefers-color-scheme
     if (currentTheme !== 'system') {
       root.classList.add(currentTheme);
+    } else if (systemTheme) {
+      root.classList.add(systemTheme.theme);
     }
+    root.classList.toggle('high-contrast', systemTheme?.highContrast ?? false);
 
     setResolvedTheme(resolved);
-  }, [getResolvedTheme]);
+  }, [getResolvedTheme, systemTheme]);
 
   // Set theme and persist
   const setTheme = useCallback((newTheme: Theme) => {
@@ -62,6 +72,22 @@ export function useTheme() {
     setTheme(newTheme);
   }, [theme, getResolvedTheme, setTheme]);
 
+  // Follow the OS appearance reported by the desktop app shell
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+
+    getSystemTheme().then((current) => {
+      if (current) setSystemTheme(current);
+    });
+    onSystemThemeChanged(setSystemTheme).then((unlistenFn) => {
+      unlisten = unlistenFn;
+    });
+
+    return () => {
+      unlisten?.();
+    };
+  }, []);
+
   // Apply theme on mount and watch for system preference changes
   useEffect(() => {
     applyTheme(theme);
@@ -18,8 +18,9 @@
 
 import { useReducer, useRef, useCallback, useEffect } from 'react';
 import type { Message, Thought, ConversationState, ConfirmationRequest, BillingError } from '../types';
-import { acquireWakeLock, releaseWakeLock } from '../utils/tauri';
+import { acquireWakeLock, releaseWakeLock, onSystemWake, confirmNatively } from '../utils/tauri';
 import { formatToolCallsForSidebar } from '../utils/formatting';
+import { resolveWebSocketUrl } from '../utils/api';
 
 // ============================================================================
 // Types
@@ -931,6 +932,30 @@ export function useWebSocketChat(options: UseWebSocketChatOptions) {
                 break;
 
             case 'confirmation_request':
+                // High-risk confirmations are answered in the desktop shell's
+                // native dialog, and the page only relays the signed answer
+                if (message.data.native) {
+                    const requestId = message.data.requestId;
+                    void confirmNatively(requestId)
+                        .catch((err): { selectedOptionId: string; signature?: string } => {
+                            console.error('[ws] Native confirmation failed:', err);
+                            return { selectedOptionId: 'no' };
+                        })
+                        .then(({ selectedOptionId, signature }) => {
+                            wsRef.current?.send(JSON.stringify({
+                                type: 'confirmation_response',
+                                conversationId: convId,
+                                runId,
+                                data: {
+                                    requestId,
+                                    selectedOptionId,
+                                    signature,
+                                    timestamp: new Date().toISOString(),
+                                },
+                            }));
+                        });
+                    break;
+                }
                 dispatch({
                     type: 'CONFIRMATION_REQUEST',
                     conversationId: convId,
@@ -967,8 +992,16 @@ export function useWebSocketChat(options: UseWebSocketChatOptions) {
     }, []);
 
     // Connect to WebSocket
-    const connect = useCallback(() => {
-        const ws = new WebSocket(wsUrl);
+    const connect = useCallback(async () => {
+        let url: string;
+        try {
+            url = await resolveWebSocketUrl(wsUrl);
+        } catch (e) {
+            console.warn('Failed to get WebSocket URL:', e);
+            setTimeout(connect, 3000);
+            return;
+        }
+        const ws = new WebSocket(url);
 
         ws.onopen = () => {
             dispatch({ type: 'CONNECTION_OPENED' });
@@ -999,6 +1032,16 @@ export function useWebSocketChat(options: UseWebSocketChatOptions) {
         };
     }, [connect]);
 
+    // The socket can look open after sleep while the connection is dead;
+    // closing it makes onclose reconnect
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        onSystemWake(() => wsRef.current?.close()).then((fn) => {
+            unlisten = fn;
+        });
+        return () => unlisten?.();
+    }, []);
+
     // Actions
     const sendMessage = useCallback((content: string, conversationId?: string, options?: SendMessageOptions) => {
         if (!wsRef.current || wsRef.current.readyState !== WebSocket.OPEN) return;
@@ -41,6 +41,8 @@ export type ConfirmationRequest = {
     options: ConfirmationOption[];
     defaultOptionId?: string;
     timeoutMs?: number;
+    /** Answered in the desktop shell's native dialog rather than in the page */
+    native?: boolean;
 };
 
 // Source of the confirmation - determines visual treatment and response channel
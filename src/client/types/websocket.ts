@@ -41,6 +41,8 @@ export interface ConfirmationResponse {
         textValue?: string;
     };
     persistPreference?: boolean;
+    /** The desktop shell's signature, for native confirmations */
+    signature?: string;
     timestamp: string;
 }
 
@@ -2,10 +2,14 @@
  * API client utilities for making requests to the Pipali server.
  *
  * In web mode, requests go to relative URLs (same origin).
- * In Tauri mode, requests go to the sidecar's localhost URL.
+ * In Tauri mode, requests go through the shell's pipali-api:// protocol,
+ * which adds the auth token on the way to the sidecar.
  */
 
 let apiBaseUrl = "";
+let serverUrl = "";
+let webSocketUrlResolver: ((url: string) => Promise<string>) | null = null;
+let beforeRequest: (() => Promise<void>) | null = null;
 
 /**
  * Set the base URL for all API requests.
@@ -22,6 +26,46 @@ export function getApiBaseUrl(): string {
     return apiBaseUrl;
 }
 
+/**
+ * Set the server's own address, when it differs from the API base URL.
+ * In Tauri mode this is the sidecar's localhost URL.
+ */
+export function setServerUrl(url: string) {
+    serverUrl = url;
+}
+
+/**
+ * Get the server's own address, for URLs opened outside the webview such as
+ * sign-in callbacks. Falls back to the API base URL.
+ */
+export function getServerUrl(): string {
+    return serverUrl || apiBaseUrl;
+}
+
+/**
+ * Set how WebSocket URLs are completed before connecting, e.g. with a
+ * single-use ticket from the Tauri shell.
+ */
+export function setWebSocketUrlResolver(resolver: ((url: string) => Promise<string>) | null) {
+    webSocketUrlResolver = resolver;
+}
+
+/**
+ * Run a hook before every API request, e.g. to start a sidecar that is only
+ * started on first use. A rejected hook fails the request.
+ */
+export function setBeforeApiRequest(hook: (() => Promise<void>) | null) {
+    beforeRequest = hook;
+}
+
+/**
+ * Get the URL to open a WebSocket connection on. Resolve it afresh for every
+ * connection, as the Tauri shell's tickets only work once.
+ */
+export async function resolveWebSocketUrl(url: string): Promise<string> {
+    return webSocketUrlResolver ? webSocketUrlResolver(url) : url;
+}
+
 /**
  * Make a fetch request to the API, prepending the base URL if configured.
  *
@@ -29,10 +73,10 @@ export function getApiBaseUrl(): string {
  * @param init - Fetch options
  * @returns Fetch response
  */
-export function apiFetch(
+export async function apiFetch(
     path: string,
     init?: RequestInit
 ): Promise<Response> {
-    const url = `${apiBaseUrl}${path}`;
-    return fetch(url, init);
+    await beforeRequest?.();
+    return fetch(`${apiBaseUrl}${path}`, init);
 }
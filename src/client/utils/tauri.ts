@@ -109,6 +109,145 @@ export async function onSidecarReady(callback: () => void): Promise<() => void>
     }
 }
 
+/**
+ * Make sure the sidecar server is running, starting it if it was deferred
+ * or stopped. Resolves once it is ready; a no-op in web mode.
+ */
+export async function ensureSidecarRunning(): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('ensure_sidecar_running');
+}
+
+/**
+ * Lifecycle status of the sidecar server, as tracked by the Tauri shell.
+ */
+export type SidecarStatus = 'stopped' | 'starting' | 'ready' | 'unhealthy' | 'crashed' | 'restarting';
+
+/**
+ * Get the sidecar server's current lifecycle status.
+ * Returns null in web mode, where there is no sidecar to track.
+ */
+export async function getSidecarStatus(): Promise<SidecarStatus | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<SidecarStatus>('get_sidecar_status');
+    } catch (err) {
+        console.warn('[tauri] Failed to get sidecar status:', err);
+        return null;
+    }
+}
+
+/**
+ * A line of sidecar server output captured by the Tauri shell.
+ */
+export interface SidecarLogLine {
+    /** Milliseconds since the Unix epoch */
+    timestamp: number;
+    stream: 'stdout' | 'stderr' | 'shell';
+    level: 'info' | 'warn' | 'error';
+    line: string;
+}
+
+/**
+ * Get the most recent sidecar server output lines, oldest first.
+ * Returns an empty list in web mode.
+ */
+export async function getSidecarLogs(lastN?: number): Promise<SidecarLogLine[]> {
+    if (!isTauri()) return [];
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<SidecarLogLine[]>('get_sidecar_logs', { lastN });
+    } catch (err) {
+        console.warn('[tauri] Failed to get sidecar logs:', err);
+        return [];
+    }
+}
+
+/**
+ * Resource use of the sidecar server, sampled by the Tauri shell.
+ */
+export interface SidecarResourceUsage {
+    pid: number;
+    /** Resident memory of the server process, in bytes */
+    memoryBytes: number;
+    virtualMemoryBytes: number;
+    /** CPU use since the previous sample, where 100 is one full core */
+    cpuPercent: number;
+    /** Open file descriptors, or null where the platform can't count them */
+    openFiles: number | null;
+    /** Totals across the server and the processes it launched */
+    treeMemoryBytes: number;
+    treeCpuPercent: number;
+    treeProcessCount: number;
+}
+
+/**
+ * Sample the sidecar server's memory, CPU and open file use.
+ * Returns null in web mode or when the sidecar isn't running.
+ */
+export async function getSidecarResourceUsage(): Promise<SidecarResourceUsage | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<SidecarResourceUsage>('get_sidecar_resource_usage');
+    } catch (err) {
+        console.warn('[tauri] Failed to get sidecar resource usage:', err);
+        return null;
+    }
+}
+
+/**
+ * What the sidecar server reports about itself, for the About dialog and diagnostics.
+ * Fields are null when the server doesn't report them.
+ */
+export interface ServerInfo {
+    version: string | null;
+    /** Tag of the newest database migration the server has applied */
+    schemaVersion: string | null;
+    /** Names of the configured AI model providers */
+    providers: string[] | null;
+    shellPid: number | null;
+}
+
+/**
+ * Get the server's version, database schema version and configured providers.
+ * Returns null in web mode or when the server hasn't reported them yet.
+ */
+export async function getServerInfo(): Promise<ServerInfo | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<ServerInfo>('get_server_info');
+    } catch (err) {
+        console.warn('[tauri] Failed to get server info:', err);
+        return null;
+    }
+}
+
+/**
+ * Follow sidecar server output live, e.g. for a log viewer during startup.
+ *
+ * @param callback - Function to call with each new output line
+ * @returns Cleanup function to unsubscribe from the event
+ */
+export async function onSidecarLog(callback: (line: SidecarLogLine) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<SidecarLogLine>('sidecar://log', (event) => {
+            callback(event.payload);
+        });
+    } catch (err) {
+        console.warn('[tauri] Failed to setup sidecar log listener:', err);
+        return () => {};
+    }
+}
+
 /**
  * Listen for window-shown events from Tauri.
  * Used to focus the chat input when the app window is shown via shortcut or tray.
@@ -135,114 +274,1559 @@ export async function onWindowShown(callback: () => void): Promise<() => void> {
 }
 
 /**
- * Open a file with the system's default application.
- * In Tauri v2, uses the opener plugin's openPath function.
- * Only works in the desktop app - no-op in web mode.
- *
- * @param filePath - The file path to open (can be file:// URL or absolute path)
- * @returns true if the file was opened successfully, false otherwise
+ * Move the sidecar server to another port, remembered for future launches.
+ * Resolves once the server is up on the new port.
  */
-export async function openFile(filePath: string): Promise<boolean> {
-    // Convert file:// URL to a local filesystem path if needed
-    let path = filePath;
-    if (filePath.startsWith('file://')) {
-        try {
-            const url = new URL(filePath);
-            // Typical macOS file URLs are file:///Users/...
-            // URL.pathname is already decoded for most characters, but keep it explicit.
-            path = decodeURIComponent(url.pathname);
-            // Windows drive paths come through as /C:/Users/...; strip the leading slash.
-            if (/^\/[a-zA-Z]:\//.test(path)) {
-                path = path.slice(1);
-            }
-        } catch {
-            // Fallback: strip scheme prefix
-            try {
-                path = decodeURIComponent(filePath.replace(/^file:\/\//, ''));
-            } catch {
-                path = filePath.replace(/^file:\/\//, '');
-            }
-        }
+export async function setSidecarPort(port: number): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_sidecar_port', { port });
+}
+
+/**
+ * Choose whether closing the window hides Pipali to the tray, keeping
+ * automations running, or quits the app.
+ */
+export async function setCloseToTray(enabled: boolean): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_close_to_tray', { enabled });
+}
+
+/**
+ * Open the active profile's data folder, with its database and attachments,
+ * in the system file manager.
+ */
+export async function openDataDir(): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('open_data_dir');
+}
+
+/** Space Pipali takes on disk, in bytes */
+export interface DiskUsage {
+    databaseBytes: number;
+    attachmentsBytes: number;
+    logsBytes: number;
+    cachesBytes: number;
+    /** Server updates and unpacked runtimes */
+    downloadsBytes: number;
+    crashDumpsBytes: number;
+}
+
+/**
+ * Break down the space Pipali takes on disk, for the storage settings.
+ */
+export async function getDiskUsage(): Promise<DiskUsage | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<DiskUsage>('get_disk_usage');
+    } catch (err) {
+        console.warn('[tauri] Failed to get disk usage:', err);
+        return null;
     }
+}
 
-    console.log('[openFile] Opening file:', path, { isTauri: isTauri(), isDesktop: isDesktopMode() });
+/**
+ * What a global shortcut does: bring up the main window or the quick-ask bar,
+ * or ask about the selected text. On macOS the latter asks about the text
+ * last copied with ⌘C, as reading another app's selection needs accessibility
+ * access.
+ */
+export type HotkeyAction = 'summon' | 'quickAsk' | 'askSelection';
 
-    if (!isTauri()) {
-        console.warn('[openFile] Not in Tauri environment, cannot open file');
-        return false;
+/**
+ * Get the global shortcut bound to an action, e.g. 'Alt+Space'.
+ * Returns null when it is turned off or in web mode.
+ */
+export async function getGlobalShortcut(action: HotkeyAction = 'summon'): Promise<string | null> {
+    if (!isTauri()) return null;
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<string | null>('get_global_shortcut', { action });
+}
+
+/**
+ * Rebind the global shortcut for an action, e.g. to
+ * 'CommandOrControl+Shift+Space', or pass null to turn it off.
+ * Rejects if the shortcut is invalid or taken by another app.
+ */
+export async function setGlobalShortcut(
+    shortcut: string | null,
+    action: HotkeyAction = 'summon',
+): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_global_shortcut', { shortcut, action });
+}
+
+/**
+ * Show a count of unread responses and pending confirmations on the dock icon
+ * (a dot on the Windows taskbar), or clear it with 0. The shell clears it when
+ * the window is focused.
+ */
+export async function setBadgeCount(count: number): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('set_badge_count', { count });
+    } catch (err) {
+        console.warn('[tauri] Failed to set badge count:', err);
     }
+}
 
+/**
+ * Drag a file out of the window into another app. Call from a dragstart
+ * handler, after preventing the webview's own drag.
+ */
+export async function startFileDrag(path: string): Promise<void> {
+    if (!isTauri()) return;
     try {
-        const { openPath } = await import('@tauri-apps/plugin-opener');
-        console.log('[openFile] Using Tauri opener plugin openPath...');
-        await openPath(path);
-        console.log('[openFile] File opened successfully');
-        return true;
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('start_drag', { path });
     } catch (err) {
-        console.error('[openFile] openPath failed:', err);
+        console.warn('[tauri] Failed to start file drag:', err);
     }
+}
 
-    // Fallback: reveal the file in its folder (better than doing nothing)
+/**
+ * A file or folder picked for Pipali to read
+ */
+export interface AccessGrant {
+    path: string;
+    isDir: boolean;
+    grantedAt: string;
+}
+
+/**
+ * Pick files with the native file picker. Pipali keeps access to them across
+ * restarts. Resolves to no paths if the user cancels.
+ */
+export async function pickFiles(options: { title?: string; multiple?: boolean } = {}): Promise<string[]> {
+    if (!isTauri()) return [];
     try {
-        const { revealItemInDir } = await import('@tauri-apps/plugin-opener');
-        console.log('[openFile] Falling back to revealItemInDir...');
-        await revealItemInDir(path);
-        console.log('[openFile] revealItemInDir succeeded');
-        return true;
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<string[]>('pick_files', { title: options.title, multiple: options.multiple ?? false });
     } catch (err) {
-        console.error('[openFile] revealItemInDir fallback failed:', err);
-        return false;
+        console.warn('[tauri] Failed to pick files:', err);
+        return [];
     }
 }
 
 /**
- * Acquire a wake lock to prevent OS idle sleep.
- * Uses reference counting in Rust — safe to call multiple times for parallel tasks.
+ * Pick a folder with the native folder picker. Pipali keeps access to it
+ * across restarts. Resolves to null if the user cancels.
  */
-export async function acquireWakeLock(): Promise<void> {
+export async function pickFolder(title?: string): Promise<string | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<string | null>('pick_folder', { title });
+    } catch (err) {
+        console.warn('[tauri] Failed to pick folder:', err);
+        return null;
+    }
+}
+
+/**
+ * List the files and folders picked for Pipali to read.
+ */
+export async function listAccessGrants(): Promise<AccessGrant[]> {
+    if (!isTauri()) return [];
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<AccessGrant[]>('list_access_grants');
+    } catch (err) {
+        console.warn('[tauri] Failed to list access grants:', err);
+        return [];
+    }
+}
+
+/**
+ * Forget a picked file or folder.
+ */
+export async function revokeAccessGrant(path: string): Promise<void> {
     if (!isTauri()) return;
     try {
         const { invoke } = await import('@tauri-apps/api/core');
-        await invoke('acquire_wake_lock');
+        await invoke('revoke_access_grant', { path });
     } catch (err) {
-        console.warn('[wakeLock] Failed to acquire:', err);
+        console.warn('[tauri] Failed to revoke access grant:', err);
     }
 }
 
 /**
- * Release a wake lock. OS sleep is re-enabled when all tasks have released.
+ * A chat message right-clicked, for the native context menu
  */
-export async function releaseWakeLock(): Promise<void> {
+export interface MessageContextMenuTarget {
+    messageId: string;
+    /** The selected text, or the whole message as shown */
+    text: string;
+    /** The message as written */
+    markdown: string;
+    /** Paths of files attached to the message */
+    attachments: string[];
+    canRetry: boolean;
+}
+
+/**
+ * Show the native context menu for a chat message. Call from a contextmenu
+ * handler, after preventing the webview's own menu.
+ */
+export async function showMessageContextMenu(target: MessageContextMenuTarget): Promise<void> {
     if (!isTauri()) return;
     try {
         const { invoke } = await import('@tauri-apps/api/core');
-        await invoke('release_wake_lock');
+        await invoke('show_context_menu', { target });
     } catch (err) {
-        console.warn('[wakeLock] Failed to release:', err);
+        console.warn('[tauri] Failed to show context menu:', err);
     }
 }
 
 /**
- * Listen for deep link events from Tauri.
- * Deep links are custom URL schemes (e.g., pipali://chat/conversationId) that
- * can be used to navigate the app to specific locations.
- *
- * @param callback - Function to call when a deep link is received, with the URL string
- * @returns Cleanup function to unsubscribe from the event
+ * Listen for Retry picked from a message's context menu, with the message id.
  */
-export async function listenForDeepLinks(callback: (url: string) => void): Promise<() => void> {
+export async function onRetryFromContextMenu(callback: (messageId: string) => void): Promise<() => void> {
     if (!isTauri()) {
         return () => {};
     }
 
     try {
-        const { listen } = await import('@tauri-apps/api/event');
-        const unlisten = await listen<string>('deep-link', (event) => {
+        const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
+        const unlisten = await getCurrentWebviewWindow().listen<string>('context-menu://retry', (event) => {
             callback(event.payload);
         });
         return unlisten;
     } catch (err) {
-        console.warn('[tauri] Failed to setup deep link listener:', err);
+        console.warn('[tauri] Failed to setup context menu listener:', err);
         return () => {};
     }
 }
+
+/**
+ * Keep the main window above other windows, or stop doing so.
+ */
+export async function setAlwaysOnTop(enabled: boolean): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('set_always_on_top', { enabled });
+    } catch (err) {
+        console.warn('[tauri] Failed to set always on top:', err);
+    }
+}
+
+/**
+ * Open a conversation in a window of its own, or focus the window already
+ * showing it.
+ */
+export async function openConversationWindow(conversationId: string): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('open_conversation_window', { conversationId });
+    } catch (err) {
+        console.warn('[tauri] Failed to open conversation window:', err);
+    }
+}
+
+/**
+ * Save the image on the system clipboard for attaching to a message.
+ * Resolves to null if there's no image on the clipboard.
+ */
+export async function readClipboardImage(): Promise<FileAttachment | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<FileAttachment | null>('read_clipboard_image');
+    } catch (err) {
+        console.warn('[tauri] Failed to read clipboard image:', err);
+        return null;
+    }
+}
+
+/**
+ * Copy a PNG image file to the system clipboard
+ */
+export async function writeClipboardImage(path: string): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('write_clipboard_image', { path });
+    } catch (err) {
+        console.warn('[tauri] Failed to write clipboard image:', err);
+    }
+}
+
+/**
+ * Get the rich text on the system clipboard as HTML, or null if there is none
+ */
+export async function readClipboardHtml(): Promise<string | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<string | null>('read_clipboard_html');
+    } catch (err) {
+        console.warn('[tauri] Failed to read clipboard HTML:', err);
+        return null;
+    }
+}
+
+/**
+ * Copy rich text to the system clipboard, with plain text for apps that
+ * don't take HTML
+ */
+export async function writeClipboardHtml(html: string, altText?: string): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('write_clipboard_html', { html, altText });
+    } catch (err) {
+        console.warn('[tauri] Failed to write clipboard HTML:', err);
+    }
+}
+
+/**
+ * A display or window that can be captured
+ */
+export interface CaptureTarget {
+    id: number;
+    name: string;
+    primary: boolean | null;
+}
+
+/**
+ * List the displays and windows that can be captured
+ */
+export async function listCaptureTargets(): Promise<{ displays: CaptureTarget[]; windows: CaptureTarget[] }> {
+    if (!isTauri()) return { displays: [], windows: [] };
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke('list_capture_targets');
+}
+
+/**
+ * Capture a display, the primary one by default, or a window and attach the
+ * screenshot. Rejects with the reason if it couldn't be captured, such as
+ * missing screen recording permission on macOS.
+ */
+export async function captureScreen(target?: { displayId?: number; windowId?: number }): Promise<FileAttachment> {
+    if (!isTauri()) throw new Error('Screen capture is only available in the desktop app');
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<FileAttachment>('capture_screen', {
+        displayId: target?.displayId ?? null,
+        windowId: target?.windowId ?? null,
+    });
+}
+
+/**
+ * Read the text in an image on-device. Rejects with the reason if the OS
+ * can't, such as Tesseract not being installed on Linux.
+ */
+export async function ocrImage(path: string): Promise<string> {
+    if (!isTauri()) throw new Error('Text recognition is only available in the desktop app');
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<string>('ocr_image', { path });
+}
+
+/**
+ * Show the quick-ask prompt bar for a one-off question
+ */
+export async function showQuickAsk(): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('show_quick_ask');
+}
+
+/**
+ * Choose whether Pipali starts in the tray without showing its window,
+ * as it does when launched at login.
+ */
+export async function setStartHidden(enabled: boolean): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_start_hidden', { enabled });
+}
+
+/**
+ * Choose whether the desktop shell raises OS notifications for automations
+ * and server errors while the window isn't focused.
+ */
+export async function setNativeNotifications(enabled: boolean): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_native_notifications', { enabled });
+}
+
+/**
+ * Whether Pipali starts when the user logs in.
+ * Returns false in web mode.
+ */
+export async function getAutostart(): Promise<boolean> {
+    if (!isTauri()) return false;
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<boolean>('get_autostart');
+}
+
+/**
+ * Start Pipali at login, hidden in the tray, so automations keep running.
+ */
+export async function setAutostart(enabled: boolean): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_autostart', { enabled });
+}
+
+export type SidecarPriority = 'normal' | 'low' | 'background';
+
+/**
+ * Set the sidecar server's scheduling priority, remembered for future launches.
+ * With lowerWhenUnfocused it is also lowered while the window isn't focused.
+ */
+export async function setSidecarPriority(
+    priority: SidecarPriority,
+    lowerWhenUnfocused?: boolean,
+): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_sidecar_priority', { priority, lowerWhenUnfocused });
+}
+
+/**
+ * Get the sidecar server's current scheduling priority.
+ * Returns 'normal' in web mode.
+ */
+export async function getSidecarPriority(): Promise<SidecarPriority> {
+    if (!isTauri()) return 'normal';
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<SidecarPriority>('get_sidecar_priority');
+}
+
+/**
+ * A crash dump or OS crash report of the sidecar server
+ */
+export interface CrashDump {
+    name: string;
+    path: string;
+    sizeBytes: number;
+    /** Unix epoch milliseconds */
+    createdAt: number;
+}
+
+/**
+ * List the kept sidecar crash dumps, newest first, for diagnostics.
+ * Returns an empty list in web mode.
+ */
+export async function listCrashDumps(): Promise<CrashDump[]> {
+    if (!isTauri()) return [];
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<CrashDump[]>('list_crash_dumps');
+    } catch (err) {
+        console.warn('[listCrashDumps] Failed to list crash dumps:', err);
+        return [];
+    }
+}
+
+/**
+ * Delete all kept sidecar crash dumps
+ */
+export async function clearCrashDumps(): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('clear_crash_dumps');
+}
+
+/**
+ * A profile with its own database and sidecar server
+ */
+export interface ProfileInfo {
+    name: string;
+    dataDir: string;
+    /** Port its server runs on, or will start on */
+    port: number | null;
+    running: boolean;
+    active: boolean;
+}
+
+/**
+ * List the default profile followed by the user's own.
+ * Returns an empty list in web mode.
+ */
+export async function listProfiles(): Promise<ProfileInfo[]> {
+    if (!isTauri()) return [];
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<ProfileInfo[]>('list_profiles');
+    } catch (err) {
+        console.warn('[tauri] Failed to list profiles:', err);
+        return [];
+    }
+}
+
+/**
+ * Create a profile with its own data directory and port.
+ */
+export async function createProfile(name: string): Promise<ProfileInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<ProfileInfo>('create_profile', { name });
+}
+
+/**
+ * Start a profile's server next to the active one.
+ * Resolves with its port once it is ready.
+ */
+export async function startProfile(name: string): Promise<number> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<number>('start_profile', { name });
+}
+
+/**
+ * Stop a profile's background server.
+ */
+export async function stopProfile(name: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('stop_profile', { name });
+}
+
+/**
+ * Make a profile the active one. The window reloads against its server.
+ */
+export async function switchProfile(name: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('switch_profile', { name });
+}
+
+/**
+ * Listen for the system waking from sleep.
+ * The Tauri shell health-checks the sidecar first, so connections opened
+ * from the callback should reach a live server.
+ *
+ * @param callback - Function to call after the system wakes
+ * @returns Cleanup function to unsubscribe from the event
+ */
+export async function onSystemWake(callback: () => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisten = await listen('power://wake', () => {
+            console.log('[onSystemWake] System wake event received');
+            callback();
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[onSystemWake] Failed to setup listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Show a file selected in Finder, Explorer or the Linux file manager.
+ * Only files the shell would open can be revealed.
+ *
+ * @returns true if the file was revealed, false otherwise
+ */
+export async function revealInFileManager(path: string): Promise<boolean> {
+    if (!isTauri()) return false;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('reveal_in_file_manager', { path });
+        return true;
+    } catch (err) {
+        console.warn('[tauri] Failed to reveal file:', err);
+        return false;
+    }
+}
+
+/**
+ * Open a file with the system's default application.
+ * The shell only opens files in the user's home folder, Pipali's temp dir or
+ * picked folders, and never programs or scripts.
+ * Only works in the desktop app - no-op in web mode.
+ *
+ * @param filePath - The file path to open (can be file:// URL or absolute path)
+ * @returns true if the file was opened successfully, false otherwise
+ */
+export async function openFile(filePath: string): Promise<boolean> {
+    // Convert file:// URL to a local filesystem path if needed
+    let path = filePath;
+    if (filePath.startsWith('file://')) {
+        try {
+            const url = new URL(filePath);
+            // Typical macOS file URLs are file:///Users/...
+            // URL.pathname is already decoded for most characters, but keep it explicit.
+            path = decodeURIComponent(url.pathname);
+            // Windows drive paths come through as /C:/Users/...; strip the leading slash.
+            if (/^\/[a-zA-Z]:\//.test(path)) {
+                path = path.slice(1);
+            }
+        } catch {
+            // Fallback: strip scheme prefix
+            try {
+                path = decodeURIComponent(filePath.replace(/^file:\/\//, ''));
+            } catch {
+                path = filePath.replace(/^file:\/\//, '');
+            }
+        }
+    }
+
+    console.log('[openFile] Opening file:', path, { isTauri: isTauri(), isDesktop: isDesktopMode() });
+
+    if (!isTauri()) {
+        console.warn('[openFile] Not in Tauri environment, cannot open file');
+        return false;
+    }
+
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('open_path', { path });
+        console.log('[openFile] File opened successfully');
+        return true;
+    } catch (err) {
+        console.error('[openFile] openPath failed:', err);
+    }
+
+    // Fallback: reveal the file in its folder (better than doing nothing)
+    console.log('[openFile] Falling back to revealing the file...');
+    return revealInFileManager(path);
+}
+
+/**
+ * Acquire a wake lock to prevent OS idle sleep.
+ * Uses reference counting in Rust — safe to call multiple times for parallel tasks.
+ */
+export async function acquireWakeLock(): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('acquire_wake_lock');
+    } catch (err) {
+        console.warn('[wakeLock] Failed to acquire:', err);
+    }
+}
+
+/**
+ * Release a wake lock. OS sleep is re-enabled when all tasks have released.
+ */
+export async function releaseWakeLock(): Promise<void> {
+    if (!isTauri()) return;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        await invoke('release_wake_lock');
+    } catch (err) {
+        console.warn('[wakeLock] Failed to release:', err);
+    }
+}
+
+/**
+ * Where a deep link navigates to, as parsed by the Rust shell
+ */
+export type DeepLinkRoute =
+    | { page: 'home' }
+    | { page: 'chat'; conversationId: string }
+    | { page: 'automations'; automationId: string | null }
+    | { page: 'skills' }
+    | { page: 'tools' }
+    | { page: 'settings' }
+    | { page: 'ask'; text: string };
+
+/**
+ * Take the route of the last deep link the app hasn't navigated to yet,
+ * e.g. the link the app was launched with.
+ */
+export async function takePendingDeepLink(): Promise<DeepLinkRoute | null> {
+    if (!isTauri()) {
+        return null;
+    }
+
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<DeepLinkRoute | null>('take_pending_deep_link');
+    } catch (err) {
+        console.warn('[tauri] Failed to take pending deep link:', err);
+        return null;
+    }
+}
+
+/**
+ * Listen for deep link events from Tauri.
+ * Deep links are custom URL schemes (e.g., pipali://conversation/conversationId,
+ * pipali://automation/automationId) that navigate the app to specific locations.
+ *
+ * @param callback - Function to call with the route when a deep link is received
+ * @returns Cleanup function to unsubscribe from the event
+ */
+export async function listenForDeepLinks(callback: (route: DeepLinkRoute) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        // Only the main window follows links, not conversation windows
+        const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
+        const unlisten = await getCurrentWebviewWindow().listen('deep-link', async () => {
+            // The route is kept in the shell until taken, so it's only followed once
+            const route = await takePendingDeepLink();
+            if (route) {
+                callback(route);
+            }
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup deep link listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Command line of a second launch, forwarded to the running instance
+ */
+export interface SecondInstanceArgs {
+    args: string[];
+    cwd: string;
+}
+
+/**
+ * Listen for Pipali being launched again while already running.
+ * The second launch exits and its arguments are passed here instead.
+ */
+export async function onSecondInstance(callback: (launch: SecondInstanceArgs) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisten = await listen<SecondInstanceArgs>('second-instance', (event) => {
+            callback(event.payload);
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup second instance listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Listen for automation confirmations answered from a desktop notification,
+ * with the ID of the confirmation.
+ */
+export async function onConfirmationAnsweredFromNotification(callback: (confirmationId: string) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisten = await listen<string>('automation://confirmation-responded', (event) => {
+            callback(event.payload);
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup notification response listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Menu bar items the frontend handles (macOS)
+ */
+export type MenuAction = 'new-conversation' | 'settings' | 'find';
+
+/**
+ * Listen for menu bar items picked by the user.
+ */
+export async function onMenuAction(callback: (action: MenuAction) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
+        const unlisten = await getCurrentWebviewWindow().listen<MenuAction>('menu://action', (event) => {
+            callback(event.payload);
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup menu listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * A file dropped onto the window and attached to the next message
+ */
+export interface FileAttachment {
+    path: string;
+    name: string;
+    sizeBytes: number;
+    /** Text read from an image on-device, sent in place of the image */
+    text?: string;
+}
+
+/**
+ * Files dropped onto the window, as checked by the server
+ */
+export interface DroppedFiles {
+    attachments: FileAttachment[];
+    rejected: { path: string; reason: string }[];
+}
+
+/**
+ * Listen for files dropped onto the window, and for files being dragged
+ * over it so the drop target can be highlighted.
+ */
+export async function onFilesDropped(
+    onDrop: (files: DroppedFiles) => void,
+    onHover: (hovering: boolean) => void,
+): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { getCurrentWebviewWindow } = await import('@tauri-apps/api/webviewWindow');
+        const currentWindow = getCurrentWebviewWindow();
+        const unlistenDrop = await currentWindow.listen<DroppedFiles>('file-drop://attached', (event) => {
+            onDrop(event.payload);
+        });
+        const unlistenHover = await currentWindow.listen<boolean>('file-drop://hover', (event) => {
+            onHover(event.payload);
+        });
+        return () => {
+            unlistenDrop();
+            unlistenHover();
+        };
+    } catch (err) {
+        console.warn('[tauri] Failed to setup file drop listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * The OS appearance as seen by the shell
+ */
+export interface SystemTheme {
+    theme: 'light' | 'dark';
+    highContrast: boolean;
+}
+
+/**
+ * Get the OS appearance, or null outside the desktop app
+ */
+export async function getSystemTheme(): Promise<SystemTheme | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<SystemTheme>('get_system_theme');
+    } catch (err) {
+        console.warn('[tauri] Failed to get system theme:', err);
+        return null;
+    }
+}
+
+/**
+ * Listen for the OS switching between light, dark and high contrast.
+ * Unlike prefers-color-scheme, this also updates on Linux webviews.
+ */
+export async function onSystemThemeChanged(callback: (theme: SystemTheme) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisten = await listen<SystemTheme>('system://theme-changed', (event) => {
+            callback(event.payload);
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup system theme listener:', err);
+        return () => {};
+    }
+}
+
+export interface NetworkStatus {
+    online: boolean;
+    /** RFC 3339 time the status last changed, if it has */
+    changedAt: string | null;
+}
+
+/**
+ * Get whether the shell can reach the internet.
+ * Returns null outside Tauri, where the browser's navigator.onLine applies.
+ */
+export async function getNetworkStatus(): Promise<NetworkStatus | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<NetworkStatus>('get_network_status');
+    } catch (err) {
+        console.warn('[tauri] Failed to get network status:', err);
+        return null;
+    }
+}
+
+/**
+ * Listen for the internet going away (`network://offline`) and coming back
+ * (`network://online`), as detected by the shell.
+ */
+export async function onNetworkStatusChanged(callback: (online: boolean) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlistenOnline = await listen('network://online', () => callback(true));
+        const unlistenOffline = await listen('network://offline', () => callback(false));
+        return () => {
+            unlistenOnline();
+            unlistenOffline();
+        };
+    } catch (err) {
+        console.warn('[tauri] Failed to setup network status listener:', err);
+        return () => {};
+    }
+}
+
+export interface PowerSource {
+    onBattery: boolean;
+    /** Charge left, if the system has a battery */
+    batteryPercent: number | null;
+}
+
+/**
+ * Get whether the system is running on battery, and how charged it is.
+ */
+export async function getPowerSource(): Promise<PowerSource | null> {
+    if (!isTauri()) return null;
+    try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        return await invoke<PowerSource>('get_power_source');
+    } catch (err) {
+        console.warn('[tauri] Failed to get power source:', err);
+        return null;
+    }
+}
+
+/**
+ * Listen for the system switching between battery and mains power, or its
+ * battery level changing.
+ */
+export async function onPowerSourceChanged(callback: (power: PowerSource) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisten = await listen<PowerSource>('power://source-changed', (event) => {
+            callback(event.payload);
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup power source listener:', err);
+        return () => {};
+    }
+}
+
+export interface DataDirMigrationProgress {
+    copiedBytes: number;
+    totalBytes: number;
+}
+
+/**
+ * Move the default profile's database to another folder, e.g. on a bigger
+ * drive. The server restarts on the new folder once the copy is done, and
+ * the old files are left in place.
+ */
+export async function migrateDataDir(newPath: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('migrate_data_dir', { newPath });
+}
+
+/**
+ * Listen for progress while the database is copied to its new folder.
+ */
+export async function onDataDirMigrationProgress(
+    callback: (progress: DataDirMigrationProgress) => void,
+): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisten = await listen<DataDirMigrationProgress>('data-dir://migration-progress', (event) => {
+            callback(event.payload);
+        });
+        return unlisten;
+    } catch (err) {
+        console.warn('[tauri] Failed to setup data dir migration listener:', err);
+        return () => {};
+    }
+}
+
+export interface AuditLogExport {
+    path: string;
+    entries: number;
+    /** Whether every entry's hash checks out and none are missing, i.e. the log hasn't been edited */
+    intact: boolean;
+}
+
+/**
+ * Save a copy of the log of privileged shell commands where the user picks.
+ * Resolves to null if they cancel.
+ */
+export async function exportAuditLog(): Promise<AuditLogExport | null> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<AuditLogExport | null>('export_audit_log');
+}
+
+export interface NativeConfirmation {
+    selectedOptionId: string;
+    signature: string;
+}
+
+/**
+ * Ask the user to approve a high-risk tool call in a native dialog. The shell
+ * signs the answer, which the server requires before going ahead.
+ */
+export async function confirmNatively(requestId: string): Promise<NativeConfirmation> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<NativeConfirmation>('confirm_natively', { requestId });
+}
+
+/**
+ * Save a secret, such as OPENAI_API_KEY, in the OS keychain. The shell hands
+ * it to the server, which reads it like the environment variable of that name.
+ */
+export async function setSecret(name: string, value: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_secret', { name, value });
+}
+
+/**
+ * Read a secret from the OS keychain, or null if it isn't set.
+ */
+export async function getSecret(name: string): Promise<string | null> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<string | null>('get_secret', { name });
+}
+
+/**
+ * Remove a secret from the OS keychain.
+ */
+export async function deleteSecret(name: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('delete_secret', { name });
+}
+
+/**
+ * Whether privacy mode is on, refusing screenshots and clipboard reads.
+ */
+export async function getPrivacyMode(): Promise<boolean> {
+    if (!isTauri()) return false;
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<boolean>('get_privacy_mode');
+}
+
+/**
+ * Turn privacy mode on or off. While on, the shell refuses screenshots,
+ * clipboard reads and selection capture, whatever the agent asks for.
+ */
+export async function setPrivacyMode(enabled: boolean): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_privacy_mode', { enabled });
+}
+
+/**
+ * Listen for privacy mode being turned on or off, e.g. from the tray menu.
+ */
+export async function onPrivacyModeChanged(callback: (enabled: boolean) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<boolean>('privacy-mode://changed', (event) => callback(event.payload));
+    } catch (err) {
+        console.warn('[tauri] Failed to setup privacy mode listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Hide Pipali's windows until the user authenticates with Touch ID, Windows
+ * Hello or their account password. Fails where the OS offers none of these.
+ */
+export async function lockApp(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('lock_app');
+}
+
+/**
+ * Ask the user to authenticate and unlock Pipali. Resolves to false if they
+ * cancel the prompt.
+ */
+export async function unlockApp(): Promise<boolean> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return await invoke<boolean>('unlock_app');
+}
+
+/**
+ * Keep the database encrypted on disk from now on, with its key in the OS
+ * keychain. Restarts the server, which encrypts the existing database first.
+ */
+export async function encryptDatabase(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('encrypt_database');
+}
+
+export interface UpdateInfo {
+    version: string;
+    currentVersion: string;
+    notes: string | null;
+    /** Release date, in RFC 3339 */
+    date: string | null;
+    /** Size of the download, if the update server says */
+    sizeBytes: number | null;
+}
+
+export interface UpdateDownloadProgress {
+    downloadedBytes: number;
+    /** Size of the download, if the update server said */
+    totalBytes: number | null;
+}
+
+export interface UpdateListeners {
+    /** An update was found that isn't downloaded yet */
+    onAvailable?: (update: UpdateInfo) => void;
+    onProgress?: (progress: UpdateDownloadProgress) => void;
+    /** The update is downloaded and verified, ready for `installUpdate` */
+    onReady?: (update: UpdateInfo) => void;
+}
+
+/**
+ * Listen for the shell finding, downloading and verifying an app update.
+ */
+export async function onAppUpdate(listeners: UpdateListeners): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisteners = await Promise.all([
+            listen<UpdateInfo>('update://available', (event) => listeners.onAvailable?.(event.payload)),
+            listen<UpdateDownloadProgress>('update://progress', (event) => listeners.onProgress?.(event.payload)),
+            listen<UpdateInfo>('update://ready', (event) => listeners.onReady?.(event.payload)),
+        ]);
+        return () => unlisteners.forEach((unlisten) => unlisten());
+    } catch (err) {
+        console.warn('[tauri] Failed to setup app update listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Check for an app update without downloading it. Resolves to null if the
+ * app is up to date.
+ */
+export async function checkForUpdates(): Promise<UpdateInfo | null> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<UpdateInfo | null>('check_for_updates');
+}
+
+/**
+ * Download and verify the update `checkForUpdates` found. Progress arrives
+ * through `onAppUpdate`'s `onProgress`.
+ */
+export async function downloadUpdate(): Promise<UpdateInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<UpdateInfo>('download_update');
+}
+
+export type UpdateChannel = 'stable' | 'beta' | 'nightly';
+
+/**
+ * Get the release channel the app updates from.
+ */
+export async function getUpdateChannel(): Promise<UpdateChannel> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<UpdateChannel>('get_update_channel');
+}
+
+/**
+ * Switch the release channel and check it for an update straight away.
+ * Moving back to stable keeps the running version until stable catches up,
+ * unless `downgrade` is set.
+ */
+export async function setUpdateChannel(channel: UpdateChannel, downgrade = false): Promise<UpdateInfo | null> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<UpdateInfo | null>('set_update_channel', { channel, downgrade });
+}
+
+/**
+ * Choose whether downloaded updates install when the app quits instead of
+ * asking to restart, and whether the app starts again afterwards.
+ */
+export async function setInstallUpdatesOnExit(enabled: boolean, relaunch?: boolean): Promise<void> {
+    if (!isTauri()) return;
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_install_updates_on_exit', { enabled, relaunch });
+}
+
+/**
+ * Stop the server, install the downloaded update and relaunch into it.
+ */
+export async function installUpdate(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('install_update');
+}
+
+/**
+ * Listen for a server-only update being downloaded, which is used from the
+ * server's next start or straight away with `applyServerUpdate`.
+ */
+export async function onServerUpdateStaged(callback: (version: string) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<string>('server-update://staged', (event) => callback(event.payload));
+    } catch (err) {
+        console.warn('[tauri] Failed to setup server update listener:', err);
+        return () => {};
+    }
+}
+
+export interface ServerRollback {
+    failedVersion: string;
+    /** Downloaded server now in use, or null for the bundled server */
+    restoredVersion: string | null;
+}
+
+/**
+ * Listen for a downloaded server being dropped after it failed to start or
+ * kept crashing, and the server it replaced being restored.
+ */
+export async function onServerUpdateRolledBack(callback: (rollback: ServerRollback) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<ServerRollback>('server-update://rolled-back', (event) => callback(event.payload));
+    } catch (err) {
+        console.warn('[tauri] Failed to setup server rollback listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Restart the server on the downloaded server update now.
+ */
+export async function applyServerUpdate(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('apply_server_update');
+}
+
+export type DiagnosticStatus = 'pass' | 'warn' | 'fail' | 'skipped';
+
+export interface DiagnosticCheck {
+    /** e.g. "sidecarBinary", "sharedLibraries", "port", "dataDir", "health" or "webview" */
+    id: string;
+    status: DiagnosticStatus;
+    detail: string;
+}
+
+export interface DiagnosticsReport {
+    appVersion: string;
+    os: string;
+    arch: string;
+    checks: DiagnosticCheck[];
+}
+
+/**
+ * Check the server binary, its libraries, port, data folder and health
+ * endpoint, and the webview, for troubleshooting and bug reports.
+ */
+export async function runDiagnostics(): Promise<DiagnosticsReport> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<DiagnosticsReport>('run_diagnostics');
+}
+
+export type OnboardingStep = 'welcome' | 'signIn' | 'permissions' | 'autostart';
+
+export interface OnboardingStatus {
+    /** Whether this is the app's first launch */
+    firstRun: boolean;
+    completedSteps: OnboardingStep[];
+    /** Step to show next, or null once onboarding is done */
+    nextStep: OnboardingStep | null;
+}
+
+/**
+ * Get onboarding progress, which the shell keeps so it survives cleared
+ * storage and is the same in every window.
+ */
+export async function getOnboardingStatus(): Promise<OnboardingStatus | null> {
+    if (!isTauri()) return null;
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<OnboardingStatus>('get_onboarding_status');
+}
+
+/**
+ * Mark an onboarding step finished or skipped. Resolves with the step to
+ * show next.
+ */
+export async function completeOnboardingStep(step: OnboardingStep): Promise<OnboardingStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<OnboardingStatus>('complete_onboarding_step', { step });
+}
+
+/**
+ * Go through onboarding again from the start.
+ */
+export async function resetOnboarding(): Promise<OnboardingStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<OnboardingStatus>('reset_onboarding');
+}
+
+export interface BackupInfo {
+    /** Folder the backup was written to */
+    path: string;
+    /** When the backup was taken, in RFC 3339 */
+    createdAt: string;
+    databaseBytes: number;
+    /** Whether the snapshot is encrypted with the database key */
+    encrypted: boolean;
+    attachmentCount: number;
+}
+
+export interface BackupProgress {
+    writtenBytes: number;
+    totalBytes: number | null;
+}
+
+/**
+ * Back up the database and a manifest of attachments to a new timestamped
+ * folder under `destPath`, while the server keeps running.
+ */
+export async function backupDatabase(destPath: string): Promise<BackupInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<BackupInfo>('backup_database', { destPath });
+}
+
+/**
+ * Listen for progress writing a database backup.
+ */
+export async function onBackupProgress(callback: (progress: BackupProgress) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<BackupProgress>('backup://progress', (event) => callback(event.payload));
+    } catch (err) {
+        console.warn('[tauri] Failed to setup backup progress listener:', err);
+        return () => {};
+    }
+}
+
+export type BackupFrequency = 'off' | 'daily' | 'weekly';
+
+export interface BackupSchedule {
+    frequency: BackupFrequency;
+    /** Folder automatic backups go in */
+    dir: string;
+    /** Backups kept before older ones are deleted */
+    keep: number;
+    /** When the newest backup in the folder was taken, in RFC 3339 */
+    lastBackupAt: string | null;
+}
+
+/**
+ * Get how often, and where, the database is backed up automatically.
+ */
+export async function getBackupSchedule(): Promise<BackupSchedule> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<BackupSchedule>('get_backup_schedule');
+}
+
+/**
+ * Change the automatic backup schedule. `dir` and `keep` stay as they were
+ * if left out.
+ */
+export async function setBackupSchedule(frequency: BackupFrequency, dir?: string, keep?: number): Promise<BackupSchedule> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<BackupSchedule>('set_backup_schedule', { frequency, dir, keep });
+}
+
+export interface ScheduledBackupListeners {
+    onCompleted?: (backup: BackupInfo) => void;
+    onFailed?: (error: string) => void;
+}
+
+/**
+ * Listen for automatic backups finishing or failing.
+ */
+export async function onScheduledBackup(listeners: ScheduledBackupListeners): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        const unlisteners = await Promise.all([
+            listen<BackupInfo>('backup://completed', (event) => listeners.onCompleted?.(event.payload)),
+            listen<string>('backup://failed', (event) => listeners.onFailed?.(event.payload)),
+        ]);
+        return () => unlisteners.forEach((unlisten) => unlisten());
+    } catch (err) {
+        console.warn('[tauri] Failed to setup scheduled backup listener:', err);
+        return () => {};
+    }
+}
+
+export type ExportFormat = 'markdown' | 'json';
+
+export interface ExportInfo {
+    /** Folder the export was written to */
+    path: string;
+    /** File extension of the conversations, `md` or `json` */
+    format: string;
+    conversationCount: number;
+    attachmentCount: number;
+}
+
+export interface ExportProgress {
+    exported: number;
+    total: number;
+    /** Title of the conversation just written */
+    title: string;
+}
+
+/**
+ * Export conversations, one file each plus the attachments they refer to, to
+ * a new timestamped folder. Without `destPath` the user picks the folder, and
+ * this resolves to null if they cancel. Exports every conversation unless
+ * `conversationIds` is given.
+ */
+export async function exportConversations(
+    format: ExportFormat,
+    options: { conversationIds?: string[]; destPath?: string } = {}
+): Promise<ExportInfo | null> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<ExportInfo | null>('export_conversations', {
+        format,
+        conversationIds: options.conversationIds ?? null,
+        destPath: options.destPath ?? null,
+    });
+}
+
+/**
+ * Stop the running export. It fails once the conversation being written is
+ * done, and its folder is removed.
+ */
+export async function cancelExport(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke('cancel_export');
+}
+
+/**
+ * Listen for each conversation an export writes.
+ */
+export async function onExportProgress(callback: (progress: ExportProgress) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<ExportProgress>('export://progress', (event) => callback(event.payload));
+    } catch (err) {
+        console.warn('[tauri] Failed to setup export progress listener:', err);
+        return () => {};
+    }
+}
+
+/**
+ * Where to import from Khoj: the JSON its "Export chats" setting saves (or a
+ * folder of them), picked by the user if no path is given, or a Khoj server.
+ */
+export type KhojSource =
+    | { kind: 'export'; path?: string }
+    | { kind: 'server'; url: string; apiKey?: string };
+
+export interface KhojImportSummary {
+    conversationsImported: number;
+    /** Conversations the server refused, with why */
+    conversationsFailed: string[];
+    filesImported: number;
+    /** Indexed files still where Khoj found them, read in place */
+    filesOnDisk: number;
+    /** Folder the other indexed files were saved to */
+    filesDir: string | null;
+}
+
+export interface KhojImportProgress {
+    stage: 'conversations' | 'files';
+    done: number;
+    total: number;
+}
+
+/**
+ * Import conversations, and a server's indexed files, from Khoj. Resolves to
+ * null if the user cancels picking the export.
+ */
+export async function importFromKhoj(source: KhojSource): Promise<KhojImportSummary | null> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<KhojImportSummary | null>('import_from_khoj', { source });
+}
+
+/**
+ * Listen for each conversation and file an import from Khoj brings in.
+ */
+export async function onKhojImportProgress(callback: (progress: KhojImportProgress) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+
+    try {
+        const { listen } = await import('@tauri-apps/api/event');
+        return await listen<KhojImportProgress>('khoj-import://progress', (event) => callback(event.payload));
+    } catch (err) {
+        console.warn('[tauri] Failed to setup Khoj import progress listener:', err);
+        return () => {};
+    }
+}
+
+export interface CompactionReport {
+    databaseBytesBefore: number;
+    databaseBytesAfter: number;
+    /** Attachments no conversation referred to, which were deleted */
+    attachmentsRemoved: number;
+    attachmentBytesRemoved: number;
+    /** Space freed in all, in bytes */
+    reclaimedBytes: number;
+}
+
+/**
+ * Give the space deleted conversations took back to the disk and delete
+ * unused attachments, then restart the server. Fails while chats or
+ * automations are running.
+ */
+export async function compactDatabase(): Promise<CompactionReport> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<CompactionReport>('compact_database');
+}
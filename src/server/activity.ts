@@ -0,0 +1,39 @@
+/**
+ * Tracks when the server last did anything for the user, so the desktop
+ * shell can stop it while it sits idle in the tray.
+ */
+
+import { getAllActiveConversationIds } from './sessions';
+import {
+    getRunningExecutionCount,
+    getQueueLength,
+    getActiveCronJobCount,
+    getActiveFileWatcherCount,
+} from './automation';
+
+let lastActivityAt = Date.now();
+
+export function recordActivity() {
+    lastActivityAt = Date.now();
+}
+
+/** Chats and automations running or queued to run */
+export function getActiveTaskCount(): number {
+    return getAllActiveConversationIds().length
+        + getRunningExecutionCount()
+        + getQueueLength();
+}
+
+/** Seconds since the last request, or 0 while a chat or automation is running */
+export function getIdleSecs(): number {
+    if (getActiveTaskCount() > 0) {
+        recordActivity();
+        return 0;
+    }
+    return Math.floor((Date.now() - lastActivityAt) / 1000);
+}
+
+/** Whether scheduled automations need the server kept running */
+export function hasScheduledWork(): boolean {
+    return getActiveCronJobCount() + getActiveFileWatcherCount() > 0;
+}
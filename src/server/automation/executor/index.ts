@@ -17,11 +17,15 @@ import type { ConfirmationRequest, ConfirmationResponse } from '../../processor/
 import { createStandardConfirmationOptions } from '../../processor/confirmation/confirmation.types';
 import { createChildLogger } from '../../logger';
 import { maxIterations } from '../../utils';
+import { isOnline, waitUntilOnline } from '../../network';
+import { isBackgroundThrottled } from '../../throttle';
 
 const log = createChildLogger({ component: 'automation' });
 
 // Max concurrent executions
 const MAX_CONCURRENT = 3;
+// Max concurrent executions while the shell asks to save battery
+const MAX_CONCURRENT_THROTTLED = 1;
 
 // Retry configuration
 const MAX_RETRIES = 2;
@@ -152,9 +156,15 @@ export async function queueExecution(
  * Process the execution queue
  */
 async function processQueue(): Promise<void> {
+    // Leave executions queued while offline, and pick them up once back online
+    if (!isOnline()) {
+        waitUntilOnline().then(processQueue);
+        return;
+    }
+
     while (
         executionQueue.length > 0 &&
-        runningExecutions.size < MAX_CONCURRENT
+        runningExecutions.size < (isBackgroundThrottled() ? MAX_CONCURRENT_THROTTLED : MAX_CONCURRENT)
     ) {
         const item = executionQueue.shift();
         if (!item) break;
@@ -175,6 +185,11 @@ async function runExecutionWithRetry(
     let lastError: Error | null = null;
 
     for (let attempt = 0; attempt <= MAX_RETRIES; attempt++) {
+        // Hold off retries while offline rather than spend them on timeouts
+        if (attempt > 0 && !isOnline()) {
+            log.info(`Waiting for the network before running ${executionId}`);
+            await waitUntilOnline();
+        }
         try {
             await runExecution(executionId, automationId, triggerData);
             return; // Success - exit retry loop
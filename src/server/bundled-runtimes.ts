@@ -116,7 +116,8 @@ export async function getBundledRuntimes(): Promise<BundledRuntimes> {
     // Check if we have bundled runtimes
     if (bundledDirInfo) {
         const bundledDir = bundledDirInfo.dir;
-        const bunPath = path.join(bundledDir, `bun${ext}`);
+        // AppImages run an extracted copy of Bun, as the bundled one gets patched
+        const bunPath = process.env.PIPALI_BUNDLED_BUN || path.join(bundledDir, `bun${ext}`);
         const uvPath = path.join(bundledDir, `uv${ext}`);
         const uvxPath = path.join(bundledDir, `uvx${ext}`);
 
@@ -0,0 +1,204 @@
+/**
+ * Encryption at rest for the database, with a key the desktop shell keeps in
+ * the OS keychain and sends in its handshake.
+ *
+ * PGlite can't encrypt its data directory, so an encrypted database is held
+ * in memory and saved to disk as an AES-256-GCM encrypted dump, once writes
+ * settle, and first thing on shutdown.
+ */
+
+import { createCipheriv, createDecipheriv, randomBytes } from 'crypto';
+import fs from 'fs';
+import { PGlite, type PGliteOptions } from '@electric-sql/pglite';
+import { createChildLogger } from '../logger';
+
+const log = createChildLogger({ component: 'db-encryption' });
+
+const MAGIC = Buffer.from('PIPALIDB1');
+const IV_LENGTH = 12;
+const TAG_LENGTH = 16;
+/** How often to check for writes to save */
+const SAVE_CHECK_INTERVAL_MS = 1_000;
+/** Longest writes wait to be saved while more keep coming */
+const MAX_SAVE_DELAY_MS = 5_000;
+
+let databaseKey: Buffer | null = null;
+let saveTimer: ReturnType<typeof setInterval> | undefined;
+let saving: Promise<void> = Promise.resolve();
+let savedLsn: string | null = null;
+/** WAL position at the last check, and when the first unsaved write was seen */
+let checkedLsn: string | null = null;
+let unsavedSince: number | null = null;
+let checking = false;
+
+/** Set the key from the shell's handshake, as 64 hex characters */
+export function setDatabaseKey(key: string | null | undefined) {
+    if (!key) {
+        databaseKey = null;
+        return;
+    }
+    if (!/^[0-9a-f]{64}$/i.test(key)) {
+        throw new Error('Invalid database key from the desktop shell');
+    }
+    databaseKey = Buffer.from(key, 'hex');
+}
+
+export function isDatabaseEncrypted(): boolean {
+    return databaseKey !== null;
+}
+
+/** Where the encrypted dump of a data directory is kept */
+function getEncryptedDatabasePath(dataDir: string): string {
+    return `${dataDir}.enc`;
+}
+
+function encrypt(key: Buffer, plaintext: Uint8Array): Buffer {
+    const iv = randomBytes(IV_LENGTH);
+    const cipher = createCipheriv('aes-256-gcm', key, iv);
+    const ciphertext = Buffer.concat([cipher.update(plaintext), cipher.final()]);
+    return Buffer.concat([MAGIC, iv, cipher.getAuthTag(), ciphertext]);
+}
+
+function decrypt(key: Buffer, data: Buffer): Buffer {
+    if (!data.subarray(0, MAGIC.length).equals(MAGIC)) {
+        throw new Error('Not an encrypted Pipali database');
+    }
+    const ivStart = MAGIC.length;
+    const tagStart = ivStart + IV_LENGTH;
+    const decipher = createDecipheriv('aes-256-gcm', key, data.subarray(ivStart, tagStart));
+    decipher.setAuthTag(data.subarray(tagStart, tagStart + TAG_LENGTH));
+    try {
+        return Buffer.concat([decipher.update(data.subarray(tagStart + TAG_LENGTH)), decipher.final()]);
+    } catch {
+        throw new Error('Failed to decrypt the database, the key from the keychain does not match');
+    }
+}
+
+/** Write a database dump encrypted, replacing the previous one atomically */
+async function writeDump(key: Buffer, dump: Blob, filePath: string) {
+    const tarball = Bun.gzipSync(new Uint8Array(await dump.arrayBuffer()));
+    const tempPath = `${filePath}.tmp`;
+    fs.writeFileSync(tempPath, encrypt(key, tarball), { mode: 0o600 });
+    fs.renameSync(tempPath, filePath);
+}
+
+async function currentLsn(client: PGlite): Promise<string | null> {
+    try {
+        const result = await client.query<{ lsn: string }>('SELECT pg_current_wal_lsn()::text AS lsn');
+        return result.rows[0]?.lsn ?? null;
+    } catch {
+        return null;
+    }
+}
+
+/** Save the in-memory database if it's been written to since the last save */
+function saveEncryptedDatabase(client: PGlite, dataDir: string): Promise<void> {
+    const key = databaseKey;
+    if (!key) return Promise.resolve();
+    saving = saving.then(async () => {
+        const lsn = await currentLsn(client);
+        if (lsn !== null && lsn === savedLsn) return;
+        await writeDump(key, await client.dumpDataDir('none'), getEncryptedDatabasePath(dataDir));
+        savedLsn = lsn;
+    }).catch(error => {
+        log.error({ err: error }, 'Failed to save the encrypted database');
+    });
+    return saving;
+}
+
+/**
+ * Save once a check finds no writes since the one before, or the oldest
+ * unsaved write has waited MAX_SAVE_DELAY_MS, so a busy database isn't dumped
+ * after every statement.
+ */
+async function saveSettledWrites(client: PGlite, dataDir: string) {
+    if (checking) return;
+    checking = true;
+    try {
+        const lsn = await currentLsn(client);
+        if (lsn !== null && lsn === savedLsn) {
+            unsavedSince = null;
+            return;
+        }
+        const writing = lsn === null || lsn !== checkedLsn;
+        checkedLsn = lsn;
+        unsavedSince ??= Date.now();
+        if (writing && Date.now() - unsavedSince < MAX_SAVE_DELAY_MS) return;
+        await saveEncryptedDatabase(client, dataDir);
+        unsavedSince = null;
+    } finally {
+        checking = false;
+    }
+}
+
+/**
+ * Encrypt a plaintext data directory in place: dump it, write the encrypted
+ * dump, then delete the plaintext files.
+ */
+async function encryptDataDir(key: Buffer, dataDir: string, options: PGliteOptions) {
+    log.info('Encrypting the existing database...');
+    const plaintext = await PGlite.create(dataDir, options);
+    const dump = await plaintext.dumpDataDir('none');
+    await plaintext.close();
+    await writeDump(key, dump, getEncryptedDatabasePath(dataDir));
+    fs.rmSync(dataDir, { recursive: true, force: true });
+    log.info('Database encrypted.');
+}
+
+/**
+ * Open the database in a data directory, encrypted if the shell sent a key.
+ *
+ * Refuses to open an encrypted database without the key, rather than
+ * starting an empty one in its place.
+ */
+export async function openDatabase(dataDir: string, options: PGliteOptions): Promise<PGlite> {
+    const encryptedPath = getEncryptedDatabasePath(dataDir);
+    const key = databaseKey;
+    if (!key) {
+        if (fs.existsSync(encryptedPath)) {
+            throw new Error('The database is encrypted, but the desktop shell did not send its key');
+        }
+        return PGlite.create(dataDir, options);
+    }
+
+    if (!fs.existsSync(encryptedPath) && fs.existsSync(`${dataDir}/PG_VERSION`)) {
+        await encryptDataDir(key, dataDir, options);
+    }
+    const loadDataDir = fs.existsSync(encryptedPath)
+        ? new Blob([Bun.gunzipSync(decrypt(key, fs.readFileSync(encryptedPath)))])
+        : undefined;
+    const client = await PGlite.create('memory://', { ...options, loadDataDir });
+    savedLsn = loadDataDir ? await currentLsn(client) : null;
+    checkedLsn = savedLsn;
+    unsavedSince = null;
+
+    saveTimer = setInterval(() => void saveSettledWrites(client, dataDir), SAVE_CHECK_INTERVAL_MS);
+    return client;
+}
+
+/**
+ * Snapshot the live database as a gzipped tarball of its data directory, for
+ * backups taken while the server runs. Encrypted like the `.enc` file when
+ * the database is, so a backup never holds the data in plaintext.
+ *
+ * The dump is read between queries, and restores like a database after a
+ * crash, by replaying its WAL. Checkpointing first keeps that replay short.
+ */
+export async function dumpDatabase(client: PGlite): Promise<{ data: Uint8Array; encrypted: boolean }> {
+    await client.exec('CHECKPOINT');
+    const dump = await client.dumpDataDir('none');
+    const tarball = Bun.gzipSync(new Uint8Array(await dump.arrayBuffer()));
+    const key = databaseKey;
+    return key ? { data: encrypt(key, tarball), encrypted: true } : { data: tarball, encrypted: false };
+}
+
+/**
+ * Stop saving on a timer, and save what's been written since the last save.
+ * Called as shutdown starts, so a slow shutdown can't cost committed writes,
+ * and again as the database closes.
+ */
+export async function stopSavingEncryptedDatabase(client: PGlite, dataDir: string): Promise<void> {
+    clearInterval(saveTimer);
+    saveTimer = undefined;
+    await saveEncryptedDatabase(client, dataDir);
+}
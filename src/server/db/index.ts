@@ -1,20 +1,32 @@
-import { PGlite } from '@electric-sql/pglite';
 import { eq } from 'drizzle-orm';
 import { drizzle } from 'drizzle-orm/pglite';
 import { getDbName, getPGliteConfig } from './utils';
+import { isDatabaseEncrypted, openDatabase, stopSavingEncryptedDatabase } from './encryption';
+import { readShellHandshake } from '../handshake';
 import { AiModelApi, ChatModel, User, UserChatModel, type ChatModelWithApi } from './schema';
 import { createChildLogger } from '../logger';
 
 const log = createChildLogger({ component: 'db' });
 
+// The handshake may carry the database's encryption key
+await readShellHandshake();
+
 const dbName = getDbName();
 const config = await getPGliteConfig();
-export const client = await PGlite.create(dbName, config);
+export const client = await openDatabase(dbName, config);
 export const db = drizzle(client);
 
+/** Save an encrypted database to disk now, without waiting for its timer */
+export async function saveDatabase(): Promise<void> {
+    if (isDatabaseEncrypted()) {
+        await stopSavingEncryptedDatabase(client, dbName);
+    }
+}
+
 /** Gracefully close the database connection */
 export async function closeDatabase(): Promise<void> {
     log.info('Closing database connection...');
+    await saveDatabase();
     await client.close();
     log.info('Database connection closed.');
 }
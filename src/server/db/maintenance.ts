@@ -0,0 +1,35 @@
+import type { PGlite } from '@electric-sql/pglite';
+
+/**
+ * Rewrite every table without the space deleted rows leave behind. Postgres
+ * reuses that space for new rows but never hands it back, so the database
+ * stays the size it was before large conversations were deleted.
+ *
+ * Takes an exclusive lock on each table in turn, so other queries wait
+ * until it's done.
+ */
+export async function vacuumDatabase(client: PGlite): Promise<void> {
+    await client.exec('VACUUM FULL');
+    await client.exec('CHECKPOINT');
+}
+
+/**
+ * Which of the given file paths any conversation mentions. Attachments are
+ * only tied to conversations by their path appearing in a message.
+ */
+export async function findReferencedPaths(client: PGlite, paths: string[]): Promise<string[]> {
+    const referenced: string[] = [];
+    for (const filePath of paths) {
+        // Matched as it's written in the trajectory's JSON, where Windows
+        // backslashes are escaped
+        const needle = JSON.stringify(filePath).slice(1, -1);
+        const result = await client.query<{ found: boolean }>(
+            'SELECT EXISTS (SELECT 1 FROM conversation WHERE position($1 in trajectory::text) > 0) AS found',
+            [needle],
+        );
+        if (result.rows[0]?.found) {
+            referenced.push(filePath);
+        }
+    }
+    return referenced;
+}
@@ -0,0 +1,41 @@
+/**
+ * One-shot handshake the desktop shell writes to stdin at launch. It carries
+ * the auth token, API keys and database key, which would be visible to other
+ * processes in the command line or environment.
+ */
+
+import { setShellAuthToken } from './security';
+import { setShellSecrets } from './secrets';
+import { setDatabaseKey } from './db/encryption';
+
+interface ShellHandshake {
+    authToken?: string;
+    secrets?: Record<string, string>;
+    databaseKey?: string | null;
+}
+
+let handshakeRead: Promise<void> | undefined;
+
+/**
+ * Read the handshake if the shell said it would send one. Resolves once
+ * stdin closes, so the server doesn't serve requests before it has the token.
+ * Stdin is only read once, however many callers wait on it.
+ */
+export function readShellHandshake(): Promise<void> {
+    handshakeRead ??= read();
+    return handshakeRead;
+}
+
+async function read(): Promise<void> {
+    if (process.env.PIPALI_HANDSHAKE_STDIN !== '1') return;
+
+    let handshake: ShellHandshake;
+    try {
+        handshake = JSON.parse(await Bun.stdin.text());
+    } catch {
+        throw new Error('Invalid handshake from the desktop shell');
+    }
+    if (handshake.authToken) setShellAuthToken(handshake.authToken);
+    setShellSecrets(handshake.secrets ?? {});
+    setDatabaseKey(handshake.databaseKey);
+}
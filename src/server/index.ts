@@ -1,10 +1,11 @@
 import { migrate } from "drizzle-orm/pglite/migrator";
 import { sql } from "drizzle-orm";
 import { parseArgs } from "util";
-import { db, closeDatabase } from "./db";
+import { db, closeDatabase, saveDatabase } from "./db";
 import app from "./routes";
 import api from "./routes/api";
 import { initializeDatabase } from "./init";
+import { readShellHandshake } from "./handshake";
 import { getMigrationsFolder } from "./utils";
 import { loadSkills, installBuiltinSkills } from "./skills";
 import { initializeUserContext } from "./user-context";
@@ -19,6 +20,10 @@ import { configureAuth, isAuthenticated, getPlatformUserInfo } from "./auth";
 import { createChildLogger } from './logger';
 import { initializeSandbox, shutdownSandbox } from './sandbox';
 import { initPlatformTransport, shutdownPlatformTransport } from './telemetry/platform-transport';
+import { setShutdownHandler } from './shutdown';
+import { isRequestAuthorized, getShellAuthToken } from './security';
+import { setSchemaVersion } from './version';
+import { recordActivity } from './activity';
 
 const log = createChildLogger({ component: 'server' });
 
@@ -167,10 +172,26 @@ async function runBundledMigrations() {
     await runTaggedMigrations(migrations);
 }
 
+/** Tag of the newest migration in this build, i.e. the schema it runs against once migrated */
+async function getLatestMigrationTag(): Promise<string | null> {
+    if (IS_COMPILED_BINARY) {
+        return EMBEDDED_MIGRATIONS.at(-1)?.tag ?? null;
+    }
+    try {
+        const journal = await Bun.file(`${getMigrationsFolder()}/meta/_journal.json`).json();
+        return journal?.entries?.at(-1)?.tag ?? null;
+    } catch {
+        return null;
+    }
+}
+
 async function main() {
     // Parse CLI arguments
     const config = getServerConfig();
 
+    // Secrets from the desktop shell, before anything can use them
+    await readShellHandshake();
+
     // Configure auth module
     configureAuth({
         platformUrl: config.platformUrl,
@@ -185,6 +206,7 @@ async function main() {
     } else {
         await migrate(db, { migrationsFolder: getMigrationsFolder() });
     }
+    setSchemaVersion(await getLatestMigrationTag());
 
     // Initialize database (creates user, sets up models from env vars in anon mode)
     await initializeDatabase();
@@ -260,10 +282,14 @@ async function main() {
   // This prevents Bun from restarting the server when files change during tests
   const isDevelopmentMode = !IS_COMPILED_BINARY && process.env.PIPALI_TEST_MODE !== 'true';
 
+  // The desktop shell bundles its own frontend, so its server is API-only
+  // and local browsers can't load the UI from it
+  const apiOnly = process.env.PIPALI_API_ONLY === '1';
+
   // Paths for quieter logging (e.g frequent polling endpoints)
   const QUIETER_PATHS = new Set(['/api/automations/confirmations/pending']);
 
-  const server = Bun.serve<WebSocketData, any>({
+  const serve = (port: number, tls?: Bun.TLSOptions) => Bun.serve<WebSocketData, any>({
     async fetch(req, server) {
         const url = new URL(req.url);
         if (QUIETER_PATHS.has(url.pathname)) {
@@ -271,6 +297,16 @@ async function main() {
         } else {
             log.info(`[${req.method}] ${url.pathname}`);
         }
+        // Background polling doesn't count as use for idle shutdown
+        if (!QUIETER_PATHS.has(url.pathname) && url.pathname !== '/api/health') {
+            recordActivity();
+        }
+
+        // Only the desktop shell's webview knows the token when one is set
+        if (!isRequestAuthorized(req, url)) {
+            log.warn(`Rejected unauthenticated request to ${url.pathname}`);
+            return new Response("Unauthorized", { status: 401 });
+        }
 
         // WebSocket
         if (url.pathname === "/ws/chat") {
@@ -291,17 +327,44 @@ async function main() {
         }
 
         // Static and frontend routes
+        if (apiOnly) {
+            return new Response("Not Found", { status: 404 });
+        }
         const res = await app.fetch(req, server);
         return res;
     },
     websocket: websocketHandler,
     hostname: config.host,
-    port: config.port,
+    port,
+    tls,
     development: isDevelopmentMode,
   });
 
+  const server = serve(config.port);
+
   log.info(`Server listening on http://${config.host}:${server.port}`);
 
+  // The desktop shell may ask for a second listener with a certificate it
+  // generated and trusts, so its webview can reach the server over https/wss
+  const tlsPort = parseInt(process.env.PIPALI_TLS_PORT ?? '', 10);
+  const tlsCert = process.env.PIPALI_TLS_CERT;
+  const tlsKey = process.env.PIPALI_TLS_KEY;
+  const tlsServer = tlsPort && tlsCert && tlsKey
+      ? serve(tlsPort, { cert: Bun.file(tlsCert), key: Bun.file(tlsKey) })
+      : null;
+  if (tlsServer) {
+      log.info(`Server listening on https://${config.host}:${tlsServer.port}`);
+  }
+
+  // Tell the desktop shell we're ready, so it needn't poll the health endpoint.
+  // Written directly to stdout as the shell matches this exact line.
+  if (process.env.PIPALI_SHELL_PID) {
+      process.stdout.write(`READY port=${server.port}\n`);
+  }
+  if (getShellAuthToken()) {
+      log.info('🔑 API and WebSocket requests require the shell auth token');
+  }
+
   // Log auth status (authentication is now handled via the frontend login page)
   if (!config.anon && !alreadyAuthenticated) {
       log.info('🔐 Authentication required - sign in via the web interface');
@@ -323,6 +386,10 @@ async function main() {
 
     try {
       server.stop();
+      tlsServer?.stop();
+      // Save an encrypted database before the steps below can use up the time
+      // the shell gives us to exit
+      await saveDatabase();
       await stopAutomationSystem();
       await closeMcpClients();
       await shutdownSandbox();
@@ -335,6 +402,7 @@ async function main() {
     }
   };
 
+  setShutdownHandler(shutdown);
   process.on('SIGINT', () => void shutdown('SIGINT'));
   process.on('SIGTERM', () => void shutdown('SIGTERM'));
   process.on('SIGHUP', () => void shutdown('SIGHUP'));
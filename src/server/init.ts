@@ -3,6 +3,7 @@ import { User, AiModelApi, ChatModel, McpServer } from './db/schema';
 import { eq } from 'drizzle-orm';
 import { getDefaultUser } from './utils';
 import { createChildLogger } from './logger';
+import { getSecret, hasShellSecret, secretRef } from './secrets';
 
 const log = createChildLogger({ component: 'init' });
 
@@ -35,6 +36,37 @@ async function setupChatModelProvider(providerName: string, modelType: 'openai'
     log.info(`🤖 Added ${providerName} ai models.`);
 }
 
+/**
+ * API key to store for a provider. Keys from the shell's keychain are stored
+ * as a reference, so the key itself stays out of the database.
+ */
+function providerApiKey(name: string): string | undefined {
+    if (hasShellSecret(name)) return secretRef(name);
+    return getSecret(name);
+}
+
+/**
+ * Add chat model providers for the API keys that are set, unless chat models
+ * are configured already. Run again when the shell hands over its keys.
+ */
+export async function setupChatModelProviders() {
+    const existingChatModels = await db.select().from(ChatModel).limit(1);
+    if (existingChatModels.length > 0) return;
+
+    const openaiApiKey = providerApiKey('OPENAI_API_KEY');
+    if (openaiApiKey && (!process.env.OPENAI_BASE_URL || process.env.OPENAI_BASE_URL == 'https://api.openai.com/v1')) {
+        await setupChatModelProvider('OpenAI', 'openai', openaiApiKey, defaultOpenAIModels, true, process.env.OPENAI_BASE_URL);
+    }
+    const geminiApiKey = providerApiKey('GEMINI_API_KEY');
+    if (geminiApiKey) {
+        await setupChatModelProvider('Google Gemini', 'google', geminiApiKey, defaultGeminiModels, true);
+    }
+    const anthropicApiKey = providerApiKey('ANTHROPIC_API_KEY');
+    if (anthropicApiKey) {
+        await setupChatModelProvider('Anthropic', 'anthropic', anthropicApiKey, defaultAnthropicModels, true);
+    }
+}
+
 export async function initializeDatabase() {
     // 1. Create default local user (used to associate all local state in the embedded DB)
     const defaultUserEmail = getDefaultUser().email;
@@ -50,19 +82,7 @@ export async function initializeDatabase() {
     }
 
     // 2. Create Chat Model Configurations - only if no chat models exist
-    const existingChatModels = await db.select().from(ChatModel).limit(1);
-
-    if (existingChatModels.length === 0) {
-        if (process.env.OPENAI_API_KEY && (!process.env.OPENAI_BASE_URL || process.env.OPENAI_BASE_URL == 'https://api.openai.com/v1')) {
-            await setupChatModelProvider('OpenAI', 'openai', process.env.OPENAI_API_KEY, defaultOpenAIModels, true, process.env.OPENAI_BASE_URL);
-        }
-        if (process.env.GEMINI_API_KEY) {
-            await setupChatModelProvider('Google Gemini', 'google', process.env.GEMINI_API_KEY, defaultGeminiModels, true);
-        }
-        if (process.env.ANTHROPIC_API_KEY) {
-            await setupChatModelProvider('Anthropic', 'anthropic', process.env.ANTHROPIC_API_KEY, defaultAnthropicModels, true);
-        }
-    }
+    await setupChatModelProviders();
 
     // 3. Setup default MCP servers
     await setupDefaultMcpServers();
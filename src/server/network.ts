@@ -0,0 +1,33 @@
+/**
+ * Internet reachability as reported by the desktop shell, so cloud calls
+ * can wait for the connection to return instead of timing out repeatedly.
+ * Without a shell the server assumes it's online.
+ */
+
+import { createChildLogger } from './logger';
+
+const log = createChildLogger({ component: 'network' });
+
+let online = true;
+let waiters: Array<() => void> = [];
+
+export function isOnline(): boolean {
+    return online;
+}
+
+export function setOnline(value: boolean) {
+    if (value === online) return;
+    online = value;
+    log.info(online ? 'Back online, resuming cloud calls' : 'Offline, pausing cloud calls');
+    if (online) {
+        const resume = waiters;
+        waiters = [];
+        resume.forEach(resolve => resolve());
+    }
+}
+
+/** Resolves once the shell reports the internet is reachable */
+export function waitUntilOnline(): Promise<void> {
+    if (online) return Promise.resolve();
+    return new Promise(resolve => waiters.push(resolve));
+}
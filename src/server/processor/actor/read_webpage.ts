@@ -18,6 +18,7 @@ import {
     requestOperationConfirmation,
 } from '../confirmation';
 import { createChildLogger } from '../../logger';
+import { getSecret } from '../../secrets';
 
 const log = createChildLogger({ component: 'read_webpage' });
 
@@ -26,7 +27,7 @@ const FETCH_REQUEST_TIMEOUT = 60000;
 
 // Get environment variables at runtime (not module load time)
 function getExaApiKey(): string | undefined {
-    return process.env.EXA_API_KEY;
+    return getSecret('EXA_API_KEY');
 }
 
 function getExaApiBaseUrl(): string {
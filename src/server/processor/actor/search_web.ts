@@ -10,6 +10,7 @@ import { WebSearchProvider } from '../../db/schema';
 import { desc, eq } from 'drizzle-orm';
 import { platformFetch } from '../../http/platform-fetch';
 import { createChildLogger } from '../../logger';
+import { getSecret } from '../../secrets';
 
 const log = createChildLogger({ component: 'search_web' });
 
@@ -21,7 +22,7 @@ const SERPER_MAX_QUERY_LENGTH = 2048;
 
 // Get environment variables at runtime (not module load time)
 function getExaApiKey(): string | undefined {
-    return process.env.EXA_API_KEY;
+    return getSecret('EXA_API_KEY');
 }
 
 function getExaApiBaseUrl(): string {
@@ -29,7 +30,7 @@ function getExaApiBaseUrl(): string {
 }
 
 function getSerperApiKey(): string | undefined {
-    return process.env.SERPER_DEV_API_KEY;
+    return getSecret('SERPER_DEV_API_KEY');
 }
 
 function getSerperApiBaseUrl(): string {
@@ -15,6 +15,7 @@ import {
     CONFIRMATION_OPTIONS,
     createStandardConfirmationOptions,
 } from './confirmation.types';
+import { requiresNativeConfirmation } from './native-confirmation';
 
 /**
  * Callback function type for requesting confirmation from user
@@ -210,8 +211,15 @@ export async function requestOperationConfirmation(
         ? `${operation}:${details.operationSubType}`
         : operation;
 
+    // Create the confirmation request
+    const request = createFileOperationConfirmation(operation, filePath, details);
+
+    // High-risk operations are always confirmed in the shell's native dialog,
+    // even if the user opted to skip confirmations for this operation type
+    request.native = requiresNativeConfirmation(request);
+
     // Check if user has opted to skip confirmations for this operation (or operation+subtype combo)
-    if (!requiresConfirmation(confirmationKey, context.preferences)) {
+    if (!request.native && !requiresConfirmation(confirmationKey, context.preferences)) {
         return {
             approved: true,
             selectedOption: CONFIRMATION_OPTIONS.YES_DONT_ASK,
@@ -219,9 +227,6 @@ export async function requestOperationConfirmation(
         };
     }
 
-    // Create the confirmation request
-    const request = createFileOperationConfirmation(operation, filePath, details);
-
     // Request confirmation from user via callback
     const response = await context.requestConfirmation(request);
 
@@ -94,6 +94,8 @@ export interface ConfirmationRequest {
     defaultOptionId?: string;
     /** Timeout in milliseconds (0 = no timeout) */
     timeoutMs?: number;
+    /** Shown by the desktop shell in a native dialog, whose signed answer is required */
+    native?: boolean;
 }
 
 /**
@@ -117,6 +119,8 @@ export interface ConfirmationResponse {
     };
     /** Whether user chose to persist this preference */
     persistPreference?: boolean;
+    /** The desktop shell's signature over the selected option, for native confirmations */
+    signature?: string;
     /** Timestamp of response */
     timestamp: string;
 }
@@ -0,0 +1,80 @@
+/**
+ * Native Confirmation
+ *
+ * High-risk confirmations are shown by the desktop shell in a native dialog
+ * instead of the webview, which a compromised page could fake or auto-accept.
+ * The shell signs the user's choice with its auth token, which never reaches
+ * the webview, so an approval only counts with a valid signature.
+ */
+
+import { createHmac, timingSafeEqual } from 'crypto';
+import { getShellAuthToken, isSensitivePath } from '../../security';
+import {
+    type ConfirmationRequest,
+    type ConfirmationResponse,
+    CONFIRMATION_OPTIONS,
+} from './confirmation.types';
+
+/** Operations always confirmed natively, whatever their risk level */
+const NATIVE_OPERATIONS = new Set(['read_sensitive_file', 'grep_sensitive_path']);
+
+/** Native confirmations awaiting an answer, for the shell to look up */
+const pendingNative = new Map<string, ConfirmationRequest>();
+
+/**
+ * Check whether a confirmation must go through the shell's native dialog.
+ * Only when running under the desktop shell, which sets an auth token.
+ */
+export function requiresNativeConfirmation(request: ConfirmationRequest): boolean {
+    if (!getShellAuthToken()) {
+        return false;
+    }
+    if (NATIVE_OPERATIONS.has(request.operation) || request.context?.riskLevel === 'high') {
+        return true;
+    }
+    // Shell commands that only read stay in the webview, as they're frequent
+    if (request.operation === 'execute_command') {
+        return request.context?.operationType !== 'read-only';
+    }
+    return request.context?.affectedFiles?.some(isSensitivePath) ?? false;
+}
+
+/**
+ * Signature the shell attaches to the option the user picked
+ */
+export function signConfirmation(token: string, requestId: string, selectedOptionId: string): string {
+    return createHmac('sha256', token).update(`${requestId}:${selectedOptionId}`).digest('hex');
+}
+
+export function trackNativeConfirmation(request: ConfirmationRequest): void {
+    pendingNative.set(request.requestId, request);
+}
+
+export function untrackNativeConfirmation(requestId: string): void {
+    pendingNative.delete(requestId);
+}
+
+/**
+ * Get a pending native confirmation, so the shell can show what it's for
+ * without trusting the webview's description of it
+ */
+export function getNativeConfirmation(requestId: string): ConfirmationRequest | undefined {
+    return pendingNative.get(requestId);
+}
+
+/**
+ * Check a response to a native confirmation. Declining needs no signature,
+ * so the webview can still cancel when the shell's dialog fails.
+ */
+export function verifyNativeConfirmation(response: ConfirmationResponse): boolean {
+    if (response.selectedOptionId === CONFIRMATION_OPTIONS.NO) {
+        return true;
+    }
+    const token = getShellAuthToken();
+    if (!token || !response.signature) {
+        return false;
+    }
+    const expected = Buffer.from(signConfirmation(token, response.requestId, response.selectedOptionId));
+    const actual = Buffer.from(response.signature);
+    return actual.length === expected.length && timingSafeEqual(actual, expected);
+}
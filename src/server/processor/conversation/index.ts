@@ -6,6 +6,7 @@ import { sendMessageToGpt } from './openai';
 import type { ATIFTrajectory } from './atif/atif.types';
 import { withTokenRefresh, PlatformAuthError } from '../../http/platform-fetch';
 import { createChildLogger } from '../../logger';
+import { resolveApiKey } from '../../secrets';
 
 const log = createChildLogger({ component: 'llm' });
 
@@ -105,7 +106,7 @@ export async function sendMessageToModel(
             const response = await sendMessageToGpt(
                 messages,
                 chatModelWithApi.chatModel.name,
-                chatModelWithApi.aiModelApi?.apiKey,
+                resolveApiKey(chatModelWithApi.aiModelApi?.apiKey),
                 chatModelWithApi.aiModelApi?.apiBaseUrl,
                 tools,
                 toolChoice,
@@ -1,11 +1,13 @@
 import os from 'os';
 import path from 'path';
-import { realpath } from 'fs/promises';
+import { realpath, stat } from 'fs/promises';
 import { Hono } from 'hono';
 import { cors } from 'hono/cors';
 import { z } from 'zod';
 import { zValidator } from '@hono/zod-validator';
-import { db, getDefaultChatModel } from '../db';
+import { client, db, getDefaultChatModel } from '../db';
+import { dumpDatabase } from '../db/encryption';
+import { findReferencedPaths, vacuumDatabase } from '../db/maintenance';
 import { Conversation } from '../db/schema';
 import { eq, desc, isNull, and } from 'drizzle-orm';
 import { AiModelApi, ChatModel, User, UserChatModel } from '../db/schema';
@@ -28,6 +30,16 @@ import {
     isSandboxEnabled,
     isSandboxSupported,
 } from '../sandbox';
+import { isSensitivePath, getSensitivePathReason } from '../security/path-validator';
+import { requestShutdown } from '../shutdown';
+import { SERVER_VERSION, getSchemaVersion } from '../version';
+import { getIdleSecs, hasScheduledWork, getActiveTaskCount } from '../activity';
+import { setOnline } from '../network';
+import { setBackgroundThrottled } from '../throttle';
+import { getShellAuthTokenHash, issueWebSocketTicket } from '../security/shell-auth';
+import { setShellSecrets } from '../secrets';
+import { getNativeConfirmation } from '../processor/confirmation';
+import { setupChatModelProviders } from '../init';
 
 const log = createChildLogger({ component: 'api' });
 
@@ -51,8 +63,109 @@ api.use('*', cors({
     credentials: true,
 }));
 
-// Health check endpoint for Tauri sidecar readiness detection
-api.get('/health', (c) => c.json({ status: 'ok' }));
+// Health check endpoint for Tauri sidecar readiness detection.
+// shellPid identifies the desktop shell that spawned this server, and
+// authTokenSha256 the token it was given, so a later shell can recognise (and
+// take over) a sidecar orphaned by a previous run.
+// version lets the shell refuse to run against an incompatible server build.
+// schemaVersion and providers are shown in the shell's About and diagnostics,
+// idleSecs and keepAlive drive its idle shutdown, and activeTasks keeps the
+// system awake while the agent works.
+const shellPid = process.env.PIPALI_SHELL_PID ? Number(process.env.PIPALI_SHELL_PID) : null;
+api.get('/health', async (c) => {
+    const providers = await db
+        .selectDistinct({ name: AiModelApi.name })
+        .from(AiModelApi)
+        .then(rows => rows.map(row => row.name))
+        .catch(() => null);
+    return c.json({
+        status: 'ok',
+        shellPid,
+        authTokenSha256: getShellAuthTokenHash(),
+        version: SERVER_VERSION,
+        schemaVersion: getSchemaVersion(),
+        providers,
+        idleSecs: getIdleSecs(),
+        keepAlive: hasScheduledWork(),
+        activeTasks: getActiveTaskCount(),
+    });
+});
+
+// Graceful shutdown endpoint for the Tauri shell, which cannot send SIGTERM on Windows
+api.post('/shutdown', (c) => {
+    log.info('Shutdown requested via API');
+    // Defer so the response is sent before the server stops
+    setTimeout(() => requestShutdown('API request'), 0);
+    return c.json({ status: 'shutting_down' }, 202);
+});
+
+// The Tauri shell reports when the internet goes away and comes back
+api.post('/network', zValidator('json', z.object({ online: z.boolean() })), (c) => {
+    setOnline(c.req.valid('json').online);
+    return c.json({ status: 'ok' });
+});
+
+// The Tauri shell asks for less background work while low on battery
+api.post('/throttle', zValidator('json', z.object({ throttled: z.boolean() })), (c) => {
+    setBackgroundThrottled(c.req.valid('json').throttled);
+    return c.json({ status: 'ok' });
+});
+
+// The Tauri shell hands over the API keys it keeps in the OS keychain
+api.post('/secrets', zValidator('json', z.object({ secrets: z.record(z.string(), z.string()) })), async (c) => {
+    setShellSecrets(c.req.valid('json').secrets);
+    await setupChatModelProviders();
+    return c.json({ status: 'ok' });
+});
+
+// The Tauri shell trades its auth token for a ticket the webview opens the chat WebSocket with
+api.post('/ws-ticket', (c) => {
+    return c.json({ ticket: issueWebSocketTicket() });
+});
+
+// The Tauri shell takes database backups without stopping the server
+api.get('/backup', async (c) => {
+    log.info('Database backup requested');
+    const { data, encrypted } = await dumpDatabase(client);
+    return c.body(data, 200, {
+        'Content-Type': 'application/octet-stream',
+        'Content-Length': String(data.byteLength),
+        'X-Database-Encrypted': String(encrypted),
+        'X-Schema-Version': getSchemaVersion() ?? '',
+    });
+});
+
+// The Tauri shell compacts the database after large conversations are deleted
+api.post('/vacuum', async (c) => {
+    const activeTasks = getActiveTaskCount();
+    if (activeTasks > 0) {
+        return c.json({ error: 'Wait for running chats and automations to finish' }, 409);
+    }
+    log.info('Database vacuum requested');
+    const startedAt = Date.now();
+    await vacuumDatabase(client);
+    log.info(`Vacuumed the database in ${Date.now() - startedAt}ms`);
+    return c.json({ success: true });
+});
+
+const referencedAttachmentsSchema = z.object({
+    paths: z.array(z.string().min(1)),
+});
+
+// The Tauri shell checks which attachments conversations still refer to before deleting the rest
+api.post('/attachments/referenced', zValidator('json', referencedAttachmentsSchema), async (c) => {
+    const { paths } = c.req.valid('json');
+    return c.json({ referenced: await findReferencedPaths(client, paths) });
+});
+
+// The Tauri shell looks up what a native confirmation is for before showing its dialog
+api.get('/confirmations/native/:requestId', (c) => {
+    const request = getNativeConfirmation(c.req.param('requestId'));
+    if (!request) {
+        return c.json({ error: 'Confirmation not found' }, 404);
+    }
+    return c.json(request);
+});
 
 const schema = z.object({
     message: z.string(),
@@ -672,6 +785,46 @@ api.get('/files', async (c) => {
     }
 });
 
+const attachmentsSchema = z.object({
+    paths: z.array(z.string().min(1)).min(1).max(20),
+});
+
+// Check files dropped onto the app before they're attached to a message.
+// Files under sensitive paths are refused, as the agent couldn't read them anyway.
+api.post('/attachments', zValidator('json', attachmentsSchema), async (c) => {
+    const { paths } = c.req.valid('json');
+    const attachments: { path: string; name: string; sizeBytes: number }[] = [];
+    const rejected: { path: string; reason: string }[] = [];
+
+    for (const filePath of paths) {
+        if (!path.isAbsolute(filePath)) {
+            rejected.push({ path: filePath, reason: 'Path must be absolute' });
+            continue;
+        }
+        try {
+            const real = await realpath(path.resolve(filePath));
+            if (isSensitivePath(real)) {
+                const reason = getSensitivePathReason(real) ?? 'sensitive files';
+                rejected.push({ path: filePath, reason: `Access to ${reason} is not allowed` });
+                continue;
+            }
+            const info = await stat(real);
+            if (!info.isFile()) {
+                rejected.push({ path: filePath, reason: 'Only files can be attached' });
+                continue;
+            }
+            attachments.push({ path: real, name: path.basename(real), sizeBytes: info.size });
+        } catch {
+            rejected.push({ path: filePath, reason: 'File not found' });
+        }
+    }
+
+    if (rejected.length > 0) {
+        log.info({ rejected }, 'Rejected dropped files');
+    }
+    return c.json({ attachments, rejected });
+});
+
 // Mount the automations router
 api.route('/automations', automations);
 
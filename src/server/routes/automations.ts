@@ -9,7 +9,7 @@ import { z } from 'zod';
 import { zValidator } from '@hono/zod-validator';
 import { db } from '../db';
 import { Automation, AutomationExecution, User } from '../db/schema';
-import { eq, desc } from 'drizzle-orm';
+import { and, desc, eq, gt, inArray } from 'drizzle-orm';
 import { getDefaultUser } from '../utils';
 import {
     queueExecution,
@@ -66,6 +66,45 @@ const confirmationResponseSchema = z.object({
 
 // ============== STATIC ROUTES (must come before /:id routes) ==============
 
+// List executions that finished since a given time, across all automations.
+// Polled by the desktop shell to notify when automations finish in the background.
+automations.get('/executions', async (c) => {
+    const since = new Date(c.req.query('since') || 0);
+    if (isNaN(since.getTime())) {
+        return c.json({ error: 'Invalid since timestamp' }, 400);
+    }
+    const limit = parseInt(c.req.query('limit') || '20');
+
+    const [user] = await db.select().from(User).where(eq(User.email, getDefaultUser().email));
+    if (!user) return c.json({ error: 'User not found' }, 404);
+
+    const executions = await db.select({
+        id: AutomationExecution.id,
+        automationId: AutomationExecution.automationId,
+        automationName: Automation.name,
+        conversationId: Automation.conversationId,
+        status: AutomationExecution.status,
+        errorMessage: AutomationExecution.errorMessage,
+        completedAt: AutomationExecution.completedAt,
+    })
+        .from(AutomationExecution)
+        .innerJoin(Automation, eq(AutomationExecution.automationId, Automation.id))
+        .where(and(
+            eq(Automation.userId, user.id),
+            inArray(AutomationExecution.status, ['completed', 'failed']),
+            gt(AutomationExecution.completedAt, since),
+        ))
+        .orderBy(desc(AutomationExecution.completedAt))
+        .limit(limit);
+
+    return c.json({
+        executions: executions.map(e => ({
+            ...e,
+            completedAt: e.completedAt?.toISOString(),
+        })),
+    });
+});
+
 // Get single execution details
 automations.get('/executions/:id', async (c) => {
     const id = c.req.param('id');
@@ -16,6 +16,7 @@ import { MessageCommandHandler, StopCommandHandler, ForkCommandHandler, Confirma
 import { createRunningState, getActiveRun, type Session } from './ws/session-state';
 import { createConfirmationCallback, rejectAllConfirmations } from './ws/confirmation-manager';
 import { createChildLogger } from '../logger';
+import { recordActivity } from '../activity';
 
 const log = createChildLogger({ component: 'ws' });
 
@@ -453,6 +454,7 @@ async function handleClientMessage(
 export const websocketHandler = {
     async message(ws: ServerWebSocket<WebSocketData>, message: string | Buffer) {
         if (typeof message !== 'string') return;
+        recordActivity();
 
         const ctx = getConnectionContext(ws);
         const sessions = ctx.sessions;
@@ -12,6 +12,9 @@ import {
     type ConfirmationResponse,
     type ConfirmationCallback,
     CONFIRMATION_OPTIONS,
+    trackNativeConfirmation,
+    untrackNativeConfirmation,
+    verifyNativeConfirmation,
 } from '../../processor/confirmation';
 import type { PendingConfirmation } from './message-types';
 import type { RunningState } from './session-state';
@@ -64,6 +67,9 @@ export function createConfirmationCallback(
                 resolve,
                 reject,
             });
+            if (request.native) {
+                trackNativeConfirmation(request);
+            }
 
             log.info({
                 requestId: request.requestId,
@@ -99,6 +105,15 @@ export function handleConfirmationResponse(
         return false;
     }
 
+    // Only the shell's native dialog can approve these
+    if (pending.request.native && !verifyNativeConfirmation(response)) {
+        log.warn({
+            requestId: response.requestId,
+            runId: runState.runId,
+        }, 'Rejected unsigned response to native confirmation');
+        return false;
+    }
+
     log.info({
         requestId: response.requestId,
         selectedOptionId: response.selectedOptionId,
@@ -108,6 +123,7 @@ export function handleConfirmationResponse(
 
     // Remove and resolve the specific confirmation
     runState.pendingConfirmations.delete(response.requestId);
+    untrackNativeConfirmation(response.requestId);
     pending.resolve(response);
 
     // If "Yes, don't ask again" was selected, auto-approve other pending
@@ -116,10 +132,11 @@ export function handleConfirmationResponse(
         const sourceKey = getConfirmationKey(pending.request);
         const toAutoApprove: PendingConfirmation[] = [];
 
-        // Find all pending confirmations with matching confirmation key
+        // Find all pending confirmations with matching confirmation key.
+        // Native ones still need their own answer from the shell.
         for (const [requestId, otherPending] of runState.pendingConfirmations) {
             const otherKey = getConfirmationKey(otherPending.request);
-            if (otherKey === sourceKey) {
+            if (otherKey === sourceKey && !otherPending.request.native) {
                 toAutoApprove.push(otherPending);
             }
         }
@@ -165,6 +182,7 @@ export function rejectAllConfirmations(
     }, 'Rejecting all pending confirmations');
 
     for (const [requestId, pending] of runState.pendingConfirmations) {
+        untrackNativeConfirmation(requestId);
         pending.reject(new Error(reason));
     }
     runState.pendingConfirmations.clear();
@@ -0,0 +1,35 @@
+/**
+ * API keys the desktop shell keeps in the OS keychain and hands over after
+ * startup, so they never sit in plaintext in the database or settings.
+ * Keys set in the environment still work as before.
+ */
+
+const SECRET_REF_PREFIX = 'secret:';
+
+let shellSecrets = new Map<string, string>();
+
+/** Replace the keys held for the shell, e.g. after one is added or deleted */
+export function setShellSecrets(secrets: Record<string, string>) {
+    shellSecrets = new Map(Object.entries(secrets));
+}
+
+export function hasShellSecret(name: string): boolean {
+    return shellSecrets.has(name);
+}
+
+/** Look up a key from the shell's keychain, falling back to the environment */
+export function getSecret(name: string): string | undefined {
+    return shellSecrets.get(name) ?? process.env[name];
+}
+
+/** What to store in the database in place of a key from the shell's keychain */
+export function secretRef(name: string): string {
+    return `${SECRET_REF_PREFIX}${name}`;
+}
+
+/** Resolve a stored API key, which may refer to a key from the shell */
+export function resolveApiKey(stored: string | null | undefined): string | undefined {
+    if (!stored) return undefined;
+    if (!stored.startsWith(SECRET_REF_PREFIX)) return stored;
+    return getSecret(stored.slice(SECRET_REF_PREFIX.length));
+}
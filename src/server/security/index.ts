@@ -4,7 +4,9 @@
  * Provides validation and sanitization utilities for:
  * - Sensitive file path detection
  * - Internal/private network URL detection
+ * - Shell auth token checks for API/WebSocket requests
  */
 
 export { isSensitivePath, getSensitivePathReason } from './path-validator';
 export { isInternalUrl, getInternalUrlReason } from './url-validator';
+export { isRequestAuthorized, getShellAuthToken, setShellAuthToken } from './shell-auth';
@@ -0,0 +1,108 @@
+/**
+ * Shared-secret authentication between the desktop shell and this server.
+ *
+ * The Tauri shell generates a random token at startup and passes it in its
+ * stdin handshake, or PIPALI_AUTH_TOKEN is set by hand, e.g. for an external
+ * server. When set, every API and WebSocket request must carry it,
+ * so other processes on the machine can't drive the agent through localhost.
+ * Without it (e.g. plain `bun run` in the browser) requests are not checked.
+ */
+
+import { createHash, randomBytes, timingSafeEqual } from 'crypto';
+
+/**
+ * Endpoints reachable without the token:
+ * - health: readiness polling, including of sidecars from a previous shell
+ * - auth callback/complete: hit by the system browser during platform sign-in
+ */
+const PUBLIC_PATHS = new Set([
+    '/api/health',
+    '/api/auth/callback',
+    '/api/auth/complete',
+]);
+
+/** How long a WebSocket ticket can be used for once issued */
+const TICKET_TTL_MS = 30_000;
+
+/** Unused WebSocket tickets, with when they expire */
+const webSocketTickets = new Map<string, number>();
+
+let shellAuthToken = process.env.PIPALI_AUTH_TOKEN || undefined;
+
+export function getShellAuthToken(): string | undefined {
+    return shellAuthToken;
+}
+
+export function setShellAuthToken(token: string) {
+    shellAuthToken = token;
+}
+
+/**
+ * SHA-256 of the token, reported by the health endpoint. The shell keeps only
+ * this hash on disk, and matches it to recognise a sidecar it left running.
+ */
+export function getShellAuthTokenHash(token: string | undefined = getShellAuthToken()): string | null {
+    return token ? createHash('sha256').update(token).digest('hex') : null;
+}
+
+/**
+ * Issue a ticket that opens a single WebSocket connection. The shell trades
+ * its token for one, so the webview can connect without ever holding the token.
+ */
+export function issueWebSocketTicket(now: number = Date.now()): string {
+    for (const [ticket, expiresAt] of webSocketTickets) {
+        if (expiresAt <= now) webSocketTickets.delete(ticket);
+    }
+    const ticket = randomBytes(32).toString('hex');
+    webSocketTickets.set(ticket, now + TICKET_TTL_MS);
+    return ticket;
+}
+
+function redeemWebSocketTicket(ticket: string, now: number): boolean {
+    const expiresAt = webSocketTickets.get(ticket);
+    webSocketTickets.delete(ticket);
+    return expiresAt !== undefined && expiresAt > now;
+}
+
+/**
+ * Read the token from the Authorization header, or from the `token` query
+ * parameter for requests that can't set headers (WebSockets, <img> sources).
+ */
+function getRequestToken(req: Request, url: URL): string | null {
+    const header = req.headers.get('authorization');
+    if (header?.startsWith('Bearer ')) {
+        return header.slice('Bearer '.length);
+    }
+    return url.searchParams.get('token');
+}
+
+function tokensMatch(actual: string, expected: string): boolean {
+    const a = Buffer.from(actual);
+    const b = Buffer.from(expected);
+    return a.length === b.length && timingSafeEqual(a, b);
+}
+
+/**
+ * Check whether a request may reach the API or WebSocket endpoints.
+ * Frontend assets stay public so the web UI can load.
+ */
+export function isRequestAuthorized(
+    req: Request,
+    url: URL,
+    expected: string | undefined = getShellAuthToken(),
+    now: number = Date.now(),
+): boolean {
+    if (!expected) return true;
+    if (!url.pathname.startsWith('/api') && !url.pathname.startsWith('/ws')) return true;
+    if (PUBLIC_PATHS.has(url.pathname)) return true;
+    // CORS preflights never carry credentials
+    if (req.method === 'OPTIONS') return true;
+
+    const ticket = url.searchParams.get('ticket');
+    if (url.pathname.startsWith('/ws') && ticket !== null) {
+        return redeemWebSocketTicket(ticket, now);
+    }
+
+    const token = getRequestToken(req, url);
+    return token !== null && tokensMatch(token, expected);
+}
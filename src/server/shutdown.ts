@@ -0,0 +1,22 @@
+/**
+ * Process-wide graceful shutdown hook.
+ *
+ * The server entry point registers its shutdown routine here so that
+ * non-signal triggers (e.g. the Tauri shell on Windows, which cannot
+ * send SIGTERM) can request the same clean shutdown path.
+ */
+
+type ShutdownHandler = (reason: string) => Promise<void>;
+
+let shutdownHandler: ShutdownHandler | null = null;
+
+export function setShutdownHandler(handler: ShutdownHandler) {
+    shutdownHandler = handler;
+}
+
+/**
+ * Request a graceful shutdown. No-op until the server has registered a handler.
+ */
+export function requestShutdown(reason: string) {
+    void shutdownHandler?.(reason);
+}
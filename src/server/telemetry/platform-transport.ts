@@ -7,12 +7,14 @@
  */
 
 import { getPlatformUrl, getValidAccessToken, isAuthenticated } from '../auth';
+import { isOnline } from '../network';
 import type { TelemetryErrorEvent, TelemetryBatch, ErrorCategory, ErrorSeverity } from './types';
 
 // Configuration
 const BATCH_SIZE = 10;
 const FLUSH_INTERVAL_MS = 30_000; // 30 seconds
 const REQUEST_TIMEOUT_MS = 10_000; // 10 seconds
+const MAX_OFFLINE_QUEUE = 100; // Oldest events are dropped past this while offline
 
 // Module state
 let eventQueue: TelemetryErrorEvent[] = [];
@@ -158,6 +160,11 @@ export function queueLogEntry(logEntry: {
  */
 async function flushEvents(): Promise<void> {
     if (eventQueue.length === 0) return;
+    // Keep events queued until the shell reports the network is back
+    if (!isOnline()) {
+        eventQueue = eventQueue.slice(-MAX_OFFLINE_QUEUE);
+        return;
+    }
 
     // Check if authenticated
     const authenticated = await isAuthenticated();
@@ -0,0 +1,20 @@
+/**
+ * Whether background work should be held back, as requested by the desktop
+ * shell while the laptop runs low on battery.
+ */
+
+import { createChildLogger } from './logger';
+
+const log = createChildLogger({ component: 'throttle' });
+
+let throttled = false;
+
+export function isBackgroundThrottled(): boolean {
+    return throttled;
+}
+
+export function setBackgroundThrottled(value: boolean) {
+    if (value === throttled) return;
+    throttled = value;
+    log.info(throttled ? 'Throttling background work to save battery' : 'No longer throttling background work');
+}
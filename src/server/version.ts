@@ -0,0 +1,15 @@
+import packageJson from '../../package.json';
+
+/** Version of this server build, checked by the desktop shell on startup */
+export const SERVER_VERSION: string = packageJson.version;
+
+/** Tag of the newest database migration applied at startup */
+let schemaVersion: string | null = null;
+
+export function setSchemaVersion(tag: string | null) {
+    schemaVersion = tag;
+}
+
+export function getSchemaVersion(): string | null {
+    return schemaVersion;
+}
@@ -0,0 +1,123 @@
+import { test, expect, describe, beforeEach, afterEach } from 'bun:test';
+import { PGlite } from '@electric-sql/pglite';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { getPGliteConfig } from '../../src/server/db/utils';
+import { dumpDatabase, openDatabase, setDatabaseKey, stopSavingEncryptedDatabase } from '../../src/server/db/encryption';
+
+const KEY = 'ab'.repeat(32);
+
+let tempDir: string;
+let dataDir: string;
+
+beforeEach(() => {
+    tempDir = fs.mkdtempSync(path.join(os.tmpdir(), 'pipali-db-encryption-'));
+    dataDir = path.join(tempDir, 'db');
+});
+
+afterEach(() => {
+    setDatabaseKey(null);
+    fs.rmSync(tempDir, { recursive: true, force: true });
+});
+
+async function close(client: PGlite) {
+    await stopSavingEncryptedDatabase(client, dataDir);
+    await client.close();
+}
+
+describe('openDatabase', () => {
+    test('should encrypt an existing plaintext database in place', async () => {
+        const config = await getPGliteConfig();
+        const plaintext = await PGlite.create(dataDir, config);
+        await plaintext.exec("CREATE TABLE notes (body text); INSERT INTO notes VALUES ('hello')");
+        await plaintext.close();
+
+        setDatabaseKey(KEY);
+        const client = await openDatabase(dataDir, config);
+        await close(client);
+
+        expect(fs.existsSync(dataDir)).toBe(false);
+        const encrypted = fs.readFileSync(`${dataDir}.enc`);
+        expect(encrypted.includes('hello')).toBe(false);
+
+        const reopened = await openDatabase(dataDir, config);
+        const result = await reopened.query<{ body: string }>('SELECT body FROM notes');
+        expect(result.rows).toEqual([{ body: 'hello' }]);
+        await close(reopened);
+    }, 60_000);
+
+    test('should keep writes made while encrypted', async () => {
+        const config = await getPGliteConfig();
+        setDatabaseKey(KEY);
+        const client = await openDatabase(dataDir, config);
+        await client.exec("CREATE TABLE notes (body text); INSERT INTO notes VALUES ('saved')");
+        await close(client);
+
+        const reopened = await openDatabase(dataDir, config);
+        const result = await reopened.query<{ body: string }>('SELECT body FROM notes');
+        expect(result.rows).toEqual([{ body: 'saved' }]);
+        await close(reopened);
+    }, 60_000);
+
+    test('should save writes once they settle, without waiting for shutdown', async () => {
+        const config = await getPGliteConfig();
+        setDatabaseKey(KEY);
+        const client = await openDatabase(dataDir, config);
+        await client.exec("CREATE TABLE notes (body text); INSERT INTO notes VALUES ('committed')");
+        await Bun.sleep(4_000);
+
+        // Copy the file as the timer left it, before closing saves it again
+        const copyDir = path.join(tempDir, 'copy');
+        fs.copyFileSync(`${dataDir}.enc`, `${copyDir}.enc`);
+        await close(client);
+
+        const reopened = await openDatabase(copyDir, config);
+        const result = await reopened.query<{ body: string }>('SELECT body FROM notes');
+        expect(result.rows).toEqual([{ body: 'committed' }]);
+        await stopSavingEncryptedDatabase(reopened, copyDir);
+        await reopened.close();
+    }, 60_000);
+
+    test('should refuse to open an encrypted database without its key', async () => {
+        const config = await getPGliteConfig();
+        setDatabaseKey(KEY);
+        await close(await openDatabase(dataDir, config));
+
+        setDatabaseKey(null);
+        await expect(openDatabase(dataDir, config)).rejects.toThrow('encrypted');
+        setDatabaseKey('cd'.repeat(32));
+        await expect(openDatabase(dataDir, config)).rejects.toThrow('does not match');
+    }, 60_000);
+});
+
+describe('dumpDatabase', () => {
+    test('should snapshot a plaintext database as a gzipped tarball', async () => {
+        const config = await getPGliteConfig();
+        const client = await openDatabase(dataDir, config);
+        await client.exec("CREATE TABLE notes (body text); INSERT INTO notes VALUES ('backed up')");
+
+        const { data, encrypted } = await dumpDatabase(client);
+        await close(client);
+
+        expect(encrypted).toBe(false);
+        const restored = await PGlite.create({ ...config, loadDataDir: new Blob([Bun.gunzipSync(data)]) });
+        const result = await restored.query<{ body: string }>('SELECT body FROM notes');
+        expect(result.rows).toEqual([{ body: 'backed up' }]);
+        await restored.close();
+    }, 60_000);
+
+    test('should keep the snapshot of an encrypted database encrypted', async () => {
+        const config = await getPGliteConfig();
+        setDatabaseKey(KEY);
+        const client = await openDatabase(dataDir, config);
+        await client.exec("CREATE TABLE notes (body text); INSERT INTO notes VALUES ('secret')");
+
+        const { data, encrypted } = await dumpDatabase(client);
+        await close(client);
+
+        expect(encrypted).toBe(true);
+        expect(Buffer.from(data).subarray(0, 9).toString()).toBe('PIPALIDB1');
+        expect(Buffer.from(data).includes('secret')).toBe(false);
+    }, 60_000);
+});
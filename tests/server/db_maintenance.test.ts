@@ -0,0 +1,55 @@
+import { test, expect, describe, beforeEach, afterEach } from 'bun:test';
+import { PGlite } from '@electric-sql/pglite';
+import { getPGliteConfig } from '../../src/server/db/utils';
+import { findReferencedPaths, vacuumDatabase } from '../../src/server/db/maintenance';
+
+let client: PGlite;
+
+beforeEach(async () => {
+    client = await PGlite.create(await getPGliteConfig());
+    await client.exec('CREATE TABLE conversation (id serial PRIMARY KEY, trajectory jsonb NOT NULL)');
+});
+
+afterEach(async () => {
+    await client.close();
+});
+
+async function addConversation(message: string) {
+    const trajectory = { steps: [{ step_id: 1, source: 'user', message }] };
+    await client.query('INSERT INTO conversation (trajectory) VALUES ($1)', [JSON.stringify(trajectory)]);
+}
+
+describe('findReferencedPaths', () => {
+    test('should only return paths a conversation mentions', async () => {
+        await addConversation('What is in /data/attachments/screenshot-1.png?');
+
+        const referenced = await findReferencedPaths(client, [
+            '/data/attachments/screenshot-1.png',
+            '/data/attachments/screenshot-2.png',
+        ]);
+
+        expect(referenced).toEqual(['/data/attachments/screenshot-1.png']);
+    }, 60_000);
+
+    test('should match Windows paths despite their escaped backslashes', async () => {
+        const windowsPath = 'C:\\Users\\me\\AppData\\Roaming\\ai.pipali\\attachments\\paste.png';
+        await addConversation(`Describe ${windowsPath}`);
+
+        expect(await findReferencedPaths(client, [windowsPath])).toEqual([windowsPath]);
+    }, 60_000);
+});
+
+describe('vacuumDatabase', () => {
+    test('should keep the rows that were not deleted', async () => {
+        await addConversation('kept');
+        await addConversation('x'.repeat(100_000));
+        await client.exec("DELETE FROM conversation WHERE trajectory->'steps'->0->>'message' <> 'kept'");
+
+        await vacuumDatabase(client);
+
+        const result = await client.query<{ message: string }>(
+            "SELECT trajectory->'steps'->0->>'message' AS message FROM conversation",
+        );
+        expect(result.rows).toEqual([{ message: 'kept' }]);
+    }, 60_000);
+});
@@ -0,0 +1,71 @@
+import { test, expect, describe, beforeAll, afterAll } from 'bun:test';
+import os from 'os';
+import path from 'path';
+import { setShellAuthToken } from '../../src/server/security';
+import {
+    createFileOperationConfirmation,
+    requiresNativeConfirmation,
+    signConfirmation,
+    verifyNativeConfirmation,
+    type ConfirmableOperation,
+} from '../../src/server/processor/confirmation';
+
+const TOKEN = 'test-token';
+
+function request(operation: ConfirmableOperation, filePath: string, operationSubType?: string) {
+    return createFileOperationConfirmation(operation, filePath, {
+        toolName: operation,
+        toolArgs: {},
+        operationSubType,
+    });
+}
+
+function response(requestId: string, selectedOptionId: string, signature?: string) {
+    return { requestId, selectedOptionId, signature, timestamp: new Date().toISOString() };
+}
+
+beforeAll(() => {
+    setShellAuthToken(TOKEN);
+});
+
+// Other test files share the module, and expect no shell token
+afterAll(() => {
+    setShellAuthToken('');
+});
+
+describe('requiresNativeConfirmation', () => {
+    test('should require it for sensitive paths', () => {
+        const sshKey = path.join(os.homedir(), '.ssh', 'id_rsa');
+        expect(requiresNativeConfirmation(request('read_sensitive_file', sshKey))).toBe(true);
+        expect(requiresNativeConfirmation(request('write_file', sshKey))).toBe(true);
+    });
+
+    test('should require it for shell commands that write', () => {
+        expect(requiresNativeConfirmation(request('execute_command', '/tmp', 'read-write'))).toBe(true);
+        expect(requiresNativeConfirmation(request('execute_command', '/tmp', 'write-only'))).toBe(true);
+        expect(requiresNativeConfirmation(request('execute_command', '/tmp', 'read-only'))).toBe(false);
+    });
+
+    test('should leave ordinary file edits to the webview', () => {
+        expect(requiresNativeConfirmation(request('edit_file', '/tmp/notes.md'))).toBe(false);
+    });
+});
+
+describe('verifyNativeConfirmation', () => {
+    test('should accept an approval signed with the shell token', () => {
+        const signature = signConfirmation(TOKEN, 'req-1', 'yes');
+        expect(verifyNativeConfirmation(response('req-1', 'yes', signature))).toBe(true);
+    });
+
+    test('should reject unsigned or wrongly signed approvals', () => {
+        expect(verifyNativeConfirmation(response('req-1', 'yes'))).toBe(false);
+        expect(verifyNativeConfirmation(response('req-1', 'yes', signConfirmation('wrong', 'req-1', 'yes')))).toBe(false);
+        // A signature for one request or option doesn't carry over to another
+        expect(verifyNativeConfirmation(response('req-2', 'yes', signConfirmation(TOKEN, 'req-1', 'yes')))).toBe(false);
+        expect(verifyNativeConfirmation(response('req-1', 'yes_dont_ask', signConfirmation(TOKEN, 'req-1', 'no')))).toBe(false);
+    });
+
+    test('should accept unsigned denials', () => {
+        expect(verifyNativeConfirmation(response('req-1', 'no'))).toBe(true);
+    });
+});
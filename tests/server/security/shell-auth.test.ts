@@ -0,0 +1,75 @@
+import { test, expect, describe } from 'bun:test';
+import { getShellAuthTokenHash, isRequestAuthorized, issueWebSocketTicket } from '../../../src/server/security/shell-auth';
+
+const TOKEN = 'test-token';
+
+function check(path: string, init?: RequestInit, expected: string | undefined = TOKEN): boolean {
+    const req = new Request(`http://127.0.0.1:6464${path}`, init);
+    return isRequestAuthorized(req, new URL(req.url), expected);
+}
+
+describe('isRequestAuthorized', () => {
+    test('should allow everything when no token is configured', () => {
+        expect(check('/api/conversations', undefined, undefined)).toBe(true);
+        expect(check('/ws/chat', undefined, undefined)).toBe(true);
+    });
+
+    test('should reject API and WebSocket requests without the token', () => {
+        expect(check('/api/conversations')).toBe(false);
+        expect(check('/api/shutdown', { method: 'POST' })).toBe(false);
+        expect(check('/ws/chat')).toBe(false);
+    });
+
+    test('should accept the token as a bearer header', () => {
+        expect(check('/api/conversations', { headers: { Authorization: `Bearer ${TOKEN}` } })).toBe(true);
+    });
+
+    test('should accept the token as a query parameter', () => {
+        expect(check(`/ws/chat?token=${TOKEN}`)).toBe(true);
+        expect(check(`/api/files?path=%2Ftmp%2Fa.png&token=${TOKEN}`)).toBe(true);
+    });
+
+    test('should reject a wrong token', () => {
+        expect(check('/api/conversations', { headers: { Authorization: 'Bearer wrong' } })).toBe(false);
+        expect(check('/ws/chat?token=test-token-but-longer')).toBe(false);
+    });
+
+    test('should leave public endpoints and frontend assets open', () => {
+        expect(check('/api/health')).toBe(true);
+        expect(check('/api/auth/callback?desktop=1')).toBe(true);
+        expect(check('/api/auth/complete', { method: 'POST' })).toBe(true);
+        expect(check('/')).toBe(true);
+        expect(check('/dist/app.js')).toBe(true);
+    });
+
+    test('should allow CORS preflight requests', () => {
+        expect(check('/api/conversations', { method: 'OPTIONS' })).toBe(true);
+    });
+
+    test('should accept a WebSocket ticket only once', () => {
+        const ticket = issueWebSocketTicket();
+        expect(check(`/ws/chat?ticket=${ticket}`)).toBe(true);
+        expect(check(`/ws/chat?ticket=${ticket}`)).toBe(false);
+    });
+
+    test('should reject expired or unknown WebSocket tickets', () => {
+        const ticket = issueWebSocketTicket(Date.now() - 60_000);
+        expect(check(`/ws/chat?ticket=${ticket}`)).toBe(false);
+        expect(check('/ws/chat?ticket=unknown')).toBe(false);
+    });
+
+    test('should not accept a WebSocket ticket for API requests', () => {
+        const ticket = issueWebSocketTicket();
+        expect(check(`/api/conversations?ticket=${ticket}`)).toBe(false);
+    });
+});
+
+describe('getShellAuthTokenHash', () => {
+    test('should hash the token with SHA-256', () => {
+        expect(getShellAuthTokenHash(TOKEN)).toBe('4c5dc9b7708905f77f5e5d16316b5dfb425e68cb326dcd55a860e90a7707031e');
+    });
+
+    test('should report no hash without a token', () => {
+        expect(getShellAuthTokenHash('')).toBeNull();
+    });
+});